@@ -0,0 +1,162 @@
+
+mod reader;
+mod writer;
+
+pub use reader::Archive;
+pub use writer::ArchiveWriter;
+
+/// Magic bytes at the start of every archive file
+pub const ARCHIVE_MAGIC: [u8; 4] = *b"SPAK";
+
+/// Version of the archive format written by this crate
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Size of the fixed header at the start of an archive file:
+///   magic [u8; 4], version u32, entry count u32, reserved u32, table of contents offset u64
+pub(crate) const HEADER_SIZE: usize = 24;
+
+/// ArchiveError enum
+/// Errors that can occur while reading or writing an archive
+#[derive(Debug)]
+pub enum ArchiveError {
+    IoError(String),
+    BadFormat(String),
+    NotFound(String),
+    CompressionError(String)
+}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(e: std::io::Error) -> Self {
+        ArchiveError::IoError(format!("{:?}", e))
+    }
+}
+
+/// Compression enum
+/// The way in which an entry's data is stored in the archive
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Compression {
+    None,
+    Deflate
+}
+
+impl Compression {
+
+    pub(crate) fn to_code(self) -> u32 {
+        match self {
+            Compression::None => 0,
+            Compression::Deflate => 1
+        }
+    }
+
+    pub(crate) fn from_code(code: u32) -> Result<Compression, ArchiveError> {
+        match code {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Deflate),
+            _ => Err(ArchiveError::BadFormat(format!("Unknown compression code: {}", code)))
+        }
+    }
+}
+
+/// EntryOptions struct
+/// Options applied to a single entry when it is added to an archive. The alignment is the byte
+/// boundary that the stored data will start on, relative to the start of the file.
+#[derive(Copy, Clone, Debug)]
+pub struct EntryOptions {
+    pub compression: Compression,
+    pub alignment: u32
+}
+
+impl Default for EntryOptions {
+    fn default() -> Self {
+        EntryOptions {
+            compression: Compression::Deflate,
+            alignment: 8
+        }
+    }
+}
+
+/// EntryInfo struct
+/// Table of contents record for a single entry in an archive
+#[derive(Clone, Debug)]
+pub struct EntryInfo {
+    pub path: String,
+    pub compression: Compression,
+    pub alignment: u32,
+    pub offset: u64,
+    pub stored_size: u64,
+    pub original_size: u64
+}
+
+/// Normalise an asset path so that lookups are independent of the platform separator and of any
+/// leading "./" or "/"
+pub fn normalise_path(path: &str) -> String {
+    path.replace('\\', "/")
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Archive,
+        ArchiveWriter,
+        Compression,
+        EntryOptions,
+        normalise_path
+    };
+
+    /// Test suite
+    /// Check that entries survive a round trip, with and without compression
+    #[test]
+    fn entries_round_trip() {
+        let text: Vec<u8> = "The quick brown fox jumps over the lazy dog. ".repeat(50).into_bytes();
+        let binary: Vec<u8> = (0..=255u8).collect();
+
+        let mut writer = ArchiveWriter::new();
+        writer.add_entry("text/fox.txt", &text, EntryOptions::default()).unwrap();
+        writer.add_entry("binary.bin", &binary, EntryOptions {
+            compression: Compression::None,
+            alignment: 256
+        }).unwrap();
+        let bytes = writer.to_bytes();
+
+        let archive = Archive::from_bytes(bytes).unwrap();
+        assert_eq!(archive.entries().len(), 2);
+        assert_eq!(archive.read("text/fox.txt").unwrap(), text);
+        assert_eq!(archive.read("binary.bin").unwrap(), binary);
+
+        let text_entry = archive.entry("text/fox.txt").unwrap();
+        assert_eq!(text_entry.compression, Compression::Deflate);
+        assert!(text_entry.stored_size < text_entry.original_size);
+        let binary_entry = archive.entry("binary.bin").unwrap();
+        assert_eq!(binary_entry.offset % 256, 0);
+        assert_eq!(binary_entry.stored_size, binary_entry.original_size);
+    }
+
+    /// Test suite
+    /// Check that paths are normalised on both writing and lookup, and duplicates are rejected
+    #[test]
+    fn paths_are_normalised() {
+        assert_eq!(normalise_path("./models\\River.mdl"), "models/River.mdl");
+        assert_eq!(normalise_path("/textures//a.png"), "textures/a.png");
+
+        let mut writer = ArchiveWriter::new();
+        writer.add_entry(".\\models\\River.mdl", &[1, 2, 3], EntryOptions::default()).unwrap();
+        assert!(writer.add_entry("models/River.mdl", &[4], EntryOptions::default()).is_err());
+
+        let archive = Archive::from_bytes(writer.to_bytes()).unwrap();
+        assert!(archive.contains("/models/River.mdl"));
+        assert!(!archive.contains("models/Terrain.mdl"));
+        assert!(archive.read("models/Terrain.mdl").is_err());
+    }
+
+    /// Test suite
+    /// Check that data which is not an archive is rejected
+    #[test]
+    fn bad_data_is_rejected() {
+        assert!(Archive::from_bytes(vec![0; 8]).is_err());
+        assert!(Archive::from_bytes(b"NOPE".repeat(10)).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+
+use crate::{
+    ArchiveError,
+    Compression,
+    EntryInfo,
+    ARCHIVE_MAGIC,
+    ARCHIVE_VERSION,
+    HEADER_SIZE,
+    normalise_path
+};
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    sync::Mutex
+};
+
+/// ArchiveData enum
+/// Where entry data is read from; either the whole archive held in memory, or an open file that
+/// is read from on demand
+enum ArchiveData {
+    Memory(Vec<u8>),
+    File(Mutex<File>)
+}
+
+/// Archive struct
+/// A read-only view of an archive, with its table of contents indexed by path
+pub struct Archive {
+    data: ArchiveData,
+    entries: Vec<EntryInfo>,
+    index: HashMap<String, usize>
+}
+
+impl Archive {
+
+    /// Open an archive file. Only the header and table of contents are read up front.
+    pub fn open(file_path: &Path) -> Result<Archive, ArchiveError> {
+        let mut file = File::open(file_path)
+            .map_err(|e| ArchiveError::IoError(format!("Error opening file: {:?} - {:?}", file_path, e)))?;
+        let file_length = file.metadata()?.len();
+
+        let mut header = [0u8; HEADER_SIZE];
+        file.read_exact(&mut header)
+            .map_err(|_| ArchiveError::BadFormat(format!("File too short for archive header: {:?}", file_path)))?;
+        let (entry_count, toc_offset) = parse_header(&header, file_length)?;
+
+        let mut toc = vec![0u8; (file_length - toc_offset) as usize];
+        file.seek(SeekFrom::Start(toc_offset))?;
+        file.read_exact(&mut toc)?;
+        let entries = parse_toc(&toc, entry_count, toc_offset)?;
+
+        Archive::from_parts(ArchiveData::File(Mutex::new(file)), entries)
+    }
+
+    /// Use an archive that is already held in memory
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Archive, ArchiveError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(ArchiveError::BadFormat("Data too short for archive header".to_string()));
+        }
+        let (entry_count, toc_offset) = parse_header(&bytes[0..HEADER_SIZE], bytes.len() as u64)?;
+        let entries = parse_toc(&bytes[toc_offset as usize..], entry_count, toc_offset)?;
+        Archive::from_parts(ArchiveData::Memory(bytes), entries)
+    }
+
+    fn from_parts(data: ArchiveData, entries: Vec<EntryInfo>) -> Result<Archive, ArchiveError> {
+        let mut index = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            if index.insert(entry.path.clone(), i).is_some() {
+                return Err(ArchiveError::BadFormat(format!("Duplicate entry: {}", entry.path)));
+            }
+        }
+        Ok(Archive { data, entries, index })
+    }
+
+    /// All entries in the archive, in the order they are stored
+    pub fn entries(&self) -> &[EntryInfo] {
+        &self.entries
+    }
+
+    /// Look up the table of contents record for a path
+    pub fn entry(&self, path: &str) -> Option<&EntryInfo> {
+        self.index.get(&normalise_path(path))
+            .map(|i| &self.entries[*i])
+    }
+
+    /// Check whether the archive has an entry for a path
+    pub fn contains(&self, path: &str) -> bool {
+        self.index.contains_key(&normalise_path(path))
+    }
+
+    /// Read an entry's data, decompressing it if necessary
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, ArchiveError> {
+        let entry = self.entry(path)
+            .ok_or_else(|| ArchiveError::NotFound(path.to_string()))?;
+
+        let stored = match &self.data {
+            ArchiveData::Memory(bytes) => {
+                let start = entry.offset as usize;
+                bytes[start..(start + entry.stored_size as usize)].to_vec()
+            },
+            ArchiveData::File(file) => {
+                let mut file = file.lock()
+                    .map_err(|_| ArchiveError::IoError("Archive file lock poisoned".to_string()))?;
+                let mut stored = vec![0u8; entry.stored_size as usize];
+                file.seek(SeekFrom::Start(entry.offset))?;
+                file.read_exact(&mut stored)?;
+                stored
+            }
+        };
+
+        let data = match entry.compression {
+            Compression::None => stored,
+            Compression::Deflate => miniz_oxide::inflate::decompress_to_vec(&stored)
+                .map_err(|e| ArchiveError::CompressionError(format!("{}: {:?}", entry.path, e)))?
+        };
+        if data.len() as u64 != entry.original_size {
+            return Err(ArchiveError::CompressionError(
+                format!("{}: expected {} bytes, got {}", entry.path, entry.original_size, data.len())));
+        }
+        Ok(data)
+    }
+}
+
+/// Validate the fixed header, returning the entry count and table of contents offset
+fn parse_header(header: &[u8], total_length: u64) -> Result<(usize, u64), ArchiveError> {
+    if header[0..4] != ARCHIVE_MAGIC {
+        return Err(ArchiveError::BadFormat("Missing archive magic".to_string()));
+    }
+    let version = read_u32(header, 4);
+    if version != ARCHIVE_VERSION {
+        return Err(ArchiveError::BadFormat(format!("Unsupported archive version: {}", version)));
+    }
+    let entry_count = read_u32(header, 8) as usize;
+    let toc_offset = read_u64(header, 16);
+    if toc_offset < HEADER_SIZE as u64 || toc_offset > total_length {
+        return Err(ArchiveError::BadFormat(format!("Table of contents offset out of range: {}", toc_offset)));
+    }
+    Ok((entry_count, toc_offset))
+}
+
+/// Parse the table of contents, checking that every entry lies within the data region
+fn parse_toc(toc: &[u8], entry_count: usize, toc_offset: u64) -> Result<Vec<EntryInfo>, ArchiveError> {
+    let truncated = || ArchiveError::BadFormat("Table of contents is truncated".to_string());
+    let mut entries = Vec::with_capacity(entry_count);
+    let mut pos: usize = 0;
+    for _ in 0..entry_count {
+        if pos + 4 > toc.len() {
+            return Err(truncated());
+        }
+        let path_length = read_u32(toc, pos) as usize;
+        pos += 4;
+        if pos + path_length + 32 > toc.len() {
+            return Err(truncated());
+        }
+        let path = String::from_utf8(toc[pos..(pos + path_length)].to_vec())
+            .map_err(|_| ArchiveError::BadFormat("Entry path is not valid UTF-8".to_string()))?;
+        pos += path_length;
+        let entry = EntryInfo {
+            path,
+            compression: Compression::from_code(read_u32(toc, pos))?,
+            alignment: read_u32(toc, pos + 4),
+            offset: read_u64(toc, pos + 8),
+            stored_size: read_u64(toc, pos + 16),
+            original_size: read_u64(toc, pos + 24)
+        };
+        pos += 32;
+        let in_range = match entry.offset.checked_add(entry.stored_size) {
+            Some(end) => entry.offset >= HEADER_SIZE as u64 && end <= toc_offset,
+            None => false
+        };
+        if !in_range {
+            return Err(ArchiveError::BadFormat(format!("Entry data out of range: {}", entry.path)));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&bytes[at..(at + 4)]);
+    u32::from_le_bytes(value)
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&bytes[at..(at + 8)]);
+    u64::from_le_bytes(value)
+}
@@ -0,0 +1,119 @@
+
+use crate::{
+    ArchiveError,
+    Compression,
+    EntryInfo,
+    EntryOptions,
+    ARCHIVE_MAGIC,
+    ARCHIVE_VERSION,
+    HEADER_SIZE,
+    normalise_path
+};
+
+use std::path::Path;
+
+/// Compression level passed to the deflate encoder; 0 is fastest and 10 is smallest
+const DEFLATE_LEVEL: u8 = 9;
+
+/// ArchiveWriter struct
+/// Collects entries in memory and lays them out into an archive file. Entries are stored in the
+/// order they are added, each padded to its requested alignment, and followed by the table of
+/// contents.
+pub struct ArchiveWriter {
+    entries: Vec<EntryInfo>,
+    data: Vec<u8>
+}
+
+impl Default for ArchiveWriter {
+    fn default() -> Self {
+        ArchiveWriter::new()
+    }
+}
+
+impl ArchiveWriter {
+
+    /// Create a new writer with no entries
+    pub fn new() -> ArchiveWriter {
+        ArchiveWriter {
+            entries: vec![],
+            data: vec![0; HEADER_SIZE]
+        }
+    }
+
+    /// Add an entry with the given bytes under the given path
+    pub fn add_entry(&mut self, path: &str, bytes: &[u8], options: EntryOptions) -> Result<(), ArchiveError> {
+        let path = normalise_path(path);
+        if path.is_empty() {
+            return Err(ArchiveError::BadFormat("Entry path is empty".to_string()));
+        }
+        if self.entries.iter().any(|e| e.path == path) {
+            return Err(ArchiveError::BadFormat(format!("Duplicate entry: {}", path)));
+        }
+        if options.alignment == 0 || !options.alignment.is_power_of_two() {
+            return Err(ArchiveError::BadFormat(
+                format!("Alignment must be a power of two, got {} for {}", options.alignment, path)));
+        }
+
+        let stored = match options.compression {
+            Compression::None => bytes.to_vec(),
+            Compression::Deflate => miniz_oxide::deflate::compress_to_vec(bytes, DEFLATE_LEVEL)
+        };
+
+        let alignment = options.alignment as usize;
+        let padding = (alignment - self.data.len() % alignment) % alignment;
+        self.data.resize(self.data.len() + padding, 0);
+        let offset = self.data.len() as u64;
+        self.data.extend_from_slice(&stored);
+
+        self.entries.push(EntryInfo {
+            path,
+            compression: options.compression,
+            alignment: options.alignment,
+            offset,
+            stored_size: stored.len() as u64,
+            original_size: bytes.len() as u64
+        });
+        Ok(())
+    }
+
+    /// Add an entry with the contents of a file on disk under the given path
+    pub fn add_file(&mut self, path: &str, file_path: &Path, options: EntryOptions) -> Result<(), ArchiveError> {
+        let bytes = std::fs::read(file_path)
+            .map_err(|e| ArchiveError::IoError(format!("Error reading file: {:?} - {:?}", file_path, e)))?;
+        self.add_entry(path, &bytes, options)
+    }
+
+    /// Number of entries added so far
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Produce the complete archive as a byte vector
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.data.clone();
+        let toc_offset = bytes.len() as u64;
+
+        for entry in self.entries.iter() {
+            bytes.extend_from_slice(&(entry.path.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(entry.path.as_bytes());
+            bytes.extend_from_slice(&entry.compression.to_code().to_le_bytes());
+            bytes.extend_from_slice(&entry.alignment.to_le_bytes());
+            bytes.extend_from_slice(&entry.offset.to_le_bytes());
+            bytes.extend_from_slice(&entry.stored_size.to_le_bytes());
+            bytes.extend_from_slice(&entry.original_size.to_le_bytes());
+        }
+
+        bytes[0..4].copy_from_slice(&ARCHIVE_MAGIC);
+        bytes[4..8].copy_from_slice(&ARCHIVE_VERSION.to_le_bytes());
+        bytes[8..12].copy_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        bytes[12..16].copy_from_slice(&0u32.to_le_bytes());
+        bytes[16..24].copy_from_slice(&toc_offset.to_le_bytes());
+        bytes
+    }
+
+    /// Write the complete archive to a file
+    pub fn write_to_file(&self, file_path: &Path) -> Result<(), ArchiveError> {
+        std::fs::write(file_path, self.to_bytes())
+            .map_err(|e| ArchiveError::IoError(format!("Error writing file: {:?} - {:?}", file_path, e)))
+    }
+}
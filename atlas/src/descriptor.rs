@@ -0,0 +1,103 @@
+
+use crate::{
+    AtlasError,
+    SpriteRect
+};
+
+use std::collections::HashMap;
+
+/// AtlasDescriptor struct
+/// The table of sprite names and their locations within an atlas. Its text form has a header line
+/// giving the atlas size, followed by one line per sprite:
+///   atlas <width> <height>
+///   sprite <name> <x> <y> <width> <height>
+#[derive(Clone, Debug, Default)]
+pub struct AtlasDescriptor {
+    pub width: u32,
+    pub height: u32,
+    sprites: Vec<(String, SpriteRect)>,
+    index: HashMap<String, usize>
+}
+
+impl AtlasDescriptor {
+
+    /// Create a new descriptor for an atlas of the given size, with no sprites
+    pub fn new(width: u32, height: u32) -> AtlasDescriptor {
+        AtlasDescriptor {
+            width,
+            height,
+            sprites: vec![],
+            index: HashMap::new()
+        }
+    }
+
+    /// Add a sprite; names must be unique and must not contain whitespace
+    pub fn add_sprite(&mut self, name: &str, rect: SpriteRect) -> Result<(), AtlasError> {
+        if name.is_empty() || name.chars().any(char::is_whitespace) {
+            return Err(AtlasError::BadInput(format!("Invalid sprite name: {:?}", name)));
+        }
+        if rect.x + rect.width > self.width || rect.y + rect.height > self.height {
+            return Err(AtlasError::BadInput(format!("Sprite lies outside the atlas: {}", name)));
+        }
+        if self.index.insert(name.to_string(), self.sprites.len()).is_some() {
+            return Err(AtlasError::BadInput(format!("Duplicate sprite: {}", name)));
+        }
+        self.sprites.push((name.to_string(), rect));
+        Ok(())
+    }
+
+    /// All sprites, in the order they were added
+    pub fn sprites(&self) -> &[(String, SpriteRect)] {
+        &self.sprites
+    }
+
+    /// Look up the area holding a sprite, in pixels
+    pub fn sprite(&self, name: &str) -> Option<SpriteRect> {
+        self.index.get(name)
+            .map(|i| self.sprites[*i].1)
+    }
+
+    /// Look up the texture coordinates of a sprite, as [left, top, right, bottom]
+    pub fn uv_rect(&self, name: &str) -> Option<[f32; 4]> {
+        self.sprite(name)
+            .map(|rect| rect.uv_rect(self.width, self.height))
+    }
+
+    /// Produce the text form of the descriptor
+    pub fn to_text(&self) -> String {
+        let mut text = format!("atlas {} {}\n", self.width, self.height);
+        for (name, rect) in self.sprites.iter() {
+            text.push_str(&format!("sprite {} {} {} {} {}\n", name, rect.x, rect.y, rect.width, rect.height));
+        }
+        text
+    }
+
+    /// Parse the text form of a descriptor
+    pub fn from_text(text: &str) -> Result<AtlasDescriptor, AtlasError> {
+        let mut descriptor: Option<AtlasDescriptor> = None;
+        for (line_no, line) in text.lines().enumerate() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty() {
+                continue;
+            }
+            let bad_line = || AtlasError::BadFormat(format!("Bad atlas descriptor line {}: {}", line_no + 1, line));
+            let number = |part: &str| part.parse::<u32>().map_err(|_| bad_line());
+            match (parts[0], descriptor.as_mut()) {
+                ("atlas", None) if parts.len() == 3 => {
+                    descriptor = Some(AtlasDescriptor::new(number(parts[1])?, number(parts[2])?));
+                },
+                ("sprite", Some(descriptor)) if parts.len() == 6 => {
+                    let rect = SpriteRect {
+                        x: number(parts[2])?,
+                        y: number(parts[3])?,
+                        width: number(parts[4])?,
+                        height: number(parts[5])?
+                    };
+                    descriptor.add_sprite(parts[1], rect)?;
+                },
+                _ => return Err(bad_line())
+            }
+        }
+        descriptor.ok_or_else(|| AtlasError::BadFormat("Atlas descriptor has no header".to_string()))
+    }
+}
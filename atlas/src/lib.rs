@@ -0,0 +1,249 @@
+
+mod descriptor;
+mod packer;
+
+pub use descriptor::AtlasDescriptor;
+pub use packer::pack;
+
+/// Magic bytes at the start of every packed atlas file
+pub const ATLAS_MAGIC: [u8; 4] = *b"SATL";
+
+/// Version of the packed atlas format written by this crate
+pub const ATLAS_VERSION: u32 = 1;
+
+/// Size of the fixed header at the start of a packed atlas file:
+///   magic [u8; 4], version u32, width u32, height u32, descriptor length u32
+const HEADER_SIZE: usize = 20;
+
+/// AtlasError enum
+/// Errors that can occur while packing, reading or writing an atlas
+#[derive(Debug)]
+pub enum AtlasError {
+    IoError(String),
+    BadInput(String),
+    DoesNotFit(String),
+    BadFormat(String)
+}
+
+/// SpriteImage struct
+/// A named image to be packed into an atlas, with its pixel data as 8-bit RGBA
+#[derive(Clone, Debug)]
+pub struct SpriteImage {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+
+/// PackOptions struct
+/// Options for packing an atlas. Padding is the space left clear around every sprite, and bleed
+/// is how much of that space is filled by extending each sprite's edge pixels outwards, so that
+/// filtering and mipmapping near the edges does not pick up neighbouring sprites. Bleed must not
+/// be greater than padding.
+#[derive(Copy, Clone, Debug)]
+pub struct PackOptions {
+    pub padding: u32,
+    pub bleed: u32,
+    pub max_size: u32,
+    pub power_of_two: bool
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        PackOptions {
+            padding: 2,
+            bleed: 2,
+            max_size: 4096,
+            power_of_two: true
+        }
+    }
+}
+
+/// SpriteRect struct
+/// The area of an atlas, in pixels, that holds a single sprite, not including padding
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpriteRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32
+}
+
+impl SpriteRect {
+
+    /// Texture coordinates of the sprite within an atlas of the given size, as
+    /// [left, top, right, bottom]
+    pub fn uv_rect(&self, atlas_width: u32, atlas_height: u32) -> [f32; 4] {
+        [
+            self.x as f32 / atlas_width as f32,
+            self.y as f32 / atlas_height as f32,
+            (self.x + self.width) as f32 / atlas_width as f32,
+            (self.y + self.height) as f32 / atlas_height as f32
+        ]
+    }
+}
+
+/// Atlas struct
+/// A packed atlas; a single RGBA image holding every sprite, and the table locating them
+pub struct Atlas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    pub descriptor: AtlasDescriptor
+}
+
+impl Atlas {
+
+    /// Produce a packed atlas file as a byte vector, suitable for generating at build time and
+    /// loading at run time without decoding any images
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let descriptor_text = self.descriptor.to_text();
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + descriptor_text.len() + self.pixels.len());
+        bytes.extend_from_slice(&ATLAS_MAGIC);
+        bytes.extend_from_slice(&ATLAS_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&(descriptor_text.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(descriptor_text.as_bytes());
+        bytes.extend_from_slice(&self.pixels);
+        bytes
+    }
+
+    /// Read a packed atlas file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Atlas, AtlasError> {
+        if bytes.len() < HEADER_SIZE || bytes[0..4] != ATLAS_MAGIC {
+            return Err(AtlasError::BadFormat("Missing atlas magic".to_string()));
+        }
+        let version = read_u32(bytes, 4);
+        if version != ATLAS_VERSION {
+            return Err(AtlasError::BadFormat(format!("Unsupported atlas version: {}", version)));
+        }
+        let width = read_u32(bytes, 8);
+        let height = read_u32(bytes, 12);
+        let descriptor_end = HEADER_SIZE + read_u32(bytes, 16) as usize;
+        let pixels_size = width as usize * height as usize * 4;
+        if bytes.len() != descriptor_end + pixels_size {
+            return Err(AtlasError::BadFormat(format!("Atlas data has wrong length: {}", bytes.len())));
+        }
+        let descriptor_text = std::str::from_utf8(&bytes[HEADER_SIZE..descriptor_end])
+            .map_err(|_| AtlasError::BadFormat("Atlas descriptor is not valid UTF-8".to_string()))?;
+        let descriptor = AtlasDescriptor::from_text(descriptor_text)?;
+        if descriptor.width != width || descriptor.height != height {
+            return Err(AtlasError::BadFormat("Atlas descriptor does not match image size".to_string()));
+        }
+        Ok(Atlas {
+            width,
+            height,
+            pixels: bytes[descriptor_end..].to_vec(),
+            descriptor
+        })
+    }
+
+    /// Write a packed atlas file
+    pub fn write_to_file(&self, file_path: &std::path::Path) -> Result<(), AtlasError> {
+        std::fs::write(file_path, self.to_bytes())
+            .map_err(|e| AtlasError::IoError(format!("Error writing file: {:?} - {:?}", file_path, e)))
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&bytes[at..(at + 4)]);
+    u32::from_le_bytes(value)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Atlas,
+        AtlasDescriptor,
+        PackOptions,
+        SpriteImage,
+        SpriteRect,
+        pack
+    };
+
+    fn solid_image(name: &str, width: u32, height: u32, value: u8) -> SpriteImage {
+        SpriteImage {
+            name: name.to_string(),
+            width,
+            height,
+            pixels: vec![value; (width * height * 4) as usize]
+        }
+    }
+
+    fn overlaps(a: &SpriteRect, b: &SpriteRect, padding: u32) -> bool {
+        a.x < b.x + b.width + padding && b.x < a.x + a.width + padding &&
+            a.y < b.y + b.height + padding && b.y < a.y + a.height + padding
+    }
+
+    /// Test suite
+    /// Check that sprites are all placed within the atlas, apart from each other, with their
+    /// pixels copied in
+    #[test]
+    fn sprites_are_packed_without_overlap() {
+        let images = vec![
+            solid_image("wide", 60, 10, 10),
+            solid_image("tall", 12, 50, 20),
+            solid_image("square", 30, 30, 30),
+            solid_image("tiny", 1, 1, 40)
+        ];
+        let options = PackOptions::default();
+        let atlas = pack(&images, options).unwrap();
+        assert!(atlas.width.is_power_of_two() && atlas.height.is_power_of_two());
+
+        let rects: Vec<SpriteRect> = images.iter()
+            .map(|image| atlas.descriptor.sprite(&image.name).unwrap())
+            .collect();
+        for (i, rect) in rects.iter().enumerate() {
+            assert_eq!((rect.width, rect.height), (images[i].width, images[i].height));
+            assert!(rect.x >= options.padding && rect.y >= options.padding);
+            assert!(rect.x + rect.width + options.padding <= atlas.width);
+            assert!(rect.y + rect.height + options.padding <= atlas.height);
+            for other in rects[(i + 1)..].iter() {
+                assert!(!overlaps(rect, other, options.padding));
+            }
+            let corner = ((rect.y * atlas.width + rect.x) * 4) as usize;
+            assert_eq!(atlas.pixels[corner], images[i].pixels[0]);
+        }
+    }
+
+    /// Test suite
+    /// Check that edge pixels bleed into the padding by the requested amount only, and that bad
+    /// input is rejected
+    #[test]
+    fn edges_bleed_into_padding() {
+        let images = vec![solid_image("block", 4, 4, 200)];
+        let atlas = pack(&images, PackOptions { padding: 3, bleed: 2, max_size: 64, power_of_two: false }).unwrap();
+        let rect = atlas.descriptor.sprite("block").unwrap();
+        let pixel_at = |x: u32, y: u32| atlas.pixels[((y * atlas.width + x) * 4) as usize];
+        assert_eq!(pixel_at(rect.x - 2, rect.y - 2), 200);
+        assert_eq!(pixel_at(rect.x + rect.width + 1, rect.y), 200);
+        assert_eq!(pixel_at(rect.x - 3, rect.y), 0);
+
+        assert!(pack(&images, PackOptions { padding: 1, bleed: 2, ..PackOptions::default() }).is_err());
+        assert!(pack(&[solid_image("big", 100, 100, 0)], PackOptions { max_size: 64, ..PackOptions::default() }).is_err());
+        assert!(pack(&[solid_image("a b", 1, 1, 0)], PackOptions::default()).is_err());
+        assert!(pack(&[solid_image("a", 1, 1, 0), solid_image("a", 1, 1, 0)], PackOptions::default()).is_err());
+    }
+
+    /// Test suite
+    /// Check that a packed atlas and its descriptor survive a round trip through bytes and text
+    #[test]
+    fn atlas_round_trip() {
+        let images = vec![solid_image("one", 5, 7, 1), solid_image("two", 9, 3, 2)];
+        let atlas = pack(&images, PackOptions::default()).unwrap();
+        let loaded = Atlas::from_bytes(&atlas.to_bytes()).unwrap();
+        assert_eq!((loaded.width, loaded.height), (atlas.width, atlas.height));
+        assert_eq!(loaded.pixels, atlas.pixels);
+        assert_eq!(loaded.descriptor.sprite("two"), atlas.descriptor.sprite("two"));
+
+        let descriptor = AtlasDescriptor::from_text(&atlas.descriptor.to_text()).unwrap();
+        let uv = descriptor.uv_rect("one").unwrap();
+        let rect = descriptor.sprite("one").unwrap();
+        assert_eq!(uv[0], rect.x as f32 / atlas.width as f32);
+        assert_eq!(uv[3], (rect.y + rect.height) as f32 / atlas.height as f32);
+        assert!(descriptor.sprite("three").is_none());
+        assert!(Atlas::from_bytes(b"SATL").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+
+use crate::{
+    Atlas,
+    AtlasDescriptor,
+    AtlasError,
+    PackOptions,
+    SpriteImage,
+    SpriteRect
+};
+
+use std::collections::HashSet;
+
+/// Pack a set of images into a single atlas. Images are placed in rows, tallest first, and the
+/// atlas is grown from the smallest size that could hold them until they all fit.
+pub fn pack(images: &[SpriteImage], options: PackOptions) -> Result<Atlas, AtlasError> {
+    validate(images, &options)?;
+
+    // Each image takes up its own size plus padding on every side
+    let padded_sizes: Vec<(u32, u32)> = images.iter()
+        .map(|image| (image.width + 2 * options.padding, image.height + 2 * options.padding))
+        .collect();
+    let mut order: Vec<usize> = (0..images.len()).collect();
+    order.sort_by(|a, b| padded_sizes[*b].1.cmp(&padded_sizes[*a].1)
+        .then(padded_sizes[*b].0.cmp(&padded_sizes[*a].0)));
+
+    let total_area: u64 = padded_sizes.iter().map(|(w, h)| *w as u64 * *h as u64).sum();
+    let widest = padded_sizes.iter().map(|(w, _)| *w).max().unwrap_or(1);
+    let tallest = padded_sizes.iter().map(|(_, h)| *h).max().unwrap_or(1);
+    let side = ((total_area as f64).sqrt().ceil() as u32).max(1);
+    let (mut width, mut height) = match options.power_of_two {
+        true => (side.max(widest).next_power_of_two(), side.max(tallest).next_power_of_two()),
+        false => (side.max(widest), side.max(tallest))
+    };
+
+    let positions = loop {
+        if width > options.max_size || height > options.max_size {
+            return Err(AtlasError::DoesNotFit(
+                format!("Images do not fit in an atlas of at most {}x{}", options.max_size, options.max_size)));
+        }
+        if let Some(positions) = place_in_rows(&padded_sizes, &order, width, height) {
+            break positions;
+        }
+        let grow = |size: u32| match options.power_of_two {
+            true => size * 2,
+            false => size + size / 2 + 1
+        };
+        if width <= height {
+            width = grow(width);
+        } else {
+            height = grow(height);
+        }
+    };
+
+    // Without the power of two constraint, trim off any unused space
+    if !options.power_of_two {
+        width = positions.iter().zip(padded_sizes.iter()).map(|((x, _), (w, _))| x + w).max().unwrap_or(1);
+        height = positions.iter().zip(padded_sizes.iter()).map(|((_, y), (_, h))| y + h).max().unwrap_or(1);
+    }
+
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    let mut descriptor = AtlasDescriptor::new(width, height);
+    for (image, (x, y)) in images.iter().zip(positions.iter()) {
+        let rect = SpriteRect {
+            x: x + options.padding,
+            y: y + options.padding,
+            width: image.width,
+            height: image.height
+        };
+        copy_with_bleed(image, rect, options.bleed, &mut pixels, width);
+        descriptor.add_sprite(&image.name, rect)?;
+    }
+
+    Ok(Atlas {
+        width,
+        height,
+        pixels,
+        descriptor
+    })
+}
+
+/// Check the options and images before packing
+fn validate(images: &[SpriteImage], options: &PackOptions) -> Result<(), AtlasError> {
+    if images.is_empty() {
+        return Err(AtlasError::BadInput("No images to pack".to_string()));
+    }
+    if options.bleed > options.padding {
+        return Err(AtlasError::BadInput(
+            format!("Bleed ({}) cannot be greater than padding ({})", options.bleed, options.padding)));
+    }
+    let mut names = HashSet::new();
+    for image in images.iter() {
+        if image.width == 0 || image.height == 0 {
+            return Err(AtlasError::BadInput(format!("Image has no size: {}", image.name)));
+        }
+        if image.pixels.len() != image.width as usize * image.height as usize * 4 {
+            return Err(AtlasError::BadInput(format!("Image data does not match its size: {}", image.name)));
+        }
+        if !names.insert(image.name.as_str()) {
+            return Err(AtlasError::BadInput(format!("Duplicate sprite: {}", image.name)));
+        }
+    }
+    Ok(())
+}
+
+/// Try to place every padded image in rows within an atlas of the given size, in the given
+/// order. Returns the top-left corner of each padded image, indexed the same as the sizes, or None
+/// if they do not all fit.
+fn place_in_rows(padded_sizes: &[(u32, u32)], order: &[usize], width: u32, height: u32) -> Option<Vec<(u32, u32)>> {
+    let mut positions = vec![(0, 0); padded_sizes.len()];
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for index in order.iter() {
+        let (w, h) = padded_sizes[*index];
+        if x + w > width {
+            x = 0;
+            y += row_height;
+            row_height = 0;
+        }
+        if w > width || y + h > height {
+            return None;
+        }
+        positions[*index] = (x, y);
+        x += w;
+        row_height = row_height.max(h);
+    }
+    Some(positions)
+}
+
+/// Copy an image into the atlas at the given area, extending its edge pixels outwards by the
+/// bleed amount on every side
+fn copy_with_bleed(image: &SpriteImage, rect: SpriteRect, bleed: u32, pixels: &mut [u8], atlas_width: u32) {
+    for dest_y in (rect.y - bleed)..(rect.y + rect.height + bleed) {
+        let src_y = dest_y.clamp(rect.y, rect.y + rect.height - 1) - rect.y;
+        for dest_x in (rect.x - bleed)..(rect.x + rect.width + bleed) {
+            let src_x = dest_x.clamp(rect.x, rect.x + rect.width - 1) - rect.x;
+            let src = ((src_y * image.width + src_x) * 4) as usize;
+            let dest = ((dest_y * atlas_width + dest_x) * 4) as usize;
+            pixels[dest..(dest + 4)].copy_from_slice(&image.pixels[src..(src + 4)]);
+        }
+    }
+}
@@ -5,11 +5,14 @@ use std::fs::File;
 use std::io::Read;
 
 /// Config struct
-/// Configuration for how Collada data is translated to model instances. The only currently-
-/// supported feature is merging models together under a new name.
+/// Configuration for how Collada data is translated to model instances. Models can be merged
+/// together under a new name, and lower levels of detail generated for them.
 #[derive(Debug, Deserialize, Default)]
 pub struct Config {
-    pub merges: Vec<Merge>
+    #[serde(default)]
+    pub merges: Vec<Merge>,
+    #[serde(default)]
+    pub lods: Vec<Lod>
 }
 
 impl Config {
@@ -35,3 +38,13 @@ pub struct Merge {
     pub name: String,
     pub geometries: Vec<String>
 }
+
+/// Lod struct
+/// Defines lower levels of detail to generate for a model, after any merges, by simplifying it to
+/// each given ratio of its triangle count. Each level is a new model named after the source with
+/// suffixes _lod1, _lod2 and so on.
+#[derive(Debug, Deserialize)]
+pub struct Lod {
+    pub model: String,
+    pub ratios: Vec<f32>
+}
@@ -8,9 +8,12 @@ use elements::{
     Matrix,
     Node
 };
-use model::types::{
-    Model,
-    StaticVertex
+use model::{
+    simplify::make_lod_chain,
+    types::{
+        Model,
+        StaticVertex
+    }
 };
 use serde::Deserialize;
 use serde_xml_rs::from_reader;
@@ -33,6 +36,20 @@ impl COLLADA {
     /// Translate the data held by this instance into instances of model::types::Model.
     /// Alter behaviour of this translation according to the supplied configuration.
     pub fn extract_models(&self, config: config::Config) -> Vec<Model<StaticVertex>> {
+        let mut models = self.extract_merged_models(&config);
+        for lod_config in config.lods.iter() {
+            let source = models.iter()
+                .find(|m| m.name.eq(&lod_config.model))
+                .unwrap_or_else(|| panic!("Did not find model named {} to make LODs for", lod_config.model));
+            let mut lods = make_lod_chain(source, &lod_config.ratios);
+            models.append(&mut lods);
+        }
+        models
+    }
+
+    /// Extract the models, merging them as configured.
+    /// For internal use.
+    fn extract_merged_models(&self, config: &config::Config) -> Vec<Model<StaticVertex>> {
         let mut pre_merge_models: Vec<Model<StaticVertex>> = vec![];
         for geometry in self.library_geometries.items.iter() {
             let mesh = &geometry.mesh;
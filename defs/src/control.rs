@@ -1,21 +1,49 @@
 
 /// KeyCode enum
 /// Platform-independent abstraction for key codes that the engine recognises.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum KeyCode {
     Unknown,
     Left,
     Up,
     Down,
-    Right
+    Right,
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown
 }
 
 /// InputState enum
 /// Platform-independent abstraction for the state that an input can be in at any given moment.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum InputState {
     Pressed,
     Released
 }
 
+/// MouseButton enum
+/// Platform-independent abstraction for the mouse buttons that the engine recognises.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle
+}
+
+/// InputEvent enum
+/// A single discrete input, as received from the platform between one update and the next.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum InputEvent {
+    Key(KeyCode, InputState),
+    MouseButton(MouseButton, InputState),
+    Character(char)
+}
+
 /// Control trait
 /// Abstraction for an entity that polls and receives input states.
 pub trait Control {
@@ -26,9 +54,29 @@ pub trait Control {
     /// Process a keyboard event
     fn process_keyboard_event(&mut self, keycode: KeyCode, state: InputState);
 
+    /// Process the mouse moving, given as fractions of the window width and height from its
+    /// top-left corner, or None if the mouse has left the window
+    fn process_mouse_move(&mut self, position: Option<(f32, f32)>);
+
+    /// Process a mouse button event
+    fn process_mouse_button_event(&mut self, button: MouseButton, state: InputState);
+
+    /// Process a character typed as text, after keyboard layout and modifiers are applied
+    fn process_character(&mut self, c: char);
+
     /// Retrieve the left/right direction currently being input
     fn get_dx(&self) -> f32;
 
     /// Retrieve the up/down direction currently being input
     fn get_dy(&self) -> f32;
+
+    /// Retrieve the mouse position, as fractions of the window width and height from its
+    /// top-left corner, if it is within the window
+    fn get_mouse_position(&self) -> Option<(f32, f32)>;
+
+    /// Check whether a mouse button is currently held down
+    fn is_mouse_button_down(&self, button: MouseButton) -> bool;
+
+    /// Retrieve the discrete events received before the most recent update, in order
+    fn get_frame_events(&self) -> &[InputEvent];
 }
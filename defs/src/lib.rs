@@ -6,6 +6,7 @@ pub mod ubo;
 use cgmath::Matrix4;
 use image::ImageError;
 use winit::error::OsError;
+use std::collections::HashMap;
 
 /// Camera trait
 /// Abstraction for an entity that has a position and a projection view, such as a player-controlled
@@ -41,6 +42,12 @@ pub trait SceneInfo {
         &self,
         pass_index: usize,
         step_index: usize) -> (*const u8, usize);
+
+    /// Check whether a step might be seen this frame, so that steps whose geometry is entirely
+    /// outside the camera's view can be skipped; by default everything is drawn
+    fn is_step_visible(&self, _pass_index: usize, _step_index: usize) -> bool {
+        true
+    }
 }
 
 pub trait SceneUpdates {
@@ -60,6 +67,29 @@ pub trait SceneUpdates {
     /// Set up the current scene for rendering. If on_time_elapsed caused a screen transition,
     /// this function will be called on the new scene.
     fn on_pre_render(&mut self);
+
+    /// Notify this implementation that source files for its resources have changed on disk, given
+    /// as paths relative to the watched directories. Return the re-decoded resources, keyed by
+    /// their existing indices, for them to be swapped in place; by default nothing is reloaded. If
+    /// any file fails to decode, return the error and leave the scene unchanged, so that its
+    /// current resources stay in use.
+    fn on_resources_changed(&mut self, _changed_paths: &[String]) -> Result<Option<render::ResourcePreloads>, EngineError> {
+        Ok(None)
+    }
+
+    /// Return instances that this implementation moved, added or removed during the last update,
+    /// keyed by the index of the instance buffer they replace the contents of, for them to be
+    /// uploaded before the next frame is drawn; by default nothing changes.
+    fn take_instance_updates(&mut self) -> Option<HashMap<usize, Vec<render::InstanceData>>> {
+        None
+    }
+
+    /// Return vertices that this implementation rebuilt during the last update, such as those of a
+    /// UI that changed, keyed by the index of the dynamic vertex buffer they replace the contents
+    /// of, for them to be uploaded before the next frame is drawn; by default nothing changes.
+    fn take_dynamic_vbo_updates(&mut self) -> Option<HashMap<usize, Vec<model::types::StaticVertex>>> {
+        None
+    }
 }
 
 pub trait Scene: SceneInfo + SceneUpdates {}
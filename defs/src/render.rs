@@ -1,5 +1,9 @@
 
 use model::types::StaticVertex;
+use cgmath::{
+    Matrix4,
+    Vector4
+};
 use raw_window_handle::HasRawWindowHandle;
 use std::collections::HashMap;
 
@@ -25,11 +29,23 @@ pub enum PresentResult {
 pub enum Shader {
     PlainPnt,        // Position-Normal-Texture, R8G8B8A8 texture, no lighting
     PlainPntClipped, // Position-Normal-Texture, R8G8B8A8 texture, no lighting, clip Y
-    Text,            // Position-Normal-Texture, R8 texture, no lighting
+    Text,            // Position-Normal-Texture, R8 texture, no lighting, normal is an RGB tint
+    TextSdf,         // Position-Normal-Texture, distance field texture, no lighting, normal is an
+                     // RGB tint
     Cube,            // Position, cube texture, no lighting
     CubeClipped,     // Position, cube texture, no lighting, clip Y
     Water,           // Position-Normal-Texture, R8G8B8A8 texture, no lighting, projective texture
                      // coords
+    PlainPntInstanced, // Position-Normal-Texture, R8G8B8A8 texture, no lighting, per-instance
+                       // transform and tint
+}
+
+impl Shader {
+
+    /// Whether this shader reads per-instance data, so must be drawn from an instance buffer
+    pub fn is_instanced(&self) -> bool {
+        matches!(self, Shader::PlainPntInstanced)
+    }
 }
 
 /// ImageUsage enum
@@ -43,12 +59,122 @@ pub enum ImageUsage {
 }
 
 /// TexturePixelFormat enum
-/// Abstraction of the set of pixel formats known by the engine
+/// Abstraction of the set of pixel formats known by the engine. The BCn formats are block-
+/// compressed, each 4x4 block of pixels being stored in either 8 or 16 bytes.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum TexturePixelFormat {
     None,
     Rgba,
-    Unorm16
+    RgbaF16,    // RGBA as 16-bit floats, for HDR images
+    Unorm16,
+    Bc1,    // RGB with 1-bit alpha, 8 bytes per block
+    Bc2,    // RGBA with explicit 4-bit alpha, 16 bytes per block
+    Bc3,    // RGBA with interpolated alpha, 16 bytes per block
+    Bc4,    // Single channel, 8 bytes per block
+    Bc5,    // Two channels, 16 bytes per block
+    Bc7     // High quality RGBA, 16 bytes per block
+}
+
+impl TexturePixelFormat {
+
+    /// Whether this is one of the block-compressed formats
+    pub fn is_block_compressed(&self) -> bool {
+        matches!(self,
+            TexturePixelFormat::Bc1 | TexturePixelFormat::Bc2 | TexturePixelFormat::Bc3 |
+            TexturePixelFormat::Bc4 | TexturePixelFormat::Bc5 | TexturePixelFormat::Bc7)
+    }
+
+    /// Size in bytes of a single layer holding the given number of mip levels in this format, or
+    /// None if it does not fit in memory, as may be claimed by the header of a malformed file
+    pub fn checked_layer_size_bytes(&self, width: u32, height: u32, level_count: u32) -> Option<usize> {
+        let mut size: usize = 0;
+        for level in 0..level_count {
+            let level_width = width.checked_shr(level).unwrap_or(0).max(1) as usize;
+            let level_height = height.checked_shr(level).unwrap_or(0).max(1) as usize;
+            let texels_or_blocks = match self.is_block_compressed() {
+                true => level_width.div_ceil(4).checked_mul(level_height.div_ceil(4))?,
+                false => level_width.checked_mul(level_height)?
+            };
+            let unit_size = self.level_size_bytes(1, 1);
+            size = size.checked_add(unit_size.checked_mul(texels_or_blocks)?)?;
+        }
+        Some(size)
+    }
+
+    /// Size in bytes of a single mip level of a single layer in this format
+    pub fn level_size_bytes(&self, width: u32, height: u32) -> usize {
+        let blocks = || (width as usize).div_ceil(4) * (height as usize).div_ceil(4);
+        match self {
+            TexturePixelFormat::None => 0,
+            TexturePixelFormat::Rgba => 4 * width as usize * height as usize,
+            TexturePixelFormat::RgbaF16 => 8 * width as usize * height as usize,
+            TexturePixelFormat::Unorm16 => 2 * width as usize * height as usize,
+            TexturePixelFormat::Bc1 | TexturePixelFormat::Bc4 => 8 * blocks(),
+            TexturePixelFormat::Bc2 | TexturePixelFormat::Bc3 |
+            TexturePixelFormat::Bc5 | TexturePixelFormat::Bc7 => 16 * blocks()
+        }
+    }
+}
+
+/// MipmapMode enum
+/// How the mip chain of a texture is produced. With Provided, each layer's data holds the given
+/// number of levels packed one after another, largest first, each level being half the size of
+/// the previous one (rounding down, to a minimum of 1). GenerateOnGpu falls back to the CPU for
+/// RGBA textures where the device cannot blit and filter the format.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MipmapMode {
+    None,
+    Provided(u32),
+    GenerateOnGpu
+}
+
+/// SamplerFilter enum
+/// Filtering used when sampling between texels or between mip levels
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SamplerFilter {
+    Nearest,
+    Linear
+}
+
+/// SamplerAddressMode enum
+/// Behaviour when sampling outside of the [0, 1] texture coordinate range
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SamplerAddressMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge
+}
+
+/// SamplerConfig struct
+/// Settings for the sampler that shaders will use to read a texture. The W address mode applies
+/// to the third coordinate used to sample cubemaps and 3D textures. Anisotropy of 1.0 or less
+/// disables anisotropic filtering; larger values are clamped to what the device supports, and
+/// ignored if it supports none.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SamplerConfig {
+    pub min_filter: SamplerFilter,
+    pub mag_filter: SamplerFilter,
+    pub mipmap_filter: SamplerFilter,
+    pub address_mode_u: SamplerAddressMode,
+    pub address_mode_v: SamplerAddressMode,
+    pub address_mode_w: SamplerAddressMode,
+    pub max_anisotropy: f32,
+    pub lod_bias: f32
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        SamplerConfig {
+            min_filter: SamplerFilter::Linear,
+            mag_filter: SamplerFilter::Linear,
+            mipmap_filter: SamplerFilter::Linear,
+            address_mode_u: SamplerAddressMode::Repeat,
+            address_mode_v: SamplerAddressMode::Repeat,
+            address_mode_w: SamplerAddressMode::Repeat,
+            max_anisotropy: 1.0,
+            lod_bias: 0.0
+        }
+    }
 }
 
 /// VertexFormat enum
@@ -76,6 +202,35 @@ pub struct VboCreationData {
     pub index_data: Option<Vec<u16>>
 }
 
+/// DynamicVboCreationData struct
+/// Specification for how a dynamic vertex buffer is to be created, for vertices that are rebuilt
+/// often, such as those of a UI. The capacity is the most vertices it can ever hold; the initial
+/// vertices are drawn until they are next updated. Dynamic buffers share indices with other
+/// vertex buffers, and are not drawn indexed.
+pub struct DynamicVboCreationData {
+    pub vertex_format: VertexFormat,
+    pub capacity: usize,
+    pub vertex_data: Vec<StaticVertex>
+}
+
+/// InstanceData struct
+/// Per-instance vertex attributes for instanced shaders: the model matrix placing one copy of a
+/// mesh in the world, and a colour multiplied with its texture
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InstanceData {
+    pub model_matrix: Matrix4<f32>,
+    pub tint: Vector4<f32>
+}
+
+/// InstanceBufferCreationData struct
+/// Specification for how an instance buffer is to be created. The capacity is the most instances
+/// it can ever hold; the initial instances are drawn until they are next updated.
+pub struct InstanceBufferCreationData {
+    pub capacity: usize,
+    pub instances: Vec<InstanceData>
+}
+
 /// TextureCreationData struct
 /// Specification for how a texture resource is to be created
 pub struct TextureCreationData {
@@ -83,7 +238,9 @@ pub struct TextureCreationData {
     pub width: u32,
     pub height: u32,
     pub format: TexturePixelFormat,
-    pub usage: ImageUsage
+    pub usage: ImageUsage,
+    pub mipmaps: MipmapMode,
+    pub sampler: SamplerConfig
 }
 
 /// FramebufferCreationData struct
@@ -99,21 +256,27 @@ pub struct FramebufferCreationData {
 
 /// DrawingStep struct
 /// Description of a rendering step, including the shader and the resources needed by the shader.
+/// Steps with an instance buffer draw the mesh once per instance currently in that buffer, and
+/// must use an instanced shader.
+#[derive(Clone)]
 pub struct DrawingStep {
     pub shader: Shader,
     pub vbo_index: usize,
     pub vbo_format: VertexFormat,
     pub draw_indexed: bool,
     pub texture_indices: Vec<usize>,
-    pub depth_test: bool
+    pub depth_test: bool,
+    pub instance_buffer_index: Option<usize>
 }
 
 /// DrawingPass struct
 /// Description of a set of drawing commands, run in sequence, targeting a single framebuffer
-/// (render target).
+/// (render target). Reversed depth clears the depth buffer to zero and keeps the nearest fragments
+/// by their greater depth, to suit cameras with reversed-Z projections.
 pub struct DrawingPass {
     pub target: FramebufferTarget,
-    pub steps: Vec<DrawingStep>
+    pub steps: Vec<DrawingStep>,
+    pub reversed_depth: bool
 }
 
 /// DrawingDescription
@@ -127,7 +290,9 @@ pub struct DrawingDescription {
 /// order to render a scene.
 pub struct ResourcePreloads {
     pub vbo_preloads: HashMap<usize, VboCreationData>,
-    pub texture_preloads: HashMap<usize, TextureCreationData>
+    pub texture_preloads: HashMap<usize, TextureCreationData>,
+    pub instance_preloads: HashMap<usize, InstanceBufferCreationData>,
+    pub dynamic_vbo_preloads: HashMap<usize, DynamicVboCreationData>
 }
 
 /// RendererApi trait
@@ -163,6 +328,33 @@ pub trait RendererApi {
         description: &DrawingDescription
     ) -> Result<(), crate::EngineError>;
 
+    /// Instruct this implementation to replace the given VBOs and textures, which are already
+    /// loaded, with new data; any indices not already loaded will be loaded. Anything that refers
+    /// to the replaced resources is rebuilt using the given description.
+    fn replace_scene_resources(
+        &mut self,
+        replacements: &ResourcePreloads,
+        description: &DrawingDescription
+    ) -> Result<(), crate::EngineError>;
+
+    /// Instruct this implementation to replace the contents of an instance buffer, which is
+    /// already loaded, with the given instances; there must be no more than its capacity. Steps
+    /// drawing from the buffer use the new instances from the next frame drawn.
+    fn update_instance_data(
+        &mut self,
+        instance_buffer_index: usize,
+        instances: &[InstanceData]
+    ) -> Result<(), crate::EngineError>;
+
+    /// Instruct this implementation to replace the vertices of a dynamic vertex buffer, which is
+    /// already loaded, without rebuilding anything; there must be no more than its capacity. Steps
+    /// drawing from the buffer use the new vertices from the next frame drawn.
+    fn update_dynamic_vbo_data(
+        &mut self,
+        vbo_index: usize,
+        vertices: &[StaticVertex]
+    ) -> Result<(), crate::EngineError>;
+
     /// Retrueve the current aspect ratio of the client area, as known to this implementation
     fn get_aspect_ratio(&self) -> f32;
 }
@@ -14,10 +14,25 @@ pub struct MvpClippingUbo {
     pub unused: [f32; 2]
 }
 
+/// TextPaintUbo struct
+/// Uniforms for both text shaders. Only the camera matrix and paint color are used by the plain
+/// text shader; the rest control the distance field shader. Widths and the shadow offset are in
+/// texels of the glyph texture, and the distance range is the number of texels over which the
+/// distance field runs from fully outside to fully inside. Set multi_channel to 1.0 for MSDF
+/// textures and 0.0 for single channel ones.
 #[repr(C)]
 pub struct TextPaintUbo {
     pub camera_matrix: Matrix4<f32>,
-    pub paint_color: Vector4<f32>
+    pub paint_color: Vector4<f32>,
+    pub outline_color: Vector4<f32>,
+    pub shadow_color: Vector4<f32>,
+    pub glow_color: Vector4<f32>,
+    pub shadow_offset: [f32; 2],
+    pub outline_width: f32,
+    pub glow_width: f32,
+    pub distance_range: f32,
+    pub multi_channel: f32,
+    pub unused: [f32; 2]
 }
 
 #[repr(C)]
@@ -0,0 +1,130 @@
+
+use cgmath::{
+    Matrix4,
+    Rad
+};
+use crate::camera::{
+    make_vulkan_perspective_matrix,
+    make_vulkan_reversed_z_perspective_matrix
+};
+
+/// CameraConfig struct
+/// Projection and movement settings for the player camera. The defaults give the projection,
+/// movement and terrain following of its original fixed behaviour.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct CameraConfig {
+    pub projection: ProjectionConfig,
+    pub movement: MovementTuning,
+    pub terrain: TerrainFollowing
+}
+
+/// ProjectionConfig struct
+/// The perspective projection of a camera: its vertical field of view, its near and far clip
+/// planes, and whether depth is reversed so that the far plane maps to 0. The defaults give a 90
+/// degree field of view, clip planes at 1 and 100, and conventional depth.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProjectionConfig {
+    pub field_of_view: Rad<f32>,
+    pub near_plane: f32,
+    pub far_plane: f32,
+    pub reversed_z: bool
+}
+
+impl Default for ProjectionConfig {
+
+    /// Settings matching the player camera's original fixed behaviour
+    fn default() -> ProjectionConfig {
+        ProjectionConfig {
+            field_of_view: Rad(std::f32::consts::FRAC_PI_2),
+            near_plane: 1.0,
+            far_plane: 100.0,
+            reversed_z: false
+        }
+    }
+}
+
+impl ProjectionConfig {
+
+    /// Settings for cameras that look around a scene rather than play through it - the orbit,
+    /// free-fly and flight path cameras - with a 60 degree field of view and clip planes at 0.1
+    /// and 100
+    pub fn spectator() -> ProjectionConfig {
+        ProjectionConfig {
+            field_of_view: Rad(std::f32::consts::FRAC_PI_3),
+            near_plane: 0.1,
+            ..ProjectionConfig::default()
+        }
+    }
+
+    /// Create the projection matrix for these settings at the given aspect ratio
+    pub fn make_projection_matrix(&self, aspect_ratio: f32) -> Matrix4<f32> {
+        match self.reversed_z {
+            true => make_vulkan_reversed_z_perspective_matrix(
+                aspect_ratio,
+                self.field_of_view,
+                self.near_plane,
+                self.far_plane),
+            false => make_vulkan_perspective_matrix(
+                aspect_ratio,
+                self.field_of_view,
+                self.near_plane,
+                self.far_plane)
+        }
+    }
+}
+
+/// MovementTuning struct
+/// Speeds and rates for cameras with momentum. Linear speeds are in units per second and angular
+/// speeds in radians per second, with accelerations and decelerations per second. Inputs smaller
+/// than the deadzone are treated as released.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MovementTuning {
+    pub deadzone: f32,
+    pub max_speed: f32,
+    pub max_reverse_speed: f32,
+    pub acceleration: f32,
+    pub deceleration: f32,
+    pub max_angular_speed: f32,
+    pub angular_acceleration: f32,
+    pub angular_deceleration: f32
+}
+
+impl Default for MovementTuning {
+
+    /// Tuning matching the player camera's original fixed behaviour
+    fn default() -> MovementTuning {
+        MovementTuning {
+            deadzone: 0.01,
+            max_speed: 8.0,
+            max_reverse_speed: 3.0,
+            acceleration: 9.0,
+            deceleration: 25.0,
+            max_angular_speed: 3.0,
+            angular_acceleration: 4.0,
+            angular_deceleration: 10.0
+        }
+    }
+}
+
+/// TerrainFollowing struct
+/// How a camera rides over the ground of a collision world: the height of the eye above the
+/// ground, the rate per second at which it closes the gap to that height, and the radius it keeps
+/// clear of walls and the edges of the world
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TerrainFollowing {
+    pub eye_height: f32,
+    pub smoothing: f32,
+    pub collision_radius: f32
+}
+
+impl Default for TerrainFollowing {
+
+    /// Eye height and radius to suit the scale of the example scenes
+    fn default() -> TerrainFollowing {
+        TerrainFollowing {
+            eye_height: 2.0,
+            smoothing: 8.0,
+            collision_radius: 0.5
+        }
+    }
+}
@@ -0,0 +1,426 @@
+
+use defs::{
+    Camera,
+    control::Control
+};
+use cgmath::{
+    InnerSpace,
+    Matrix,
+    Matrix3,
+    Matrix4,
+    Quaternion,
+    Rad,
+    Vector3
+};
+use crate::camera::{
+    flight_path::Easing,
+    make_look_at_view_matrix
+};
+
+/// Shake struct
+/// Modifier that shakes the view of another camera by an amount of trauma, which is added by
+/// impacts and wears off over time. The shake grows with the square of the trauma, so that small
+/// knocks are subtle and big ones violent, and follows smooth noise rather than jittering.
+pub struct Shake<C: Camera> {
+    inner: C,
+    trauma: f32,
+    decay: f32,
+    max_angle: f32,
+    max_offset: f32,
+    frequency: f32,
+    elapsed_secs: f32
+}
+
+impl<C: Camera> Shake<C> {
+
+    /// Default trauma lost per second, largest turn in radians, largest offset in units, and rate
+    /// of noise in changes per second
+    const DEFAULT_DECAY: f32 = 1.0;
+    const DEFAULT_MAX_ANGLE: f32 = 0.1;
+    const DEFAULT_MAX_OFFSET: f32 = 0.3;
+    const DEFAULT_FREQUENCY: f32 = 15.0;
+
+    /// Wrap a camera, initially without any trauma
+    pub fn new(inner: C) -> Shake<C> {
+        Shake {
+            inner,
+            trauma: 0.0,
+            decay: Self::DEFAULT_DECAY,
+            max_angle: Self::DEFAULT_MAX_ANGLE,
+            max_offset: Self::DEFAULT_MAX_OFFSET,
+            frequency: Self::DEFAULT_FREQUENCY,
+            elapsed_secs: 0.0
+        }
+    }
+
+    /// Add trauma, where the total is kept between 0 and 1
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Set the turn in radians and offset in units at full trauma
+    pub fn set_limits(&mut self, max_angle: f32, max_offset: f32) {
+        self.max_angle = max_angle;
+        self.max_offset = max_offset;
+    }
+
+    /// Set the trauma lost per second
+    pub fn set_decay(&mut self, decay: f32) {
+        self.decay = decay;
+    }
+
+    /// Set how many times per second the shake changes direction
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+    }
+
+    pub fn get_trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Camera> Camera for Shake<C> {
+
+    fn update_aspect(&mut self, aspect_ratio: f32) {
+        self.inner.update_aspect(aspect_ratio);
+    }
+
+    /// Update the inner camera, and let the trauma wear off
+    fn update(&mut self, time_step_millis: u64, controller: &dyn Control) {
+        let time_step_secs: f32 = 0.001 * time_step_millis as f32;
+        self.inner.update(time_step_millis, controller);
+        self.elapsed_secs += time_step_secs;
+        self.trauma = (self.trauma - self.decay * time_step_secs).max(0.0);
+    }
+
+    /// Get the inner camera's view, turned and offset by noise scaled by the shake
+    fn get_view_matrix(&self) -> Matrix4<f32> {
+        let shake = self.trauma * self.trauma;
+        if shake == 0.0 {
+            return self.inner.get_view_matrix();
+        }
+        let time = self.elapsed_secs * self.frequency;
+        let angle = |seed: f32| Rad(self.max_angle * shake * smooth_noise(seed, time));
+        let offset = Vector3::new(
+            self.max_offset * shake * smooth_noise(4.0, time),
+            self.max_offset * shake * smooth_noise(5.0, time),
+            self.max_offset * shake * smooth_noise(6.0, time));
+        Matrix4::from_angle_z(angle(1.0)) *
+            Matrix4::from_angle_x(angle(2.0)) *
+            Matrix4::from_angle_y(angle(3.0)) *
+            Matrix4::from_translation(offset) *
+            self.inner.get_view_matrix()
+    }
+
+    fn get_projection_matrix(&self) -> Matrix4<f32> {
+        self.inner.get_projection_matrix()
+    }
+}
+
+/// SmoothFollow struct
+/// Modifier that trails a target, such as a moving model, from an offset in the target's own
+/// space, always facing the target's origin. Movement towards the offset is critically damped, so
+/// the camera catches up as fast as it can without overshooting. The projection is taken from the
+/// inner camera, whose view is unused.
+pub struct SmoothFollow<C: Camera> {
+    inner: C,
+    target_transform: Matrix4<f32>,
+    offset: Vector3<f32>,
+    smooth_time_secs: f32,
+    position: Vector3<f32>,
+    velocity: Vector3<f32>
+}
+
+impl<C: Camera> SmoothFollow<C> {
+
+    /// Wrap a camera, following from an offset relative to the target with the given time to
+    /// roughly catch up, placed at the offset from a target at the origin
+    pub fn new(inner: C, offset: Vector3<f32>, smooth_time_secs: f32) -> SmoothFollow<C> {
+        SmoothFollow {
+            inner,
+            target_transform: Matrix4::from_scale(1.0),
+            offset,
+            smooth_time_secs: smooth_time_secs.max(0.0001),
+            position: offset,
+            velocity: Vector3::new(0.0, 0.0, 0.0)
+        }
+    }
+
+    /// Set the model transform of the target, which the camera will move after
+    pub fn set_target_transform(&mut self, transform: Matrix4<f32>) {
+        self.target_transform = transform;
+    }
+
+    /// Move straight to the offset from the target, such as after a cut
+    pub fn snap_to_target(&mut self) {
+        self.position = self.desired_position();
+        self.velocity = Vector3::new(0.0, 0.0, 0.0);
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn target_position(&self) -> Vector3<f32> {
+        self.target_transform.w.truncate()
+    }
+
+    fn desired_position(&self) -> Vector3<f32> {
+        (self.target_transform * self.offset.extend(1.0)).truncate()
+    }
+}
+
+impl<C: Camera> Camera for SmoothFollow<C> {
+
+    fn update_aspect(&mut self, aspect_ratio: f32) {
+        self.inner.update_aspect(aspect_ratio);
+    }
+
+    /// Update the inner camera, and move towards the offset from the target as a critically
+    /// damped spring
+    fn update(&mut self, time_step_millis: u64, controller: &dyn Control) {
+        let time_step_secs: f32 = 0.001 * time_step_millis as f32;
+        self.inner.update(time_step_millis, controller);
+        let omega = 2.0 / self.smooth_time_secs;
+        let x = omega * time_step_secs;
+        let decay = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+        let desired = self.desired_position();
+        let change = self.position - desired;
+        let impulse = (self.velocity + change * omega) * time_step_secs;
+        self.velocity = (self.velocity - impulse * omega) * decay;
+        self.position = desired + (change + impulse) * decay;
+    }
+
+    /// Get the view from the current position towards the target
+    fn get_view_matrix(&self) -> Matrix4<f32> {
+        make_look_at_view_matrix(self.position, self.target_position())
+    }
+
+    fn get_projection_matrix(&self) -> Matrix4<f32> {
+        self.inner.get_projection_matrix()
+    }
+}
+
+/// FovKick struct
+/// Modifier that briefly widens the view of another camera, as for a burst of speed, then eases
+/// back to the inner camera's own field of view
+pub struct FovKick<C: Camera> {
+    inner: C,
+    kick: f32,
+    recovery: f32
+}
+
+impl<C: Camera> FovKick<C> {
+
+    /// Default rate per second at which a kick wears off
+    const DEFAULT_RECOVERY: f32 = 4.0;
+
+    /// Wrap a camera, initially without any kick
+    pub fn new(inner: C) -> FovKick<C> {
+        FovKick {
+            inner,
+            kick: 0.0,
+            recovery: Self::DEFAULT_RECOVERY
+        }
+    }
+
+    /// Widen the view, where an amount of 0.25 shows a quarter as much again in each direction
+    pub fn kick(&mut self, amount: f32) {
+        self.kick = (self.kick + amount).max(0.0);
+    }
+
+    /// Set the rate per second at which a kick wears off
+    pub fn set_recovery(&mut self, recovery: f32) {
+        self.recovery = recovery;
+    }
+
+    pub fn get_kick(&self) -> f32 {
+        self.kick
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Camera> Camera for FovKick<C> {
+
+    fn update_aspect(&mut self, aspect_ratio: f32) {
+        self.inner.update_aspect(aspect_ratio);
+    }
+
+    /// Update the inner camera, and let the kick wear off
+    fn update(&mut self, time_step_millis: u64, controller: &dyn Control) {
+        let time_step_secs: f32 = 0.001 * time_step_millis as f32;
+        self.inner.update(time_step_millis, controller);
+        self.kick *= (-self.recovery * time_step_secs).exp();
+    }
+
+    fn get_view_matrix(&self) -> Matrix4<f32> {
+        self.inner.get_view_matrix()
+    }
+
+    /// Get the inner camera's projection, scaled down across the screen by the kick
+    fn get_projection_matrix(&self) -> Matrix4<f32> {
+        let mut projection = self.inner.get_projection_matrix();
+        let scale = 1.0 / (1.0 + self.kick);
+        projection.x.x *= scale;
+        projection.y.y *= scale;
+        projection
+    }
+}
+
+/// CameraTransition struct
+/// Blends from one camera to another over a duration, moving the eye in a straight line and
+/// turning it smoothly between the two views, while both cameras carry on updating. Once finished,
+/// it shows the second camera exactly.
+pub struct CameraTransition<A: Camera, B: Camera> {
+    from: A,
+    to: B,
+    duration_secs: f32,
+    elapsed_secs: f32,
+    easing: Easing
+}
+
+impl<A: Camera, B: Camera> CameraTransition<A, B> {
+
+    /// Start a transition taking the given number of seconds, easing in and out
+    pub fn new(from: A, to: B, duration_secs: f32) -> CameraTransition<A, B> {
+        CameraTransition {
+            from,
+            to,
+            duration_secs,
+            elapsed_secs: 0.0,
+            easing: Easing::EaseInOut
+        }
+    }
+
+    /// Change how the blend speeds up and slows down over the duration
+    pub fn with_easing(mut self, easing: Easing) -> CameraTransition<A, B> {
+        self.easing = easing;
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed_secs >= self.duration_secs
+    }
+
+    /// Get how far the view is blended towards the second camera, from 0 to 1
+    pub fn get_blend(&self) -> f32 {
+        match self.duration_secs > 0.0 {
+            true => self.easing.apply(self.elapsed_secs / self.duration_secs),
+            false => 1.0
+        }
+    }
+
+    pub fn get_from(&self) -> &A {
+        &self.from
+    }
+
+    pub fn get_from_mut(&mut self) -> &mut A {
+        &mut self.from
+    }
+
+    pub fn get_to(&self) -> &B {
+        &self.to
+    }
+
+    pub fn get_to_mut(&mut self) -> &mut B {
+        &mut self.to
+    }
+
+    /// Take the second camera, to use directly once the transition has finished
+    pub fn into_to(self) -> B {
+        self.to
+    }
+}
+
+impl<A: Camera, B: Camera> Camera for CameraTransition<A, B> {
+
+    fn update_aspect(&mut self, aspect_ratio: f32) {
+        self.from.update_aspect(aspect_ratio);
+        self.to.update_aspect(aspect_ratio);
+    }
+
+    /// Update both cameras, and advance the blend
+    fn update(&mut self, time_step_millis: u64, controller: &dyn Control) {
+        self.from.update(time_step_millis, controller);
+        self.to.update(time_step_millis, controller);
+        self.elapsed_secs = (self.elapsed_secs + 0.001 * time_step_millis as f32).min(self.duration_secs);
+    }
+
+    /// Get a view with the eye position and orientation blended between the two cameras
+    fn get_view_matrix(&self) -> Matrix4<f32> {
+        let blend = self.get_blend();
+        if blend >= 1.0 {
+            return self.to.get_view_matrix();
+        }
+        let (from_rotation, from_eye) = decompose_view_matrix(&self.from.get_view_matrix());
+        let (to_rotation, to_eye) = decompose_view_matrix(&self.to.get_view_matrix());
+        let to_rotation = match from_rotation.dot(to_rotation) < 0.0 {
+            true => -to_rotation,
+            false => to_rotation
+        };
+        let rotation = from_rotation.slerp(to_rotation, blend);
+        let eye = from_eye + (to_eye - from_eye) * blend;
+        Matrix4::from(rotation) * Matrix4::from_translation(-eye)
+    }
+
+    /// Get the projection blended between the two cameras
+    fn get_projection_matrix(&self) -> Matrix4<f32> {
+        let from = self.from.get_projection_matrix();
+        let to = self.to.get_projection_matrix();
+        from + (to - from) * self.get_blend()
+    }
+}
+
+/// Split a view matrix with no scaling into its rotation and the position of the eye
+fn decompose_view_matrix(view: &Matrix4<f32>) -> (Quaternion<f32>, Vector3<f32>) {
+    let rotation = Matrix3::from_cols(view.x.truncate(), view.y.truncate(), view.z.truncate());
+    let eye = -(rotation.transpose() * view.w.truncate());
+    (Quaternion::from(rotation).normalize(), eye)
+}
+
+/// Get smooth noise between -1 and 1, varying over time, with a different pattern for each seed
+fn smooth_noise(seed: f32, time: f32) -> f32 {
+    let hash = |n: f32| {
+        let value = (n * 12.9898 + seed * 78.233).sin() * 43758.547;
+        (value - value.floor()) * 2.0 - 1.0
+    };
+    let start = time.floor();
+    let t = time - start;
+    let blend = t * t * (3.0 - 2.0 * t);
+    hash(start) + (hash(start + 1.0) - hash(start)) * blend
+}
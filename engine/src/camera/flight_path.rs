@@ -0,0 +1,390 @@
+
+use defs::{
+    Camera,
+    EngineError,
+    control::Control
+};
+use cgmath::{
+    InnerSpace,
+    Matrix4,
+    Vector3
+};
+use crate::camera::{
+    config::ProjectionConfig,
+    make_look_at_view_matrix
+};
+
+/// Number of straight pieces each segment is split into when measuring the length of a path
+const LENGTH_SAMPLES_PER_SEGMENT: usize = 16;
+
+/// SplineKind enum
+/// The kinds of curve that a flight path can follow
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SplineKind {
+    /// Passes through every control point, with a smooth curve between each pair
+    CatmullRom,
+    /// Cubic Bezier segments, given as an anchor point followed by two handles and the next anchor
+    /// for each segment. Passes through the anchors only.
+    Bezier
+}
+
+/// Easing enum
+/// How progress along a flight path speeds up and slows down over its duration
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut
+}
+
+impl Easing {
+
+    /// Map a fraction of the duration onto a fraction of the distance along the path
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+/// FlightPath struct
+/// A spline through authored control points, travelled at an even speed over a duration, with
+/// optional points to look at along the way. Look-at targets are given one per key point - each
+/// control point of a Catmull-Rom spline, or each anchor of a Bezier spline - and are blended
+/// smoothly between keys. Without them, the camera looks along the path.
+pub struct FlightPath {
+    kind: SplineKind,
+    points: Vec<Vector3<f32>>,
+    look_at_targets: Option<Vec<Vector3<f32>>>,
+    duration_secs: f32,
+    easing: Easing,
+    looping: bool,
+    segment_lengths: Vec<f32>
+}
+
+impl FlightPath {
+
+    /// Create a new path taking the given number of seconds to travel, with linear easing. A
+    /// looping path joins its end back to its start and repeats. Catmull-Rom splines need at least
+    /// two points, and Bezier splines need three points per segment plus the final anchor, which
+    /// is omitted when looping as it is the first point.
+    pub fn new(
+        kind: SplineKind,
+        points: Vec<Vector3<f32>>,
+        duration_secs: f32,
+        looping: bool
+    ) -> Result<FlightPath, EngineError> {
+        let mut path = FlightPath {
+            kind,
+            points,
+            look_at_targets: None,
+            duration_secs,
+            easing: Easing::Linear,
+            looping,
+            segment_lengths: vec![]
+        };
+        path.validate_points()?;
+        if duration_secs <= 0.0 {
+            return Err(EngineError::GeneralError(format!("Flight path duration must be positive: {}", duration_secs)));
+        }
+        path.measure();
+        Ok(path)
+    }
+
+    /// Read the points of a curve exported from Blender as Wavefront OBJ, where the curve becomes
+    /// a chain of vertices joined by line elements, and make a Catmull-Rom path through them. The
+    /// export is expected to use Blender's default axes of Y up and -Z forward, which are mapped
+    /// back to Blender's own axes so that the path lines up with models imported from Collada,
+    /// which keep them unchanged.
+    pub fn from_obj_curve(obj_text: &str, duration_secs: f32, looping: bool) -> Result<FlightPath, EngineError> {
+        let mut vertices: Vec<Vector3<f32>> = vec![];
+        let mut chain: Vec<usize> = vec![];
+        for (line_index, line) in obj_text.lines().enumerate() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => {
+                    let coords: Vec<f32> = parts.take(3)
+                        .map(|part| part.parse::<f32>())
+                        .collect::<Result<Vec<f32>, _>>()
+                        .map_err(|_| EngineError::DecodeError(format!("Bad vertex in OBJ curve, line {}", line_index + 1)))?;
+                    if coords.len() != 3 {
+                        return Err(EngineError::DecodeError(format!("Bad vertex in OBJ curve, line {}", line_index + 1)));
+                    }
+                    vertices.push(Vector3::new(coords[0], -coords[2], coords[1]));
+                },
+                Some("l") => {
+                    for part in parts {
+                        let index = resolve_obj_index(part, vertices.len())
+                            .ok_or_else(|| EngineError::DecodeError(format!("Bad line element in OBJ curve, line {}", line_index + 1)))?;
+                        if chain.last() != Some(&index) {
+                            chain.push(index);
+                        }
+                    }
+                },
+                _ => ()
+            }
+        }
+        let points = match chain.is_empty() {
+            true => vertices,
+            false => chain.iter().map(|index| vertices[*index]).collect()
+        };
+        FlightPath::new(SplineKind::CatmullRom, points, duration_secs, looping)
+    }
+
+    /// Set points to look at, one per key point of the path
+    pub fn with_look_at_targets(mut self, targets: Vec<Vector3<f32>>) -> Result<FlightPath, EngineError> {
+        if targets.len() != self.key_count() {
+            return Err(EngineError::GeneralError(format!(
+                "Flight path needs {} look-at targets, but {} were given", self.key_count(), targets.len())));
+        }
+        self.look_at_targets = Some(targets);
+        Ok(self)
+    }
+
+    /// Set how progress speeds up and slows down over the duration
+    pub fn with_easing(mut self, easing: Easing) -> FlightPath {
+        self.easing = easing;
+        self
+    }
+
+    pub fn get_duration_secs(&self) -> f32 {
+        self.duration_secs
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// The total length of the path
+    pub fn length(&self) -> f32 {
+        self.segment_lengths.iter().sum()
+    }
+
+    /// Get the position and the point to look at, a given number of seconds after starting
+    pub fn sample(&self, elapsed_secs: f32) -> (Vector3<f32>, Vector3<f32>) {
+        let time_fraction = match self.looping {
+            true => (elapsed_secs / self.duration_secs).rem_euclid(1.0),
+            false => (elapsed_secs / self.duration_secs).clamp(0.0, 1.0)
+        };
+        let (segment, u) = self.locate(self.easing.apply(time_fraction) * self.length());
+        let position = self.segment_point(segment, u);
+        let look_at = match &self.look_at_targets {
+            Some(targets) => catmull_rom(
+                targets[self.key_index(segment as isize - 1)],
+                targets[self.key_index(segment as isize)],
+                targets[self.key_index(segment as isize + 1)],
+                targets[self.key_index(segment as isize + 2)],
+                u),
+            None => {
+                let ahead = self.segment_point(segment, (u + 0.01).min(1.0));
+                let behind = self.segment_point(segment, (u - 0.01).max(0.0));
+                position + (ahead - behind)
+            }
+        };
+        (position, look_at)
+    }
+
+    fn validate_points(&self) -> Result<(), EngineError> {
+        let count = self.points.len();
+        let valid = match (self.kind, self.looping) {
+            (SplineKind::CatmullRom, _) => count >= 2,
+            (SplineKind::Bezier, false) => count >= 4 && count % 3 == 1,
+            (SplineKind::Bezier, true) => count >= 3 && count.is_multiple_of(3)
+        };
+        match valid {
+            true => Ok(()),
+            false => Err(EngineError::GeneralError(
+                format!("Wrong number of points for {:?} flight path: {}", self.kind, count)))
+        }
+    }
+
+    /// The number of points the path passes through, and the number of look-at targets it takes
+    fn key_count(&self) -> usize {
+        match self.kind {
+            SplineKind::CatmullRom => self.points.len(),
+            SplineKind::Bezier => self.points.len().div_ceil(3)
+        }
+    }
+
+    fn segment_count(&self) -> usize {
+        match self.looping {
+            true => self.key_count(),
+            false => self.key_count() - 1
+        }
+    }
+
+    /// Find a key point by index, wrapping around for looping paths and repeating the end points
+    /// otherwise
+    fn key_index(&self, index: isize) -> usize {
+        let count = self.key_count() as isize;
+        match self.looping {
+            true => index.rem_euclid(count) as usize,
+            false => index.clamp(0, count - 1) as usize
+        }
+    }
+
+    /// Get a point a fraction of the way along a segment
+    fn segment_point(&self, segment: usize, u: f32) -> Vector3<f32> {
+        match self.kind {
+            SplineKind::CatmullRom => catmull_rom(
+                self.points[self.key_index(segment as isize - 1)],
+                self.points[self.key_index(segment as isize)],
+                self.points[self.key_index(segment as isize + 1)],
+                self.points[self.key_index(segment as isize + 2)],
+                u),
+            SplineKind::Bezier => {
+                let start = segment * 3;
+                let end = (start + 3) % self.points.len();
+                bezier(self.points[start], self.points[start + 1], self.points[start + 2], self.points[end], u)
+            }
+        }
+    }
+
+    /// Measure each segment, so that the path can be travelled at an even speed
+    fn measure(&mut self) {
+        self.segment_lengths = (0..self.segment_count())
+            .map(|segment| {
+                (0..LENGTH_SAMPLES_PER_SEGMENT)
+                    .map(|step| {
+                        let u0 = step as f32 / LENGTH_SAMPLES_PER_SEGMENT as f32;
+                        let u1 = (step + 1) as f32 / LENGTH_SAMPLES_PER_SEGMENT as f32;
+                        (self.segment_point(segment, u1) - self.segment_point(segment, u0)).magnitude()
+                    })
+                    .sum()
+            })
+            .collect();
+    }
+
+    /// Find the segment, and fraction along it, that is a given distance along the path
+    fn locate(&self, distance: f32) -> (usize, f32) {
+        let mut remaining = distance;
+        for (segment, length) in self.segment_lengths.iter().enumerate() {
+            if remaining <= *length || segment == self.segment_lengths.len() - 1 {
+                let u = match *length > 0.0 {
+                    true => (remaining / length).clamp(0.0, 1.0),
+                    false => 0.0
+                };
+                return (segment, u);
+            }
+            remaining -= length;
+        }
+        (0, 0.0)
+    }
+}
+
+/// FlightPathCamera struct
+/// Camera that flies along a FlightPath without responding to input, as for cutscenes and
+/// attract-mode flyovers. Stops at the end of the path unless it loops.
+pub struct FlightPathCamera {
+    path: FlightPath,
+    elapsed_secs: f32,
+    position: Vector3<f32>,
+    look_at: Vector3<f32>,
+    projection: ProjectionConfig,
+    perspective_projection: Matrix4<f32>
+}
+
+impl FlightPathCamera {
+
+    /// Creates a new camera at the start of the path, with the spectator projection
+    pub fn new(aspect_ratio: f32, path: FlightPath) -> FlightPathCamera {
+        Self::with_projection(aspect_ratio, path, ProjectionConfig::spectator())
+    }
+
+    /// Creates a new camera at the start of the path, projecting as per the supplied config
+    pub fn with_projection(aspect_ratio: f32, path: FlightPath, projection: ProjectionConfig) -> FlightPathCamera {
+        let (position, look_at) = path.sample(0.0);
+        FlightPathCamera {
+            path,
+            elapsed_secs: 0.0,
+            position,
+            look_at,
+            projection,
+            perspective_projection: projection.make_projection_matrix(aspect_ratio)
+        }
+    }
+
+    /// Jump to a time along the path
+    pub fn set_elapsed_secs(&mut self, elapsed_secs: f32) {
+        self.elapsed_secs = elapsed_secs;
+        let (position, look_at) = self.path.sample(elapsed_secs);
+        self.position = position;
+        self.look_at = look_at;
+    }
+
+    /// Check whether the camera has reached the end of a path that does not loop
+    pub fn is_finished(&self) -> bool {
+        !self.path.is_looping() && self.elapsed_secs >= self.path.get_duration_secs()
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    pub fn get_look_at(&self) -> Vector3<f32> {
+        self.look_at
+    }
+}
+
+impl Camera for FlightPathCamera {
+
+    /// Updates the projection matrix to fit the new aspect ratio
+    fn update_aspect(&mut self, aspect_ratio: f32) {
+        self.perspective_projection = self.projection.make_projection_matrix(aspect_ratio);
+    }
+
+    /// Move along the path by the time step, ignoring the controller
+    fn update(&mut self, time_step_millis: u64, _controller: &dyn Control) {
+        let mut elapsed_secs = self.elapsed_secs + 0.001 * time_step_millis as f32;
+        if self.path.is_looping() {
+            elapsed_secs %= self.path.get_duration_secs();
+        }
+        self.set_elapsed_secs(elapsed_secs.min(self.path.get_duration_secs()));
+    }
+
+    /// Get the view matrix, facing the look-at point from the current position
+    fn get_view_matrix(&self) -> Matrix4<f32> {
+        make_look_at_view_matrix(self.position, self.look_at)
+    }
+
+    /// Get the stored perspective projection matrix
+    fn get_projection_matrix(&self) -> Matrix4<f32> {
+        self.perspective_projection
+    }
+}
+
+/// Resolve a vertex reference from an OBJ element, which counts from 1, or backwards from the
+/// latest vertex if negative
+fn resolve_obj_index(reference: &str, vertex_count: usize) -> Option<usize> {
+    let index = reference.split('/').next()?.parse::<isize>().ok()?;
+    let resolved = match index {
+        i if i > 0 => i - 1,
+        i if i < 0 => vertex_count as isize + i,
+        _ => return None
+    };
+    match resolved >= 0 && (resolved as usize) < vertex_count {
+        true => Some(resolved as usize),
+        false => None
+    }
+}
+
+/// Evaluate a uniform Catmull-Rom segment between p1 and p2
+fn catmull_rom(p0: Vector3<f32>, p1: Vector3<f32>, p2: Vector3<f32>, p3: Vector3<f32>, u: f32) -> Vector3<f32> {
+    let u2 = u * u;
+    let u3 = u2 * u;
+    (p1 * 2.0
+        + (p2 - p0) * u
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * u2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * u3) * 0.5
+}
+
+/// Evaluate a cubic Bezier segment
+fn bezier(p0: Vector3<f32>, p1: Vector3<f32>, p2: Vector3<f32>, p3: Vector3<f32>, u: f32) -> Vector3<f32> {
+    let v = 1.0 - u;
+    p0 * (v * v * v) + p1 * (3.0 * v * v * u) + p2 * (3.0 * v * u * u) + p3 * (u * u * u)
+}
@@ -0,0 +1,140 @@
+
+use defs::{
+    Camera,
+    control::{
+        Control,
+        InputEvent,
+        InputState,
+        KeyCode,
+        MouseButton
+    }
+};
+use cgmath::{
+    Matrix4,
+    Vector3
+};
+use crate::camera::{
+    MouseDrag,
+    config::ProjectionConfig,
+    make_yaw_pitch_view_matrix,
+    yaw_pitch_forward
+};
+
+/// FreeFlyCamera struct
+/// Debug camera that flies freely in any direction. Dragging with the right mouse button looks
+/// around, up and down inputs fly forwards and backwards in the direction being looked, left and
+/// right inputs move sideways, and the page up and page down keys rise and descend. Moves at a
+/// constant speed, without momentum.
+pub struct FreeFlyCamera {
+    position: Vector3<f32>,
+    yaw: f32,
+    pitch: f32,
+    speed: f32,
+    rising: bool,
+    descending: bool,
+    drag: MouseDrag,
+    projection: ProjectionConfig,
+    perspective_projection: Matrix4<f32>
+}
+
+impl FreeFlyCamera {
+
+    /// Default speed of flying in units per second, and of looking in radians per window width or
+    /// height dragged
+    const DEFAULT_SPEED: f32 = 5.0;
+    const LOOK_SPEED: f32 = std::f32::consts::PI;
+
+    /// Limit on pitch, just short of looking straight up or down
+    const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+    /// Creates a new camera at a position, turned to the given yaw and pitch, where positive pitch
+    /// looks downwards, with the spectator projection
+    pub fn new(aspect_ratio: f32, position: Vector3<f32>, yaw: f32, pitch: f32) -> FreeFlyCamera {
+        Self::with_projection(aspect_ratio, position, yaw, pitch, ProjectionConfig::spectator())
+    }
+
+    /// Creates a new camera as per new, projecting as per the supplied config
+    pub fn with_projection(
+        aspect_ratio: f32,
+        position: Vector3<f32>,
+        yaw: f32,
+        pitch: f32,
+        projection: ProjectionConfig
+    ) -> FreeFlyCamera {
+        FreeFlyCamera {
+            position,
+            yaw,
+            pitch: pitch.clamp(-Self::MAX_PITCH, Self::MAX_PITCH),
+            speed: Self::DEFAULT_SPEED,
+            rising: false,
+            descending: false,
+            drag: MouseDrag::new(MouseButton::Right),
+            projection,
+            perspective_projection: projection.make_projection_matrix(aspect_ratio)
+        }
+    }
+
+    /// Set the speed of flying, in units per second
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    pub fn get_yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn get_pitch(&self) -> f32 {
+        self.pitch
+    }
+}
+
+impl Camera for FreeFlyCamera {
+
+    /// Updates the projection matrix to fit the new aspect ratio
+    fn update_aspect(&mut self, aspect_ratio: f32) {
+        self.perspective_projection = self.projection.make_projection_matrix(aspect_ratio);
+    }
+
+    /// Look and fly as per the mouse dragging, directional inputs and page keys in the supplied
+    /// controller
+    fn update(&mut self, time_step_millis: u64, controller: &dyn Control) {
+        let time_step_secs: f32 = 0.001 * time_step_millis as f32;
+
+        for event in controller.get_frame_events().iter() {
+            match event {
+                InputEvent::Key(KeyCode::PageUp, state) => self.rising = *state == InputState::Pressed,
+                InputEvent::Key(KeyCode::PageDown, state) => self.descending = *state == InputState::Pressed,
+                _ => ()
+            }
+        }
+
+        let (drag_x, drag_y) = self.drag.update(controller);
+        self.yaw = (self.yaw - Self::LOOK_SPEED * drag_x) % (2.0 * std::f32::consts::PI);
+        self.pitch = (self.pitch + Self::LOOK_SPEED * drag_y).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+
+        // Up is -y, as the view looks along +z with y downwards
+        let forward = yaw_pitch_forward(self.yaw, self.pitch);
+        let right = Vector3::new(self.yaw.cos(), 0.0, self.yaw.sin());
+        let vertical = match (self.rising, self.descending) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0
+        };
+        let movement = forward * controller.get_dy() + right * controller.get_dx() + Vector3::new(0.0, vertical, 0.0);
+        self.position += movement * self.speed * time_step_secs;
+    }
+
+    /// Get the view matrix, based on the camera's position and orientation
+    fn get_view_matrix(&self) -> Matrix4<f32> {
+        make_yaw_pitch_view_matrix(self.position, self.yaw, self.pitch)
+    }
+
+    /// Get the stored perspective projection matrix
+    fn get_projection_matrix(&self) -> Matrix4<f32> {
+        self.perspective_projection
+    }
+}
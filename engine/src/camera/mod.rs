@@ -1,3 +1,493 @@
 
+pub mod config;
+pub mod effects;
+pub mod flight_path;
+pub mod free_fly;
 pub mod null;
+pub mod orbit;
+pub mod orthographic;
 pub mod player;
+
+use defs::control::{
+    Control,
+    MouseButton
+};
+use cgmath::{
+    Matrix4,
+    Rad,
+    Vector3
+};
+
+/// Creates a perspective projection matrix suitable for Vulkan, which looks along +z with y
+/// downwards, mapping depth from the near plane to the far plane onto 0 to 1
+pub fn make_vulkan_perspective_matrix(
+    aspect_ratio: f32,
+    fov_y: Rad<f32>,
+    near_plane: f32,
+    far_plane: f32
+) -> Matrix4<f32> {
+    let focal_length = 1.0 / (fov_y.0 * 0.5).tan();
+    Matrix4::<f32>::new(
+        focal_length / aspect_ratio, 0.0, 0.0, 0.0,
+        0.0, focal_length, 0.0, 0.0,
+        0.0, 0.0, far_plane / (far_plane - near_plane), 1.0,
+        0.0, 0.0, (-far_plane * near_plane) / (far_plane - near_plane), 0.0
+    )
+}
+
+/// Creates a perspective projection matrix like make_vulkan_perspective_matrix, but mapping depth
+/// from the near plane to the far plane onto 1 to 0, which spreads depth precision more evenly
+/// with distance. Passes drawn with it need reversed depth.
+pub fn make_vulkan_reversed_z_perspective_matrix(
+    aspect_ratio: f32,
+    fov_y: Rad<f32>,
+    near_plane: f32,
+    far_plane: f32
+) -> Matrix4<f32> {
+    let focal_length = 1.0 / (fov_y.0 * 0.5).tan();
+    Matrix4::<f32>::new(
+        focal_length / aspect_ratio, 0.0, 0.0, 0.0,
+        0.0, focal_length, 0.0, 0.0,
+        0.0, 0.0, near_plane / (near_plane - far_plane), 1.0,
+        0.0, 0.0, (far_plane * near_plane) / (far_plane - near_plane), 0.0
+    )
+}
+
+/// Creates an orthographic projection matrix suitable for Vulkan, showing the area from
+/// (left, top) to (right, bottom) with y downwards, and mapping depth from the near plane to the
+/// far plane onto 0 to 1
+pub fn make_vulkan_orthographic_matrix(
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+    near_plane: f32,
+    far_plane: f32
+) -> Matrix4<f32> {
+    let width = right - left;
+    let height = bottom - top;
+    let depth = far_plane - near_plane;
+    Matrix4::<f32>::new(
+        2.0 / width, 0.0, 0.0, 0.0,
+        0.0, 2.0 / height, 0.0, 0.0,
+        0.0, 0.0, 1.0 / depth, 0.0,
+        -(right + left) / width, -(bottom + top) / height, -near_plane / depth, 1.0
+    )
+}
+
+/// Creates a view matrix for a camera at a position, turned by yaw about the y axis and then
+/// pitched about its own x axis. Positive pitch looks downwards, and positive yaw turns left.
+pub fn make_yaw_pitch_view_matrix(position: Vector3<f32>, yaw: f32, pitch: f32) -> Matrix4<f32> {
+    Matrix4::from_angle_x(Rad(pitch)) * Matrix4::from_angle_y(Rad(yaw)) * Matrix4::from_translation(-position)
+}
+
+/// Creates a view matrix for a camera at a position facing a target, staying upright
+pub fn make_look_at_view_matrix(position: Vector3<f32>, target: Vector3<f32>) -> Matrix4<f32> {
+    let direction = target - position;
+    let horizontal = (direction.x * direction.x + direction.z * direction.z).sqrt();
+    let yaw = (-direction.x).atan2(direction.z);
+    let pitch = direction.y.atan2(horizontal);
+    make_yaw_pitch_view_matrix(position, yaw, pitch)
+}
+
+/// Get the direction a camera faces, for the yaw and pitch as taken by make_yaw_pitch_view_matrix
+pub fn yaw_pitch_forward(yaw: f32, pitch: f32) -> Vector3<f32> {
+    Vector3::new(-yaw.sin() * pitch.cos(), pitch.sin(), yaw.cos() * pitch.cos())
+}
+
+/// MouseDrag struct
+/// Tracks movement of the mouse while a button is held, for cameras that are turned by dragging
+pub struct MouseDrag {
+    button: MouseButton,
+    last_position: Option<(f32, f32)>
+}
+
+impl MouseDrag {
+
+    /// Create a new instance which tracks dragging with the given button
+    pub fn new(button: MouseButton) -> MouseDrag {
+        MouseDrag {
+            button,
+            last_position: None
+        }
+    }
+
+    /// Get how far the mouse has moved since the last update, as fractions of the window size, or
+    /// zero if the button was not held down for the whole time
+    pub fn update(&mut self, controller: &dyn Control) -> (f32, f32) {
+        let position = match controller.is_mouse_button_down(self.button) {
+            true => controller.get_mouse_position(),
+            false => None
+        };
+        let movement = match (self.last_position, position) {
+            (Some(last), Some(current)) => (current.0 - last.0, current.1 - last.1),
+            _ => (0.0, 0.0)
+        };
+        self.last_position = position;
+        movement
+    }
+}
+
+/// Test suite
+/// Check that each camera places what it looks at where expected in clip space, and responds to
+/// input within its limits
+#[cfg(test)]
+mod test {
+    use defs::{
+        Camera,
+        control::{
+            Control,
+            InputEvent,
+            InputState,
+            KeyCode,
+            MouseButton
+        }
+    };
+    use cgmath::{
+        InnerSpace,
+        Matrix4,
+        SquareMatrix,
+        Vector3,
+        Vector4
+    };
+    use crate::camera::{
+        config::{
+            CameraConfig,
+            MovementTuning,
+            ProjectionConfig
+        },
+        effects::{
+            CameraTransition,
+            FovKick,
+            Shake,
+            SmoothFollow
+        },
+        flight_path::{
+            Easing,
+            FlightPath,
+            FlightPathCamera,
+            SplineKind
+        },
+        free_fly::FreeFlyCamera,
+        null::NullCamera,
+        orbit::OrbitCamera,
+        orthographic::OrthographicCamera,
+        player::PlayerCamera
+    };
+    use crate::collision::StaticGeometry;
+
+    /// TestControl struct
+    /// Input for a single update, set directly by a test
+    #[derive(Default)]
+    struct TestControl {
+        dx: f32,
+        dy: f32,
+        mouse_position: Option<(f32, f32)>,
+        right_down: bool,
+        events: Vec<InputEvent>
+    }
+
+    impl Control for TestControl {
+        fn update(&mut self) {}
+        fn process_keyboard_event(&mut self, _keycode: KeyCode, _state: InputState) {}
+        fn process_mouse_move(&mut self, _position: Option<(f32, f32)>) {}
+        fn process_mouse_button_event(&mut self, _button: MouseButton, _state: InputState) {}
+        fn process_character(&mut self, _c: char) {}
+        fn get_dx(&self) -> f32 { self.dx }
+        fn get_dy(&self) -> f32 { self.dy }
+        fn get_mouse_position(&self) -> Option<(f32, f32)> { self.mouse_position }
+        fn is_mouse_button_down(&self, button: MouseButton) -> bool { button == MouseButton::Right && self.right_down }
+        fn get_frame_events(&self) -> &[InputEvent] { &self.events }
+    }
+
+    /// Transform a world position to normalised device coordinates
+    fn to_ndc(camera: &dyn Camera, point: Vector3<f32>) -> Vector3<f32> {
+        let clip = camera.get_projection_matrix() * camera.get_view_matrix() * Vector4::new(point.x, point.y, point.z, 1.0);
+        Vector3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w)
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.0001, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn orbit_camera_keeps_target_centred_within_zoom_limits() {
+        let target = Vector3::new(2.0, -1.0, 3.0);
+        let mut camera = OrbitCamera::new(1.0, target, 10.0, 5.0, 20.0);
+        let centre = to_ndc(&camera, target);
+        assert_near(centre.x, 0.0);
+        assert_near(centre.y, 0.0);
+
+        let zoom_in = TestControl { dy: 1.0, ..TestControl::default() };
+        for _ in 0..100 {
+            camera.update(100, &zoom_in);
+        }
+        assert_near(camera.get_distance(), 5.0);
+
+        let orbit = TestControl { dx: 1.0, ..TestControl::default() };
+        camera.update(500, &orbit);
+        let centre = to_ndc(&camera, target);
+        assert_near(centre.x, 0.0);
+        assert_near(centre.y, 0.0);
+        let eye_to_target = target - camera.get_position();
+        assert_near((eye_to_target.x * eye_to_target.x + eye_to_target.y * eye_to_target.y + eye_to_target.z * eye_to_target.z).sqrt(), 5.0);
+    }
+
+    #[test]
+    fn free_fly_camera_turns_with_mouse_and_moves_where_it_looks() {
+        let mut camera = FreeFlyCamera::new(1.0, Vector3::new(0.0, 0.0, 0.0), 0.0, 0.0);
+        let mut control = TestControl { right_down: true, mouse_position: Some((0.5, 0.5)), ..TestControl::default() };
+        camera.update(16, &control);
+        control.mouse_position = Some((0.5, 0.75));
+        camera.update(16, &control);
+        assert!(camera.get_pitch() > 0.0);
+
+        // Looking downwards, which is +y, moving forwards descends
+        let forward = TestControl { dy: 1.0, ..TestControl::default() };
+        camera.update(1000, &forward);
+        assert!(camera.get_position().y > 0.0);
+        assert!(camera.get_position().z > 0.0);
+
+        // Page up rises, towards -y, until released
+        let start_y = camera.get_position().y;
+        let rise = TestControl { events: vec![InputEvent::Key(KeyCode::PageUp, InputState::Pressed)], ..TestControl::default() };
+        camera.update(1000, &rise);
+        camera.update(1000, &TestControl::default());
+        let risen_y = camera.get_position().y;
+        assert!(risen_y < start_y);
+        let release = TestControl { events: vec![InputEvent::Key(KeyCode::PageUp, InputState::Released)], ..TestControl::default() };
+        camera.update(1000, &release);
+        camera.update(1000, &TestControl::default());
+        assert_near(camera.get_position().y, risen_y);
+    }
+
+    #[test]
+    fn orthographic_camera_maps_visible_area_to_clip_space() {
+        let mut camera = OrthographicCamera::new(2.0, 5.0);
+        camera.set_position(10.0, 20.0);
+        let top_left = to_ndc(&camera, Vector3::new(0.0, 15.0, 0.0));
+        assert_near(top_left.x, -1.0);
+        assert_near(top_left.y, -1.0);
+        let bottom_right = to_ndc(&camera, Vector3::new(20.0, 25.0, 0.0));
+        assert_near(bottom_right.x, 1.0);
+        assert_near(bottom_right.y, 1.0);
+
+        camera.update_aspect(1.0);
+        let right_edge = to_ndc(&camera, Vector3::new(15.0, 20.0, 0.0));
+        assert_near(right_edge.x, 1.0);
+    }
+
+    #[test]
+    fn player_camera_config_sets_clip_planes_depth_direction_and_speed() {
+        let config = CameraConfig {
+            projection: ProjectionConfig { near_plane: 0.5, far_plane: 50.0, ..ProjectionConfig::default() },
+            movement: MovementTuning { max_speed: 2.0, ..MovementTuning::default() },
+            ..CameraConfig::default()
+        };
+        let mut camera = PlayerCamera::with_config(1.0, Vector3::new(0.0, 0.0, 0.0), 0.0, config);
+        assert_near(to_ndc(&camera, Vector3::new(0.0, 0.0, 0.5)).z, 0.0);
+        assert_near(to_ndc(&camera, Vector3::new(0.0, 0.0, 50.0)).z, 1.0);
+
+        // A 90 degree field of view puts points at 45 degrees on the edge of the view
+        assert_near(to_ndc(&camera, Vector3::new(0.0, 10.0, 10.0)).y, 1.0);
+
+        let forward = TestControl { dy: 1.0, ..TestControl::default() };
+        for _ in 0..100 {
+            camera.update(100, &forward);
+        }
+        assert_near(camera.get_speed(), 2.0);
+
+        let reversed = PlayerCamera::with_config(
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            0.0,
+            CameraConfig { projection: ProjectionConfig { reversed_z: true, ..config.projection }, ..config });
+        assert_near(to_ndc(&reversed, Vector3::new(0.0, 0.0, 0.5)).z, 1.0);
+        assert_near(to_ndc(&reversed, Vector3::new(0.0, 0.0, 50.0)).z, 0.0);
+        assert_near(to_ndc(&reversed, Vector3::new(0.0, 10.0, 10.0)).y, 1.0);
+    }
+
+    #[test]
+    fn spectator_cameras_take_projection_config() {
+        let projection = ProjectionConfig { near_plane: 0.5, far_plane: 50.0, ..ProjectionConfig::default() };
+        let orbit = OrbitCamera::with_projection(1.0, Vector3::new(0.0, 0.0, 0.0), 10.0, 2.0, 50.0, projection);
+        let free_fly = FreeFlyCamera::with_projection(1.0, Vector3::new(0.0, 0.0, 0.0), 0.0, 0.0, projection);
+        let path = FlightPath::new(
+            SplineKind::CatmullRom,
+            vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 10.0)],
+            1.0,
+            false).unwrap();
+        let flight = FlightPathCamera::with_projection(1.0, path, projection);
+        let cameras: [(&dyn Camera, Vector3<f32>); 3] = [
+            (&orbit, orbit.get_position()),
+            (&free_fly, free_fly.get_position()),
+            (&flight, flight.get_position())
+        ];
+        for (camera, eye) in cameras.iter() {
+            let forward = (camera.get_view_matrix().invert().unwrap() * Vector4::new(0.0, 0.0, 1.0, 0.0)).truncate();
+            assert_near(to_ndc(*camera, eye + forward * 0.5).z, 0.0);
+            assert_near(to_ndc(*camera, eye + forward * 50.0).z, 1.0);
+        }
+
+        // Without a config they keep the 60 degree field of view, putting points at 30 degrees on
+        // the edge of the view
+        let default_fly = FreeFlyCamera::new(1.0, Vector3::new(0.0, 0.0, 0.0), 0.0, 0.0);
+        assert_near(to_ndc(&default_fly, Vector3::new(0.0, 10.0 * (std::f32::consts::PI / 6.0).tan(), 10.0)).y, 1.0);
+    }
+
+    #[test]
+    fn player_camera_climbs_ground_and_stops_at_world_edge() {
+
+        // Ground rising by one unit per unit of z, towards -y, from z = 0 to 20
+        let corners = [
+            Vector3::new(-10.0, 0.0, 0.0),
+            Vector3::new(10.0, 0.0, 0.0),
+            Vector3::new(-10.0, -20.0, 20.0),
+            Vector3::new(10.0, -20.0, 20.0)
+        ];
+        let geometry = StaticGeometry::from_triangles(vec![
+            [corners[0], corners[1], corners[3]],
+            [corners[0], corners[3], corners[2]]
+        ]);
+        let config = CameraConfig::default();
+        let mut camera = PlayerCamera::with_config(1.0, Vector3::new(0.0, 5.0, 2.0), 0.0, config);
+        camera.set_collision_world(Box::new(geometry));
+        assert_near(camera.get_y(), -2.0 - config.terrain.eye_height);
+
+        let forward = TestControl { dy: 1.0, ..TestControl::default() };
+        for _ in 0..30 {
+            camera.update(100, &forward);
+        }
+        assert!(camera.get_z() > 10.0);
+        assert!((camera.get_y() - (-camera.get_z() - config.terrain.eye_height)).abs() < 1.0);
+
+        for _ in 0..100 {
+            camera.update(100, &forward);
+        }
+        assert_near(camera.get_z(), 20.0 - config.terrain.collision_radius);
+        assert!((camera.get_y() - (-20.0 + config.terrain.collision_radius - config.terrain.eye_height)).abs() < 0.1);
+    }
+
+    /// Get the position of the eye from a camera's view matrix
+    fn eye_position(camera: &dyn Camera) -> Vector3<f32> {
+        camera.get_view_matrix().invert().unwrap().w.truncate()
+    }
+
+    #[test]
+    fn shake_and_fov_kick_wear_off() {
+        let still = TestControl::default();
+        let mut shake = Shake::new(FreeFlyCamera::new(1.0, Vector3::new(1.0, 2.0, 3.0), 0.5, 0.0));
+        shake.add_trauma(0.8);
+        shake.update(100, &still);
+        assert!(shake.get_trauma() > 0.0);
+        assert!((eye_position(&shake) - Vector3::new(1.0, 2.0, 3.0)).magnitude() > 0.0001);
+        for _ in 0..10 {
+            shake.update(100, &still);
+        }
+        assert_eq!(shake.get_trauma(), 0.0);
+        assert_eq!(shake.get_view_matrix(), shake.inner().get_view_matrix());
+
+        let mut kick = FovKick::new(shake);
+        let unkicked = kick.get_projection_matrix();
+        kick.kick(0.5);
+        assert_near(kick.get_projection_matrix().y.y, unkicked.y.y / 1.5);
+        for _ in 0..50 {
+            kick.update(100, &still);
+        }
+        assert_near(kick.get_projection_matrix().y.y, unkicked.y.y);
+    }
+
+    #[test]
+    fn transition_blends_eye_and_follow_catches_up_without_overshoot() {
+        let still = TestControl::default();
+        let from = FreeFlyCamera::new(1.0, Vector3::new(0.0, 0.0, 0.0), 0.0, 0.0);
+        let to = FreeFlyCamera::new(1.0, Vector3::new(10.0, -4.0, 0.0), 1.0, 0.3);
+        let mut transition = CameraTransition::new(from, to, 2.0).with_easing(Easing::Linear);
+        transition.update(1000, &still);
+        let middle = eye_position(&transition);
+        assert_near(middle.x, 5.0);
+        assert_near(middle.y, -2.0);
+        transition.update(1000, &still);
+        assert!(transition.is_finished());
+        assert_eq!(transition.get_view_matrix(), transition.get_to().get_view_matrix());
+
+        let mut follow = SmoothFollow::new(NullCamera::default(), Vector3::new(0.0, -2.0, -5.0), 0.5);
+        follow.set_target_transform(Matrix4::from_translation(Vector3::new(10.0, 0.0, 0.0)));
+        let mut last_x = follow.get_position().x;
+        for _ in 0..100 {
+            follow.update(50, &still);
+            let x = follow.get_position().x;
+            assert!(x >= last_x && x <= 10.0001);
+            last_x = x;
+        }
+        assert_near(follow.get_position().x, 10.0);
+        assert_near(follow.get_position().z, -5.0);
+        let centre = follow.get_view_matrix() * Vector4::new(10.0, 0.0, 0.0, 1.0);
+        assert_near(centre.x, 0.0);
+        assert_near(centre.y, 0.0);
+    }
+
+    #[test]
+    fn flight_path_passes_through_points_at_even_speed() {
+        let points = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(10.0, 0.0, 0.0),
+            Vector3::new(10.0, 0.0, 10.0),
+            Vector3::new(0.0, 0.0, 10.0)
+        ];
+        let path = FlightPath::new(SplineKind::CatmullRom, points.clone(), 3.0, false).unwrap();
+        assert_near(path.sample(0.0).0.x, 0.0);
+        let end = path.sample(5.0).0;
+        assert_near(end.x, 0.0);
+        assert_near(end.z, 10.0);
+        let (middle, _) = path.sample(1.5);
+        assert_near(middle.z, 5.0);
+        assert!(middle.x > 10.0 && middle.x < 12.0);
+
+        let looping = FlightPath::new(SplineKind::CatmullRom, points.clone(), 4.0, true).unwrap()
+            .with_easing(Easing::EaseInOut);
+        assert!((looping.sample(4.0).0 - looping.sample(0.0).0).magnitude() < 0.0001);
+        assert!(FlightPath::new(SplineKind::Bezier, points.clone(), 1.0, false).is_ok());
+        assert!(FlightPath::new(SplineKind::Bezier, points.clone(), 1.0, true).is_err());
+        assert!(FlightPath::new(SplineKind::CatmullRom, points.clone(), 1.0, false).unwrap()
+            .with_look_at_targets(vec![Vector3::new(0.0, 0.0, 0.0)]).is_err());
+
+        // Looking at a fixed target keeps it centred throughout
+        let target = Vector3::new(5.0, 2.0, 5.0);
+        let path = FlightPath::new(SplineKind::CatmullRom, points, 3.0, false).unwrap()
+            .with_look_at_targets(vec![target; 4]).unwrap();
+        let mut camera = FlightPathCamera::new(1.0, path);
+        let control = TestControl::default();
+        while !camera.is_finished() {
+            camera.update(250, &control);
+            let centre = to_ndc(&camera, target);
+            assert_near(centre.x, 0.0);
+            assert_near(centre.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn flight_path_imported_from_obj_curve() {
+        let obj = "# Blender OBJ export
+o BezierCurve
+v 0.0 1.0 0.0
+v 1.0 1.0 -1.0
+v 2.0 3.0 -4.0
+l 1 2
+l 2 3
+";
+        let path = FlightPath::from_obj_curve(obj, 2.0, false).unwrap();
+
+        // Y-up, -Z forward points return to Blender's Z-up, Y forward axes, as Collada models use
+        let start = path.sample(0.0).0;
+        assert_near(start.x, 0.0);
+        assert_near(start.y, 0.0);
+        assert_near(start.z, 1.0);
+        let end = path.sample(2.0).0;
+        assert_near(end.x, 2.0);
+        assert_near(end.y, 4.0);
+        assert_near(end.z, 3.0);
+        assert!(FlightPath::from_obj_curve("v 0 0 0", 1.0, false).is_err());
+        assert!(FlightPath::from_obj_curve("v 0 0 0\nv 1 0 0\nl 1 3", 1.0, false).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+
+use defs::{
+    Camera,
+    control::{
+        Control,
+        MouseButton
+    }
+};
+use cgmath::{
+    Matrix4,
+    Vector3
+};
+use crate::camera::{
+    MouseDrag,
+    config::ProjectionConfig,
+    make_yaw_pitch_view_matrix,
+    yaw_pitch_forward
+};
+
+/// OrbitCamera struct
+/// Camera that circles a target point, always facing it. Left and right inputs orbit around the
+/// target, up and down inputs zoom in and out within the distance limits, and dragging with the
+/// right mouse button orbits both around and over the target.
+pub struct OrbitCamera {
+    target: Vector3<f32>,
+    distance: f32,
+    min_distance: f32,
+    max_distance: f32,
+    yaw: f32,
+    pitch: f32,
+    drag: MouseDrag,
+    projection: ProjectionConfig,
+    perspective_projection: Matrix4<f32>
+}
+
+impl OrbitCamera {
+
+    /// Speeds of orbiting in radians per second, zooming as a fraction of the distance per second,
+    /// and dragging in radians per window width or height
+    const ORBIT_SPEED: f32 = 1.5;
+    const ZOOM_SPEED: f32 = 1.0;
+    const DRAG_SPEED: f32 = std::f32::consts::PI;
+
+    /// Limit on pitch, just short of looking straight up or down at the target
+    const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+    /// Creates a new camera looking at the target from the given distance, clamped to the zoom
+    /// limits, slightly above the target and along -z from it, with the spectator projection
+    pub fn new(
+        aspect_ratio: f32,
+        target: Vector3<f32>,
+        distance: f32,
+        min_distance: f32,
+        max_distance: f32
+    ) -> OrbitCamera {
+        Self::with_projection(aspect_ratio, target, distance, min_distance, max_distance, ProjectionConfig::spectator())
+    }
+
+    /// Creates a new camera as per new, projecting as per the supplied config
+    pub fn with_projection(
+        aspect_ratio: f32,
+        target: Vector3<f32>,
+        distance: f32,
+        min_distance: f32,
+        max_distance: f32,
+        projection: ProjectionConfig
+    ) -> OrbitCamera {
+        OrbitCamera {
+            target,
+            distance: distance.min(max_distance).max(min_distance),
+            min_distance,
+            max_distance,
+            yaw: 0.0,
+            pitch: std::f32::consts::FRAC_PI_6,
+            drag: MouseDrag::new(MouseButton::Right),
+            projection,
+            perspective_projection: projection.make_projection_matrix(aspect_ratio)
+        }
+    }
+
+    /// Move the point that the camera circles
+    pub fn set_target(&mut self, target: Vector3<f32>) {
+        self.target = target;
+    }
+
+    /// Set the angles around and over the target, with positive pitch looking down onto it
+    pub fn set_angles(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    /// Multiply the distance from the target by a factor, within the zoom limits
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.distance = (self.distance * factor).min(self.max_distance).max(self.min_distance);
+    }
+
+    pub fn get_target(&self) -> Vector3<f32> {
+        self.target
+    }
+
+    pub fn get_distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn get_yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn get_pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    /// Get the position of the camera itself
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.target - yaw_pitch_forward(self.yaw, self.pitch) * self.distance
+    }
+}
+
+impl Camera for OrbitCamera {
+
+    /// Updates the projection matrix to fit the new aspect ratio
+    fn update_aspect(&mut self, aspect_ratio: f32) {
+        self.perspective_projection = self.projection.make_projection_matrix(aspect_ratio);
+    }
+
+    /// Orbit and zoom as per the directional inputs and mouse dragging in the supplied controller
+    fn update(&mut self, time_step_millis: u64, controller: &dyn Control) {
+        let time_step_secs: f32 = 0.001 * time_step_millis as f32;
+        let (drag_x, drag_y) = self.drag.update(controller);
+        let yaw = self.yaw - Self::ORBIT_SPEED * time_step_secs * controller.get_dx() - Self::DRAG_SPEED * drag_x;
+        let pitch = self.pitch + Self::DRAG_SPEED * drag_y;
+        self.set_angles(yaw % (2.0 * std::f32::consts::PI), pitch);
+        self.zoom_by((-Self::ZOOM_SPEED * time_step_secs * controller.get_dy()).exp());
+    }
+
+    /// Get the view matrix, based on the camera's position around the target
+    fn get_view_matrix(&self) -> Matrix4<f32> {
+        make_yaw_pitch_view_matrix(self.get_position(), self.yaw, self.pitch)
+    }
+
+    /// Get the stored perspective projection matrix
+    fn get_projection_matrix(&self) -> Matrix4<f32> {
+        self.perspective_projection
+    }
+}
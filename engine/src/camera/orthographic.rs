@@ -0,0 +1,111 @@
+
+use defs::{
+    Camera,
+    control::Control
+};
+use cgmath::{
+    Matrix4,
+    Vector3
+};
+use crate::camera::make_vulkan_orthographic_matrix;
+
+/// OrthographicCamera struct
+/// Camera without perspective, for 2D and UI scenes. Shows an area centred on its position, with
+/// y downwards, whose height is fixed and whose width follows the aspect ratio. Pans with the
+/// directional inputs if given a pan speed, and otherwise stays where it is put.
+pub struct OrthographicCamera {
+    position_x: f32,
+    position_y: f32,
+    half_height: f32,
+    aspect_ratio: f32,
+    pan_speed: f32,
+    orthographic_projection: Matrix4<f32>
+}
+
+impl OrthographicCamera {
+
+    /// Constant near and far plane distances used for the orthographic projection
+    const NEAR_PLANE: f32 = -1.0;
+    const FAR_PLANE: f32 = 1.0;
+
+    /// Creates a new camera centred on the origin, showing from -half_height to half_height
+    /// vertically
+    pub fn new(aspect_ratio: f32, half_height: f32) -> OrthographicCamera {
+        OrthographicCamera {
+            position_x: 0.0,
+            position_y: 0.0,
+            half_height,
+            aspect_ratio,
+            pan_speed: 0.0,
+            orthographic_projection: Self::make_projection(aspect_ratio, half_height)
+        }
+    }
+
+    /// Move the point at the centre of the view
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.position_x = x;
+        self.position_y = y;
+    }
+
+    /// Change half the height of the area shown, to zoom in or out
+    pub fn set_half_height(&mut self, half_height: f32) {
+        self.half_height = half_height;
+        self.orthographic_projection = Self::make_projection(self.aspect_ratio, half_height);
+    }
+
+    /// Set the speed of panning with the directional inputs, in units per second
+    pub fn set_pan_speed(&mut self, pan_speed: f32) {
+        self.pan_speed = pan_speed;
+    }
+
+    pub fn get_x(&self) -> f32 {
+        self.position_x
+    }
+
+    pub fn get_y(&self) -> f32 {
+        self.position_y
+    }
+
+    pub fn get_half_height(&self) -> f32 {
+        self.half_height
+    }
+
+    fn make_projection(aspect_ratio: f32, half_height: f32) -> Matrix4<f32> {
+        let half_width = half_height * aspect_ratio;
+        make_vulkan_orthographic_matrix(
+            -half_width,
+            -half_height,
+            half_width,
+            half_height,
+            Self::NEAR_PLANE,
+            Self::FAR_PLANE)
+    }
+}
+
+impl Camera for OrthographicCamera {
+
+    /// Updates the projection matrix to fit the new aspect ratio
+    fn update_aspect(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+        self.orthographic_projection = Self::make_projection(aspect_ratio, self.half_height);
+    }
+
+    /// Pan as per the directional inputs in the supplied controller, where up is -y
+    fn update(&mut self, time_step_millis: u64, controller: &dyn Control) {
+        let distance = self.pan_speed * 0.001 * time_step_millis as f32;
+        self.position_x += distance * controller.get_dx();
+        self.position_y -= distance * controller.get_dy();
+    }
+
+    /// Get the view matrix, based on the camera's position
+    fn get_view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::<f32>::from_translation(
+            Vector3::<f32> { x: -self.position_x, y: -self.position_y, z: 0.0 }
+        )
+    }
+
+    /// Get the stored orthographic projection matrix
+    fn get_projection_matrix(&self) -> Matrix4<f32> {
+        self.orthographic_projection
+    }
+}
@@ -8,12 +8,20 @@ use cgmath::{
     Rad,
     Vector3
 };
+use crate::{
+    camera::config::CameraConfig,
+    collision::CollisionWorld
+};
 
 /// PlayerCamera struct
 /// Camera object that responds to user input - namely forward, backwards, left and right. Uses
 /// a momentum mechanic such that it accelerates to a maximum speed over time and also decelerates
-/// over time. The momentum mechanic applies to both linear and angular velocities.
+/// over time. The momentum mechanic applies to both linear and angular velocities, and is tuned by
+/// the camera's config along with its projection. Given a collision world, the camera rides over
+/// its ground, slides along its walls and stays within its edges.
 pub struct PlayerCamera {
+    config: CameraConfig,
+    world: Option<Box<dyn CollisionWorld>>,
     speed: f32,
     angular_speed: f32,
     rotation: f32,
@@ -25,41 +33,69 @@ pub struct PlayerCamera {
 
 impl PlayerCamera {
 
-    /// Constant near and far plane distances used for the perspective projection
-    const NEAR_PLANE: f32 = 1.0;
-    const FAR_PLANE: f32 = 100.0;
-
-    /// Creates a new camera with zero speed and oriented at the supplied angle
+    /// Creates a new camera with zero speed and oriented at the supplied angle, with the default
+    /// config
     pub fn new(aspect_ratio: f32, x: f32, y: f32, z: f32, angle_rad: f32) -> PlayerCamera {
+        Self::with_config(aspect_ratio, Vector3::new(x, y, z), angle_rad, CameraConfig::default())
+    }
+
+    /// Creates a new camera with zero speed at the supplied position and angle, projecting and
+    /// moving as per the supplied config
+    pub fn with_config(
+        aspect_ratio: f32,
+        position: Vector3<f32>,
+        angle_rad: f32,
+        config: CameraConfig
+    ) -> PlayerCamera {
         PlayerCamera {
+            config,
+            world: None,
             speed: 0.0,
             angular_speed: 0.0,
             rotation: angle_rad,
-            position_x: x,
-            position_y: y,
-            position_z: z,
-            perspective_projection: Self::make_vulkan_perspective_matrix(
-                aspect_ratio,
-                Self::NEAR_PLANE,
-                Self::FAR_PLANE)
+            position_x: position.x,
+            position_y: position.y,
+            position_z: position.z,
+            perspective_projection: config.projection.make_projection_matrix(aspect_ratio)
         }
     }
 
-    /// Creates a projection matrix suitable for Vulkan. Note that OpenGL, DirectX, etc may need
-    /// alternate implementations due to differing up/down coordinates or clip volumes.
-    fn make_vulkan_perspective_matrix(
-        aspect_ratio: f32,
-        near_plane: f32,
-        far_plane: f32
-    ) -> Matrix4<f32> {
-        let half_width = aspect_ratio;
-        let half_height = 1.0;
-        Matrix4::<f32>::new(
-            near_plane / half_width, 0.0, 0.0, 0.0,
-            0.0, near_plane / half_height, 0.0, 0.0,
-            0.0, 0.0, far_plane / (far_plane - near_plane), 1.0,
-            0.0, 0.0, (-far_plane * near_plane) / (far_plane - near_plane), 0.0
-        )
+    /// Follow the ground and collide with the walls of the supplied world, starting on the ground
+    /// beneath the camera if there is any
+    pub fn set_collision_world(&mut self, world: Box<dyn CollisionWorld>) {
+        if let Some(ground_y) = world.ground_y(self.position_x, self.position_z) {
+            self.position_y = ground_y - self.config.terrain.eye_height;
+        }
+        self.world = Some(world);
+    }
+
+    /// Move horizontally, then collide with the world and ease towards the ground height if there
+    /// is a world
+    fn move_by(&mut self, dx: f32, dz: f32, time_step_secs: f32) {
+        let mut position = Vector3::new(self.position_x + dx, self.position_y, self.position_z + dz);
+        if let Some(world) = &self.world {
+            let terrain = self.config.terrain;
+            let push = world.push_out_sphere(position, terrain.collision_radius);
+            let (min, max) = world.bounds();
+            position.x = (position.x + push.x)
+                .min(max.x - terrain.collision_radius)
+                .max(min.x + terrain.collision_radius);
+            position.z = (position.z + push.z)
+                .min(max.z - terrain.collision_radius)
+                .max(min.z + terrain.collision_radius);
+            if let Some(ground_y) = world.ground_y(position.x, position.z) {
+                let target_y = ground_y - terrain.eye_height;
+                let blend = 1.0 - (-terrain.smoothing * time_step_secs).exp();
+                position.y += (target_y - position.y) * blend;
+            }
+        }
+        self.position_x = position.x;
+        self.position_y = position.y;
+        self.position_z = position.z;
+    }
+
+    pub fn get_config(&self) -> &CameraConfig {
+        &self.config
     }
 
     pub fn get_x(&self) -> f32 {
@@ -91,23 +127,21 @@ impl Camera for PlayerCamera {
 
     /// Updates the projection matrix to fit the new aspect ratio
     fn update_aspect(&mut self, aspect_ratio: f32) {
-        self.perspective_projection = Self::make_vulkan_perspective_matrix(
-            aspect_ratio,
-            Self::NEAR_PLANE,
-            Self::FAR_PLANE);
+        self.perspective_projection = self.config.projection.make_projection_matrix(aspect_ratio);
     }
 
     /// Move the camera as per the up/down/left/right inputs in the supplied controller
     fn update(&mut self, time_step_millis: u64, controller: &dyn Control) {
 
         let time_step_secs: f32 = 0.001 * time_step_millis as f32;
+        let tuning = self.config.movement;
 
         // Update angular speed
         self.angular_speed = {
-            let deadzone: f32 = 0.01;
-            let max_speed: f32 = 3.0;
-            let accel: f32 = 4.0;
-            let decel: f32 = 10.0;
+            let deadzone = tuning.deadzone;
+            let max_speed = tuning.max_angular_speed;
+            let accel = tuning.angular_acceleration;
+            let decel = tuning.angular_deceleration;
             let dx = controller.get_dx();
 
             if self.angular_speed == 0.0 {
@@ -138,11 +172,11 @@ impl Camera for PlayerCamera {
 
         // Update linear speed
         self.speed = {
-            let deadzone: f32 = 0.01;
-            let max_speed: f32 = 8.0;
-            let max_reverse_speed = -3.0;
-            let accel: f32 = 9.0;
-            let decel: f32 = 25.0;
+            let deadzone = tuning.deadzone;
+            let max_speed = tuning.max_speed;
+            let max_reverse_speed = -tuning.max_reverse_speed;
+            let accel = tuning.acceleration;
+            let decel = tuning.deceleration;
             let dy = controller.get_dy();
 
             if self.speed == 0.0 {
@@ -163,8 +197,10 @@ impl Camera for PlayerCamera {
             }
         };
 
-        self.position_x -= self.speed * time_step_secs * self.rotation.sin();
-        self.position_z += self.speed * time_step_secs * self.rotation.cos();
+        self.move_by(
+            -self.speed * time_step_secs * self.rotation.sin(),
+            self.speed * time_step_secs * self.rotation.cos(),
+            time_step_secs);
     }
 
     /// Get the view matrix, based on the camera's position and orientation
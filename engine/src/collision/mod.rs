@@ -0,0 +1,292 @@
+
+use model::types::StaticVertex;
+use cgmath::{
+    InnerSpace,
+    Vector3
+};
+
+/// CollisionWorld trait
+/// Static surroundings that a camera can walk over and bump into, with y downwards
+pub trait CollisionWorld {
+
+    /// Get the y of the topmost surface directly above or below a point, if there is one
+    fn ground_y(&self, x: f32, z: f32) -> Option<f32>;
+
+    /// Get the offset that pushes a sphere out of any surfaces it overlaps, which is zero if it
+    /// is clear of them
+    fn push_out_sphere(&self, centre: Vector3<f32>, radius: f32) -> Vector3<f32>;
+
+    /// Get the minimum and maximum corners of the box that the world fits within
+    fn bounds(&self) -> (Vector3<f32>, Vector3<f32>);
+}
+
+/// StaticGeometry struct
+/// Triangles of a model, as loaded into a vertex buffer, indexed by a grid over the x-z plane so
+/// that each query only tests the triangles nearby
+pub struct StaticGeometry {
+    triangles: Vec<[Vector3<f32>; 3]>,
+    min: Vector3<f32>,
+    max: Vector3<f32>,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>
+}
+
+impl StaticGeometry {
+
+    /// Number of passes used to push a sphere out of surfaces, so that it settles into corners
+    /// where several surfaces meet
+    const PUSH_OUT_PASSES: usize = 4;
+
+    /// Build from a non-indexed triangle list, as used for drawing the model
+    pub fn from_vertices(vertices: &[StaticVertex]) -> StaticGeometry {
+        let triangles: Vec<[Vector3<f32>; 3]> = vertices
+            .chunks_exact(3)
+            .map(|v| [
+                Vector3::new(v[0].px, v[0].py, v[0].pz),
+                Vector3::new(v[1].px, v[1].py, v[1].pz),
+                Vector3::new(v[2].px, v[2].py, v[2].pz)
+            ])
+            .collect();
+        Self::from_triangles(triangles)
+    }
+
+    /// Build from triangles given by their corners
+    pub fn from_triangles(triangles: Vec<[Vector3<f32>; 3]>) -> StaticGeometry {
+        let mut min = Vector3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = Vector3::new(f32::MIN, f32::MIN, f32::MIN);
+        for corner in triangles.iter().flatten() {
+            min = Vector3::new(min.x.min(corner.x), min.y.min(corner.y), min.z.min(corner.z));
+            max = Vector3::new(max.x.max(corner.x), max.y.max(corner.y), max.z.max(corner.z));
+        }
+        if triangles.is_empty() {
+            min = Vector3::new(0.0, 0.0, 0.0);
+            max = Vector3::new(0.0, 0.0, 0.0);
+        }
+
+        // Aim for around one triangle per cell, spread over the x-z area
+        let area = ((max.x - min.x) * (max.z - min.z)).max(f32::EPSILON);
+        let cell_size = (area / triangles.len().max(1) as f32).sqrt().max(0.001);
+        let columns = (((max.x - min.x) / cell_size) as usize + 1).min(1024);
+        let rows = (((max.z - min.z) / cell_size) as usize + 1).min(1024);
+        let cell_size = cell_size.max((max.x - min.x) / columns as f32).max((max.z - min.z) / rows as f32);
+
+        let mut geometry = StaticGeometry {
+            triangles,
+            min,
+            max,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![vec![]; columns * rows]
+        };
+        for (index, triangle) in geometry.triangles.iter().enumerate() {
+            let low_x = triangle[0].x.min(triangle[1].x).min(triangle[2].x);
+            let high_x = triangle[0].x.max(triangle[1].x).max(triangle[2].x);
+            let low_z = triangle[0].z.min(triangle[1].z).min(triangle[2].z);
+            let high_z = triangle[0].z.max(triangle[1].z).max(triangle[2].z);
+            let (first_column, last_column, first_row, last_row) = geometry.cell_range(low_x, high_x, low_z, high_z);
+            for row in first_row..=last_row {
+                for column in first_column..=last_column {
+                    geometry.cells[row * geometry.columns + column].push(index);
+                }
+            }
+        }
+        geometry
+    }
+
+    /// Get the range of grid columns and rows covering an area of the x-z plane, clamped to the
+    /// grid
+    fn cell_range(&self, low_x: f32, high_x: f32, low_z: f32, high_z: f32) -> (usize, usize, usize, usize) {
+        let column = |x: f32| (((x - self.min.x) / self.cell_size).max(0.0) as usize).min(self.columns - 1);
+        let row = |z: f32| (((z - self.min.z) / self.cell_size).max(0.0) as usize).min(self.rows - 1);
+        (column(low_x), column(high_x), row(low_z), row(high_z))
+    }
+
+    /// Get the triangles in the cells covering an area of the x-z plane, each listed once
+    fn triangles_near(&self, low_x: f32, high_x: f32, low_z: f32, high_z: f32) -> Vec<usize> {
+        if self.triangles.is_empty() || high_x < self.min.x || low_x > self.max.x || high_z < self.min.z || low_z > self.max.z {
+            return vec![];
+        }
+        let (first_column, last_column, first_row, last_row) = self.cell_range(low_x, high_x, low_z, high_z);
+        let mut indices = vec![];
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                indices.extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+}
+
+impl CollisionWorld for StaticGeometry {
+
+    /// Find the topmost triangle whose x-z projection contains the point, and get its y there
+    fn ground_y(&self, x: f32, z: f32) -> Option<f32> {
+        let mut ground: Option<f32> = None;
+        for index in self.triangles_near(x, x, z, z) {
+            let [a, b, c] = self.triangles[index];
+            let denominator = (b.z - c.z) * (a.x - c.x) + (c.x - b.x) * (a.z - c.z);
+            if denominator.abs() < f32::EPSILON {
+                continue;
+            }
+            let u = ((b.z - c.z) * (x - c.x) + (c.x - b.x) * (z - c.z)) / denominator;
+            let v = ((c.z - a.z) * (x - c.x) + (a.x - c.x) * (z - c.z)) / denominator;
+            let w = 1.0 - u - v;
+            let tolerance = -0.0001;
+            if u < tolerance || v < tolerance || w < tolerance {
+                continue;
+            }
+            let y = u * a.y + v * b.y + w * c.y;
+            ground = Some(ground.map_or(y, |g| g.min(y)));
+        }
+        ground
+    }
+
+    /// Repeatedly push the sphere away from the closest point of each triangle it overlaps
+    fn push_out_sphere(&self, centre: Vector3<f32>, radius: f32) -> Vector3<f32> {
+        let nearby = self.triangles_near(centre.x - radius, centre.x + radius, centre.z - radius, centre.z + radius);
+        let mut position = centre;
+        for _ in 0..Self::PUSH_OUT_PASSES {
+            let mut moved = false;
+            for index in nearby.iter() {
+                let triangle = &self.triangles[*index];
+                let closest = closest_point_on_triangle(position, triangle);
+                let offset = position - closest;
+                let distance = offset.magnitude();
+                if distance >= radius {
+                    continue;
+                }
+                let direction = match distance > 0.0001 {
+                    true => offset / distance,
+                    false => {
+                        let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+                        match normal.magnitude() > f32::EPSILON {
+                            true => normal.normalize(),
+                            false => continue
+                        }
+                    }
+                };
+                position += direction * (radius - distance);
+                moved = true;
+            }
+            if !moved {
+                break;
+            }
+        }
+        position - centre
+    }
+
+    fn bounds(&self) -> (Vector3<f32>, Vector3<f32>) {
+        (self.min, self.max)
+    }
+}
+
+/// Find the point on a triangle closest to another point, by working out which of the corners,
+/// edges or face is nearest
+pub(crate) fn closest_point_on_triangle(point: Vector3<f32>, triangle: &[Vector3<f32>; 3]) -> Vector3<f32> {
+    let [a, b, c] = *triangle;
+    let ab = b - a;
+    let ac = c - a;
+    let ap = point - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = point - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = point - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+/// Test suite
+/// Check ground heights and sphere push-out against small hand-built scenes
+#[cfg(test)]
+mod test {
+    use cgmath::Vector3;
+    use crate::collision::{
+        CollisionWorld,
+        StaticGeometry
+    };
+
+    /// Make a square of two triangles spanning x and z from 0 to size, with each corner at the
+    /// given y, ordered as (0, 0), (size, 0), (0, size) and (size, size)
+    fn make_square(size: f32, corner_ys: [f32; 4]) -> Vec<[Vector3<f32>; 3]> {
+        let p00 = Vector3::new(0.0, corner_ys[0], 0.0);
+        let p10 = Vector3::new(size, corner_ys[1], 0.0);
+        let p01 = Vector3::new(0.0, corner_ys[2], size);
+        let p11 = Vector3::new(size, corner_ys[3], size);
+        vec![[p00, p10, p11], [p00, p11, p01]]
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.001, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn ground_follows_slope_and_is_absent_off_the_edge() {
+        let geometry = StaticGeometry::from_triangles(make_square(10.0, [0.0, -10.0, 0.0, -10.0]));
+        assert_near(geometry.ground_y(0.0, 5.0).unwrap(), 0.0);
+        assert_near(geometry.ground_y(5.0, 5.0).unwrap(), -5.0);
+        assert_near(geometry.ground_y(7.5, 2.0).unwrap(), -7.5);
+        assert!(geometry.ground_y(-1.0, 5.0).is_none());
+        assert!(geometry.ground_y(5.0, 11.0).is_none());
+        let (min, max) = geometry.bounds();
+        assert_near(min.y, -10.0);
+        assert_near(max.x, 10.0);
+    }
+
+    #[test]
+    fn sphere_pushed_out_of_wall_along_its_normal() {
+        let mut triangles = make_square(10.0, [0.0, 0.0, 0.0, 0.0]);
+        let w0 = Vector3::new(5.0, 0.0, 0.0);
+        let w1 = Vector3::new(5.0, -10.0, 0.0);
+        let w2 = Vector3::new(5.0, 0.0, 10.0);
+        let w3 = Vector3::new(5.0, -10.0, 10.0);
+        triangles.push([w0, w1, w3]);
+        triangles.push([w0, w3, w2]);
+        let geometry = StaticGeometry::from_triangles(triangles);
+
+        // Overlapping the wall by 0.2, the push is straight back out, leaving movement along it free
+        let push = geometry.push_out_sphere(Vector3::new(4.7, -2.0, 5.0), 0.5);
+        assert_near(push.x, -0.2);
+        assert_near(push.y, 0.0);
+        assert_near(push.z, 0.0);
+
+        let clear = geometry.push_out_sphere(Vector3::new(2.0, -2.0, 5.0), 0.5);
+        assert_near(clear.x, 0.0);
+        assert_near(clear.y, 0.0);
+    }
+}
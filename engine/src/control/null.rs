@@ -2,7 +2,9 @@
 use defs::control::{
     Control,
     InputState,
-    KeyCode
+    KeyCode,
+    MouseButton,
+    InputEvent
 };
 
 /// NullControl struct
@@ -25,6 +27,15 @@ impl Control for NullControl {
     /// No-op
     fn process_keyboard_event(&mut self, _keycode: KeyCode, _state: InputState) {}
 
+    /// No-op
+    fn process_mouse_move(&mut self, _position: Option<(f32, f32)>) {}
+
+    /// No-op
+    fn process_mouse_button_event(&mut self, _button: MouseButton, _state: InputState) {}
+
+    /// No-op
+    fn process_character(&mut self, _c: char) {}
+
     /// Signal no user input for left/right
     fn get_dx(&self) -> f32 {
         0.0
@@ -34,4 +45,19 @@ impl Control for NullControl {
     fn get_dy(&self) -> f32 {
         0.0
     }
+
+    /// Signal no mouse within the window
+    fn get_mouse_position(&self) -> Option<(f32, f32)> {
+        None
+    }
+
+    /// Signal no mouse buttons held
+    fn is_mouse_button_down(&self, _button: MouseButton) -> bool {
+        false
+    }
+
+    /// Signal no events
+    fn get_frame_events(&self) -> &[InputEvent] {
+        &[]
+    }
 }
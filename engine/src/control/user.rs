@@ -2,14 +2,21 @@
 use defs::control::{
     Control,
     KeyCode,
-    InputState
+    InputState,
+    MouseButton,
+    InputEvent
 };
 
 /// UserControl struct
-/// Handles left/right/up/down inputs from a keyboard
+/// Handles left/right/up/down inputs from a keyboard, the mouse, and typed text. Discrete events
+/// are collected as they arrive and made available together after the next update.
 pub struct UserControl {
     dx: f32,
-    dy: f32
+    dy: f32,
+    mouse_position: Option<(f32, f32)>,
+    mouse_buttons_down: [bool; 3],
+    pending_events: Vec<InputEvent>,
+    frame_events: Vec<InputEvent>
 }
 
 impl Default for UserControl {
@@ -18,18 +25,26 @@ impl Default for UserControl {
     fn default() -> UserControl {
         UserControl {
             dx: 0.0,
-            dy: 0.0
+            dy: 0.0,
+            mouse_position: None,
+            mouse_buttons_down: [false; 3],
+            pending_events: vec![],
+            frame_events: vec![]
         }
     }
 }
 
 impl Control for UserControl {
 
-    /// No-op; nothing can be done internally here
-    fn update(&mut self) {}
+    /// Make the events received since the last update available, replacing the previous set
+    fn update(&mut self) {
+        self.frame_events.clear();
+        self.frame_events.append(&mut self.pending_events);
+    }
 
     /// Update internal fields in response to individual keystroke events
     fn process_keyboard_event(&mut self, keycode: KeyCode, state: InputState) {
+        self.pending_events.push(InputEvent::Key(keycode, state));
         match keycode {
             KeyCode::Left => {
                 self.dx = match state {
@@ -59,6 +74,22 @@ impl Control for UserControl {
         }
     }
 
+    /// Track the mouse position
+    fn process_mouse_move(&mut self, position: Option<(f32, f32)>) {
+        self.mouse_position = position;
+    }
+
+    /// Track which mouse buttons are held, and record the event
+    fn process_mouse_button_event(&mut self, button: MouseButton, state: InputState) {
+        self.pending_events.push(InputEvent::MouseButton(button, state));
+        self.mouse_buttons_down[mouse_button_index(button)] = state == InputState::Pressed;
+    }
+
+    /// Record the typed character
+    fn process_character(&mut self, c: char) {
+        self.pending_events.push(InputEvent::Character(c));
+    }
+
     /// Retrieve the left/right input position
     fn get_dx(&self) -> f32 {
         self.dx
@@ -68,4 +99,27 @@ impl Control for UserControl {
     fn get_dy(&self) -> f32 {
         self.dy
     }
+
+    /// Retrieve the last known mouse position
+    fn get_mouse_position(&self) -> Option<(f32, f32)> {
+        self.mouse_position
+    }
+
+    /// Check whether a mouse button is held
+    fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons_down[mouse_button_index(button)]
+    }
+
+    /// Retrieve the events made available by the last update
+    fn get_frame_events(&self) -> &[InputEvent] {
+        &self.frame_events
+    }
+}
+
+fn mouse_button_index(button: MouseButton) -> usize {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Right => 1,
+        MouseButton::Middle => 2
+    }
 }
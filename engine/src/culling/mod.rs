@@ -0,0 +1,202 @@
+
+use defs::{
+    Camera,
+    render::InstanceData
+};
+use cgmath::{
+    InnerSpace,
+    Matrix,
+    Matrix4,
+    Vector3,
+    Vector4
+};
+
+/// The bounds that models are culled by, re-exported for scenes that don't depend on the model
+/// crate themselves
+pub use model::types::Bounds;
+
+/// Plane struct
+/// Plane given by a unit normal and its signed distance along that normal from the origin, such
+/// that points on the side the normal faces have positive distance from it
+#[derive(Copy, Clone, Debug)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub distance: f32
+}
+
+impl Plane {
+
+    /// Create from the coefficients of the plane equation ax + by + cz + d = 0, normalising them
+    fn from_coefficients(coefficients: Vector4<f32>) -> Plane {
+        let normal = coefficients.truncate();
+        let length = normal.magnitude().max(f32::EPSILON);
+        Plane {
+            normal: normal / length,
+            distance: coefficients.w / length
+        }
+    }
+
+    /// Get the signed distance of a point from this plane
+    pub fn distance_to(&self, point: Vector3<f32>) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+/// Frustum struct
+/// The volume that a camera sees, as the six planes bounding it with their normals facing inwards.
+/// Extracted from a combined projection and view matrix that maps the volume onto Vulkan clip
+/// space, where x and y run from -w to w and depth from 0 to w, whether or not the depth is
+/// reversed.
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    planes: [Plane; 6]
+}
+
+impl Frustum {
+
+    /// Extract the planes from a combined projection and view matrix
+    pub fn from_matrix(pv_matrix: Matrix4<f32>) -> Frustum {
+        let (x, y, z, w) = (pv_matrix.row(0), pv_matrix.row(1), pv_matrix.row(2), pv_matrix.row(3));
+        Frustum {
+            planes: [
+                Plane::from_coefficients(w + x),
+                Plane::from_coefficients(w - x),
+                Plane::from_coefficients(w + y),
+                Plane::from_coefficients(w - y),
+                Plane::from_coefficients(z),
+                Plane::from_coefficients(w - z)
+            ]
+        }
+    }
+
+    /// Extract the planes of what a camera currently sees
+    pub fn from_camera(camera: &dyn Camera) -> Frustum {
+        Self::from_matrix(camera.get_projection_matrix() * camera.get_view_matrix())
+    }
+
+    pub fn get_planes(&self) -> &[Plane; 6] {
+        &self.planes
+    }
+
+    /// Check whether any part of a sphere may be inside the frustum
+    pub fn intersects_sphere(&self, centre: Vector3<f32>, radius: f32) -> bool {
+        self.planes.iter().all(|plane| plane.distance_to(centre) >= -radius)
+    }
+
+    /// Check whether any part of an axis-aligned box may be inside the frustum, by testing the
+    /// corner furthest along each plane's normal
+    pub fn intersects_aabb(&self, min: Vector3<f32>, max: Vector3<f32>) -> bool {
+        self.planes.iter().all(|plane| {
+            let furthest = Vector3::new(
+                match plane.normal.x >= 0.0 { true => max.x, false => min.x },
+                match plane.normal.y >= 0.0 { true => max.y, false => min.y },
+                match plane.normal.z >= 0.0 { true => max.z, false => min.z });
+            plane.distance_to(furthest) >= 0.0
+        })
+    }
+
+    /// Check whether any part of a model, with the given bounds in model space, may be inside the
+    /// frustum once placed in the world by a model matrix. The bounding sphere is tested first as
+    /// it is cheaper, then the box, which fits most models more tightly.
+    pub fn intersects_bounds(&self, bounds: &Bounds, model_matrix: Matrix4<f32>) -> bool {
+        let (centre, radius) = transform_sphere(bounds, model_matrix);
+        if !self.intersects_sphere(centre, radius) {
+            return false;
+        }
+        let (min, max) = transform_aabb(bounds, model_matrix);
+        self.intersects_aabb(min, max)
+    }
+}
+
+/// Get the centre and radius of a sphere enclosing a model's bounding sphere once transformed,
+/// scaling the radius by the largest scale along any axis
+pub fn transform_sphere(bounds: &Bounds, model_matrix: Matrix4<f32>) -> (Vector3<f32>, f32) {
+    let centre = model_matrix * Vector3::from(bounds.centre).extend(1.0);
+    let scale = model_matrix.x.truncate().magnitude()
+        .max(model_matrix.y.truncate().magnitude())
+        .max(model_matrix.z.truncate().magnitude());
+    (centre.truncate(), bounds.radius * scale)
+}
+
+/// Get the minimum and maximum corners of the axis-aligned box enclosing a model's bounding box
+/// once transformed, by adding up how far each axis of the matrix moves each side of the box
+pub fn transform_aabb(bounds: &Bounds, model_matrix: Matrix4<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    let mut min = model_matrix.w.truncate();
+    let mut max = min;
+    let columns = [model_matrix.x.truncate(), model_matrix.y.truncate(), model_matrix.z.truncate()];
+    for (axis, column) in columns.iter().enumerate() {
+        let low = column * bounds.min[axis];
+        let high = column * bounds.max[axis];
+        min += Vector3::new(low.x.min(high.x), low.y.min(high.y), low.z.min(high.z));
+        max += Vector3::new(low.x.max(high.x), low.y.max(high.y), low.z.max(high.z));
+    }
+    (min, max)
+}
+
+/// Get the instances of a model that may be inside the frustum, in their original order, ready to
+/// replace the contents of an instance buffer
+pub fn cull_instances(frustum: &Frustum, bounds: &Bounds, instances: &[InstanceData]) -> Vec<InstanceData> {
+    instances.iter()
+        .filter(|instance| frustum.intersects_bounds(bounds, instance.model_matrix))
+        .copied()
+        .collect()
+}
+
+/// Test suite
+/// Check what a perspective camera's frustum keeps and rejects, including instances of a model
+#[cfg(test)]
+mod test {
+    use crate::{
+        camera::{
+            make_vulkan_perspective_matrix,
+            make_vulkan_reversed_z_perspective_matrix,
+            make_yaw_pitch_view_matrix
+        },
+        culling::{
+            cull_instances,
+            Frustum
+        }
+    };
+    use defs::render::InstanceData;
+    use model::types::Bounds;
+    use cgmath::{
+        Deg,
+        Matrix4,
+        Vector3,
+        Vector4
+    };
+
+    #[test]
+    fn frustum_keeps_visible_bounds_with_either_depth_direction() {
+        let view = make_yaw_pitch_view_matrix(Vector3::new(0.0, 0.0, -10.0), 0.0, 0.0);
+        let bounds = Bounds::from_positions(
+            [[-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]].iter().copied());
+        for projection in [
+            make_vulkan_perspective_matrix(1.0, Deg(90.0).into(), 0.1, 100.0),
+            make_vulkan_reversed_z_perspective_matrix(1.0, Deg(90.0).into(), 0.1, 100.0)
+        ] {
+            let frustum = Frustum::from_matrix(projection * view);
+            let place = |x: f32, z: f32| Matrix4::from_translation(Vector3::new(x, 0.0, z));
+
+            // Ahead, behind, off to the side at a 45 degree half-angle, and past the far plane
+            assert!(frustum.intersects_bounds(&bounds, place(0.0, 0.0)));
+            assert!(!frustum.intersects_bounds(&bounds, place(0.0, -15.0)));
+            assert!(!frustum.intersects_bounds(&bounds, place(15.0, 0.0)));
+            assert!(frustum.intersects_bounds(&bounds, place(10.5, 0.0)));
+            assert!(!frustum.intersects_bounds(&bounds, place(0.0, 95.0)));
+            assert!(!frustum.intersects_bounds(&bounds, Matrix4::from_translation(Vector3::new(0.0, 0.0, 95.0)) *
+                Matrix4::from_scale(2.0)));
+            assert!(frustum.intersects_bounds(&bounds, Matrix4::from_translation(Vector3::new(0.0, 0.0, 95.0)) *
+                Matrix4::from_scale(10.0)));
+
+            let tint = Vector4::new(1.0, 1.0, 1.0, 1.0);
+            let instances = [
+                InstanceData { model_matrix: place(-5.0, 0.0), tint },
+                InstanceData { model_matrix: place(-20.0, 0.0), tint },
+                InstanceData { model_matrix: place(5.0, 0.0), tint }
+            ];
+            let kept = cull_instances(&frustum, &bounds, &instances);
+            assert_eq!(kept, vec![instances[0], instances[2]]);
+        }
+    }
+}
@@ -0,0 +1,444 @@
+
+pub mod render;
+pub mod scene;
+
+use defs::control::Control;
+use std::{
+    any::{
+        Any,
+        TypeId
+    },
+    collections::HashMap
+};
+
+/// Entity struct
+/// Handle to an entity in a world. The generation tells apart entities that reuse the slot of one
+/// that was despawned, so that stale handles find nothing.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Entity {
+    index: u32,
+    generation: u32
+}
+
+/// ComponentStorage trait
+/// Type-erased access to the storage for one type of component
+trait ComponentStorage {
+    fn remove_at(&mut self, index: usize);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Storage struct
+/// Components of one type, stored by entity index alongside the generation of their entity
+struct Storage<T> {
+    slots: Vec<Option<(u32, T)>>
+}
+
+impl<T: 'static> ComponentStorage for Storage<T> {
+
+    fn remove_at(&mut self, index: usize) {
+        if let Some(slot) = self.slots.get_mut(index) {
+            *slot = None;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T: 'static> Storage<T> {
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        match self.slots.get(entity.index as usize) {
+            Some(Some((generation, component))) if *generation == entity.generation => Some(component),
+            _ => None
+        }
+    }
+
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.slots.get_mut(entity.index as usize) {
+            Some(Some((generation, component))) if *generation == entity.generation => Some(component),
+            _ => None
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| slot.as_ref()
+            .map(|(generation, component)| (Entity { index: index as u32, generation: *generation }, component)))
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.slots.iter_mut().enumerate().filter_map(|(index, slot)| slot.as_mut()
+            .map(|(generation, component)| (Entity { index: index as u32, generation: *generation }, component)))
+    }
+}
+
+/// World struct
+/// Entities, the components attached to them, and resources shared by all systems. Components are
+/// any 'static type, stored densely by entity index, with at most one of each type per entity.
+/// Queries visit entities in index order. Entities have no parents, so a Transform component
+/// places its entity directly in the world; objects that move together are moved by a system, or
+/// kept in a scene graph instead.
+#[derive(Default)]
+pub struct World {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free_indices: Vec<u32>,
+    storages: HashMap<TypeId, Box<dyn ComponentStorage>>,
+    resources: HashMap<TypeId, Box<dyn Any>>
+}
+
+impl World {
+
+    /// Create a world without any entities or resources
+    pub fn new() -> World {
+        World::default()
+    }
+
+    /// Create a new entity without any components
+    pub fn spawn(&mut self) -> Entity {
+        match self.free_indices.pop() {
+            Some(index) => {
+                self.alive[index as usize] = true;
+                Entity { index, generation: self.generations[index as usize] }
+            },
+            None => {
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity { index: self.generations.len() as u32 - 1, generation: 0 }
+            }
+        }
+    }
+
+    /// Remove an entity and all of its components, returning whether it was alive
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        let index = entity.index as usize;
+        for storage in self.storages.values_mut() {
+            storage.remove_at(index);
+        }
+        self.alive[index] = false;
+        self.generations[index] += 1;
+        self.free_indices.push(entity.index);
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        index < self.alive.len() && self.alive[index] && self.generations[index] == entity.generation
+    }
+
+    /// Attach a component to an entity, replacing any existing one of the same type. Does nothing
+    /// if the entity has been despawned.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        let storage = self.storage_mut_or_create::<T>();
+        let index = entity.index as usize;
+        if storage.slots.len() <= index {
+            storage.slots.resize_with(index + 1, || None);
+        }
+        storage.slots[index] = Some((entity.generation, component));
+    }
+
+    /// Detach a component from an entity, returning it
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        let storage = self.storage_mut::<T>()?;
+        let slot = storage.slots.get_mut(entity.index as usize)?;
+        match slot {
+            Some((generation, _)) if *generation == entity.generation => slot.take().map(|(_, component)| component),
+            _ => None
+        }
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        self.storage::<T>()?.get(entity)
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.storage_mut::<T>()?.get_mut(entity)
+    }
+
+    /// Visit every entity with a component of the given type
+    pub fn query<T: 'static>(&self) -> Box<dyn Iterator<Item = (Entity, &T)> + '_> {
+        match self.storage::<T>() {
+            Some(storage) => Box::new(storage.iter()),
+            None => Box::new(std::iter::empty())
+        }
+    }
+
+    /// Visit every entity with components of both given types
+    pub fn query2<A: 'static, B: 'static>(&self) -> Box<dyn Iterator<Item = (Entity, &A, &B)> + '_> {
+        match (self.storage::<A>(), self.storage::<B>()) {
+            (Some(a), Some(b)) => Box::new(a.iter()
+                .filter_map(move |(entity, first)| b.get(entity).map(|second| (entity, first, second)))),
+            _ => Box::new(std::iter::empty())
+        }
+    }
+
+    /// Change the component of the given type on every entity that has one
+    pub fn for_each_mut<T: 'static>(&mut self, mut f: impl FnMut(Entity, &mut T)) {
+        if let Some(storage) = self.storage_mut::<T>() {
+            for (entity, component) in storage.iter_mut() {
+                f(entity, component);
+            }
+        }
+    }
+
+    /// Change the component of the first type on every entity that also has one of the second
+    /// type, which is read alongside it. The two types must differ.
+    pub fn for_each2_mut<A: 'static, B: 'static>(&mut self, mut f: impl FnMut(Entity, &mut A, &B)) {
+        let mut first = match self.storages.remove(&TypeId::of::<A>()) {
+            Some(storage) => storage,
+            None => return
+        };
+        if let (Some(a), Some(b)) = (first.as_any_mut().downcast_mut::<Storage<A>>(), self.storage::<B>()) {
+            for (entity, component) in a.iter_mut() {
+                if let Some(other) = b.get(entity) {
+                    f(entity, component, other);
+                }
+            }
+        }
+        self.storages.insert(TypeId::of::<A>(), first);
+    }
+
+    /// Add or replace a resource, which is a single value shared by all systems
+    pub fn insert_resource<T: 'static>(&mut self, resource: T) {
+        self.resources.insert(TypeId::of::<T>(), Box::new(resource));
+    }
+
+    pub fn resource<T: 'static>(&self) -> Option<&T> {
+        self.resources.get(&TypeId::of::<T>())?.downcast_ref::<T>()
+    }
+
+    pub fn resource_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.resources.get_mut(&TypeId::of::<T>())?.downcast_mut::<T>()
+    }
+
+    fn storage<T: 'static>(&self) -> Option<&Storage<T>> {
+        self.storages.get(&TypeId::of::<T>())?.as_any().downcast_ref::<Storage<T>>()
+    }
+
+    fn storage_mut<T: 'static>(&mut self) -> Option<&mut Storage<T>> {
+        self.storages.get_mut(&TypeId::of::<T>())?.as_any_mut().downcast_mut::<Storage<T>>()
+    }
+
+    fn storage_mut_or_create<T: 'static>(&mut self) -> &mut Storage<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Storage::<T> { slots: vec![] }))
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()
+            .unwrap()
+    }
+}
+
+/// SystemContext struct
+/// Per-update information passed to each system as it runs
+pub struct SystemContext<'a> {
+    pub time_step_millis: u64,
+    pub controller: &'a dyn Control
+}
+
+/// System trait
+/// Logic that runs over a world once per update. Implemented for closures taking the world and
+/// the context.
+pub trait System {
+    fn run(&mut self, world: &mut World, context: &SystemContext);
+}
+
+impl<F> System for F where F: FnMut(&mut World, &SystemContext) {
+    fn run(&mut self, world: &mut World, context: &SystemContext) {
+        self(world, context)
+    }
+}
+
+/// Stage enum
+/// Points in an update at which systems run, in the order listed: input handling, then game
+/// logic, then work that depends on its results such as render extraction
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Stage {
+    PreUpdate,
+    Update,
+    PostUpdate
+}
+
+/// Schedule struct
+/// Systems to run on each update, by stage, and within each stage in the order they were added.
+/// Scenes run their schedule from on_time_elapsed, usually through a SceneWorld.
+#[derive(Default)]
+pub struct Schedule {
+    pre_update: Vec<Box<dyn System>>,
+    update: Vec<Box<dyn System>>,
+    post_update: Vec<Box<dyn System>>
+}
+
+impl Schedule {
+
+    /// Create a schedule without any systems
+    pub fn new() -> Schedule {
+        Schedule::default()
+    }
+
+    /// Add a system to run at the given stage, after those already added there
+    pub fn add_system(&mut self, stage: Stage, system: impl System + 'static) {
+        let systems = match stage {
+            Stage::PreUpdate => &mut self.pre_update,
+            Stage::Update => &mut self.update,
+            Stage::PostUpdate => &mut self.post_update
+        };
+        systems.push(Box::new(system));
+    }
+
+    /// Run every system once, stage by stage
+    pub fn run(&mut self, world: &mut World, time_step_millis: u64, controller: &dyn Control) {
+        let context = SystemContext { time_step_millis, controller };
+        for system in self.pre_update.iter_mut()
+            .chain(self.update.iter_mut())
+            .chain(self.post_update.iter_mut()) {
+            system.run(world, &context);
+        }
+    }
+}
+
+/// Test suite
+/// Check entity lifetimes, queries over several components, that a schedule runs its stages in
+/// order through to render extraction, and that a scene world keeps its steps in place
+#[cfg(test)]
+mod test {
+    use crate::{
+        control::null::NullControl,
+        ecs::{
+            render::{
+                extract_drawing_steps,
+                ExtractedSteps,
+                Material,
+                Mesh
+            },
+            scene::SceneWorld,
+            Schedule,
+            Stage,
+            SystemContext,
+            World
+        },
+        scene_graph::Transform
+    };
+    use defs::{
+        render::{
+            Shader,
+            VertexFormat
+        },
+        ubo::MvpUbo
+    };
+    use cgmath::{
+        Matrix4,
+        Vector3
+    };
+
+    /// Velocity component, in units per second
+    struct Velocity(Vector3<f32>);
+
+    #[test]
+    fn despawned_entities_are_not_found_through_stale_handles() {
+        let mut world = World::new();
+        let first = world.spawn();
+        world.insert(first, 5u32);
+        assert!(world.despawn(first));
+        assert!(!world.despawn(first));
+
+        let second = world.spawn();
+        assert!(!world.is_alive(first));
+        assert!(world.is_alive(second));
+        assert!(world.get::<u32>(second).is_none());
+        world.insert(first, 7u32);
+        assert!(world.get::<u32>(first).is_none());
+        assert_eq!(world.query::<u32>().count(), 0);
+    }
+
+    #[test]
+    fn schedule_moves_entities_then_extracts_drawing_steps() {
+        let mut world = World::new();
+        let mesh = Mesh { vbo_index: 3, vbo_format: VertexFormat::PositionNormalTexture, draw_indexed: false };
+        let boat = world.spawn();
+        world.insert(boat, Transform::default());
+        world.insert(boat, Velocity(Vector3::new(2.0, 0.0, 0.0)));
+        world.insert(boat, mesh);
+        world.insert(boat, Material { shader: Shader::PlainPnt, texture_indices: vec![1], depth_test: true });
+        let invisible = world.spawn();
+        world.insert(invisible, mesh);
+        let water = world.spawn();
+        world.insert(water, mesh);
+        world.insert(water, Material { shader: Shader::Water, texture_indices: vec![], depth_test: true });
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::PostUpdate, extract_drawing_steps);
+        schedule.add_system(Stage::Update, |world: &mut World, context: &SystemContext| {
+            let seconds = context.time_step_millis as f32 / 1000.0;
+            world.for_each2_mut::<Transform, Velocity>(|_, transform, velocity| {
+                transform.translation += velocity.0 * seconds;
+            });
+        });
+        schedule.run(&mut world, 500, &NullControl::default());
+
+        let extracted = world.resource::<ExtractedSteps>().unwrap();
+        assert_eq!(extracted.entities, vec![boat, water]);
+        assert_eq!(extracted.steps[0].shader, Shader::PlainPnt);
+        assert_eq!(extracted.steps[0].texture_indices, vec![1]);
+        assert_eq!(extracted.steps[1].shader, Shader::Water);
+        assert_eq!(extracted.steps[1].vbo_index, 3);
+        assert!((extracted.matrices[0].w.x - 1.0).abs() < 0.0001);
+        assert!((extracted.matrices[1].w.x).abs() < 0.0001);
+    }
+
+    #[test]
+    fn scene_world_keeps_steps_in_place_as_entities_move_and_despawn() {
+        let mut world = World::new();
+        let mesh = Mesh { vbo_index: 1, vbo_format: VertexFormat::PositionNormalTexture, draw_indexed: false };
+        let material = Material { shader: Shader::PlainPnt, texture_indices: vec![0], depth_test: true };
+        let boat = world.spawn();
+        world.insert(boat, Transform::default());
+        world.insert(boat, Velocity(Vector3::new(2.0, 0.0, 0.0)));
+        world.insert(boat, mesh);
+        world.insert(boat, material.clone());
+        let buoy = world.spawn();
+        world.insert(buoy, mesh);
+        world.insert(buoy, material.clone());
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, |world: &mut World, context: &SystemContext| {
+            let seconds = context.time_step_millis as f32 / 1000.0;
+            world.for_each2_mut::<Transform, Velocity>(|_, transform, velocity| {
+                transform.translation += velocity.0 * seconds;
+            });
+        });
+        let mut scene_world = SceneWorld::new(world, schedule);
+        assert_eq!(scene_world.step_count(), 2);
+        assert_eq!(scene_world.make_steps()[1].vbo_index, 1);
+
+        // Spawning after creation adds no steps, and despawning hides a step without moving others
+        let late = scene_world.world_mut().spawn();
+        scene_world.world_mut().insert(late, mesh);
+        scene_world.world_mut().insert(late, material);
+        scene_world.world_mut().despawn(buoy);
+        let pv_matrix = Matrix4::from_scale(2.0);
+        scene_world.update(pv_matrix, 500, &NullControl::default());
+        assert_eq!(scene_world.step_count(), 2);
+        assert_eq!(scene_world.step_entity(0), boat);
+        assert!(scene_world.is_step_visible(0));
+        assert!(!scene_world.is_step_visible(1));
+        let (ptr, size) = scene_world.get_ubo_data_ptr_and_size(0);
+        assert_eq!(size, std::mem::size_of::<MvpUbo>());
+        let ubo = unsafe { &*(ptr as *const MvpUbo) };
+        assert!((ubo.matrix.w.x - 2.0).abs() < 0.0001);
+        assert!((ubo.matrix.x.x - 2.0).abs() < 0.0001);
+        assert!(scene_world.world().resource::<ExtractedSteps>().is_some());
+    }
+}
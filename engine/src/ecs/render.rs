@@ -0,0 +1,91 @@
+
+use crate::{
+    culling::Frustum,
+    ecs::{
+        Entity,
+        SystemContext,
+        World
+    },
+    scene_graph::Transform
+};
+use defs::render::{
+    DrawingStep,
+    Shader,
+    VertexFormat
+};
+use model::types::Bounds;
+use cgmath::{
+    Matrix4,
+    SquareMatrix
+};
+
+/// Mesh struct
+/// Component naming the vertex buffer that an entity is drawn from
+#[derive(Copy, Clone, Debug)]
+pub struct Mesh {
+    pub vbo_index: usize,
+    pub vbo_format: VertexFormat,
+    pub draw_indexed: bool
+}
+
+/// Material struct
+/// Component describing how an entity's mesh is shaded
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub shader: Shader,
+    pub texture_indices: Vec<usize>,
+    pub depth_test: bool
+}
+
+/// ViewProjection struct
+/// Resource holding the camera's combined projection and view matrix for the current update
+#[derive(Copy, Clone, Debug)]
+pub struct ViewProjection(pub Matrix4<f32>);
+
+/// ExtractedSteps struct
+/// Resource filled by render extraction with one drawing step per entity that has both a mesh and
+/// a material, in entity order. Entries at the same position in each list belong together, so the
+/// index of a step also gives the entity it was made from, the matrix for its uniform buffer, and
+/// whether it needs drawing this frame. The matrix is the entity's world-space transform (or
+/// identity if it has none) combined with the ViewProjection resource, if there is one; entities
+/// with a Bounds component are only visible if those bounds, once transformed, are within that
+/// camera's view.
+#[derive(Default)]
+pub struct ExtractedSteps {
+    pub steps: Vec<DrawingStep>,
+    pub entities: Vec<Entity>,
+    pub matrices: Vec<Matrix4<f32>>,
+    pub visible: Vec<bool>
+}
+
+/// Build drawing steps from entities with mesh and material components, replacing the contents
+/// of the ExtractedSteps resource. Meant to be added to a schedule's PostUpdate stage.
+pub fn extract_drawing_steps(world: &mut World, _context: &SystemContext) {
+    let (pv_matrix, frustum) = match world.resource::<ViewProjection>() {
+        Some(ViewProjection(matrix)) => (*matrix, Some(Frustum::from_matrix(*matrix))),
+        None => (Matrix4::identity(), None)
+    };
+    let mut extracted = ExtractedSteps::default();
+    for (entity, mesh, material) in world.query2::<Mesh, Material>() {
+        let model_matrix = match world.get::<Transform>(entity) {
+            Some(transform) => transform.to_matrix(),
+            None => Matrix4::identity()
+        };
+        extracted.steps.push(DrawingStep {
+            shader: material.shader,
+            vbo_index: mesh.vbo_index,
+            vbo_format: mesh.vbo_format,
+            draw_indexed: mesh.draw_indexed,
+            texture_indices: material.texture_indices.clone(),
+            depth_test: material.depth_test,
+            instance_buffer_index: None
+        });
+        extracted.entities.push(entity);
+        extracted.matrices.push(pv_matrix * model_matrix);
+        extracted.visible.push(match (&frustum, world.get::<Bounds>(entity)) {
+            (Some(frustum), Some(bounds)) => frustum.intersects_bounds(bounds, model_matrix),
+            _ => true
+        });
+    }
+    world.insert_resource(extracted);
+}
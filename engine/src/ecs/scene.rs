@@ -0,0 +1,122 @@
+
+use crate::{
+    control::null::NullControl,
+    ecs::{
+        Entity,
+        Schedule,
+        SystemContext,
+        World,
+        render::{
+            extract_drawing_steps,
+            ExtractedSteps,
+            ViewProjection
+        }
+    }
+};
+use defs::{
+    control::Control,
+    render::DrawingStep,
+    ubo::MvpUbo
+};
+use cgmath::{
+    Matrix4,
+    SquareMatrix
+};
+
+/// SceneWorld struct
+/// Glue between a world and the scene that draws it. The scene runs the world's schedule from its
+/// on_time_elapsed through update, and places the world's drawing steps as a run within one of
+/// its passes, forwarding the uniform buffer and visibility queries for that run here. The steps
+/// are fixed when this is created, one per entity with a mesh and a material at that time, so that
+/// the scene's description stays valid; later on, those entities move as their transforms change
+/// and are hidden once despawned or culled, while entities given meshes afterwards are not drawn.
+/// Each step's shader takes an MvpUbo, such as PlainPnt or Water.
+pub struct SceneWorld {
+    world: World,
+    schedule: Schedule,
+    entities: Vec<Entity>,
+    steps: Vec<DrawingStep>,
+    ubos: Vec<MvpUbo>,
+    visible: Vec<bool>
+}
+
+impl SceneWorld {
+
+    /// Take ownership of a world and its schedule, fixing the drawing steps from the entities in
+    /// the world now. Render extraction runs after the schedule, so it need not be added to it.
+    pub fn new(mut world: World, schedule: Schedule) -> SceneWorld {
+        let controller = NullControl::default();
+        extract_drawing_steps(&mut world, &SystemContext { time_step_millis: 0, controller: &controller });
+        let extracted = world.resource_mut::<ExtractedSteps>().map(std::mem::take).unwrap_or_default();
+        let ubos = extracted.matrices.iter().map(|matrix| MvpUbo { matrix: *matrix }).collect();
+        SceneWorld {
+            world,
+            schedule,
+            entities: extracted.entities,
+            steps: extracted.steps,
+            ubos,
+            visible: extracted.visible
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Run the schedule as seen from the camera with the given combined projection and view, then
+    /// extract the steps' matrices and visibility for the next frame
+    pub fn update(&mut self, pv_matrix: Matrix4<f32>, time_step_millis: u64, controller: &dyn Control) {
+        self.world.insert_resource(ViewProjection(pv_matrix));
+        self.schedule.run(&mut self.world, time_step_millis, controller);
+        extract_drawing_steps(&mut self.world, &SystemContext { time_step_millis, controller });
+        let extracted = match self.world.resource::<ExtractedSteps>() {
+            Some(extracted) => extracted,
+            None => return
+        };
+        for (step_index, entity) in self.entities.iter().enumerate() {
+            match extracted.entities.iter().position(|e| e == entity) {
+                Some(index) => {
+                    self.ubos[step_index].matrix = extracted.matrices[index];
+                    self.visible[step_index] = extracted.visible[index];
+                },
+                None => {
+                    self.ubos[step_index].matrix = Matrix4::identity();
+                    self.visible[step_index] = false;
+                }
+            }
+        }
+    }
+
+    /// The drawing steps for the scene to place in its description, one per entity
+    pub fn make_steps(&self) -> Vec<DrawingStep> {
+        self.steps.clone()
+    }
+
+    /// The number of drawing steps
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Get the entity that a step draws
+    pub fn step_entity(&self, step_index: usize) -> Entity {
+        self.entities[step_index]
+    }
+
+    /// Get a pointer to the uniform buffer of a step, counted from the first of the world's steps,
+    /// and its size in bytes
+    pub fn get_ubo_data_ptr_and_size(&self, step_index: usize) -> (*const u8, usize) {
+        (
+            &self.ubos[step_index] as *const MvpUbo as *const u8,
+            std::mem::size_of::<MvpUbo>()
+        )
+    }
+
+    /// Check whether a step, counted from the first of the world's steps, needs drawing this frame
+    pub fn is_step_visible(&self, step_index: usize) -> bool {
+        self.visible[step_index]
+    }
+}
@@ -1,9 +1,19 @@
 
 pub mod camera;
+pub mod collision;
 pub mod control;
+pub mod culling;
+pub mod ecs;
+pub mod localisation;
+pub mod lod;
 pub mod timer;
 pub mod util;
+pub mod vfs;
+pub mod watcher;
 pub mod scene;
+pub mod scene_graph;
+pub mod spatial;
+pub mod ui;
 
 use defs::{
     EngineError,
@@ -12,7 +22,8 @@ use defs::{
     control::{
         Control,
         KeyCode,
-        InputState
+        InputState,
+        MouseButton
     },
     render::{
         RendererApi,
@@ -24,7 +35,10 @@ use defs::{
 use renderer::null::NullRenderer;
 
 use raw_window_handle::HasRawWindowHandle;
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    path::Path
+};
 
 /// Engine struct
 /// The encapsulation of everything needed to run the application, with the exception of OS-
@@ -37,6 +51,7 @@ pub struct Engine<R> where R : RendererApi {
     controller: Box<dyn Control>,
     timer: Box<dyn crate::timer::Timer>,
     drawing_description: DrawingDescription,
+    resource_watcher: Option<crate::watcher::ResourceWatcher>
 }
 
 impl<R: 'static> Engine<R> where R : RendererApi {
@@ -55,9 +70,20 @@ impl<R: 'static> Engine<R> where R : RendererApi {
             controller: Box::new(crate::control::null::NullControl::default()),
             timer: Box::new(crate::timer::null::NullTimer::default()),
             drawing_description: DrawingDescription { passes: Vec::new() },
+            resource_watcher: None
         }
     }
 
+    /// Watch a directory of resource source files, polling it at the given interval. When files
+    /// change, the current scene is notified and any resources it re-decodes are swapped into
+    /// the renderer in place. Intended for use during development; may be called for more than
+    /// one directory, in which case the first poll interval given is used.
+    pub fn watch_resources(&mut self, root: &Path, poll_interval_millis: u64) {
+        self.resource_watcher
+            .get_or_insert_with(|| crate::watcher::ResourceWatcher::new(poll_interval_millis))
+            .watch_directory(root);
+    }
+
     /// Initialise the engine, given an available window to use. Creates the renderer and does
     /// the initialisation required by the current scene.
     pub fn initialise(&mut self, window_owner: &dyn HasRawWindowHandle) {
@@ -80,6 +106,22 @@ impl<R: 'static> Engine<R> where R : RendererApi {
         self.controller.process_keyboard_event(keycode, state);
     }
 
+    /// Pass mouse movement to the controller, as fractions of the window size from its top-left
+    /// corner, or None if the mouse has left the window
+    pub fn process_mouse_move(&mut self, position: Option<(f32, f32)>) {
+        self.controller.process_mouse_move(position);
+    }
+
+    /// Pass mouse button events to the controller
+    pub fn process_mouse_button_event(&mut self, button: MouseButton, state: InputState) {
+        self.controller.process_mouse_button_event(button, state);
+    }
+
+    /// Pass typed characters to the controller
+    pub fn process_character(&mut self, c: char) {
+        self.controller.process_character(c);
+    }
+
     /// Retrieve a time step from the engine's timer
     pub fn pull_time_step_millis(&mut self) -> u64 {
         self.timer.pull_time_step_millis()
@@ -112,8 +154,42 @@ impl<R: 'static> Engine<R> where R : RendererApi {
             let resource_preloads = self.scene_host.get_current().make_preloads();
             let description = self.scene_host.get_current().make_description();
             self.renderer.recreate_scene_resources(&resource_preloads, &description).unwrap();
+            self.drawing_description = description;
+        }
+        if let Some(watcher) = self.resource_watcher.as_mut() {
+            let changed_paths = watcher.on_time_elapsed(time_step_millis);
+            if !changed_paths.is_empty() {
+
+                // A file saved part-way through an edit should not end the session, so failures
+                // are reported and the renderer keeps the resources it already had
+                match self.scene_host.on_resources_changed(&changed_paths) {
+                    Ok(Some(replacements)) => {
+                        if let Err(e) = self.renderer.replace_scene_resources(&replacements, &self.drawing_description) {
+                            eprintln!("[Error] Failed to replace reloaded resources: {:?}", e);
+                        }
+                    },
+                    Ok(None) => {},
+                    Err(e) => eprintln!("[Error] Failed to reload changed resources: {:?}", e)
+                }
+            }
         }
         self.scene_host.on_pre_render();
+
+        // As with reloads, a rejected update is reported and the buffer keeps its previous data
+        if let Some(updates) = self.scene_host.take_instance_updates() {
+            for (instance_buffer_index, instances) in updates.iter() {
+                if let Err(e) = self.renderer.update_instance_data(*instance_buffer_index, instances) {
+                    eprintln!("[Error] Failed to update instance buffer {}: {:?}", instance_buffer_index, e);
+                }
+            }
+        }
+        if let Some(updates) = self.scene_host.take_dynamic_vbo_updates() {
+            for (vbo_index, vertices) in updates.iter() {
+                if let Err(e) = self.renderer.update_dynamic_vbo_data(*vbo_index, vertices) {
+                    eprintln!("[Error] Failed to update dynamic VBO {}: {:?}", vbo_index, e);
+                }
+            }
+        }
     }
 
     /// Perform the render event. Instructs the renderer to draw the frame and then does some
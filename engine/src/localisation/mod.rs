@@ -0,0 +1,612 @@
+
+use defs::EngineError;
+use model::types::StaticVertex;
+use crate::util::textbuffer::{
+    TextAlignment,
+    TextGenerator
+};
+use crate::vfs::Vfs;
+
+use std::collections::HashMap;
+
+/// PluralCategory enum
+/// The plural forms that a language may distinguish between, as named by the Unicode CLDR. A
+/// string table gives each form of a string as `key[category] = value`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other
+}
+
+impl PluralCategory {
+
+    /// The name of the category, as written in string table keys
+    pub fn name(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other"
+        }
+    }
+
+    /// Find the category with the given name
+    pub fn from_name(name: &str) -> Option<PluralCategory> {
+        match name {
+            "zero" => Some(PluralCategory::Zero),
+            "one" => Some(PluralCategory::One),
+            "two" => Some(PluralCategory::Two),
+            "few" => Some(PluralCategory::Few),
+            "many" => Some(PluralCategory::Many),
+            "other" => Some(PluralCategory::Other),
+            _ => None
+        }
+    }
+}
+
+/// PluralRule enum
+/// How a language picks the plural form for a whole-number count
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PluralRule {
+    /// One for exactly 1, other otherwise; English, German, Spanish and many more
+    OneOther,
+    /// One for 0 and 1, other otherwise; French and Portuguese
+    ZeroOneOther,
+    /// A single form for every count; Japanese, Chinese, Korean and others
+    OtherOnly,
+    /// One, few and many by the last digits; Russian and Ukrainian
+    EastSlavic,
+    /// One for exactly 1, few and many by the last digits otherwise; Polish
+    Polish,
+    /// One for 1, few for 2 to 4, other otherwise; Czech and Slovak
+    Czech
+}
+
+impl PluralRule {
+
+    /// Choose the rule for a language code such as "en" or "pt-BR", from its primary subtag.
+    /// Languages without a known rule use OneOther.
+    pub fn for_language(language: &str) -> PluralRule {
+        let primary = language.split(['-', '_'])
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        match primary.as_str() {
+            "fr" | "pt" => PluralRule::ZeroOneOther,
+            "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" => PluralRule::OtherOnly,
+            "ru" | "uk" | "be" => PluralRule::EastSlavic,
+            "pl" => PluralRule::Polish,
+            "cs" | "sk" => PluralRule::Czech,
+            _ => PluralRule::OneOther
+        }
+    }
+
+    /// Get the plural category for a count
+    pub fn category(&self, count: i64) -> PluralCategory {
+        let n = count.unsigned_abs();
+        let last_digit = n % 10;
+        let last_two_digits = n % 100;
+        match self {
+            PluralRule::OneOther => match n {
+                1 => PluralCategory::One,
+                _ => PluralCategory::Other
+            },
+            PluralRule::ZeroOneOther => match n {
+                0 | 1 => PluralCategory::One,
+                _ => PluralCategory::Other
+            },
+            PluralRule::OtherOnly => PluralCategory::Other,
+            PluralRule::EastSlavic => {
+                if last_digit == 1 && last_two_digits != 11 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&last_digit) && !(12..=14).contains(&last_two_digits) {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Many
+                }
+            },
+            PluralRule::Polish => {
+                if n == 1 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&last_digit) && !(12..=14).contains(&last_two_digits) {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Many
+                }
+            },
+            PluralRule::Czech => match n {
+                1 => PluralCategory::One,
+                2..=4 => PluralCategory::Few,
+                _ => PluralCategory::Other
+            }
+        }
+    }
+}
+
+/// StringTable struct
+/// The strings of a single language, read from a file of `key = value` lines. Lines starting
+/// with # are comments. Plural forms are given as `key[one] = value`, `key[other] = value` and
+/// so on, and values may contain \n for a line break and \\ for a backslash.
+pub struct StringTable {
+    language: String,
+    plural_rule: PluralRule,
+    entries: HashMap<String, String>
+}
+
+impl StringTable {
+
+    /// Read a table from the contents of a string file
+    pub fn parse(language: &str, text: &str) -> Result<StringTable, EngineError> {
+        let mut entries = HashMap::new();
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.trim_start_matches('\u{feff}').trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let sign_pos = line.find('=')
+                .ok_or_else(|| EngineError::DecodeError(
+                    format!("Expected key = value in {} strings, line {}", language, line_index + 1)))?;
+            let key = line[0..sign_pos].trim();
+            let (base_key, category) = split_plural_key(key);
+            let valid_category = category.map(|c| PluralCategory::from_name(c).is_some()).unwrap_or(true);
+            if base_key.is_empty() || !valid_category {
+                return Err(EngineError::DecodeError(
+                    format!("Bad key in {} strings, line {}: {}", language, line_index + 1, key)));
+            }
+            let value = unescape_value(line[(sign_pos + 1)..].trim());
+            if entries.insert(key.to_string(), value).is_some() {
+                return Err(EngineError::DecodeError(
+                    format!("Duplicate key in {} strings, line {}: {}", language, line_index + 1, key)));
+            }
+        }
+        Ok(StringTable {
+            language: language.to_string(),
+            plural_rule: PluralRule::for_language(language),
+            entries
+        })
+    }
+
+    /// The language code of the table
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Check whether the table has a string, in any of its plural forms
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key) || self.entries.keys().any(|k| split_plural_key(k).0 == key)
+    }
+
+    /// Every key in the table, without plural suffixes
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.entries.keys()
+            .map(|k| split_plural_key(k).0)
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+
+    /// Get a string that has no plural forms, or the other form of one that does
+    fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key)
+            .or_else(|| self.entries.get(&format!("{}[other]", key)))
+            .map(|value| value.as_str())
+    }
+
+    /// Get the plural form of a string that suits the count, falling back to the string without a
+    /// plural suffix and then to its other form
+    fn get_plural(&self, key: &str, count: i64) -> Option<&str> {
+        let category = self.plural_rule.category(count);
+        self.entries.get(&format!("{}[{}]", key, category.name()))
+            .map(|value| value.as_str())
+            .or_else(|| self.get(key))
+    }
+}
+
+/// Localisation struct
+/// A set of string tables, one per language, with one of them selected as the current language.
+/// Strings missing from the current language are taken from the fallback language, which is the
+/// first language added unless set otherwise, and failing that are shown as their key. Switching
+/// language increases the generation, which text built from these strings can compare against to
+/// know when to regenerate; see LocalisedText.
+#[derive(Default)]
+pub struct Localisation {
+    tables: Vec<StringTable>,
+    current: usize,
+    fallback: usize,
+    generation: u64
+}
+
+impl Localisation {
+
+    /// Create a new instance with no languages
+    pub fn new() -> Localisation {
+        Localisation::default()
+    }
+
+    /// Add a language from the contents of a string file, replacing any table already held for
+    /// the language. The first language added becomes the current and fallback language.
+    pub fn add_language_from_text(&mut self, language: &str, text: &str) -> Result<(), EngineError> {
+        let table = StringTable::parse(language, text)?;
+        match self.tables.iter().position(|t| t.language == language) {
+            Some(index) => {
+                self.tables[index] = table;
+                if index == self.current || index == self.fallback {
+                    self.generation += 1;
+                }
+            },
+            None => self.tables.push(table)
+        };
+        Ok(())
+    }
+
+    /// Add a language from a string file in the VFS, taking the language code from the file name
+    /// without its extension, so that "strings/fr.strings" holds French
+    pub fn load_language(&mut self, vfs: &Vfs, path: &str) -> Result<(), EngineError> {
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        let language = file_name.split('.').next().unwrap_or(file_name);
+        if language.is_empty() {
+            return Err(EngineError::GeneralError(format!("No language code in string file name: {}", path)));
+        }
+        let text = vfs.read_to_string(path)?;
+        self.add_language_from_text(language, &text)
+    }
+
+    /// The codes of every language added, in the order they were added
+    pub fn languages(&self) -> Vec<&str> {
+        self.tables.iter().map(|t| t.language()).collect()
+    }
+
+    /// The code of the current language, or None if no languages have been added
+    pub fn current_language(&self) -> Option<&str> {
+        self.tables.get(self.current).map(|t| t.language())
+    }
+
+    /// Switch to another language that has been added
+    pub fn set_language(&mut self, language: &str) -> Result<(), EngineError> {
+        let index = self.find_table(language)?;
+        if index != self.current {
+            self.current = index;
+            self.generation += 1;
+        }
+        Ok(())
+    }
+
+    /// Choose the language that strings missing from the current language are taken from
+    pub fn set_fallback_language(&mut self, language: &str) -> Result<(), EngineError> {
+        let index = self.find_table(language)?;
+        if index != self.fallback {
+            self.fallback = index;
+            self.generation += 1;
+        }
+        Ok(())
+    }
+
+    /// A counter that increases whenever the strings returned may have changed
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Get a string in the current language
+    pub fn get(&self, key: &str) -> String {
+        self.lookup(key, |table| table.get(key))
+    }
+
+    /// Get a string in the current language, replacing each {name} within it with the value of
+    /// the parameter of that name. {{ and }} are written as single braces.
+    pub fn format(&self, key: &str, params: &[(&str, &str)]) -> String {
+        substitute(&self.lookup(key, |table| table.get(key)), params)
+    }
+
+    /// Get the plural form of a string that suits the count, in the current language, with
+    /// parameters replaced as for format. The count is available as the parameter {count}.
+    pub fn format_plural(&self, key: &str, count: i64, params: &[(&str, &str)]) -> String {
+        let count_text = count.to_string();
+        let mut all_params = vec![("count", count_text.as_str())];
+        all_params.extend_from_slice(params);
+        substitute(&self.lookup(key, |table| table.get_plural(key, count)), &all_params)
+    }
+
+    /// Find the keys present in some language but missing from another, as pairs of the language
+    /// code and key
+    pub fn missing_keys(&self) -> Vec<(String, String)> {
+        let mut all_keys: Vec<&str> = self.tables.iter()
+            .flat_map(|t| t.keys())
+            .collect();
+        all_keys.sort_unstable();
+        all_keys.dedup();
+        let mut missing = vec![];
+        for table in self.tables.iter() {
+            for key in all_keys.iter() {
+                if !table.contains(key) {
+                    missing.push((table.language.clone(), key.to_string()));
+                }
+            }
+        }
+        missing
+    }
+
+    fn find_table(&self, language: &str) -> Result<usize, EngineError> {
+        self.tables.iter()
+            .position(|t| t.language == language)
+            .ok_or_else(|| EngineError::GeneralError(format!("Language not loaded: {}", language)))
+    }
+
+    /// Look up a string in the current language, then the fallback, then use the key itself
+    fn lookup<'a, F>(&'a self, key: &str, find: F) -> String
+        where F : Fn(&'a StringTable) -> Option<&'a str>
+    {
+        self.tables.get(self.current)
+            .and_then(&find)
+            .or_else(|| self.tables.get(self.fallback).and_then(&find))
+            .unwrap_or(key)
+            .to_string()
+    }
+}
+
+/// LocalisedText struct
+/// A string from a Localisation laid out by a TextGenerator, which keeps its vertices and lays
+/// them out again after the language changes or its parameters are altered
+pub struct LocalisedText {
+    key: String,
+    params: Vec<(String, String)>,
+    count: Option<i64>,
+    left_top: (f32, f32),
+    box_size: (f32, f32),
+    max_line_height: f32,
+    horizontal_alignment: TextAlignment,
+    vertical_alignment: TextAlignment,
+    generation: Option<u64>,
+    vertices: Vec<StaticVertex>
+}
+
+impl LocalisedText {
+
+    /// Create a new instance, with layout parameters as for TextGenerator::generate_vertex_buffer.
+    /// Vertices are not generated until refresh is called.
+    pub fn new(
+        key: &str,
+        left_top: (f32, f32),
+        box_size: (f32, f32),
+        max_line_height: f32,
+        horizontal_alignment: TextAlignment,
+        vertical_alignment: TextAlignment
+    ) -> LocalisedText {
+        LocalisedText {
+            key: key.to_string(),
+            params: vec![],
+            count: None,
+            left_top,
+            box_size,
+            max_line_height,
+            horizontal_alignment,
+            vertical_alignment,
+            generation: None,
+            vertices: vec![]
+        }
+    }
+
+    /// Set the value of a parameter, marking the text for regeneration if it changed
+    pub fn set_param(&mut self, name: &str, value: &str) {
+        match self.params.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) if existing == value => return,
+            Some((_, existing)) => *existing = value.to_string(),
+            None => self.params.push((name.to_string(), value.to_string()))
+        };
+        self.generation = None;
+    }
+
+    /// Set the count that picks the plural form, or None for a string without plural forms,
+    /// marking the text for regeneration if it changed
+    pub fn set_count(&mut self, count: Option<i64>) {
+        if self.count != count {
+            self.count = count;
+            self.generation = None;
+        }
+    }
+
+    /// The string as it reads in the current language
+    pub fn text(&self, localisation: &Localisation) -> String {
+        let params: Vec<(&str, &str)> = self.params.iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        match self.count {
+            Some(count) => localisation.format_plural(&self.key, count, &params),
+            None => localisation.format(&self.key, &params)
+        }
+    }
+
+    /// Regenerate the vertices if the language or parameters have changed since they were last
+    /// generated. Returns true if they were regenerated, in which case any vertex buffer made
+    /// from them should be replaced.
+    pub fn refresh(&mut self, localisation: &Localisation, generator: &TextGenerator) -> bool {
+        if self.generation == Some(localisation.generation()) {
+            return false;
+        }
+        self.vertices = generator.generate_vertex_buffer(
+            &self.text(localisation),
+            self.left_top,
+            self.box_size,
+            self.max_line_height,
+            self.horizontal_alignment,
+            self.vertical_alignment);
+        self.generation = Some(localisation.generation());
+        true
+    }
+
+    /// The vertices generated by the last refresh
+    pub fn vertices(&self) -> &[StaticVertex] {
+        &self.vertices
+    }
+}
+
+/// Split a key into its base key and plural category name, if it has one
+fn split_plural_key(key: &str) -> (&str, Option<&str>) {
+    match (key.find('['), key.ends_with(']')) {
+        (Some(open), true) => (key[0..open].trim_end(), Some(&key[(open + 1)..(key.len() - 1)])),
+        _ => (key, None)
+    }
+}
+
+/// Replace the escape sequences allowed in string file values
+fn unescape_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => result.push('\\')
+            },
+            _ => result.push(c)
+        }
+    }
+    result
+}
+
+/// Replace each {name} in a string with the value of the named parameter, leaving unknown names
+/// as they are, and replace {{ and }} with single braces
+fn substitute(text: &str, params: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut remaining = text;
+    while let Some(pos) = remaining.find(['{', '}']) {
+        result.push_str(&remaining[0..pos]);
+        let rest = &remaining[pos..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            result.push_str(&rest[0..1]);
+            remaining = &rest[2..];
+            continue;
+        }
+        let name_end = match rest.starts_with('{') {
+            true => rest.find('}'),
+            false => None
+        };
+        match name_end.and_then(|end| params.iter().find(|(name, _)| *name == &rest[1..end]).map(|p| (end, p.1))) {
+            Some((end, value)) => {
+                result.push_str(value);
+                remaining = &rest[(end + 1)..];
+            },
+            None => {
+                result.push_str(&rest[0..1]);
+                remaining = &rest[1..];
+            }
+        }
+    }
+    result.push_str(remaining);
+    result
+}
+
+/// Test suite
+/// Check that string files are read, parameters and plural forms are applied, and that switching
+/// language causes localised text to be regenerated
+#[cfg(test)]
+mod test {
+    use crate::localisation::{
+        Localisation,
+        LocalisedText,
+        PluralCategory,
+        PluralRule
+    };
+    use crate::util::textbuffer::{
+        TextAlignment,
+        TextGenerator
+    };
+
+    const TEST_FONT: &str = "common lineHeight=10 base=8 scaleW=16 scaleH=16
+char id=63 x=0 y=0 width=4 height=8 xoffset=0 yoffset=0 xadvance=5 page=0";
+
+    const ENGLISH: &str = "# Menu strings
+menu.title = Settings
+menu.greeting = Hello, {name}! {{braces}}
+menu.lines = First\\nSecond
+items[one] = {count} item
+items[other] = {count} items
+only_english = Fallback";
+
+    const RUSSIAN: &str = "menu.title = Настройки
+menu.greeting = Привет, {name}!
+menu.lines = Первая\\nВторая
+items[one] = {count} предмет
+items[few] = {count} предмета
+items[many] = {count} предметов";
+
+    fn make_localisation() -> Localisation {
+        let mut localisation = Localisation::new();
+        localisation.add_language_from_text("en", ENGLISH).unwrap();
+        localisation.add_language_from_text("ru", RUSSIAN).unwrap();
+        localisation
+    }
+
+    #[test]
+    fn strings_formatted_with_params_and_fallbacks() {
+        let mut localisation = make_localisation();
+        assert_eq!(localisation.current_language(), Some("en"));
+        assert_eq!(localisation.get("menu.title"), "Settings");
+        assert_eq!(localisation.get("menu.lines"), "First\nSecond");
+        assert_eq!(localisation.format("menu.greeting", &[("name", "Ann")]), "Hello, Ann! {braces}");
+        assert_eq!(localisation.format("menu.greeting", &[]), "Hello, {name}! {braces}");
+        assert_eq!(localisation.get("no.such.key"), "no.such.key");
+
+        localisation.set_language("ru").unwrap();
+        assert_eq!(localisation.get("menu.title"), "Настройки");
+        assert_eq!(localisation.get("only_english"), "Fallback");
+        assert!(localisation.set_language("de").is_err());
+        assert_eq!(localisation.missing_keys(), vec![("ru".to_string(), "only_english".to_string())]);
+
+        assert!(Localisation::new().add_language_from_text("en", "no separator").is_err());
+        assert!(Localisation::new().add_language_from_text("en", "a[lots] = b").is_err());
+        assert!(Localisation::new().add_language_from_text("en", "a = b\na = c").is_err());
+    }
+
+    #[test]
+    fn plural_forms_chosen_by_language_rules() {
+        let english = PluralRule::for_language("en-GB");
+        assert_eq!(english.category(1), PluralCategory::One);
+        assert_eq!(english.category(0), PluralCategory::Other);
+        assert_eq!(PluralRule::for_language("fr").category(0), PluralCategory::One);
+        assert_eq!(PluralRule::for_language("ja").category(1), PluralCategory::Other);
+        let polish = PluralRule::for_language("pl");
+        assert_eq!(polish.category(22), PluralCategory::Few);
+        assert_eq!(polish.category(21), PluralCategory::Many);
+
+        let mut localisation = make_localisation();
+        assert_eq!(localisation.format_plural("items", 1, &[]), "1 item");
+        assert_eq!(localisation.format_plural("items", 0, &[]), "0 items");
+        localisation.set_language("ru").unwrap();
+        let counts = [1, 3, 5, 11, 21, 22, 112];
+        let texts: Vec<String> = counts.iter()
+            .map(|count| localisation.format_plural("items", *count, &[]))
+            .collect();
+        assert_eq!(texts, vec![
+            "1 предмет", "3 предмета", "5 предметов", "11 предметов", "21 предмет", "22 предмета", "112 предметов"]);
+    }
+
+    #[test]
+    fn text_regenerated_when_language_changes() {
+        let generator = TextGenerator::from_resource(TEST_FONT);
+        let mut localisation = make_localisation();
+        let mut text = LocalisedText::new(
+            "menu.title", (0.0, 0.0), (20.0, 1.0), 1.0, TextAlignment::Start, TextAlignment::Start);
+        assert!(text.refresh(&localisation, &generator));
+        assert_eq!(text.vertices().len(), 6 * "Settings".len());
+        assert!(!text.refresh(&localisation, &generator));
+
+        localisation.set_language("ru").unwrap();
+        assert!(text.refresh(&localisation, &generator));
+        assert_eq!(text.vertices().len(), 6 * "Настройки".chars().count());
+
+        localisation.set_language("ru").unwrap();
+        assert!(!text.refresh(&localisation, &generator));
+        text.set_count(Some(2));
+        assert!(text.refresh(&localisation, &generator));
+        text.set_count(Some(2));
+        assert!(!text.refresh(&localisation, &generator));
+    }
+}
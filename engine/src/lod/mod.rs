@@ -0,0 +1,130 @@
+
+use cgmath::{
+    Matrix4,
+    Vector3
+};
+
+/// Get the fraction of the viewport's height that a sphere covers on screen, for a camera with
+/// the given view and projection matrices. This grows without limit as the camera gets inside the
+/// sphere.
+pub fn projected_sphere_size(
+    view_matrix: Matrix4<f32>,
+    projection_matrix: Matrix4<f32>,
+    centre: Vector3<f32>,
+    radius: f32
+) -> f32 {
+    let depth = (view_matrix * centre.extend(1.0)).z;
+    match depth > radius {
+        true => radius * projection_matrix.y.y / depth,
+        false => f32::INFINITY
+    }
+}
+
+/// LodChain struct
+/// Picks which of a model's levels of detail to draw, from the full-detail level 0 down through
+/// coarser levels, based on how large it appears on screen. Each level has a minimum screen size,
+/// as taken by projected_sphere_size, at which it is still used, after which the next one takes
+/// over. To stop a model flickering between levels while its size hovers around a threshold, it
+/// only moves to a coarser level once it is a given fraction smaller than the threshold, and only
+/// moves back once it is that fraction larger.
+pub struct LodChain {
+    min_screen_sizes: Vec<f32>,
+    hysteresis: f32,
+    current_level: usize
+}
+
+impl LodChain {
+
+    /// Fraction of a threshold by which screen size must pass it to change level, by default
+    pub const DEFAULT_HYSTERESIS: f32 = 0.1;
+
+    /// Create a chain given the minimum screen size for each level but the last, which is used at
+    /// any size; these should decrease from one level to the next. Starts at full detail.
+    pub fn new(min_screen_sizes: Vec<f32>) -> LodChain {
+        LodChain {
+            min_screen_sizes,
+            hysteresis: Self::DEFAULT_HYSTERESIS,
+            current_level: 0
+        }
+    }
+
+    /// Change the fraction of a threshold by which screen size must pass it to change level
+    pub fn with_hysteresis(self, hysteresis: f32) -> LodChain {
+        LodChain {
+            hysteresis: hysteresis.max(0.0),
+            ..self
+        }
+    }
+
+    /// Get the number of levels in this chain
+    pub fn level_count(&self) -> usize {
+        self.min_screen_sizes.len() + 1
+    }
+
+    pub fn get_current_level(&self) -> usize {
+        self.current_level
+    }
+
+    /// Move to the level suited to the given screen size, returning it. Moves as many levels as
+    /// needed at once, so that a model that jumps across the screen does not lag behind.
+    pub fn update(&mut self, screen_size: f32) -> usize {
+        while self.current_level > 0 &&
+            screen_size >= self.min_screen_sizes[self.current_level - 1] * (1.0 + self.hysteresis) {
+            self.current_level -= 1;
+        }
+        while self.current_level < self.min_screen_sizes.len() &&
+            screen_size < self.min_screen_sizes[self.current_level] * (1.0 - self.hysteresis) {
+            self.current_level += 1;
+        }
+        self.current_level
+    }
+
+    /// Check whether the given level is the one currently picked; with one drawing step per
+    /// level, this tells which step to draw
+    pub fn is_level_visible(&self, level: usize) -> bool {
+        level == self.current_level
+    }
+}
+
+/// Test suite
+/// Check screen size estimates and that levels only change once past the hysteresis band
+#[cfg(test)]
+mod test {
+    use crate::{
+        camera::{
+            make_vulkan_perspective_matrix,
+            make_yaw_pitch_view_matrix
+        },
+        lod::{
+            projected_sphere_size,
+            LodChain
+        }
+    };
+    use cgmath::{
+        Deg,
+        Vector3
+    };
+
+    #[test]
+    fn levels_switch_by_screen_size_with_hysteresis() {
+        let view = make_yaw_pitch_view_matrix(Vector3::new(0.0, 0.0, 0.0), 0.0, 0.0);
+        let projection = make_vulkan_perspective_matrix(1.0, Deg(90.0).into(), 0.1, 1000.0);
+
+        // With a 90 degree view, a sphere of radius 1 at distance 10 covers a tenth of the height
+        let size = projected_sphere_size(view, projection, Vector3::new(0.0, 0.0, 10.0), 1.0);
+        assert!((size - 0.1).abs() < 0.0001);
+        assert!(projected_sphere_size(view, projection, Vector3::new(0.0, 0.0, 0.5), 1.0).is_infinite());
+
+        let mut chain = LodChain::new(vec![0.2, 0.05]).with_hysteresis(0.1);
+        assert_eq!(chain.level_count(), 3);
+        assert_eq!(chain.update(0.5), 0);
+        assert_eq!(chain.update(0.19), 0);
+        assert_eq!(chain.update(0.17), 1);
+        assert_eq!(chain.update(0.21), 1);
+        assert_eq!(chain.update(0.23), 0);
+        assert_eq!(chain.update(0.01), 2);
+        assert!(chain.is_level_visible(2));
+        assert_eq!(chain.update(0.054), 2);
+        assert_eq!(chain.update(1.0), 0);
+    }
+}
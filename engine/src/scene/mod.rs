@@ -1,11 +1,20 @@
 
 use defs::{
+    EngineError,
     Scene,
     SceneManager,
-    control::Control
+    control::Control,
+    render::{
+        InstanceData,
+        ResourcePreloads
+    }
 };
+use model::types::StaticVertex;
 use lockfree::queue::Queue;
-use std::mem::MaybeUninit;
+use std::{
+    collections::HashMap,
+    mem::MaybeUninit
+};
 
 /// SceneHost struct
 /// Manages scene objects, including holding on to the currently-active scene, and allowing new
@@ -52,6 +61,22 @@ impl SceneHost {
         self.scene_info.on_pre_render();
     }
 
+    /// Notify the current scene that resource source files changed, returning any resources it
+    /// re-decoded as a result
+    pub fn on_resources_changed(&mut self, changed_paths: &[String]) -> Result<Option<ResourcePreloads>, EngineError> {
+        self.scene_info.on_resources_changed(changed_paths)
+    }
+
+    /// Collect any instances that the current scene changed while updating
+    pub fn take_instance_updates(&mut self) -> Option<HashMap<usize, Vec<InstanceData>>> {
+        self.scene_info.take_instance_updates()
+    }
+
+    /// Collect any dynamic vertices that the current scene rebuilt while updating
+    pub fn take_dynamic_vbo_updates(&mut self) -> Option<HashMap<usize, Vec<StaticVertex>>> {
+        self.scene_info.take_dynamic_vbo_updates()
+    }
+
     /// Flush the scene queue, activating any new scenes found as they replace the current scene
     pub fn drain_queue(&mut self) -> bool {
         let mut changed = false;
@@ -0,0 +1,240 @@
+
+use defs::EngineError;
+use cgmath::{
+    Euler,
+    Matrix4,
+    One,
+    Quaternion,
+    Rad,
+    SquareMatrix,
+    Vector3
+};
+
+/// Transform struct
+/// Position, rotation and scale of an object relative to its parent, applied to the object's
+/// vertices as scale first, then rotation, then translation
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>
+}
+
+impl Default for Transform {
+
+    /// Transform that leaves the object where it is
+    fn default() -> Transform {
+        Transform {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0)
+        }
+    }
+}
+
+impl Transform {
+
+    /// Create a transform that only moves the object
+    pub fn from_translation(translation: Vector3<f32>) -> Transform {
+        Transform {
+            translation,
+            ..Transform::default()
+        }
+    }
+
+    /// Create a transform from a translation, rotations in radians about the x, y and z axes
+    /// (applied in that order), and a scale
+    pub fn from_components(translation: Vector3<f32>, angles: Vector3<f32>, scale: Vector3<f32>) -> Transform {
+        Transform {
+            translation,
+            rotation: Quaternion::from(Euler::new(Rad(angles.x), Rad(angles.y), Rad(angles.z))),
+            scale
+        }
+    }
+
+    /// Get the matrix that applies this transform
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation) *
+            Matrix4::from(self.rotation) *
+            Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+/// NodeId struct
+/// Handle to a node within the scene graph that created it
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct NodeId(usize);
+
+/// Node struct
+/// An object's own transform, its place in the hierarchy, and its cached world transform
+struct Node {
+    local: Transform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    world: Matrix4<f32>,
+    dirty: bool
+}
+
+/// SceneGraph struct
+/// Hierarchy of transforms for the objects in a scene, where each node moves with its parent.
+/// Changing a node marks it dirty, and world transforms are only recalculated for dirty nodes and
+/// their descendants when the graph is next updated - typically once per frame, before model
+/// matrices are copied into uniform buffers.
+#[derive(Default)]
+pub struct SceneGraph {
+    nodes: Vec<Node>,
+    roots: Vec<NodeId>
+}
+
+impl SceneGraph {
+
+    /// Create an empty graph
+    pub fn new() -> SceneGraph {
+        SceneGraph::default()
+    }
+
+    /// Add a node with a transform relative to its parent, or to the world if it has none
+    pub fn add_node(&mut self, parent: Option<NodeId>, local: Transform) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            local,
+            parent,
+            children: vec![],
+            world: Matrix4::identity(),
+            dirty: true
+        });
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id)
+        }
+        id
+    }
+
+    /// Move a node, along with its descendants, under a new parent or to the top of the hierarchy.
+    /// Fails if the new parent is the node itself or one of its descendants.
+    pub fn set_parent(&mut self, node: NodeId, parent: Option<NodeId>) -> Result<(), EngineError> {
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            if current == node {
+                return Err(EngineError::GeneralError(
+                    format!("Cannot parent scene graph node {:?} under its own descendant", node)));
+            }
+            ancestor = self.nodes[current.0].parent;
+        }
+        match self.nodes[node.0].parent {
+            Some(old_parent) => self.nodes[old_parent.0].children.retain(|child| *child != node),
+            None => self.roots.retain(|root| *root != node)
+        }
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(node),
+            None => self.roots.push(node)
+        }
+        self.nodes[node.0].parent = parent;
+        self.nodes[node.0].dirty = true;
+        Ok(())
+    }
+
+    /// Replace a node's transform relative to its parent
+    pub fn set_local_transform(&mut self, node: NodeId, local: Transform) {
+        self.nodes[node.0].local = local;
+        self.nodes[node.0].dirty = true;
+    }
+
+    pub fn get_local_transform(&self, node: NodeId) -> Transform {
+        self.nodes[node.0].local
+    }
+
+    pub fn get_parent(&self, node: NodeId) -> Option<NodeId> {
+        self.nodes[node.0].parent
+    }
+
+    pub fn get_children(&self, node: NodeId) -> &[NodeId] {
+        &self.nodes[node.0].children
+    }
+
+    /// Get a node's transform relative to the world, as of the last update
+    pub fn get_world_matrix(&self, node: NodeId) -> Matrix4<f32> {
+        self.nodes[node.0].world
+    }
+
+    /// Get the matrix for a node's uniform buffer, combining its world transform with a camera's
+    /// projection and view, as of the last update
+    pub fn get_mvp_matrix(&self, node: NodeId, pv_matrix: Matrix4<f32>) -> Matrix4<f32> {
+        pv_matrix * self.nodes[node.0].world
+    }
+
+    /// Recalculate world transforms for nodes that have changed, and their descendants, returning
+    /// how many were recalculated
+    pub fn update_world_transforms(&mut self) -> usize {
+        let mut updated = 0;
+        let mut stack: Vec<(NodeId, bool)> = self.roots.iter().rev().map(|root| (*root, false)).collect();
+        while let Some((id, parent_changed)) = stack.pop() {
+            let node = &self.nodes[id.0];
+            let changed = parent_changed || node.dirty;
+            if changed {
+                let parent_world = match node.parent {
+                    Some(parent) => self.nodes[parent.0].world,
+                    None => Matrix4::identity()
+                };
+                let node = &mut self.nodes[id.0];
+                node.world = parent_world * node.local.to_matrix();
+                node.dirty = false;
+                updated += 1;
+            }
+            stack.extend(self.nodes[id.0].children.iter().rev().map(|child| (*child, changed)));
+        }
+        updated
+    }
+}
+
+/// Test suite
+/// Check that world transforms combine down the hierarchy, and that only changed branches are
+/// recalculated
+#[cfg(test)]
+mod test {
+    use cgmath::{
+        Vector3,
+        Vector4
+    };
+    use crate::scene_graph::{
+        SceneGraph,
+        Transform
+    };
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.0001, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn child_moves_with_parent_and_only_dirty_branches_update() {
+        let mut graph = SceneGraph::new();
+        let boat = graph.add_node(None, Transform::from_translation(Vector3::new(10.0, 0.0, 0.0)));
+        let mast = graph.add_node(Some(boat), Transform::from_components(
+            Vector3::new(0.0, -2.0, 0.0),
+            Vector3::new(0.0, std::f32::consts::FRAC_PI_2, 0.0),
+            Vector3::new(2.0, 2.0, 2.0)));
+        let door = graph.add_node(None, Transform::default());
+        assert_eq!(graph.update_world_transforms(), 3);
+        assert_eq!(graph.update_world_transforms(), 0);
+
+        // A point along +x on the mast is scaled, turned about y onto -z, then carried by the boat
+        let tip = graph.get_world_matrix(mast) * Vector4::new(1.0, 0.0, 0.0, 1.0);
+        assert_near(tip.x, 10.0);
+        assert_near(tip.y, -2.0);
+        assert_near(tip.z, -2.0);
+
+        graph.set_local_transform(boat, Transform::from_translation(Vector3::new(0.0, 0.0, 5.0)));
+        assert_eq!(graph.update_world_transforms(), 2);
+        let tip = graph.get_world_matrix(mast) * Vector4::new(1.0, 0.0, 0.0, 1.0);
+        assert_near(tip.x, 0.0);
+        assert_near(tip.z, 3.0);
+
+        assert!(graph.set_parent(boat, Some(mast)).is_err());
+        graph.set_parent(mast, Some(door)).unwrap();
+        assert_eq!(graph.update_world_transforms(), 1);
+        assert_eq!(graph.get_children(boat).len(), 0);
+        let tip = graph.get_world_matrix(mast) * Vector4::new(1.0, 0.0, 0.0, 1.0);
+        assert_near(tip.x, 0.0);
+        assert_near(tip.z, -2.0);
+    }
+}
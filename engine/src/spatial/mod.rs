@@ -0,0 +1,596 @@
+
+use crate::{
+    collision::closest_point_on_triangle,
+    culling::transform_aabb,
+    ecs::World,
+    scene_graph::Transform
+};
+use model::types::{
+    Bounds,
+    Model,
+    StaticVertex
+};
+use cgmath::{
+    InnerSpace,
+    Matrix4,
+    SquareMatrix,
+    Vector3,
+    Vector4
+};
+
+/// Ray struct
+/// Half-line from an origin along a unit direction
+#[derive(Copy, Clone, Debug)]
+pub struct Ray {
+    pub origin: Vector3<f32>,
+    pub direction: Vector3<f32>
+}
+
+impl Ray {
+
+    /// Create a ray, normalising its direction
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Ray {
+        Ray {
+            origin,
+            direction: direction.normalize()
+        }
+    }
+
+    /// Create the ray from a perspective camera through a point on screen, given as fractions of
+    /// the window width and height from its top-left corner, as reported for the mouse. Fails if
+    /// the camera's matrices cannot be inverted.
+    pub fn from_screen_position(
+        position: (f32, f32),
+        view_matrix: Matrix4<f32>,
+        projection_matrix: Matrix4<f32>
+    ) -> Option<Ray> {
+        let inverse_view = view_matrix.invert()?;
+        let inverse_pv = (projection_matrix * view_matrix).invert()?;
+
+        // Any depth strictly between the clip planes gives a point on the ray, whichever way
+        // round depth is mapped
+        let on_ray = inverse_pv * Vector4::new(2.0 * position.0 - 1.0, 2.0 * position.1 - 1.0, 0.5, 1.0);
+        let origin = inverse_view.w.truncate();
+        Some(Ray::new(origin, on_ray.truncate() / on_ray.w - origin))
+    }
+
+    pub fn point_at(&self, distance: f32) -> Vector3<f32> {
+        self.origin + self.direction * distance
+    }
+}
+
+/// Aabb struct
+/// Axis-aligned box given by its minimum and maximum corners
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>
+}
+
+impl Aabb {
+
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Aabb {
+        Aabb { min, max }
+    }
+
+    /// Create the box enclosing a model's bounds once placed in the world by a model matrix
+    pub fn from_bounds(bounds: &Bounds, model_matrix: Matrix4<f32>) -> Aabb {
+        let (min, max) = transform_aabb(bounds, model_matrix);
+        Aabb { min, max }
+    }
+
+    /// Create the smallest box enclosing a set of points
+    pub fn from_points(points: &[Vector3<f32>]) -> Aabb {
+        let mut aabb = Aabb::new(Vector3::new(f32::MAX, f32::MAX, f32::MAX), Vector3::new(f32::MIN, f32::MIN, f32::MIN));
+        for point in points.iter() {
+            aabb.min = Vector3::new(aabb.min.x.min(point.x), aabb.min.y.min(point.y), aabb.min.z.min(point.z));
+            aabb.max = Vector3::new(aabb.max.x.max(point.x), aabb.max.y.max(point.y), aabb.max.z.max(point.z));
+        }
+        aabb
+    }
+
+    /// Get the smallest box enclosing both this and another box
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb::from_points(&[self.min, self.max, other.min, other.max])
+    }
+
+    pub fn centre(&self) -> Vector3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x &&
+            self.min.y <= other.max.y && self.max.y >= other.min.y &&
+            self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    /// Check whether a sphere overlaps this box, by finding the point of the box closest to it
+    pub fn intersects_sphere(&self, centre: Vector3<f32>, radius: f32) -> bool {
+        let closest = Vector3::new(
+            centre.x.clamp(self.min.x, self.max.x),
+            centre.y.clamp(self.min.y, self.max.y),
+            centre.z.clamp(self.min.z, self.max.z));
+        (closest - centre).magnitude2() <= radius * radius
+    }
+
+    /// Get the distance along a ray at which it enters this box, which is zero if it starts
+    /// inside, or None if it misses or only enters beyond the maximum distance
+    pub fn ray_entry(&self, ray: &Ray, max_distance: f32) -> Option<f32> {
+        let mut near = 0.0f32;
+        let mut far = max_distance;
+        for axis in 0..3 {
+            let inverse = 1.0 / ray.direction[axis];
+            let mut t0 = (self.min[axis] - ray.origin[axis]) * inverse;
+            let mut t1 = (self.max[axis] - ray.origin[axis]) * inverse;
+            if inverse < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // NaN from a ray lying in one of the box's planes leaves the range as it is
+            near = near.max(t0);
+            far = far.min(t1);
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+}
+
+/// Node struct
+/// Box around a range of items in a bounding volume hierarchy. Leaves hold a range of the ordered
+/// items; other nodes have their first child straight after them and their second child at the
+/// given index.
+struct Node {
+    bounds: Aabb,
+    first: usize,
+    count: usize,
+    second_child: usize
+}
+
+/// Bvh struct
+/// Bounding volume hierarchy over items known only by their index and box. Built top-down by
+/// splitting each range of items at the median of their centres along the axis where the centres
+/// spread furthest.
+struct Bvh {
+    nodes: Vec<Node>,
+    order: Vec<usize>
+}
+
+impl Bvh {
+
+    /// Most items held by a leaf
+    const LEAF_SIZE: usize = 4;
+
+    fn build(item_bounds: &[Aabb]) -> Bvh {
+        let mut bvh = Bvh {
+            nodes: vec![],
+            order: (0..item_bounds.len()).collect()
+        };
+        if !item_bounds.is_empty() {
+            bvh.build_node(item_bounds, 0, item_bounds.len());
+        }
+        bvh
+    }
+
+    /// Add the node for a range of the ordered items, then its children
+    fn build_node(&mut self, item_bounds: &[Aabb], first: usize, count: usize) {
+        let items = &mut self.order[first..(first + count)];
+        let bounds = items.iter()
+            .skip(1)
+            .fold(item_bounds[items[0]], |bounds, item| bounds.union(&item_bounds[*item]));
+        let node_index = self.nodes.len();
+        self.nodes.push(Node { bounds, first, count, second_child: 0 });
+        if count <= Self::LEAF_SIZE {
+            return;
+        }
+
+        let centres: Vec<Vector3<f32>> = items.iter().map(|item| item_bounds[*item].centre()).collect();
+        let spread = Aabb::from_points(&centres);
+        let extent = spread.max - spread.min;
+        let axis = match (extent.x >= extent.y, extent.x >= extent.z, extent.y >= extent.z) {
+            (true, true, _) => 0,
+            (false, _, true) => 1,
+            _ => 2
+        };
+        let half = count / 2;
+        items.select_nth_unstable_by(half, |a, b| item_bounds[*a].centre()[axis]
+            .partial_cmp(&item_bounds[*b].centre()[axis])
+            .unwrap_or(std::cmp::Ordering::Equal));
+
+        self.nodes[node_index].count = 0;
+        self.build_node(item_bounds, first, half);
+        self.nodes[node_index].second_child = self.nodes.len();
+        self.build_node(item_bounds, first + half, count - half);
+    }
+
+    /// Visit every item in leaves whose boxes pass the given test
+    fn visit(&self, overlaps: impl Fn(&Aabb) -> bool, mut visit_item: impl FnMut(usize)) {
+        let mut stack = match self.nodes.is_empty() {
+            true => vec![],
+            false => vec![0]
+        };
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !overlaps(&node.bounds) {
+                continue;
+            }
+            match node.count {
+                0 => stack.extend_from_slice(&[node.second_child, index + 1]),
+                _ => self.order[node.first..(node.first + node.count)].iter().for_each(|item| visit_item(*item))
+            }
+        }
+    }
+
+    /// Find the nearest item that a ray hits, where hitting an item gives a distance along the
+    /// ray and a result. Skips any nodes that the ray only enters beyond the nearest hit so far.
+    fn raycast<H>(
+        &self,
+        ray: &Ray,
+        max_distance: f32,
+        test_item: impl Fn(usize) -> Option<(f32, H)>
+    ) -> Option<(usize, f32, H)> {
+        let mut nearest: Option<(usize, f32, H)> = None;
+        let mut stack = match self.nodes.is_empty() {
+            true => vec![],
+            false => vec![0]
+        };
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let limit = nearest.as_ref().map_or(max_distance, |(_, distance, _)| *distance);
+            if node.bounds.ray_entry(ray, limit).is_none() {
+                continue;
+            }
+            if node.count == 0 {
+                stack.extend_from_slice(&[node.second_child, index + 1]);
+                continue;
+            }
+            for item in self.order[node.first..(node.first + node.count)].iter() {
+                if let Some((distance, result)) = test_item(*item) {
+                    let limit = nearest.as_ref().map_or(max_distance, |(_, distance, _)| *distance);
+                    if distance <= limit {
+                        nearest = Some((*item, distance, result));
+                    }
+                }
+            }
+        }
+        nearest
+    }
+}
+
+/// RayHit struct
+/// Where a ray first meets a surface: the distance along the ray, the point, the unit normal of
+/// the triangle hit facing back towards the ray, and the index of that triangle
+#[derive(Copy, Clone, Debug)]
+pub struct RayHit {
+    pub distance: f32,
+    pub point: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    pub triangle: usize
+}
+
+/// TriangleContact struct
+/// A triangle overlapping a sphere: the point of the triangle closest to the sphere's centre, its
+/// distance from the centre, the triangle's unit normal, and the index of the triangle
+#[derive(Copy, Clone, Debug)]
+pub struct TriangleContact {
+    pub distance: f32,
+    pub point: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    pub triangle: usize
+}
+
+/// TriangleBvh struct
+/// Bounding volume hierarchy over the triangles of static geometry, such as a model as loaded
+/// into a vertex buffer, for finding what a ray hits and what lies within an area. Triangles are
+/// indexed in the order they appear in the vertex data.
+pub struct TriangleBvh {
+    triangles: Vec<[Vector3<f32>; 3]>,
+    bvh: Bvh
+}
+
+impl TriangleBvh {
+
+    /// Build from a model, placed in the world by a model matrix
+    pub fn from_model(model: &Model<StaticVertex>, model_matrix: Matrix4<f32>) -> TriangleBvh {
+        let triangles = model.vertices
+            .chunks_exact(3)
+            .map(|v| [0, 1, 2].map(|corner| (model_matrix * Vector4::new(v[corner].px, v[corner].py, v[corner].pz, 1.0)).truncate()))
+            .collect();
+        Self::from_triangles(triangles)
+    }
+
+    /// Build from a non-indexed triangle list, as used for drawing a model
+    pub fn from_vertices(vertices: &[StaticVertex]) -> TriangleBvh {
+        let triangles = vertices
+            .chunks_exact(3)
+            .map(|v| [
+                Vector3::new(v[0].px, v[0].py, v[0].pz),
+                Vector3::new(v[1].px, v[1].py, v[1].pz),
+                Vector3::new(v[2].px, v[2].py, v[2].pz)
+            ])
+            .collect();
+        Self::from_triangles(triangles)
+    }
+
+    /// Build from triangles given by their corners
+    pub fn from_triangles(triangles: Vec<[Vector3<f32>; 3]>) -> TriangleBvh {
+        let bounds: Vec<Aabb> = triangles.iter().map(|triangle| Aabb::from_points(triangle)).collect();
+        TriangleBvh {
+            bvh: Bvh::build(&bounds),
+            triangles
+        }
+    }
+
+    pub fn get_triangle(&self, index: usize) -> [Vector3<f32>; 3] {
+        self.triangles[index]
+    }
+
+    /// Get the unit normal of a triangle, on the side its corners wind anticlockwise
+    pub fn get_normal(&self, index: usize) -> Vector3<f32> {
+        let [a, b, c] = self.triangles[index];
+        let normal = (b - a).cross(c - a);
+        match normal.magnitude2() > 0.0 {
+            true => normal.normalize(),
+            false => Vector3::new(0.0, 0.0, 0.0)
+        }
+    }
+
+    /// Find the nearest triangle that a ray hits within the maximum distance, from either side
+    pub fn raycast(&self, ray: &Ray, max_distance: f32) -> Option<RayHit> {
+        let (triangle, distance, _) = self.bvh.raycast(ray, max_distance, |index| {
+            ray_triangle_distance(ray, &self.triangles[index]).map(|distance| (distance, ()))
+        })?;
+        let normal = self.get_normal(triangle);
+        Some(RayHit {
+            distance,
+            point: ray.point_at(distance),
+            normal: match normal.dot(ray.direction) > 0.0 {
+                true => -normal,
+                false => normal
+            },
+            triangle
+        })
+    }
+
+    /// Find every triangle that a sphere overlaps, nearest first
+    pub fn overlap_sphere(&self, centre: Vector3<f32>, radius: f32) -> Vec<TriangleContact> {
+        let mut contacts = vec![];
+        self.bvh.visit(|bounds| bounds.intersects_sphere(centre, radius), |index| {
+            let point = closest_point_on_triangle(centre, &self.triangles[index]);
+            let distance = (point - centre).magnitude();
+            if distance <= radius {
+                contacts.push(TriangleContact { distance, point, normal: self.get_normal(index), triangle: index });
+            }
+        });
+        contacts.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(std::cmp::Ordering::Equal));
+        contacts
+    }
+
+    /// Find the indices of every triangle that a box overlaps, in ascending order
+    pub fn overlap_aabb(&self, aabb: &Aabb) -> Vec<usize> {
+        let mut indices = vec![];
+        self.bvh.visit(|bounds| bounds.intersects(aabb), |index| {
+            if triangle_intersects_aabb(&self.triangles[index], aabb) {
+                indices.push(index);
+            }
+        });
+        indices.sort_unstable();
+        indices
+    }
+}
+
+/// BoundsBvh struct
+/// Bounding volume hierarchy over the boxes of things that can be found by location, such as
+/// entities, each identified by a key. Rebuild it when they move.
+pub struct BoundsBvh<K: Copy> {
+    items: Vec<(K, Aabb)>,
+    bvh: Bvh
+}
+
+impl<K: Copy> BoundsBvh<K> {
+
+    /// Build from the keys and boxes of the things to find
+    pub fn new(items: Vec<(K, Aabb)>) -> BoundsBvh<K> {
+        let bounds: Vec<Aabb> = items.iter().map(|(_, aabb)| *aabb).collect();
+        BoundsBvh {
+            bvh: Bvh::build(&bounds),
+            items
+        }
+    }
+
+    /// Find the thing whose box a ray enters first within the maximum distance, with that
+    /// distance; zero if the ray starts inside it
+    pub fn raycast(&self, ray: &Ray, max_distance: f32) -> Option<(K, f32)> {
+        let (index, distance, _) = self.bvh.raycast(ray, max_distance, |index| {
+            self.items[index].1.ray_entry(ray, max_distance).map(|distance| (distance, ()))
+        })?;
+        Some((self.items[index].0, distance))
+    }
+
+    /// Find every thing whose box a sphere overlaps
+    pub fn overlap_sphere(&self, centre: Vector3<f32>, radius: f32) -> Vec<K> {
+        let mut keys = vec![];
+        self.bvh.visit(|bounds| bounds.intersects_sphere(centre, radius), |index| {
+            if self.items[index].1.intersects_sphere(centre, radius) {
+                keys.push(self.items[index].0);
+            }
+        });
+        keys
+    }
+
+    /// Find every thing whose box overlaps another box
+    pub fn overlap_aabb(&self, aabb: &Aabb) -> Vec<K> {
+        let mut keys = vec![];
+        self.bvh.visit(|bounds| bounds.intersects(aabb), |index| {
+            if self.items[index].1.intersects(aabb) {
+                keys.push(self.items[index].0);
+            }
+        });
+        keys
+    }
+}
+
+/// Build an index over the entities in a world that have model bounds, placed by their transform
+/// if they have one. As entities have no parents, that transform is already in world space, the
+/// same as render extraction draws it with.
+pub fn index_entities(world: &World) -> BoundsBvh<crate::ecs::Entity> {
+    let items = world.query::<Bounds>()
+        .map(|(entity, bounds)| {
+            let model_matrix = match world.get::<Transform>(entity) {
+                Some(transform) => transform.to_matrix(),
+                None => Matrix4::identity()
+            };
+            (entity, Aabb::from_bounds(bounds, model_matrix))
+        })
+        .collect();
+    BoundsBvh::new(items)
+}
+
+/// Get the distance along a ray at which it crosses a triangle, from either side, using the
+/// Moller-Trumbore method
+fn ray_triangle_distance(ray: &Ray, triangle: &[Vector3<f32>; 3]) -> Option<f32> {
+    let [a, b, c] = *triangle;
+    let ab = b - a;
+    let ac = c - a;
+    let p = ray.direction.cross(ac);
+    let determinant = ab.dot(p);
+    if determinant.abs() < 1e-8 {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let from_a = ray.origin - a;
+    let u = from_a.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = from_a.cross(ab);
+    let v = ray.direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = ac.dot(q) * inverse;
+    match distance >= 0.0 {
+        true => Some(distance),
+        false => None
+    }
+}
+
+/// Check whether a triangle overlaps a box, by looking for an axis that separates them among the
+/// box's axes, the triangle's normal, and the cross products of their edges
+fn triangle_intersects_aabb(triangle: &[Vector3<f32>; 3], aabb: &Aabb) -> bool {
+    let centre = aabb.centre();
+    let half = (aabb.max - aabb.min) * 0.5;
+    let corners = triangle.map(|corner| corner - centre);
+    let edges = [corners[1] - corners[0], corners[2] - corners[1], corners[0] - corners[2]];
+    let box_axes = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+
+    let mut axes = box_axes.to_vec();
+    axes.push(edges[0].cross(edges[1]));
+    for box_axis in box_axes.iter() {
+        for edge in edges.iter() {
+            axes.push(box_axis.cross(*edge));
+        }
+    }
+    axes.iter()
+        .filter(|axis| axis.magnitude2() > 1e-12)
+        .all(|axis| {
+            let projections = corners.map(|corner| corner.dot(*axis));
+            let low = projections[0].min(projections[1]).min(projections[2]);
+            let high = projections[0].max(projections[1]).max(projections[2]);
+            let reach = half.x * axis.x.abs() + half.y * axis.y.abs() + half.z * axis.z.abs();
+            low <= reach && high >= -reach
+        })
+}
+
+/// Test suite
+/// Check ray, sphere and box queries against a small set of triangles, and finding entities by
+/// their bounds
+#[cfg(test)]
+mod test {
+    use crate::{
+        ecs::World,
+        scene_graph::Transform,
+        spatial::{
+            index_entities,
+            Aabb,
+            Ray,
+            TriangleBvh
+        }
+    };
+    use model::types::Bounds;
+    use cgmath::Vector3;
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.0001, "{} is not {}", actual, expected);
+    }
+
+    /// Make a row of unit squares, two triangles each, facing -z at z = 0 and spaced 2 apart
+    /// along x, followed by one square at z = 5 behind the first
+    fn make_panels() -> Vec<[Vector3<f32>; 3]> {
+        let mut triangles = vec![];
+        let mut square = |x: f32, z: f32| {
+            let (p00, p10) = (Vector3::new(x, 0.0, z), Vector3::new(x + 1.0, 0.0, z));
+            let (p01, p11) = (Vector3::new(x, 1.0, z), Vector3::new(x + 1.0, 1.0, z));
+            triangles.push([p00, p10, p11]);
+            triangles.push([p00, p11, p01]);
+        };
+        for panel in 0..20 {
+            square(panel as f32 * 2.0, 0.0);
+        }
+        square(0.0, 5.0);
+        triangles
+    }
+
+    #[test]
+    fn queries_find_nearest_and_overlapping_triangles() {
+        let bvh = TriangleBvh::from_triangles(make_panels());
+
+        let hit = bvh.raycast(&Ray::new(Vector3::new(0.25, 0.5, -3.0), Vector3::new(0.0, 0.0, 1.0)), 100.0).unwrap();
+        assert_near(hit.distance, 3.0);
+        assert_near(hit.point.z, 0.0);
+        assert_near(hit.normal.z, -1.0);
+        assert!(hit.triangle < 2);
+
+        // From behind, the far square is hit first and its normal faces back along the ray
+        let hit = bvh.raycast(&Ray::new(Vector3::new(0.25, 0.5, 8.0), Vector3::new(0.0, 0.0, -1.0)), 100.0).unwrap();
+        assert_near(hit.distance, 3.0);
+        assert_near(hit.normal.z, 1.0);
+        assert!(hit.triangle >= 40);
+        assert!(bvh.raycast(&Ray::new(Vector3::new(1.5, 0.5, -3.0), Vector3::new(0.0, 0.0, 1.0)), 100.0).is_none());
+        assert!(bvh.raycast(&Ray::new(Vector3::new(0.25, 0.5, -3.0), Vector3::new(0.0, 0.0, 1.0)), 2.0).is_none());
+
+        let contacts = bvh.overlap_sphere(Vector3::new(10.5, 0.5, -0.4), 0.5);
+        assert_eq!(contacts.len(), 2);
+        assert!(contacts.iter().all(|contact| contact.triangle == 10 || contact.triangle == 11));
+        assert_near(contacts[0].distance, 0.4);
+
+        let inside = bvh.overlap_aabb(&Aabb::new(Vector3::new(3.5, 0.5, -1.0), Vector3::new(6.5, 0.6, 1.0)));
+        assert_eq!(inside, vec![4, 5, 6, 7]);
+
+        // The box is within the first square's bounds but only on one side of its diagonal
+        let corner = bvh.overlap_aabb(&Aabb::new(Vector3::new(0.8, 0.05, -1.0), Vector3::new(0.95, 0.15, 1.0)));
+        assert_eq!(corner, vec![0]);
+    }
+
+    #[test]
+    fn entities_found_by_transformed_bounds() {
+        let mut world = World::new();
+        let bounds = Bounds::from_positions([[-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]].iter().copied());
+        let near = world.spawn();
+        world.insert(near, bounds);
+        world.insert(near, Transform::from_translation(Vector3::new(0.0, 0.0, 10.0)));
+        let far = world.spawn();
+        world.insert(far, bounds);
+        world.insert(far, Transform::from_translation(Vector3::new(0.0, 0.0, 20.0)));
+        let unbounded = world.spawn();
+        world.insert(unbounded, Transform::default());
+
+        let index = index_entities(&world);
+        let (entity, distance) = index.raycast(&Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0)), 100.0).unwrap();
+        assert_eq!(entity, near);
+        assert_near(distance, 9.0);
+        assert_eq!(index.overlap_sphere(Vector3::new(0.0, 0.0, 22.5), 2.0), vec![far]);
+        assert_eq!(index.overlap_aabb(&Aabb::new(Vector3::new(-5.0, -5.0, 0.0), Vector3::new(5.0, 5.0, 30.0))).len(), 2);
+    }
+}
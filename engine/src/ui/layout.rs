@@ -0,0 +1,146 @@
+
+/// UiRect struct
+/// An axis-aligned rectangle in UI units, with y increasing downwards
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct UiRect {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32
+}
+
+impl UiRect {
+
+    /// Create a new instance from its edges
+    pub fn new(left: f32, top: f32, right: f32, bottom: f32) -> UiRect {
+        UiRect { left, top, right, bottom }
+    }
+
+    /// Width of the rectangle
+    pub fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    /// Height of the rectangle
+    pub fn height(&self) -> f32 {
+        self.bottom - self.top
+    }
+
+    /// Check whether a point lies within the rectangle
+    pub fn contains(&self, point: (f32, f32)) -> bool {
+        point.0 >= self.left && point.0 < self.right && point.1 >= self.top && point.1 < self.bottom
+    }
+
+    /// Shrink the rectangle by the same amount on every side
+    pub fn inset(&self, amount: f32) -> UiRect {
+        UiRect::new(self.left + amount, self.top + amount, self.right - amount, self.bottom - amount)
+    }
+
+    /// The set of floats describing this rectangle with the given texture coordinates, in the form
+    /// taken by map_ui_rects
+    pub fn with_uv(&self, uv: [f32; 4]) -> [f32; 8] {
+        [self.left, self.top, self.right, self.bottom, uv[0], uv[1], uv[2], uv[3]]
+    }
+}
+
+/// Anchor enum
+/// The point of a parent area that a panel is attached to, along with the matching point of the
+/// panel itself
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Centre,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight
+}
+
+impl Anchor {
+
+    /// Place an area of the given size within a parent area. The offset moves the area towards
+    /// the inside of the parent from the edges it is anchored to, and downwards and rightwards
+    /// where it is centred.
+    pub fn place(&self, within: &UiRect, size: (f32, f32), offset: (f32, f32)) -> UiRect {
+        let (fraction_x, fraction_y) = match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Centre => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0)
+        };
+        let direction = |fraction: f32| match fraction > 0.75 {
+            true => -1.0,
+            false => 1.0
+        };
+        let left = within.left + fraction_x * (within.width() - size.0) + direction(fraction_x) * offset.0;
+        let top = within.top + fraction_y * (within.height() - size.1) + direction(fraction_y) * offset.1;
+        UiRect::new(left, top, left + size.0, top + size.1)
+    }
+}
+
+/// StackDirection enum
+/// The direction in which a container places its children one after another
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum StackDirection {
+    Vertical,
+    Horizontal
+}
+
+/// Stack struct
+/// Places children one after another within an area, each filling the area across the direction
+/// of stacking, with a gap between neighbours
+pub struct Stack {
+    area: UiRect,
+    direction: StackDirection,
+    spacing: f32,
+    used: f32,
+    any_placed: bool
+}
+
+impl Stack {
+
+    /// Create a new instance that fills the given area
+    pub fn new(area: UiRect, direction: StackDirection, spacing: f32) -> Stack {
+        Stack {
+            area,
+            direction,
+            spacing,
+            used: 0.0,
+            any_placed: false
+        }
+    }
+
+    /// The area that children are placed within
+    pub fn area(&self) -> UiRect {
+        self.area
+    }
+
+    /// The direction in which children are placed
+    pub fn direction(&self) -> StackDirection {
+        self.direction
+    }
+
+    /// Take the next slot, which is the given length along the direction of stacking
+    pub fn next(&mut self, length: f32) -> UiRect {
+        let start = match self.any_placed {
+            true => self.used + self.spacing,
+            false => 0.0
+        };
+        self.used = start + length;
+        self.any_placed = true;
+        match self.direction {
+            StackDirection::Vertical => UiRect::new(
+                self.area.left, self.area.top + start, self.area.right, self.area.top + start + length),
+            StackDirection::Horizontal => UiRect::new(
+                self.area.left + start, self.area.top, self.area.left + start + length, self.area.bottom)
+        }
+    }
+}
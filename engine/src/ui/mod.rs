@@ -0,0 +1,713 @@
+pub mod layout;
+pub mod skin;
+
+use defs::{
+    EngineError,
+    control::{
+        Control,
+        InputEvent,
+        InputState,
+        KeyCode,
+        MouseButton
+    },
+    render::{
+        DynamicVboCreationData,
+        VertexFormat
+    }
+};
+use model::types::StaticVertex;
+use crate::ui::{
+    layout::{
+        Anchor,
+        Stack,
+        StackDirection,
+        UiRect
+    },
+    skin::{
+        SkinPart,
+        UiSkin
+    }
+};
+use crate::util::{
+    map_ui_rects,
+    textbuffer::{
+        TextAlignment,
+        TextGenerator
+    }
+};
+use std::collections::HashMap;
+
+/// UiStyle struct
+/// Sizes used when laying out widgets, in UI units. Widgets stacked vertically are widget_height
+/// tall, and those stacked horizontally are widget_width wide.
+pub struct UiStyle {
+    pub padding: f32,
+    pub spacing: f32,
+    pub widget_width: f32,
+    pub widget_height: f32,
+    pub text_height: f32,
+    pub slider_handle_width: f32,
+    pub slider_steps: u32
+}
+
+impl Default for UiStyle {
+
+    /// Construct an instance suited to a screen two units high, as in clip space
+    fn default() -> UiStyle {
+        UiStyle {
+            padding: 0.02,
+            spacing: 0.02,
+            widget_width: 0.4,
+            widget_height: 0.1,
+            text_height: 0.07,
+            slider_handle_width: 0.04,
+            slider_steps: 20
+        }
+    }
+}
+
+/// UiOutput struct
+/// Vertex data for a frame of UI. The rect vertices are drawn with Shader::PlainPnt and the
+/// skin's atlas texture, then the text vertices over them with Shader::Text and the font texture.
+/// Outputs may be compared to find whether the vertex buffers need to be updated. As the UI
+/// changes often, its vertices are best drawn from dynamic VBOs, which are updated in place.
+#[derive(PartialEq, Debug)]
+pub struct UiOutput {
+    pub rect_vertices: Vec<StaticVertex>,
+    pub text_vertices: Vec<StaticVertex>
+}
+
+impl UiOutput {
+
+    /// Make the dynamic VBOs to draw the UI from, starting with this output's vertices, with the
+    /// rects and text at the given indices; each can hold up to capacity vertices
+    pub fn make_dynamic_vbo_preloads(
+        &self,
+        rect_vbo_index: usize,
+        text_vbo_index: usize,
+        capacity: usize
+    ) -> HashMap<usize, DynamicVboCreationData> {
+        let mut preloads = HashMap::new();
+        preloads.insert(rect_vbo_index, DynamicVboCreationData {
+            vertex_format: VertexFormat::PositionNormalTexture,
+            capacity,
+            vertex_data: self.rect_vertices.clone()
+        });
+        preloads.insert(text_vbo_index, DynamicVboCreationData {
+            vertex_format: VertexFormat::PositionNormalTexture,
+            capacity,
+            vertex_data: self.text_vertices.clone()
+        });
+        preloads
+    }
+
+    /// Get this output's vertices as updates for the dynamic VBOs made by
+    /// make_dynamic_vbo_preloads, keyed by the same indices
+    pub fn dynamic_vbo_updates(&self, rect_vbo_index: usize, text_vbo_index: usize) -> HashMap<usize, Vec<StaticVertex>> {
+        let mut updates = HashMap::new();
+        updates.insert(rect_vbo_index, self.rect_vertices.clone());
+        updates.insert(text_vbo_index, self.text_vertices.clone());
+        updates
+    }
+}
+
+/// FrameInput struct
+/// The input relevant to the UI for a single frame, with the mouse position in UI units
+struct FrameInput {
+    mouse_position: Option<(f32, f32)>,
+    mouse_down: bool,
+    mouse_pressed: bool,
+    mouse_released: bool,
+    keys_pressed: Vec<KeyCode>,
+    typed: Vec<char>
+}
+
+/// Interaction struct
+/// How the input of the current frame affects a single widget
+struct Interaction {
+    hovered: bool,
+    held: bool,
+    focused: bool,
+    clicked: bool
+}
+
+/// Ui struct
+/// An immediate-mode UI. Each frame, call begin_frame, then call a function for each panel and
+/// widget to be shown, in the order they should be laid out, then end_frame to get the vertices
+/// to draw. Widget functions report what the user did to the widget during the frame, and those
+/// that edit a value do so in place. Interactive widgets are told apart between frames by an id,
+/// which must be unique among those shown.
+/// Widgets are placed one after another within the innermost open panel or group, or down the
+/// screen if there is none. Keyboard focus moves forwards with Tab or Down and backwards with Up;
+/// Enter activates the focused button or checkbox, Left and Right move the focused slider, and
+/// typed text goes into the focused text field. Clicking a widget focuses it.
+pub struct Ui {
+    skin: UiSkin,
+    text_generator: TextGenerator,
+    style: UiStyle,
+    screen: UiRect,
+    input: FrameInput,
+    focused: Option<String>,
+    active: Option<String>,
+    mouse_claimed: bool,
+    focus_order: Vec<String>,
+    previous_focus_order: Vec<String>,
+    stacks: Vec<Stack>,
+    rects: Vec<[f32; 8]>,
+    text_vertices: Vec<StaticVertex>
+}
+
+impl Ui {
+
+    /// Create a new instance, covering the given area of the screen; for UI drawn with an
+    /// identity camera matrix this is the whole of clip space, from (-1, -1) to (1, 1)
+    pub fn new(skin: UiSkin, text_generator: TextGenerator, style: UiStyle, screen: UiRect) -> Ui {
+        Ui {
+            skin,
+            text_generator,
+            style,
+            screen,
+            input: FrameInput {
+                mouse_position: None,
+                mouse_down: false,
+                mouse_pressed: false,
+                mouse_released: false,
+                keys_pressed: vec![],
+                typed: vec![]
+            },
+            focused: None,
+            active: None,
+            mouse_claimed: false,
+            focus_order: vec![],
+            previous_focus_order: vec![],
+            stacks: vec![],
+            rects: vec![],
+            text_vertices: vec![]
+        }
+    }
+
+    /// Change the area of the screen covered, such as when the aspect ratio changes
+    pub fn set_screen_rect(&mut self, screen: UiRect) {
+        self.screen = screen;
+    }
+
+    /// Get the id of the widget with keyboard focus
+    pub fn focused_id(&self) -> Option<&str> {
+        self.focused.as_deref()
+    }
+
+    /// Give keyboard focus to a widget, or to none
+    pub fn set_focus(&mut self, id: Option<&str>) {
+        self.focused = id.map(|id| id.to_string());
+    }
+
+    /// Start a new frame, taking the input events made available by the controller's last update
+    pub fn begin_frame(&mut self, controller: &dyn Control) {
+        let screen = self.screen;
+        self.input = FrameInput {
+            mouse_position: controller.get_mouse_position()
+                .map(|(x, y)| (screen.left + x * screen.width(), screen.top + y * screen.height())),
+            mouse_down: controller.is_mouse_button_down(MouseButton::Left),
+            mouse_pressed: false,
+            mouse_released: false,
+            keys_pressed: vec![],
+            typed: vec![]
+        };
+        for event in controller.get_frame_events().iter() {
+            match event {
+                InputEvent::MouseButton(MouseButton::Left, InputState::Pressed) => self.input.mouse_pressed = true,
+                InputEvent::MouseButton(MouseButton::Left, InputState::Released) => self.input.mouse_released = true,
+                InputEvent::Key(keycode, InputState::Pressed) => self.input.keys_pressed.push(*keycode),
+                InputEvent::Character(c) => self.input.typed.push(*c),
+                _ => ()
+            }
+        }
+
+        // Move focus through the widgets in the order they were shown last frame
+        for keycode in self.input.keys_pressed.iter() {
+            let step: isize = match keycode {
+                KeyCode::Tab | KeyCode::Down => 1,
+                KeyCode::Up => -1,
+                _ => continue
+            };
+            let count = self.previous_focus_order.len() as isize;
+            if count == 0 {
+                continue;
+            }
+            let current = self.focused.as_ref()
+                .and_then(|id| self.previous_focus_order.iter().position(|other| other == id));
+            let next = match current {
+                Some(index) => (index as isize + step).rem_euclid(count),
+                None if step > 0 => 0,
+                None => count - 1
+            };
+            self.focused = Some(self.previous_focus_order[next as usize].clone());
+        }
+
+        self.mouse_claimed = false;
+        self.focus_order.clear();
+        self.rects.clear();
+        self.text_vertices.clear();
+        self.stacks = vec![Stack::new(screen.inset(self.style.padding), StackDirection::Vertical, self.style.spacing)];
+    }
+
+    /// Finish the frame, giving the vertices of everything shown during it
+    pub fn end_frame(&mut self) -> UiOutput {
+        if self.input.mouse_released || !self.input.mouse_down {
+            self.active = None;
+        }
+        if self.input.mouse_pressed && !self.mouse_claimed {
+            self.focused = None;
+        }
+        self.previous_focus_order = std::mem::take(&mut self.focus_order);
+        if let Some(id) = self.focused.as_ref() {
+            if !self.previous_focus_order.contains(id) {
+                self.focused = None;
+            }
+        }
+        UiOutput {
+            rect_vertices: map_ui_rects(std::mem::take(&mut self.rects)),
+            text_vertices: std::mem::take(&mut self.text_vertices)
+        }
+    }
+
+    /// Open a panel with a background, anchored within the innermost open panel or group, or the
+    /// screen. Its children are stacked in the given direction inside its padding. Must be closed
+    /// with end.
+    pub fn begin_panel(&mut self, anchor: Anchor, size: (f32, f32), offset: (f32, f32), direction: StackDirection) {
+        let parent = self.current_stack().area();
+        let rect = anchor.place(&parent, size, offset);
+        self.push_rect(rect, SkinPart::Panel);
+        self.stacks.push(Stack::new(rect.inset(self.style.padding), direction, self.style.spacing));
+    }
+
+    /// Open a group without a background, taking the next slot of the given length in the
+    /// innermost open panel or group, so that its children can be stacked the other way. Must be
+    /// closed with end.
+    pub fn begin_group(&mut self, length: f32, direction: StackDirection) {
+        let rect = self.current_stack().next(length);
+        self.stacks.push(Stack::new(rect, direction, self.style.spacing));
+    }
+
+    /// Close the innermost open panel or group
+    pub fn end(&mut self) {
+        if self.stacks.len() > 1 {
+            self.stacks.pop();
+        }
+    }
+
+    /// Show a sprite from the skin's atlas, anchored within the innermost open panel or group, or
+    /// the screen, as with begin_panel. Fails if the atlas has no sprite of that name.
+    pub fn image(&mut self, sprite_name: &str, anchor: Anchor, size: (f32, f32), offset: (f32, f32)) -> Result<(), EngineError> {
+        let uv = self.skin.sprite_uv_rect(sprite_name)
+            .ok_or_else(|| EngineError::GeneralError(format!("No sprite in UI skin: {}", sprite_name)))?;
+        let parent = self.current_stack().area();
+        self.rects.push(anchor.place(&parent, size, offset).with_uv(uv));
+        Ok(())
+    }
+
+    /// Leave a gap of the given length before the next widget
+    pub fn space(&mut self, length: f32) {
+        self.current_stack().next(length);
+    }
+
+    /// Show a line of text
+    pub fn label(&mut self, text: &str) {
+        let rect = self.next_widget_rect();
+        self.push_text(text, rect, TextAlignment::Start);
+    }
+
+    /// Show a button, returning whether it was clicked or activated from the keyboard
+    pub fn button(&mut self, id: &str, text: &str) -> bool {
+        let rect = self.next_widget_rect();
+        let interaction = self.interact(id, rect);
+        let part = match (interaction.held && interaction.hovered, interaction.hovered) {
+            (true, _) => SkinPart::ButtonPressed,
+            (false, true) => SkinPart::ButtonHover,
+            _ => SkinPart::Button
+        };
+        self.push_rect(rect, part);
+        self.push_focus(rect, &interaction);
+        self.push_text(text, rect, TextAlignment::Centre);
+        interaction.clicked || (interaction.focused && self.key_pressed(KeyCode::Enter))
+    }
+
+    /// Show a checkbox with a label, toggling the value when clicked or activated from the
+    /// keyboard; returns whether it changed
+    pub fn checkbox(&mut self, id: &str, text: &str, value: &mut bool) -> bool {
+        let rect = self.next_widget_rect();
+        let interaction = self.interact(id, rect);
+        let toggled = interaction.clicked || (interaction.focused && self.key_pressed(KeyCode::Enter));
+        if toggled {
+            *value = !*value;
+        }
+        let box_rect = UiRect::new(rect.left, rect.top, rect.left + rect.height(), rect.bottom)
+            .inset(0.15 * rect.height());
+        let part = match *value {
+            true => SkinPart::CheckboxOn,
+            false => SkinPart::CheckboxOff
+        };
+        self.push_rect(box_rect, part);
+        self.push_focus(rect, &interaction);
+        self.push_text(text, UiRect::new(rect.left + rect.height(), rect.top, rect.right, rect.bottom), TextAlignment::Start);
+        toggled
+    }
+
+    /// Show a slider for a value between min and max, which may be dragged with the mouse or
+    /// stepped with Left and Right when focused; returns whether the value changed
+    pub fn slider(&mut self, id: &str, value: &mut f32, min: f32, max: f32) -> bool {
+        let rect = self.next_widget_rect();
+        let interaction = self.interact(id, rect);
+        let original = *value;
+        let handle_width = self.style.slider_handle_width.min(rect.width());
+        let travel = rect.width() - handle_width;
+        if interaction.held {
+            if let Some((x, _)) = self.input.mouse_position {
+                let fraction = match travel > 0.0 {
+                    true => ((x - rect.left - 0.5 * handle_width) / travel).clamp(0.0, 1.0),
+                    false => 0.0
+                };
+                *value = min + fraction * (max - min);
+            }
+        }
+        if interaction.focused {
+            let step = (max - min) / self.style.slider_steps.max(1) as f32;
+            for keycode in self.input.keys_pressed.iter() {
+                match keycode {
+                    KeyCode::Left => *value -= step,
+                    KeyCode::Right => *value += step,
+                    KeyCode::Home => *value = min,
+                    KeyCode::End => *value = max,
+                    _ => ()
+                }
+            }
+        }
+        *value = value.clamp(min.min(max), max.max(min));
+
+        let fraction = match max != min {
+            true => (*value - min) / (max - min),
+            false => 0.0
+        };
+        let track_inset = rect.height() / 3.0;
+        self.push_rect(UiRect::new(rect.left, rect.top + track_inset, rect.right, rect.bottom - track_inset), SkinPart::SliderTrack);
+        let handle_left = rect.left + fraction * travel;
+        self.push_rect(UiRect::new(handle_left, rect.top, handle_left + handle_width, rect.bottom), SkinPart::SliderHandle);
+        self.push_focus(rect, &interaction);
+        *value != original
+    }
+
+    /// Show a single-line text field, editing the text while it has focus; returns whether the
+    /// text changed
+    pub fn text_field(&mut self, id: &str, text: &mut String) -> bool {
+        let rect = self.next_widget_rect();
+        let interaction = self.interact(id, rect);
+        let mut changed = false;
+        if interaction.focused {
+            for keycode in self.input.keys_pressed.iter() {
+                if *keycode == KeyCode::Backspace {
+                    changed |= text.pop().is_some();
+                }
+            }
+            for c in self.input.typed.iter() {
+                text.push(*c);
+                changed = true;
+            }
+        }
+        self.push_rect(rect, SkinPart::TextField);
+        self.push_focus(rect, &interaction);
+        let shown = match interaction.focused {
+            true => format!("{}|", text),
+            false => text.clone()
+        };
+        self.push_text(&shown, rect, TextAlignment::Start);
+        changed
+    }
+
+    fn current_stack(&mut self) -> &mut Stack {
+        if self.stacks.is_empty() {
+            self.stacks.push(Stack::new(self.screen.inset(self.style.padding), StackDirection::Vertical, self.style.spacing));
+        }
+        self.stacks.last_mut().unwrap()
+    }
+
+    fn next_widget_rect(&mut self) -> UiRect {
+        let length = match self.current_stack().direction() {
+            StackDirection::Vertical => self.style.widget_height,
+            StackDirection::Horizontal => self.style.widget_width
+        };
+        self.current_stack().next(length)
+    }
+
+    /// Register a focusable widget and work out how this frame's input affects it
+    fn interact(&mut self, id: &str, rect: UiRect) -> Interaction {
+        self.focus_order.push(id.to_string());
+        let hovered = self.input.mouse_position
+            .map(|position| rect.contains(position))
+            .unwrap_or(false);
+        if hovered && self.input.mouse_pressed {
+            self.active = Some(id.to_string());
+            self.focused = Some(id.to_string());
+            self.mouse_claimed = true;
+        }
+        let held = self.active.as_deref() == Some(id);
+        Interaction {
+            hovered,
+            held: held && self.input.mouse_down,
+            focused: self.focused.as_deref() == Some(id),
+            clicked: held && hovered && self.input.mouse_released
+        }
+    }
+
+    fn key_pressed(&self, keycode: KeyCode) -> bool {
+        self.input.keys_pressed.contains(&keycode)
+    }
+
+    fn push_rect(&mut self, rect: UiRect, part: SkinPart) {
+        self.rects.push(rect.with_uv(self.skin.uv_rect(part)));
+    }
+
+    fn push_focus(&mut self, rect: UiRect, interaction: &Interaction) {
+        if interaction.focused {
+            self.push_rect(rect, SkinPart::Focus);
+        }
+    }
+
+    fn push_text(&mut self, text: &str, rect: UiRect, horizontal_alignment: TextAlignment) {
+        if text.is_empty() {
+            return;
+        }
+        let padding = self.style.padding;
+        let vertices = self.text_generator.generate_vertex_buffer(
+            text,
+            (rect.left + padding, rect.top),
+            ((rect.width() - 2.0 * padding).max(0.0), rect.height()),
+            self.style.text_height,
+            horizontal_alignment,
+            TextAlignment::Centre);
+        self.text_vertices.extend_from_slice(&vertices);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use defs::control::{
+        Control,
+        InputEvent,
+        InputState,
+        KeyCode,
+        MouseButton
+    };
+    use crate::ui::{
+        Ui,
+        UiStyle,
+        layout::{
+            Anchor,
+            Stack,
+            StackDirection,
+            UiRect
+        },
+        skin::{
+            SkinPart,
+            UiSkin
+        }
+    };
+    use crate::util::{
+        map_ui_rects,
+        textbuffer::TextGenerator
+    };
+    use atlas::{
+        PackOptions,
+        SpriteImage
+    };
+
+    const TEST_FONT: &str = "common lineHeight=10 base=8 scaleW=16 scaleH=16
+char id=63 x=0 y=0 width=4 height=8 xoffset=0 yoffset=0 xadvance=5 page=0";
+
+    /// TestControl struct
+    /// Input for a single frame, set directly by a test
+    struct TestControl {
+        mouse_position: Option<(f32, f32)>,
+        mouse_down: bool,
+        events: Vec<InputEvent>
+    }
+
+    impl Control for TestControl {
+        fn update(&mut self) {}
+        fn process_keyboard_event(&mut self, _keycode: KeyCode, _state: InputState) {}
+        fn process_mouse_move(&mut self, _position: Option<(f32, f32)>) {}
+        fn process_mouse_button_event(&mut self, _button: MouseButton, _state: InputState) {}
+        fn process_character(&mut self, _c: char) {}
+        fn get_dx(&self) -> f32 {
+            0.0
+        }
+        fn get_dy(&self) -> f32 {
+            0.0
+        }
+        fn get_mouse_position(&self) -> Option<(f32, f32)> {
+            self.mouse_position
+        }
+        fn is_mouse_button_down(&self, _button: MouseButton) -> bool {
+            self.mouse_down
+        }
+        fn get_frame_events(&self) -> &[InputEvent] {
+            &self.events
+        }
+    }
+
+    fn new_ui() -> Ui {
+        let (skin, _) = UiSkin::default_with_texture().unwrap();
+        new_ui_with_skin(skin)
+    }
+
+    fn new_ui_with_skin(skin: UiSkin) -> Ui {
+        let style = UiStyle {
+            padding: 0.0,
+            spacing: 0.0,
+            widget_width: 0.5,
+            widget_height: 0.25,
+            text_height: 0.2,
+            slider_handle_width: 0.0,
+            slider_steps: 10
+        };
+        Ui::new(skin, TextGenerator::from_resource(TEST_FONT), style, UiRect::new(0.0, 0.0, 1.0, 1.0))
+    }
+
+    fn input(mouse_position: Option<(f32, f32)>, mouse_down: bool, events: Vec<InputEvent>) -> TestControl {
+        TestControl {
+            mouse_position,
+            mouse_down,
+            events
+        }
+    }
+
+    fn key(keycode: KeyCode) -> InputEvent {
+        InputEvent::Key(keycode, InputState::Pressed)
+    }
+
+    /// Test suite
+    /// Check that anchors place areas against the right edges, and that stacks place children
+    /// one after another with spacing
+    #[test]
+    fn anchors_and_stacks() {
+        let screen = UiRect::new(-1.0, -1.0, 1.0, 1.0);
+        assert_eq!(Anchor::TopLeft.place(&screen, (0.5, 0.5), (0.25, 0.25)), UiRect::new(-0.75, -0.75, -0.25, -0.25));
+        assert_eq!(Anchor::BottomRight.place(&screen, (0.5, 0.5), (0.25, 0.25)), UiRect::new(0.25, 0.25, 0.75, 0.75));
+        assert_eq!(Anchor::Centre.place(&screen, (0.5, 1.0), (0.0, 0.0)), UiRect::new(-0.25, -0.5, 0.25, 0.5));
+
+        let mut stack = Stack::new(UiRect::new(0.0, 0.0, 1.0, 1.0), StackDirection::Vertical, 0.125);
+        assert_eq!(stack.next(0.25), UiRect::new(0.0, 0.0, 1.0, 0.25));
+        assert_eq!(stack.next(0.5), UiRect::new(0.0, 0.375, 1.0, 0.875));
+        let mut stack = Stack::new(UiRect::new(0.0, 0.0, 1.0, 1.0), StackDirection::Horizontal, 0.0);
+        stack.next(0.0);
+        assert_eq!(stack.next(0.5), UiRect::new(0.0, 0.0, 0.5, 1.0));
+    }
+
+    /// Test suite
+    /// Check that a button is clicked by pressing and releasing the mouse over it, over one frame
+    /// or several, but not when the mouse is released elsewhere
+    #[test]
+    fn buttons_click_with_mouse() {
+        let mut ui = new_ui();
+        let press = InputEvent::MouseButton(MouseButton::Left, InputState::Pressed);
+        let release = InputEvent::MouseButton(MouseButton::Left, InputState::Released);
+        let frames = [
+            (input(Some((0.1, 0.3)), false, vec![press, release]), [false, true]),
+            (input(Some((0.1, 0.1)), true, vec![press]), [false, false]),
+            (input(Some((0.1, 0.1)), false, vec![release]), [true, false]),
+            (input(Some((0.1, 0.1)), true, vec![press]), [false, false]),
+            (input(Some((0.1, 0.9)), false, vec![release]), [false, false])
+        ];
+        for (control, expected) in frames.iter() {
+            ui.begin_frame(control);
+            let clicked = [ui.button("first", "?"), ui.button("second", "?")];
+            let output = ui.end_frame();
+            assert_eq!(&clicked, expected);
+            assert!(output.rect_vertices.len() >= 12);
+            assert_eq!(output.text_vertices.len(), 12);
+        }
+        assert_eq!(ui.focused_id(), Some("first"));
+    }
+
+    /// Test suite
+    /// Check that the keyboard moves focus between widgets in order, and operates the focused
+    /// widget
+    #[test]
+    fn keyboard_focus_and_editing() {
+        let mut ui = new_ui();
+        let mut checked = false;
+        let mut amount = 0.5;
+        let mut name = String::from("ab");
+        let frames = vec![
+            vec![],
+            vec![key(KeyCode::Tab), key(KeyCode::Enter)],
+            vec![key(KeyCode::Down), key(KeyCode::Right), key(KeyCode::Right)],
+            vec![key(KeyCode::Tab), key(KeyCode::Backspace), InputEvent::Character('c')],
+            vec![key(KeyCode::Up), key(KeyCode::Up), key(KeyCode::End)]
+        ];
+        let mut focus = vec![];
+        for events in frames.into_iter() {
+            ui.begin_frame(&input(None, false, events));
+            ui.checkbox("check", "?", &mut checked);
+            ui.slider("slide", &mut amount, 0.0, 1.0);
+            ui.text_field("name", &mut name);
+            ui.end_frame();
+            focus.push(ui.focused_id().map(|id| id.to_string()));
+        }
+        assert_eq!(focus, vec![
+            None,
+            Some("check".to_string()),
+            Some("slide".to_string()),
+            Some("name".to_string()),
+            Some("check".to_string())
+        ]);
+        assert!(checked);
+        assert!((amount - 0.7).abs() < 1e-5);
+        assert_eq!(name, "ac");
+    }
+
+    /// Test suite
+    /// Check that images are drawn with their sprite's texture coordinates from the skin's atlas,
+    /// and that the output makes dynamic VBOs holding its vertices
+    #[test]
+    fn images_and_dynamic_vbos() {
+        let parts = [
+            SkinPart::Panel,
+            SkinPart::Button,
+            SkinPart::ButtonHover,
+            SkinPart::ButtonPressed,
+            SkinPart::CheckboxOff,
+            SkinPart::CheckboxOn,
+            SkinPart::SliderTrack,
+            SkinPart::SliderHandle,
+            SkinPart::TextField,
+            SkinPart::Focus
+        ];
+        let images: Vec<SpriteImage> = parts.iter()
+            .map(|part| part.sprite_name())
+            .chain(std::iter::once("logo"))
+            .map(|name| SpriteImage {
+                name: name.to_string(),
+                width: 4,
+                height: 4,
+                pixels: vec![255; 64]
+            })
+            .collect();
+        let atlas = atlas::pack(&images, PackOptions::default()).unwrap();
+        let mut ui = new_ui_with_skin(UiSkin::from_atlas(&atlas.descriptor).unwrap());
+
+        ui.begin_frame(&input(None, false, vec![]));
+        ui.image("logo", Anchor::BottomRight, (0.5, 0.25), (0.0, 0.0)).unwrap();
+        assert!(ui.image("missing", Anchor::TopLeft, (0.5, 0.25), (0.0, 0.0)).is_err());
+        let output = ui.end_frame();
+        let logo_uv = atlas.descriptor.uv_rect("logo").unwrap();
+        assert_eq!(output.rect_vertices, map_ui_rects(vec![UiRect::new(0.5, 0.75, 1.0, 1.0).with_uv(logo_uv)]));
+
+        let preloads = output.make_dynamic_vbo_preloads(3, 4, 60);
+        assert_eq!(preloads[&3].capacity, 60);
+        assert_eq!(preloads[&3].vertex_data, output.rect_vertices);
+        assert!(preloads[&4].vertex_data.is_empty());
+        let updates = output.dynamic_vbo_updates(3, 4);
+        assert_eq!(updates[&3], output.rect_vertices);
+        assert!(updates[&4].is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+
+use defs::{
+    EngineError,
+    render::{
+        TexturePixelFormat,
+        TextureCreationData,
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig
+    }
+};
+use atlas::{
+    AtlasDescriptor,
+    PackOptions,
+    SpriteImage
+};
+use std::collections::HashMap;
+
+/// SkinPart enum
+/// The parts of widgets that are drawn with a sprite from the skin's atlas
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SkinPart {
+    Panel,
+    Button,
+    ButtonHover,
+    ButtonPressed,
+    CheckboxOff,
+    CheckboxOn,
+    SliderTrack,
+    SliderHandle,
+    TextField,
+    Focus
+}
+
+/// Every part, in the order its texture coordinates are stored
+const SKIN_PARTS: [SkinPart; 10] = [
+    SkinPart::Panel,
+    SkinPart::Button,
+    SkinPart::ButtonHover,
+    SkinPart::ButtonPressed,
+    SkinPart::CheckboxOff,
+    SkinPart::CheckboxOn,
+    SkinPart::SliderTrack,
+    SkinPart::SliderHandle,
+    SkinPart::TextField,
+    SkinPart::Focus
+];
+
+/// Colours of the parts of the default skin, each drawn as a plain block
+const DEFAULT_SKIN_COLOURS: [[u8; 4]; 10] = [
+    [32, 36, 48, 224],
+    [64, 72, 96, 255],
+    [88, 100, 132, 255],
+    [40, 46, 64, 255],
+    [20, 22, 30, 255],
+    [96, 180, 120, 255],
+    [20, 22, 30, 255],
+    [160, 170, 196, 255],
+    [16, 18, 24, 255],
+    [255, 255, 255, 48]
+];
+
+/// Size in pixels of each sprite of the default skin
+const DEFAULT_SKIN_SPRITE_SIZE: u32 = 8;
+
+impl SkinPart {
+
+    /// The name of the sprite that the part is drawn with, as found in a skin's atlas
+    pub fn sprite_name(&self) -> &'static str {
+        match self {
+            SkinPart::Panel => "panel",
+            SkinPart::Button => "button",
+            SkinPart::ButtonHover => "button_hover",
+            SkinPart::ButtonPressed => "button_pressed",
+            SkinPart::CheckboxOff => "checkbox_off",
+            SkinPart::CheckboxOn => "checkbox_on",
+            SkinPart::SliderTrack => "slider_track",
+            SkinPart::SliderHandle => "slider_handle",
+            SkinPart::TextField => "text_field",
+            SkinPart::Focus => "focus"
+        }
+    }
+
+    fn index(&self) -> usize {
+        SKIN_PARTS.iter().position(|part| part == self).unwrap()
+    }
+}
+
+/// UiSkin struct
+/// Where each widget part is found within an atlas texture, which all UI rects are drawn with.
+/// Any other sprites in the atlas may be shown as images.
+pub struct UiSkin {
+    uv_rects: [[f32; 4]; 10],
+    sprite_uv_rects: HashMap<String, [f32; 4]>
+}
+
+impl UiSkin {
+
+    /// Create a skin from an atlas, which must hold a sprite named for each part; see
+    /// SkinPart::sprite_name. The Focus sprite is drawn over whichever widget has keyboard focus,
+    /// so is usually mostly transparent.
+    pub fn from_atlas(descriptor: &AtlasDescriptor) -> Result<UiSkin, EngineError> {
+        let mut uv_rects = [[0.0; 4]; 10];
+        let mut missing = vec![];
+        for (index, part) in SKIN_PARTS.iter().enumerate() {
+            match descriptor.uv_rect(part.sprite_name()) {
+                Some(uv) => uv_rects[index] = uv,
+                None => missing.push(part.sprite_name())
+            }
+        }
+        let sprite_uv_rects = descriptor.sprites().iter()
+            .filter_map(|(name, _)| descriptor.uv_rect(name).map(|uv| (name.clone(), uv)))
+            .collect();
+        match missing.is_empty() {
+            true => Ok(UiSkin { uv_rects, sprite_uv_rects }),
+            false => Err(EngineError::GeneralError(
+                format!("UI skin atlas is missing sprites: {}", missing.join(", "))))
+        }
+    }
+
+    /// Create a plain skin, with each part a block of flat colour, along with the atlas texture
+    /// that it must be drawn with. Useful before any artwork exists.
+    pub fn default_with_texture() -> Result<(UiSkin, TextureCreationData), EngineError> {
+        let size = DEFAULT_SKIN_SPRITE_SIZE;
+        let images: Vec<SpriteImage> = SKIN_PARTS.iter()
+            .zip(DEFAULT_SKIN_COLOURS.iter())
+            .map(|(part, colour)| SpriteImage {
+                name: part.sprite_name().to_string(),
+                width: size,
+                height: size,
+                pixels: colour.repeat((size * size) as usize)
+            })
+            .collect();
+        let atlas = atlas::pack(&images, PackOptions::default())
+            .map_err(|e| EngineError::GeneralError(format!("Error packing UI skin: {:?}", e)))?;
+        let skin = UiSkin::from_atlas(&atlas.descriptor)?;
+        let texture = TextureCreationData {
+            layer_data: Some(vec![atlas.pixels]),
+            width: atlas.width,
+            height: atlas.height,
+            format: TexturePixelFormat::Rgba,
+            usage: ImageUsage::TextureSampleOnly,
+            mipmaps: MipmapMode::None,
+            sampler: SamplerConfig::default()
+        };
+        Ok((skin, texture))
+    }
+
+    /// Get the texture coordinates of a part, as [left, top, right, bottom]
+    pub fn uv_rect(&self, part: SkinPart) -> [f32; 4] {
+        self.uv_rects[part.index()]
+    }
+
+    /// Get the texture coordinates of any sprite in the skin's atlas by name, as
+    /// [left, top, right, bottom]
+    pub fn sprite_uv_rect(&self, name: &str) -> Option<[f32; 4]> {
+        self.sprite_uv_rects.get(name).copied()
+    }
+}
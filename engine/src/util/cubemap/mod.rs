@@ -0,0 +1,414 @@
+
+use defs::{
+    EngineError,
+    render::{
+        TexturePixelFormat,
+        TextureCreationData,
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig
+    }
+};
+use crate::util::{
+    TextureCodec,
+    decode_texture,
+    f32_to_f16_bits,
+    f16_bits_to_f32
+};
+use crate::vfs::Vfs;
+
+/// The most samples taken along each axis of an output texel when shrinking the source image
+const MAX_SAMPLES_PER_AXIS: u32 = 4;
+
+/// CubemapLayout enum
+/// How the six faces of a sky box are arranged within a single source image. Directions follow
+/// the engine's view space, with +Z forward, -Y up and +X to the right:
+/// - Equirectangular is a 2:1 panorama, with forward at the centre and up along the top edge
+/// - HorizontalCross is a 4:3 image, with up, down, left, front, right and back at cells (1, 0),
+///   (1, 2), (0, 1), (1, 1), (2, 1) and (3, 1), each face seen from inside the cube
+/// - VerticalCross is a 3:4 image, laid out as the horizontal cross except that the back face is
+///   at cell (1, 3), rotated by half a turn so that it joins the bottom of the down face
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum CubemapLayout {
+    Equirectangular,
+    HorizontalCross,
+    VerticalCross
+}
+
+/// SourceImage struct
+/// The base level of a decoded image, held as floating point RGBA for resampling
+struct SourceImage {
+    pixels: Vec<[f32; 4]>,
+    width: u32,
+    height: u32
+}
+
+/// Region struct
+/// A rectangle of pixels within the source image that a sample is taken from
+#[derive(Copy, Clone)]
+struct Region {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32
+}
+
+impl SourceImage {
+
+    /// Bilinear sample at a position in pixels relative to a region, with texel centres at half
+    /// pixel offsets. Sampling wraps horizontally if requested and otherwise clamps to the region.
+    fn sample(&self, region: Region, x: f32, y: f32, wrap_x: bool) -> [f32; 4] {
+        let fx = x - 0.5;
+        let fy = y - 0.5;
+        let x0 = fx.floor();
+        let y0 = fy.floor();
+        let tx = fx - x0;
+        let ty = fy - y0;
+        let column = |i: i64| -> u32 {
+            let i = match wrap_x {
+                true => i.rem_euclid(region.width as i64),
+                false => i.clamp(0, region.width as i64 - 1)
+            };
+            region.x + i as u32
+        };
+        let row = |j: i64| -> u32 {
+            region.y + j.clamp(0, region.height as i64 - 1) as u32
+        };
+        let fetch = |i: u32, j: u32| self.pixels[(j * self.width + i) as usize];
+        let (c0, c1) = (column(x0 as i64), column(x0 as i64 + 1));
+        let (r0, r1) = (row(y0 as i64), row(y0 as i64 + 1));
+        let (p00, p10, p01, p11) = (fetch(c0, r0), fetch(c1, r0), fetch(c0, r1), fetch(c1, r1));
+        let mut result = [0.0; 4];
+        for channel in 0..4 {
+            let top = p00[channel] + (p10[channel] - p00[channel]) * tx;
+            let bottom = p01[channel] + (p11[channel] - p01[channel]) * tx;
+            result[channel] = top + (bottom - top) * ty;
+        }
+        result
+    }
+}
+
+/// Build the six layers of a sky box from a single decoded image in the given layout. Only the
+/// base level of the source is used. The faces are resampled to the given size, or if none is
+/// given, to a size matching the resolution of the source.
+pub fn make_cubemap(
+    source: &TextureCreationData,
+    layout: CubemapLayout,
+    face_size: Option<u32>
+) -> Result<TextureCreationData, EngineError> {
+    let image = read_source_image(source)?;
+    let (cell_size, default_face_size) = match layout {
+        CubemapLayout::Equirectangular => {
+            if image.width != image.height * 2 {
+                return Err(EngineError::DecodeError(
+                    format!("Equirectangular image must be twice as wide as it is high, got {}x{}", image.width, image.height)));
+            }
+            (image.width / 4, image.width / 4)
+        },
+        CubemapLayout::HorizontalCross => {
+            if image.width % 4 != 0 || image.width / 4 * 3 != image.height {
+                return Err(EngineError::DecodeError(
+                    format!("Horizontal cross image must be 4 faces wide and 3 high, got {}x{}", image.width, image.height)));
+            }
+            (image.width / 4, image.width / 4)
+        },
+        CubemapLayout::VerticalCross => {
+            if image.width % 3 != 0 || image.width / 3 * 4 != image.height {
+                return Err(EngineError::DecodeError(
+                    format!("Vertical cross image must be 3 faces wide and 4 high, got {}x{}", image.width, image.height)));
+            }
+            (image.width / 3, image.width / 3)
+        }
+    };
+    let face_size = face_size.unwrap_or(default_face_size).max(1);
+    if cell_size == 0 {
+        return Err(EngineError::DecodeError("Cube map source image is too small".to_string()));
+    }
+
+    // Take several samples per output texel when shrinking, so that detail is averaged rather than
+    // skipped over
+    let samples_per_axis = cell_size.div_ceil(face_size).clamp(1, MAX_SAMPLES_PER_AXIS);
+    let sample_weight = 1.0 / (samples_per_axis * samples_per_axis) as f32;
+
+    let mut layer_data = vec![];
+    for face in 0..6 {
+        let mut pixels = Vec::with_capacity((face_size * face_size) as usize);
+        for j in 0..face_size {
+            for i in 0..face_size {
+                let mut color = [0.0f32; 4];
+                for sample_j in 0..samples_per_axis {
+                    for sample_i in 0..samples_per_axis {
+                        let s = (i as f32 + (sample_i as f32 + 0.5) / samples_per_axis as f32) / face_size as f32;
+                        let t = (j as f32 + (sample_j as f32 + 0.5) / samples_per_axis as f32) / face_size as f32;
+                        let direction = face_direction(face, 2.0 * s - 1.0, 2.0 * t - 1.0);
+                        let sample = sample_direction(&image, layout, cell_size, direction);
+                        for channel in 0..4 {
+                            color[channel] += sample[channel] * sample_weight;
+                        }
+                    }
+                }
+                pixels.push(color);
+            }
+        }
+        layer_data.push(encode_pixels(&pixels, source.format));
+    }
+
+    Ok(TextureCreationData {
+        layer_data: Some(layer_data),
+        width: face_size,
+        height: face_size,
+        format: source.format,
+        usage: ImageUsage::Skybox,
+        mipmaps: MipmapMode::None,
+        sampler: SamplerConfig::default()
+    })
+}
+
+/// Decode a single image file and build a sky box from it in the given layout
+pub fn decode_cubemap(
+    image_file_bytes: &[u8],
+    codec: TextureCodec,
+    layout: CubemapLayout,
+    face_size: Option<u32>
+) -> Result<TextureCreationData, EngineError> {
+    let source = decode_texture(image_file_bytes, codec, ImageUsage::TextureSampleOnly)?;
+    make_cubemap(&source, layout, face_size)
+}
+
+/// Decode a single image file from the VFS and build a sky box from it in the given layout, with
+/// the codec chosen by the file extension
+pub fn decode_cubemap_from_vfs(
+    vfs: &Vfs,
+    path: &str,
+    layout: CubemapLayout,
+    face_size: Option<u32>
+) -> Result<TextureCreationData, EngineError> {
+    let codec = TextureCodec::from_file_name(path)
+        .ok_or_else(|| EngineError::DecodeError(format!("No texture codec for file: {}", path)))?;
+    decode_cubemap(&vfs.read(path)?, codec, layout, face_size)
+}
+
+/// Read the base level of a single-layer RGBA texture into floating point
+fn read_source_image(source: &TextureCreationData) -> Result<SourceImage, EngineError> {
+    let data = match &source.layer_data {
+        Some(layers) if layers.len() == 1 => &layers[0],
+        _ => return Err(EngineError::DecodeError("Cube map source must have a single layer of data".to_string()))
+    };
+    let bytes_per_pixel = match source.format {
+        TexturePixelFormat::Rgba => 4,
+        TexturePixelFormat::RgbaF16 => 8,
+        _ => return Err(EngineError::DecodeError(
+            format!("Cannot build a cube map from format {:?}", source.format)))
+    };
+    let pixel_count = source.width as usize * source.height as usize;
+    if data.len() / bytes_per_pixel < pixel_count {
+        return Err(EngineError::DecodeError(
+            format!("Cube map source has too little data for a {}x{} image", source.width, source.height)));
+    }
+    let pixels: Vec<[f32; 4]> = match source.format {
+        TexturePixelFormat::Rgba => data[0..(pixel_count * 4)]
+            .chunks_exact(4)
+            .map(|p| [p[0] as f32 / 255.0, p[1] as f32 / 255.0, p[2] as f32 / 255.0, p[3] as f32 / 255.0])
+            .collect(),
+        TexturePixelFormat::RgbaF16 => data[0..(pixel_count * 8)]
+            .chunks_exact(8)
+            .map(|p| {
+                let channel = |c: usize| f16_bits_to_f32(u16::from_le_bytes([p[c * 2], p[c * 2 + 1]]));
+                [channel(0), channel(1), channel(2), channel(3)]
+            })
+            .collect(),
+        _ => return Err(EngineError::DecodeError(
+            format!("Cannot build a cube map from format {:?}", source.format)))
+    };
+    Ok(SourceImage {
+        pixels,
+        width: source.width,
+        height: source.height
+    })
+}
+
+/// Convert floating point pixels back to the texture's format
+fn encode_pixels(pixels: &[[f32; 4]], format: TexturePixelFormat) -> Vec<u8> {
+    let mut data = vec![];
+    for pixel in pixels.iter() {
+        for value in pixel.iter() {
+            match format {
+                TexturePixelFormat::RgbaF16 => data.extend_from_slice(&f32_to_f16_bits(*value).to_le_bytes()),
+                _ => data.push((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+            };
+        }
+    }
+    data
+}
+
+/// Direction through a point on a cube face, with the faces in layer order (+X, -X, +Y, -Y, +Z,
+/// -Z) and s and t running from -1 to 1 across and down the face, as the device samples them
+fn face_direction(face: usize, s: f32, t: f32) -> [f32; 3] {
+    match face {
+        0 => [1.0, -t, -s],
+        1 => [-1.0, -t, s],
+        2 => [s, 1.0, t],
+        3 => [s, -1.0, -t],
+        4 => [s, -t, 1.0],
+        _ => [-s, -t, -1.0]
+    }
+}
+
+/// Sample the source image in the given direction
+fn sample_direction(image: &SourceImage, layout: CubemapLayout, cell_size: u32, direction: [f32; 3]) -> [f32; 4] {
+    let [x, y, z] = direction;
+    if layout == CubemapLayout::Equirectangular {
+        let length = (x * x + y * y + z * z).sqrt();
+        let longitude = x.atan2(z);
+        let latitude = (-y / length).asin();
+        let u = 0.5 + longitude / (2.0 * std::f32::consts::PI);
+        let v = 0.5 - latitude / std::f32::consts::PI;
+        let region = Region { x: 0, y: 0, width: image.width, height: image.height };
+        return image.sample(region, u * image.width as f32, v * image.height as f32, true);
+    }
+
+    // Find the face the direction points at, and the position within that face's cell, with u
+    // and v running from 0 to 1 across and down the face as seen from inside the cube
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+    let (cell, u, v) = if ax >= ay && ax >= az {
+        match x > 0.0 {
+            true => ((2, 1), -z / ax, y / ax),
+            false => ((0, 1), z / ax, y / ax)
+        }
+    } else if ay >= az {
+        match y < 0.0 {
+            true => ((1, 0), x / ay, z / ay),
+            false => ((1, 2), x / ay, -z / ay)
+        }
+    } else {
+        match z > 0.0 {
+            true => ((1, 1), x / az, y / az),
+            false => ((3, 1), -x / az, y / az)
+        }
+    };
+    let (mut u, mut v) = ((u + 1.0) * 0.5, (v + 1.0) * 0.5);
+    let mut cell = cell;
+    if layout == CubemapLayout::VerticalCross && cell == (3, 1) {
+        cell = (1, 3);
+        u = 1.0 - u;
+        v = 1.0 - v;
+    }
+    let region = Region {
+        x: cell.0 * cell_size,
+        y: cell.1 * cell_size,
+        width: cell_size,
+        height: cell_size
+    };
+    image.sample(region, u * cell_size as f32, v * cell_size as f32, false)
+}
+
+/// Test suite
+/// Check that each layout puts every face of the source into the right layer
+#[cfg(test)]
+mod test {
+    use crate::util::cubemap::{
+        make_cubemap,
+        CubemapLayout
+    };
+    use defs::render::{
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig,
+        TextureCreationData,
+        TexturePixelFormat
+    };
+
+    /// Colours of the faces in layer order: +X (right), -X (left), +Y (down), -Y (up), +Z (front)
+    /// and -Z (back)
+    const FACE_COLORS: [[u8; 4]; 6] = [
+        [255, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255, 255, 0, 255],
+        [0, 255, 255, 255],
+        [255, 0, 255, 255]
+    ];
+
+    /// Make an RGBA source image, coloured by a function of each pixel's position
+    fn make_source(width: u32, height: u32, color_at: impl Fn(u32, u32) -> [u8; 4]) -> TextureCreationData {
+        let mut data = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                data.extend_from_slice(&color_at(x, y));
+            }
+        }
+        TextureCreationData {
+            layer_data: Some(vec![data]),
+            width,
+            height,
+            format: TexturePixelFormat::Rgba,
+            usage: ImageUsage::TextureSampleOnly,
+            mipmaps: MipmapMode::None,
+            sampler: SamplerConfig::default()
+        }
+    }
+
+    /// Make a cross image with cells of the given size, where the cells at the given positions
+    /// hold the faces in layer order
+    fn make_cross(columns: u32, rows: u32, cell_size: u32, cells: [(u32, u32); 6]) -> TextureCreationData {
+        make_source(columns * cell_size, rows * cell_size, |x, y| {
+            match cells.iter().position(|cell| *cell == (x / cell_size, y / cell_size)) {
+                Some(face) => FACE_COLORS[face],
+                None => [0, 0, 0, 255]
+            }
+        })
+    }
+
+    fn assert_layers_filled(cubemap: &TextureCreationData) {
+        let layers = cubemap.layer_data.as_ref().unwrap();
+        assert_eq!(layers.len(), 6);
+        for (face, layer) in layers.iter().enumerate() {
+            assert!(layer.chunks_exact(4).all(|texel| texel == FACE_COLORS[face]), "face {}", face);
+        }
+    }
+
+    #[test]
+    fn cross_layouts_map_cells_to_layers() {
+        let horizontal = make_cross(4, 3, 4, [(2, 1), (0, 1), (1, 2), (1, 0), (1, 1), (3, 1)]);
+        let cubemap = make_cubemap(&horizontal, CubemapLayout::HorizontalCross, None).unwrap();
+        assert_eq!((cubemap.width, cubemap.height, cubemap.usage), (4, 4, ImageUsage::Skybox));
+        assert_layers_filled(&cubemap);
+
+        let vertical = make_cross(3, 4, 4, [(2, 1), (0, 1), (1, 2), (1, 0), (1, 1), (1, 3)]);
+        assert_layers_filled(&make_cubemap(&vertical, CubemapLayout::VerticalCross, Some(2)).unwrap());
+
+        assert!(make_cubemap(&vertical, CubemapLayout::HorizontalCross, None).is_err());
+        let mut short = make_cross(4, 3, 4, [(2, 1), (0, 1), (1, 2), (1, 0), (1, 1), (3, 1)]);
+        short.layer_data.as_mut().unwrap()[0].truncate(100);
+        assert!(make_cubemap(&short, CubemapLayout::HorizontalCross, None).is_err());
+    }
+
+    #[test]
+    fn equirectangular_layout_maps_directions_to_layers() {
+
+        // Colour each pixel by the face its direction points at, so that every face's centre
+        // samples a single colour
+        let (width, height) = (64, 32);
+        let source = make_source(width, height, |x, y| {
+            let longitude = ((x as f32 + 0.5) / width as f32 - 0.5) * 2.0 * std::f32::consts::PI;
+            let latitude = (0.5 - (y as f32 + 0.5) / height as f32) * std::f32::consts::PI;
+            let direction = [
+                latitude.cos() * longitude.sin(),
+                -latitude.sin(),
+                latitude.cos() * longitude.cos()
+            ];
+            let axis = (0..3)
+                .max_by(|a, b| direction[*a].abs().partial_cmp(&direction[*b].abs()).unwrap())
+                .unwrap();
+            FACE_COLORS[axis * 2 + (direction[axis] < 0.0) as usize]
+        });
+        let cubemap = make_cubemap(&source, CubemapLayout::Equirectangular, Some(8)).unwrap();
+        let layers = cubemap.layer_data.as_ref().unwrap();
+        for (face, layer) in layers.iter().enumerate() {
+            for (i, j) in [(3, 3), (4, 4), (3, 4), (4, 3)] {
+                let offset = (j * 8 + i) * 4;
+                assert_eq!(&layer[offset..(offset + 4)], &FACE_COLORS[face], "face {}", face);
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+
+use crate::util::mip_level_count;
+use defs::{
+    EngineError,
+    render::{
+        TexturePixelFormat,
+        TextureCreationData,
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig
+    }
+};
+
+/// Size of the magic number plus the DDS_HEADER structure
+const HEADER_SIZE: usize = 128;
+
+/// Size of the DDS_HEADER_DXT10 structure that follows the header when the FourCC is "DX10"
+const DX10_HEADER_SIZE: usize = 20;
+
+/// Header flag indicating that the mip map count field is valid
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+
+/// Pixel format flags for a FourCC-described format and for uncompressed RGB data
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+
+/// Caps2 flag marking a cube map, and the equivalent DX10 misc flag
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+/// Decode a DDS file, including files with the DX10 extended header. Pre-built mip levels are
+/// kept, and a cube map must be used as a sky box. Volume textures and texture arrays are not
+/// supported.
+pub fn decode_dds(bytes: &[u8], usage: ImageUsage) -> Result<TextureCreationData, EngineError> {
+    if bytes.len() < HEADER_SIZE || &bytes[0..4] != b"DDS " || read_u32(bytes, 4) != 124 {
+        return Err(EngineError::DecodeError("Not a DDS file".to_string()));
+    }
+    let flags = read_u32(bytes, 8);
+    let height = read_u32(bytes, 12);
+    let width = read_u32(bytes, 16);
+    let level_count = match flags & DDSD_MIPMAPCOUNT {
+        0 => 1,
+        _ => read_u32(bytes, 28).max(1)
+    };
+    let pixel_format_flags = read_u32(bytes, 80);
+    let four_cc = &bytes[84..88];
+    let caps2 = read_u32(bytes, 112);
+
+    let (format, face_count, data_offset) = if pixel_format_flags & DDPF_FOURCC != 0 && four_cc == b"DX10" {
+        if bytes.len() < HEADER_SIZE + DX10_HEADER_SIZE {
+            return Err(EngineError::DecodeError("DDS DX10 header is truncated".to_string()));
+        }
+        let dxgi_format = read_u32(bytes, 128);
+        let misc_flags = read_u32(bytes, 136);
+        let array_size = read_u32(bytes, 140).max(1);
+        if array_size > 1 {
+            return Err(EngineError::DecodeError("DDS texture arrays are not supported".to_string()));
+        }
+        let face_count = match misc_flags & RESOURCE_MISC_TEXTURECUBE {
+            0 => 1,
+            _ => 6
+        };
+        (dxgi_texture_format(dxgi_format)?, face_count, HEADER_SIZE + DX10_HEADER_SIZE)
+    } else {
+        let format = if pixel_format_flags & DDPF_FOURCC != 0 {
+            four_cc_texture_format(four_cc)?
+        } else if pixel_format_flags & DDPF_RGB != 0 && is_rgba8_layout(bytes) {
+            TexturePixelFormat::Rgba
+        } else {
+            return Err(EngineError::DecodeError("Unsupported DDS pixel format".to_string()));
+        };
+        let face_count = match caps2 & DDSCAPS2_CUBEMAP {
+            0 => 1,
+            _ => 6
+        };
+        (format, face_count, HEADER_SIZE)
+    };
+
+    if width == 0 || height == 0 {
+        return Err(EngineError::DecodeError("DDS texture has no size".to_string()));
+    }
+    match (face_count, usage) {
+        (6, ImageUsage::Skybox) | (1, ImageUsage::TextureSampleOnly) => (),
+        _ => return Err(EngineError::DecodeError(
+            format!("DDS file with {} faces cannot be used for {:?}", face_count, usage)))
+    };
+
+    if level_count > mip_level_count(width, height) {
+        return Err(EngineError::DecodeError(
+            format!("DDS file has {} mip levels, more than a {}x{} texture can have", level_count, width, height)));
+    }
+
+    // Each face is stored with all of its levels together, which is the layout the renderer takes
+    let face_size = format.checked_layer_size_bytes(width, height, level_count)
+        .ok_or_else(|| EngineError::DecodeError("DDS texture is too large".to_string()))?;
+    let data_end = face_size.checked_mul(face_count)
+        .and_then(|size| size.checked_add(data_offset));
+    match data_end {
+        Some(end) if end <= bytes.len() => (),
+        _ => return Err(EngineError::DecodeError("DDS image data is truncated".to_string()))
+    };
+    let layer_data: Vec<Vec<u8>> = (0..face_count)
+        .map(|face| {
+            let face_start = data_offset + face * face_size;
+            bytes[face_start..(face_start + face_size)].to_vec()
+        })
+        .collect();
+
+    Ok(TextureCreationData {
+        layer_data: Some(layer_data),
+        width,
+        height,
+        format,
+        usage,
+        mipmaps: match level_count {
+            1 => MipmapMode::None,
+            n => MipmapMode::Provided(n)
+        },
+        sampler: SamplerConfig::default()
+    })
+}
+
+/// Map a legacy FourCC code to a texture format. Premultiplied-alpha variants are treated the same
+/// as their straight-alpha counterparts.
+fn four_cc_texture_format(four_cc: &[u8]) -> Result<TexturePixelFormat, EngineError> {
+    match four_cc {
+        b"DXT1" => Ok(TexturePixelFormat::Bc1),
+        b"DXT2" | b"DXT3" => Ok(TexturePixelFormat::Bc2),
+        b"DXT4" | b"DXT5" => Ok(TexturePixelFormat::Bc3),
+        b"ATI1" | b"BC4U" => Ok(TexturePixelFormat::Bc4),
+        b"ATI2" | b"BC5U" => Ok(TexturePixelFormat::Bc5),
+        _ => Err(EngineError::DecodeError(
+            format!("Unsupported DDS FourCC: {}", String::from_utf8_lossy(four_cc))))
+    }
+}
+
+/// Map a DXGI_FORMAT value to a texture format. sRGB variants are loaded as their UNORM
+/// equivalents, the same as every other texture the engine loads.
+fn dxgi_texture_format(dxgi_format: u32) -> Result<TexturePixelFormat, EngineError> {
+    match dxgi_format {
+        10 => Ok(TexturePixelFormat::RgbaF16),
+        28 | 29 => Ok(TexturePixelFormat::Rgba),
+        71 | 72 => Ok(TexturePixelFormat::Bc1),
+        74 | 75 => Ok(TexturePixelFormat::Bc2),
+        77 | 78 => Ok(TexturePixelFormat::Bc3),
+        80 => Ok(TexturePixelFormat::Bc4),
+        83 => Ok(TexturePixelFormat::Bc5),
+        98 | 99 => Ok(TexturePixelFormat::Bc7),
+        _ => Err(EngineError::DecodeError(format!("Unsupported DXGI format: {}", dxgi_format)))
+    }
+}
+
+/// Check whether an uncompressed pixel format is 32-bit with bytes in RGBA order
+fn is_rgba8_layout(bytes: &[u8]) -> bool {
+    read_u32(bytes, 88) == 32 &&
+        read_u32(bytes, 92) == 0x0000_00ff &&
+        read_u32(bytes, 96) == 0x0000_ff00 &&
+        read_u32(bytes, 100) == 0x00ff_0000 &&
+        read_u32(bytes, 104) == 0xff00_0000
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&bytes[at..(at + 4)]);
+    u32::from_le_bytes(value)
+}
+
+/// Test suite
+/// Check that headers are read, and that malformed files are rejected rather than panicking
+#[cfg(test)]
+mod test {
+    use crate::util::dds::decode_dds;
+    use defs::render::{
+        ImageUsage,
+        MipmapMode,
+        TexturePixelFormat
+    };
+
+    /// Make the header of a DXT1 file with the given size and mip level count
+    fn make_header(width: u32, height: u32, level_count: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; 128];
+        bytes[0..4].copy_from_slice(b"DDS ");
+        bytes[4..8].copy_from_slice(&124u32.to_le_bytes());
+        bytes[8..12].copy_from_slice(&0x20000u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[28..32].copy_from_slice(&level_count.to_le_bytes());
+        bytes[80..84].copy_from_slice(&0x4u32.to_le_bytes());
+        bytes[84..88].copy_from_slice(b"DXT1");
+        bytes
+    }
+
+    #[test]
+    fn header_parsed_and_malformed_files_rejected() {
+
+        // Levels of 8x8, 4x4, 2x2 and 1x1 take 4, 1, 1 and 1 blocks of 8 bytes
+        let mut bytes = make_header(8, 8, 4);
+        bytes.extend((0..56).map(|i| i as u8));
+        let texture = decode_dds(&bytes, ImageUsage::TextureSampleOnly).unwrap();
+        assert_eq!((texture.width, texture.height), (8, 8));
+        assert_eq!(texture.format, TexturePixelFormat::Bc1);
+        assert_eq!(texture.mipmaps, MipmapMode::Provided(4));
+        assert_eq!(texture.layer_data.unwrap(), vec![(0..56).collect::<Vec<u8>>()]);
+
+        assert!(decode_dds(&bytes[0..(bytes.len() - 1)], ImageUsage::TextureSampleOnly).is_err());
+        assert!(decode_dds(&bytes[0..100], ImageUsage::TextureSampleOnly).is_err());
+        assert!(decode_dds(&bytes, ImageUsage::Skybox).is_err());
+
+        let mut too_many_levels = make_header(8, 8, 5);
+        too_many_levels.extend(vec![0u8; 64]);
+        assert!(decode_dds(&too_many_levels, ImageUsage::TextureSampleOnly).is_err());
+        let mut absurd_levels = make_header(8, 8, 40);
+        absurd_levels.extend(vec![0u8; 64]);
+        assert!(decode_dds(&absurd_levels, ImageUsage::TextureSampleOnly).is_err());
+        let huge = make_header(u32::MAX, u32::MAX, 32);
+        assert!(decode_dds(&huge, ImageUsage::TextureSampleOnly).is_err());
+    }
+}
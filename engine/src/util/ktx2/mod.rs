@@ -0,0 +1,202 @@
+
+use crate::util::mip_level_count;
+use defs::{
+    EngineError,
+    render::{
+        TexturePixelFormat,
+        TextureCreationData,
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig
+    }
+};
+
+/// The 12-byte identifier at the start of every KTX2 file
+const KTX2_IDENTIFIER: [u8; 12] = [0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// Size of the identifier, header and index sections that come before the level index
+const LEVEL_INDEX_OFFSET: usize = 80;
+
+/// Size of each entry in the level index
+const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+/// Decode a KTX2 container. Pre-built mip levels are kept, and a file with six faces is loaded as
+/// a cube map, which must then be used as a sky box. Supercompressed files and texture arrays are
+/// not supported.
+pub fn decode_ktx2(bytes: &[u8], usage: ImageUsage) -> Result<TextureCreationData, EngineError> {
+    if bytes.len() < LEVEL_INDEX_OFFSET || bytes[0..12] != KTX2_IDENTIFIER {
+        return Err(EngineError::DecodeError("Not a KTX2 file".to_string()));
+    }
+    let vk_format = read_u32(bytes, 12);
+    let width = read_u32(bytes, 20);
+    let height = read_u32(bytes, 24);
+    let depth = read_u32(bytes, 28);
+    let layer_count = read_u32(bytes, 32);
+    let face_count = read_u32(bytes, 36);
+    let level_count = read_u32(bytes, 40).max(1);
+    let supercompression = read_u32(bytes, 44);
+
+    let format = texture_format(vk_format)?;
+    if supercompression != 0 {
+        return Err(EngineError::DecodeError(
+            format!("Supercompressed KTX2 files are not supported (scheme {})", supercompression)));
+    }
+    if width == 0 || height == 0 || depth > 1 {
+        return Err(EngineError::DecodeError("Only 2D KTX2 textures are supported".to_string()));
+    }
+    if layer_count > 1 {
+        return Err(EngineError::DecodeError("KTX2 texture arrays are not supported".to_string()));
+    }
+    check_face_count(face_count, usage)?;
+    if level_count > mip_level_count(width, height) {
+        return Err(EngineError::DecodeError(
+            format!("KTX2 file has {} mip levels, more than a {}x{} texture can have", level_count, width, height)));
+    }
+
+    let index_end = LEVEL_INDEX_OFFSET + level_count as usize * LEVEL_INDEX_ENTRY_SIZE;
+    if bytes.len() < index_end {
+        return Err(EngineError::DecodeError("KTX2 level index is truncated".to_string()));
+    }
+
+    // Levels are stored with each one holding every face, whereas each layer passed to the
+    // renderer holds every level for a single face
+    let mut layer_data = vec![vec![]; face_count as usize];
+    for level in 0..level_count {
+        let entry_offset = LEVEL_INDEX_OFFSET + level as usize * LEVEL_INDEX_ENTRY_SIZE;
+        let level_offset = read_u64(bytes, entry_offset) as usize;
+        let level_length = read_u64(bytes, entry_offset + 8) as usize;
+        let face_size = format.checked_layer_size_bytes(width >> level, height >> level, 1)
+            .ok_or_else(|| EngineError::DecodeError("KTX2 texture is too large".to_string()))?;
+        if face_size.checked_mul(face_count as usize) != Some(level_length) {
+            return Err(EngineError::DecodeError(
+                format!("KTX2 level {} has unexpected size {}", level, level_length)));
+        }
+        let in_range = match level_offset.checked_add(level_length) {
+            Some(end) => end <= bytes.len(),
+            None => false
+        };
+        if !in_range {
+            return Err(EngineError::DecodeError(format!("KTX2 level {} is out of range", level)));
+        }
+        for (face, data) in layer_data.iter_mut().enumerate() {
+            let face_start = level_offset + face * face_size;
+            data.extend_from_slice(&bytes[face_start..(face_start + face_size)]);
+        }
+    }
+
+    Ok(TextureCreationData {
+        layer_data: Some(layer_data),
+        width,
+        height,
+        format,
+        usage,
+        mipmaps: match level_count {
+            1 => MipmapMode::None,
+            n => MipmapMode::Provided(n)
+        },
+        sampler: SamplerConfig::default()
+    })
+}
+
+/// Map a VkFormat value to a texture format. sRGB variants are loaded as their UNORM equivalents,
+/// the same as every other texture the engine loads.
+fn texture_format(vk_format: u32) -> Result<TexturePixelFormat, EngineError> {
+    match vk_format {
+        37 | 43 => Ok(TexturePixelFormat::Rgba),
+        97 => Ok(TexturePixelFormat::RgbaF16),
+        131..=134 => Ok(TexturePixelFormat::Bc1),
+        135 | 136 => Ok(TexturePixelFormat::Bc2),
+        137 | 138 => Ok(TexturePixelFormat::Bc3),
+        139 => Ok(TexturePixelFormat::Bc4),
+        141 => Ok(TexturePixelFormat::Bc5),
+        145 | 146 => Ok(TexturePixelFormat::Bc7),
+        _ => Err(EngineError::DecodeError(format!("Unsupported KTX2 format: {}", vk_format)))
+    }
+}
+
+/// Check that the number of faces suits the usage; cube maps have six, everything else has one
+fn check_face_count(face_count: u32, usage: ImageUsage) -> Result<(), EngineError> {
+    match (face_count, usage) {
+        (6, ImageUsage::Skybox) | (1, ImageUsage::TextureSampleOnly) => Ok(()),
+        _ => Err(EngineError::DecodeError(
+            format!("KTX2 file with {} faces cannot be used for {:?}", face_count, usage)))
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&bytes[at..(at + 4)]);
+    u32::from_le_bytes(value)
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&bytes[at..(at + 8)]);
+    u64::from_le_bytes(value)
+}
+
+/// Test suite
+/// Check that headers and the level index are read, and that malformed files are rejected rather
+/// than panicking
+#[cfg(test)]
+mod test {
+    use crate::util::ktx2::{
+        decode_ktx2,
+        KTX2_IDENTIFIER
+    };
+    use defs::render::{
+        ImageUsage,
+        MipmapMode,
+        TexturePixelFormat
+    };
+
+    /// Make an RGBA8 file of the given size, with each level's data following the level index and
+    /// holding its own level number in every byte
+    fn make_file(width: u32, height: u32, level_count: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; 80];
+        bytes[0..12].copy_from_slice(&KTX2_IDENTIFIER);
+        bytes[12..16].copy_from_slice(&37u32.to_le_bytes());
+        bytes[20..24].copy_from_slice(&width.to_le_bytes());
+        bytes[24..28].copy_from_slice(&height.to_le_bytes());
+        bytes[36..40].copy_from_slice(&1u32.to_le_bytes());
+        bytes[40..44].copy_from_slice(&level_count.to_le_bytes());
+        let mut level_offset = 80 + level_count as usize * 24;
+        let mut level_data = vec![];
+        for level in 0..level_count.min(32) {
+            let size = 4 * (width >> level).max(1) as usize * (height >> level).max(1) as usize;
+            bytes.extend_from_slice(&(level_offset as u64).to_le_bytes());
+            bytes.extend_from_slice(&(size as u64).to_le_bytes());
+            bytes.extend_from_slice(&(size as u64).to_le_bytes());
+            level_data.extend(vec![level as u8; size]);
+            level_offset += size;
+        }
+        bytes.extend(level_data);
+        bytes
+    }
+
+    #[test]
+    fn header_parsed_and_malformed_files_rejected() {
+        let bytes = make_file(4, 2, 3);
+        let texture = decode_ktx2(&bytes, ImageUsage::TextureSampleOnly).unwrap();
+        assert_eq!((texture.width, texture.height), (4, 2));
+        assert_eq!(texture.format, TexturePixelFormat::Rgba);
+        assert_eq!(texture.mipmaps, MipmapMode::Provided(3));
+        let mut expected = vec![0u8; 32];
+        expected.extend(vec![1u8; 8]);
+        expected.extend(vec![2u8; 4]);
+        assert_eq!(texture.layer_data.unwrap(), vec![expected]);
+
+        assert!(decode_ktx2(&bytes[0..(bytes.len() - 1)], ImageUsage::TextureSampleOnly).is_err());
+        assert!(decode_ktx2(&bytes[0..120], ImageUsage::TextureSampleOnly).is_err());
+        assert!(decode_ktx2(&bytes[0..40], ImageUsage::TextureSampleOnly).is_err());
+        assert!(decode_ktx2(&bytes, ImageUsage::Skybox).is_err());
+
+        assert!(decode_ktx2(&make_file(4, 2, 4), ImageUsage::TextureSampleOnly).is_err());
+        let mut absurd_levels = make_file(4, 2, 3);
+        absurd_levels[40..44].copy_from_slice(&40u32.to_le_bytes());
+        assert!(decode_ktx2(&absurd_levels, ImageUsage::TextureSampleOnly).is_err());
+        let mut huge = make_file(1, 1, 1);
+        huge[20..28].copy_from_slice(&[0xff; 8]);
+        assert!(decode_ktx2(&huge, ImageUsage::TextureSampleOnly).is_err());
+    }
+}
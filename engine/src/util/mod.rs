@@ -1,72 +1,215 @@
 
 pub mod textbuffer;
+pub mod ktx2;
+pub mod dds;
+pub mod cubemap;
+pub mod sprites;
 
 use defs::{
     EngineError,
     render::{
         TexturePixelFormat,
         TextureCreationData,
-        ImageUsage
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig
     }
 };
 use model::{
     factory::FromFile,
     types::{
+        Bounds,
         Model,
         StaticVertex
     }
 };
 use image::{
     DynamicImage,
+    ImageDecoder,
     codecs::jpeg::JpegDecoder,
-    codecs::png::PngDecoder
+    codecs::png::PngDecoder,
+    codecs::tga::TgaDecoder,
+    codecs::bmp::BmpDecoder,
+    codecs::webp::WebPDecoder,
+    codecs::hdr::HdrDecoder,
+    codecs::openexr::OpenExrDecoder
 };
+use renderer::vk_renderer::mipmaps::append_rgba_mip_chain;
+use crate::vfs::Vfs;
+
 use std::io::Cursor;
 
 #[derive(Copy, Clone)]
 pub enum TextureCodec {
     Jpeg,
-    Png
+    Png,
+    Tga,
+    Bmp,
+    WebP,
+    Hdr,
+    Exr,
+    Ktx2,
+    Dds
 }
 
-/// Decode texture data from a file, returning a defs::render::TextureCreationData instance
-pub fn decode_texture(image_file_bytes: &[u8], codec: TextureCodec, usage: ImageUsage) -> Result<TextureCreationData, EngineError> {
-    let (data, width, height) = match codec {
-        TextureCodec::Jpeg => {
-            let src_cursor = Cursor::new(image_file_bytes.to_vec());
-            let decoder = JpegDecoder::new(src_cursor).unwrap();
-            let terrain_image_pixel_data = DynamicImage::from_decoder(decoder)?;
-            let image_data_rgba = terrain_image_pixel_data.to_rgba8();
-            (image_data_rgba.to_vec(), image_data_rgba.width(), image_data_rgba.height())
-        },
-        TextureCodec::Png => {
-            let src_cursor = Cursor::new(image_file_bytes.to_vec());
-            let decoder = PngDecoder::new(src_cursor).unwrap();
-            let terrain_image_pixel_data = DynamicImage::from_decoder(decoder)?;
-            let image_data_rgba = terrain_image_pixel_data.to_rgba8();
-            (image_data_rgba.to_vec(), image_data_rgba.width(), image_data_rgba.height())
+impl TextureCodec {
+
+    /// Choose a codec based on a file's extension
+    pub fn from_file_name(file_name: &str) -> Option<TextureCodec> {
+        let extension = file_name.rsplit('.').next()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "jpg" | "jpeg" => Some(TextureCodec::Jpeg),
+            "png" => Some(TextureCodec::Png),
+            "tga" => Some(TextureCodec::Tga),
+            "bmp" => Some(TextureCodec::Bmp),
+            "webp" => Some(TextureCodec::WebP),
+            "hdr" => Some(TextureCodec::Hdr),
+            "exr" => Some(TextureCodec::Exr),
+            "ktx2" => Some(TextureCodec::Ktx2),
+            "dds" => Some(TextureCodec::Dds),
+            _ => None
         }
+    }
+}
+
+fn codec_for_vfs_path(path: &str) -> Result<TextureCodec, EngineError> {
+    TextureCodec::from_file_name(path)
+        .ok_or_else(|| EngineError::DecodeError(format!("No texture codec for file: {}", path)))
+}
+
+/// Decode texture data from a file, returning a defs::render::TextureCreationData instance.
+/// Container formats keep their own pixel format and mip levels, and may hold all six faces of a
+/// sky box; HDR and OpenEXR images are decoded to half-float RGBA, and all other images to 8-bit RGBA, with a
+/// single level.
+pub fn decode_texture(image_file_bytes: &[u8], codec: TextureCodec, usage: ImageUsage) -> Result<TextureCreationData, EngineError> {
+    let src_cursor = Cursor::new(image_file_bytes);
+    let ((data, width, height), format) = match codec {
+        TextureCodec::Ktx2 => return ktx2::decode_ktx2(image_file_bytes, usage),
+        TextureCodec::Dds => return dds::decode_dds(image_file_bytes, usage),
+        TextureCodec::Jpeg => (decode_to_rgba8(JpegDecoder::new(src_cursor)?)?, TexturePixelFormat::Rgba),
+        TextureCodec::Png => (decode_to_rgba8(PngDecoder::new(src_cursor)?)?, TexturePixelFormat::Rgba),
+        TextureCodec::Tga => (decode_to_rgba8(TgaDecoder::new(src_cursor)?)?, TexturePixelFormat::Rgba),
+        TextureCodec::Bmp => (decode_to_rgba8(BmpDecoder::new(src_cursor)?)?, TexturePixelFormat::Rgba),
+        TextureCodec::WebP => (decode_to_rgba8(WebPDecoder::new(src_cursor)?)?, TexturePixelFormat::Rgba),
+        TextureCodec::Hdr => (decode_hdr_to_rgba_f16(src_cursor)?, TexturePixelFormat::RgbaF16),
+        TextureCodec::Exr => (decode_exr_to_rgba_f16(src_cursor)?, TexturePixelFormat::RgbaF16)
     };
     Ok(TextureCreationData {
         layer_data: Some(vec![data]),
         width,
         height,
-        format: TexturePixelFormat::Rgba,
-        usage
+        format,
+        usage,
+        mipmaps: MipmapMode::None,
+        sampler: SamplerConfig::default()
     })
 }
 
+/// Decode an image to 8-bit RGBA, returning the pixel data along with the width and height
+fn decode_to_rgba8<'a>(decoder: impl ImageDecoder<'a>) -> Result<(Vec<u8>, u32, u32), EngineError> {
+    let image_data_rgba = DynamicImage::from_decoder(decoder)?.to_rgba8();
+    let (width, height) = image_data_rgba.dimensions();
+    Ok((image_data_rgba.into_raw(), width, height))
+}
+
+/// Decode a Radiance HDR image to half-float RGBA with an alpha of 1, returning the pixel data
+/// along with the width and height
+fn decode_hdr_to_rgba_f16(src_cursor: Cursor<&[u8]>) -> Result<(Vec<u8>, u32, u32), EngineError> {
+    let decoder = HdrDecoder::new(src_cursor)?;
+    let metadata = decoder.metadata();
+    let pixels = decoder.read_image_hdr()?;
+    let mut data = Vec::with_capacity(pixels.len() * 8);
+    for pixel in pixels.iter() {
+        for value in [pixel[0], pixel[1], pixel[2], 1.0].iter() {
+            data.extend_from_slice(&f32_to_f16_bits(*value).to_le_bytes());
+        }
+    }
+    Ok((data, metadata.width, metadata.height))
+}
+
+/// Decode an OpenEXR image to half-float RGBA, keeping its alpha or using 1 where it has none,
+/// returning the pixel data along with the width and height
+fn decode_exr_to_rgba_f16(src_cursor: Cursor<&[u8]>) -> Result<(Vec<u8>, u32, u32), EngineError> {
+    let image_data_rgba = DynamicImage::from_decoder(OpenExrDecoder::new(src_cursor)?)?.to_rgba32f();
+    let (width, height) = image_data_rgba.dimensions();
+    let mut data = Vec::with_capacity(image_data_rgba.as_raw().len() * 2);
+    for value in image_data_rgba.as_raw().iter() {
+        data.extend_from_slice(&f32_to_f16_bits(*value).to_le_bytes());
+    }
+    Ok((data, width, height))
+}
+
+/// Convert a 32-bit float to the bits of the nearest 16-bit float, rounding to nearest even.
+/// Values too large for a half float become infinity; NaN stays NaN.
+pub fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        let nan_bit = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan_bit;
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exponent <= 0 {
+        // Subnormal half float, or zero if too small to represent
+        if half_exponent < -10 {
+            return sign;
+        }
+        let full_mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        let half_mantissa = full_mantissa >> shift;
+        let remainder = full_mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round_up = remainder > halfway || (remainder == halfway && half_mantissa & 1 != 0);
+        return sign | (half_mantissa + round_up as u32) as u16;
+    }
+    let half = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    let remainder = mantissa & 0x1fff;
+    let round_up = remainder > 0x1000 || (remainder == 0x1000 && half & 1 != 0);
+    // Rounding may carry into the exponent, which correctly produces infinity at the top end
+    sign | (half + round_up as u32) as u16
+}
+
+/// Convert the bits of a 16-bit float to a 32-bit float
+pub fn f16_bits_to_f32(bits: u16) -> f32 {
+    let sign = ((bits & 0x8000) as u32) << 16;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x03ff) as u32;
+    let value_bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal half float; normalise it, as every such value is a normal single float
+            let shift = mantissa.leading_zeros() - 21;
+            sign | ((113 - shift) << 23) | (((mantissa << shift) & 0x03ff) << 13)
+        },
+        (0x1f, _) => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13)
+    };
+    f32::from_bits(value_bits)
+}
+
 /// Decode a set of textures using data from one or more files, returning a
-/// defs::render::TextureCreationData instance that describes an image array
+/// defs::render::TextureCreationData instance that describes an image array. Each file must hold
+/// a single layer, and all must share the same size, format and mip levels.
 pub fn decode_texture_array(image_file_bytes: Vec<&[u8]>, codec: TextureCodec, usage: ImageUsage) -> Result<TextureCreationData, EngineError> {
     let decoded_textures: Vec<_> = image_file_bytes.iter()
-        .map(|bytes| decode_texture(bytes, codec, usage).unwrap())
-        .collect();
+        .map(|bytes| decode_texture(bytes, codec, ImageUsage::TextureSampleOnly))
+        .collect::<Result<Vec<_>, _>>()?;
     let width = decoded_textures[0].width;
-    let height = decoded_textures[0].width;
+    let height = decoded_textures[0].height;
+    let format = decoded_textures[0].format;
+    let mipmaps = decoded_textures[0].mipmaps;
     if decoded_textures.iter().any(|t| t.width != width || t.height != height) {
         return Err(EngineError::DecodeError("Not all textures same size in multi-layer sources".to_string()));
     }
+    if decoded_textures.iter().any(|t| t.format != format || t.mipmaps != mipmaps) {
+        return Err(EngineError::DecodeError("Not all textures same format in multi-layer sources".to_string()));
+    }
     let layer_data: Vec<_> = decoded_textures.into_iter()
         .map(|d| d.layer_data.unwrap().first().unwrap().to_owned())
         .collect();
@@ -74,18 +217,80 @@ pub fn decode_texture_array(image_file_bytes: Vec<&[u8]>, codec: TextureCodec, u
         layer_data: Some(layer_data),
         width,
         height,
-        format: TexturePixelFormat::Rgba,
-        usage
+        format,
+        usage,
+        mipmaps,
+        sampler: SamplerConfig::default()
     })
 }
 
-/// Decode a model file generated by the model crate's utility functions.
-pub fn decode_model(model_file_bytes: &[u8]) -> (Vec<StaticVertex>, usize) {
+/// Decode texture data from a file in the VFS, with the codec chosen by the file extension
+pub fn decode_texture_from_vfs(vfs: &Vfs, path: &str, usage: ImageUsage) -> Result<TextureCreationData, EngineError> {
+    let bytes = vfs.read(path)?;
+    decode_texture(&bytes, codec_for_vfs_path(path)?, usage)
+}
+
+/// Decode a set of textures from files in the VFS into an image array, with the codec chosen by
+/// the extension of the first file
+pub fn decode_texture_array_from_vfs(vfs: &Vfs, paths: Vec<&str>, usage: ImageUsage) -> Result<TextureCreationData, EngineError> {
+    let first_path = paths.first()
+        .ok_or_else(|| EngineError::DecodeError("No files given for texture array".to_string()))?;
+    let codec = codec_for_vfs_path(first_path)?;
+    let file_bytes = paths.iter()
+        .map(|path| vfs.read(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    decode_texture_array(file_bytes.iter().map(|b| b.as_slice()).collect(), codec, usage)
+}
+
+/// Number of levels in a full mip chain for an image of the given size, down to 1x1
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Generate a full mip chain on the CPU for an 8-bit RGBA texture with a single level in each layer,
+/// using a box filter. Each layer's data is replaced with all of its levels packed together. Where
+/// a side has odd length, its last texel is shared out with the texels before it, so that every
+/// texel of one level counts towards the next.
+pub fn generate_mipmaps(texture: &mut TextureCreationData) -> Result<(), EngineError> {
+    if texture.format != TexturePixelFormat::Rgba {
+        return Err(EngineError::DecodeError("Can only generate mipmaps for RGBA textures".to_string()));
+    }
+    if texture.mipmaps != MipmapMode::None {
+        return Err(EngineError::DecodeError("Texture already has mipmaps configured".to_string()));
+    }
+    let layer_data = match texture.layer_data.as_mut() {
+        Some(data) => data,
+        None => return Err(EngineError::DecodeError("Cannot generate mipmaps without texture data".to_string()))
+    };
+    let level_count = mip_level_count(texture.width, texture.height);
+    for layer in layer_data.iter_mut() {
+        append_rgba_mip_chain(layer, texture.width, texture.height);
+    }
+    texture.mipmaps = MipmapMode::Provided(level_count);
+    Ok(())
+}
+
+/// Decode a model file generated by the model crate's utility functions. Fails if the file is
+/// cut short, such as one read while it is still being written.
+pub fn decode_model(model_file_bytes: &[u8]) -> Result<(Vec<StaticVertex>, usize), EngineError> {
+    let (vertices, vertex_count, _) = decode_model_with_bounds(model_file_bytes)?;
+    Ok((vertices, vertex_count))
+}
+
+/// Decode a model file generated by the model crate's utility functions, along with the bounds
+/// of its vertices in model space
+pub fn decode_model_with_bounds(model_file_bytes: &[u8]) -> Result<(Vec<StaticVertex>, usize, Bounds), EngineError> {
     let model: Model<StaticVertex> = unsafe {
-        Model::new_from_bytes(model_file_bytes).unwrap()
+        Model::new_from_bytes(model_file_bytes).map_err(EngineError::DecodeError)?
     };
     let vertex_count: usize = model.vertices.len();
-    (model.vertices, vertex_count)
+    Ok((model.vertices, vertex_count, model.bounds))
+}
+
+/// Decode a model file, generated by the model crate's utility functions, from the VFS
+pub fn decode_model_from_vfs(vfs: &Vfs, path: &str) -> Result<(Vec<StaticVertex>, usize), EngineError> {
+    let bytes = vfs.read(path)?;
+    decode_model(&bytes)
 }
 
 /// Make position-normal-texcoords for cube faces
@@ -177,3 +382,173 @@ pub fn map_ui_rects(source: Vec<[f32; 8]>) -> Vec<StaticVertex> {
     }
     all_rects
 }
+
+/// Test suite
+/// Check CPU-side texture processing, and that model files cut short fail to decode
+#[cfg(test)]
+mod test {
+    use crate::util::{
+        decode_model,
+        decode_texture,
+        TextureCodec,
+        f16_bits_to_f32,
+        f32_to_f16_bits,
+        generate_mipmaps,
+        mip_level_count
+    };
+    use defs::render::{
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig,
+        TextureCreationData,
+        TexturePixelFormat
+    };
+    use model::{
+        factory::FromFile,
+        types::{
+            Model,
+            StaticVertex
+        }
+    };
+
+    /// Make a single-layer RGBA texture where each texel's red is ten times its x and its green is
+    /// ten times its y
+    fn make_gradient_texture(width: u32, height: u32) -> TextureCreationData {
+        let mut data = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                data.extend_from_slice(&[(x * 10) as u8, (y * 10) as u8, 255, 128]);
+            }
+        }
+        TextureCreationData {
+            layer_data: Some(vec![data]),
+            width,
+            height,
+            format: TexturePixelFormat::Rgba,
+            usage: ImageUsage::TextureSampleOnly,
+            mipmaps: MipmapMode::None,
+            sampler: SamplerConfig::default()
+        }
+    }
+
+    #[test]
+    fn mipmaps_cover_odd_and_non_square_sizes() {
+        assert_eq!(mip_level_count(5, 3), 3);
+        assert_eq!(mip_level_count(1, 4), 3);
+        assert_eq!(mip_level_count(1, 1), 1);
+
+        // 5x3 goes to 2x1, where the second texel takes in the last three columns, then to 1x1
+        let mut texture = make_gradient_texture(5, 3);
+        generate_mipmaps(&mut texture).unwrap();
+        assert_eq!(texture.mipmaps, MipmapMode::Provided(3));
+        let data = &texture.layer_data.as_ref().unwrap()[0];
+        assert_eq!(data.len(), (15 + 2 + 1) * 4);
+        assert_eq!(&data[60..68], &[5, 10, 255, 128, 30, 10, 255, 128]);
+        assert_eq!(&data[68..72], &[18, 10, 255, 128]);
+
+        let mut texture = make_gradient_texture(1, 4);
+        generate_mipmaps(&mut texture).unwrap();
+        let data = &texture.layer_data.as_ref().unwrap()[0];
+        assert_eq!(data.len(), (4 + 2 + 1) * 4);
+        assert_eq!(&data[16..24], &[0, 5, 255, 128, 0, 25, 255, 128]);
+        assert_eq!(&data[24..28], &[0, 15, 255, 128]);
+
+        // A 1x1 texture is already its own last level
+        let mut texture = make_gradient_texture(1, 1);
+        generate_mipmaps(&mut texture).unwrap();
+        assert_eq!(texture.mipmaps, MipmapMode::Provided(1));
+        assert_eq!(texture.layer_data.as_ref().unwrap()[0], vec![0, 0, 255, 128]);
+        assert!(generate_mipmaps(&mut texture).is_err());
+    }
+
+    #[test]
+    fn half_floats_round_trip_and_round_to_nearest_even() {
+
+        // Every half float survives a round trip, other than NaNs, which only need to stay NaN
+        for bits in 0..=u16::MAX {
+            let value = f16_bits_to_f32(bits);
+            match value.is_nan() {
+                true => assert!(f16_bits_to_f32(f32_to_f16_bits(value)).is_nan()),
+                false => assert_eq!(f32_to_f16_bits(value), bits, "{:#06x} ({})", bits, value)
+            };
+        }
+
+        // Halfway between neighbouring values rounds to the even one, and anything either side
+        // of halfway rounds to the nearer; the last pair is the largest finite value and infinity
+        for bits in 0..0x7bffu16 {
+            let halfway = (f16_bits_to_f32(bits) + f16_bits_to_f32(bits + 1)) / 2.0;
+            let even = match bits & 1 { 0 => bits, _ => bits + 1 };
+            assert_eq!(f32_to_f16_bits(halfway), even, "{:#06x}", bits);
+            assert_eq!(f32_to_f16_bits(f32::from_bits(halfway.to_bits() - 1)), bits);
+            assert_eq!(f32_to_f16_bits(f32::from_bits(halfway.to_bits() + 1)), bits + 1);
+            assert_eq!(f32_to_f16_bits(-halfway), even | 0x8000);
+        }
+        assert_eq!(f32_to_f16_bits(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16_bits(65519.996), 0x7bff);
+        assert_eq!(f32_to_f16_bits(65520.0), 0x7c00);
+        assert_eq!(f32_to_f16_bits(1.0e10), 0x7c00);
+        assert_eq!(f32_to_f16_bits(-1.0e10), 0xfc00);
+
+        // Below the smallest subnormal, values flush to a zero of the same sign
+        assert_eq!(f32_to_f16_bits(2.0f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_f16_bits(2.0f32.powi(-25)), 0x0000);
+        assert_eq!(f32_to_f16_bits(1.0e-10), 0x0000);
+        assert_eq!(f32_to_f16_bits(-1.0e-10), 0x8000);
+        assert_eq!(f32_to_f16_bits(f32::from_bits(1)), 0x0000);
+        assert_eq!(f32_to_f16_bits(f32::NAN) & 0x7c00, 0x7c00);
+        assert_ne!(f32_to_f16_bits(f32::NAN) & 0x03ff, 0);
+    }
+
+    #[test]
+    fn truncated_model_files_fail_to_decode() {
+        let vertices = vec![
+            StaticVertex::from_components((0.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0)),
+            StaticVertex::from_components((1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (1.0, 0.0)),
+            StaticVertex::from_components((0.0, 0.0, 1.0), (0.0, 1.0, 0.0), (0.0, 1.0))
+        ];
+        let model = Model::new_from_components(String::from("Triangle"), vertices);
+        let file_path = std::env::temp_dir().join("engine_util_truncated_model_test.mdl");
+        unsafe {
+            model.write_to_binary_file(&file_path).unwrap();
+        }
+        let bytes = std::fs::read(&file_path).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+
+        let (decoded, vertex_count) = decode_model(&bytes).unwrap();
+        assert_eq!(vertex_count, 3);
+        assert_eq!(decoded[1].px, 1.0);
+
+        // Cut anywhere within the header, name or vertices, as a file still being written might
+        // be; older files end after the vertices, so those lengths still decode
+        let vertices_end = 8 + "Triangle".len() + 3 * 32;
+        for length in 0..vertices_end {
+            assert!(decode_model(&bytes[..length]).is_err(), "Decoded {} bytes", length);
+        }
+        assert!(decode_model(&bytes[..vertices_end]).is_ok());
+    }
+
+    #[test]
+    fn exr_images_decode_to_half_float_rgba() {
+        let pixels = vec![
+            0.5, 1.0, 2.0, 1.0,
+            -0.25, 0.0, 100.0, 0.5
+        ];
+        let image = image::Rgba32FImage::from_raw(2, 1, pixels.clone()).unwrap();
+        let mut bytes = std::io::Cursor::new(vec![]);
+        image::DynamicImage::ImageRgba32F(image)
+            .write_to(&mut bytes, image::ImageOutputFormat::OpenExr)
+            .unwrap();
+
+        assert!(matches!(TextureCodec::from_file_name("sky.EXR"), Some(TextureCodec::Exr)));
+        let texture = decode_texture(bytes.get_ref(), TextureCodec::Exr, ImageUsage::Skybox).unwrap();
+        assert_eq!((texture.width, texture.height), (2, 1));
+        assert!(matches!(texture.format, TexturePixelFormat::RgbaF16));
+        let data = &texture.layer_data.as_ref().unwrap()[0];
+        let decoded: Vec<f32> = data.chunks(2)
+            .map(|bytes| f16_bits_to_f32(u16::from_le_bytes([bytes[0], bytes[1]])))
+            .collect();
+        assert_eq!(decoded, pixels);
+
+        assert!(decode_texture(&bytes.get_ref()[..16], TextureCodec::Exr, ImageUsage::Skybox).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+
+use defs::{
+    EngineError,
+    render::{
+        TexturePixelFormat,
+        TextureCreationData,
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig
+    }
+};
+use atlas::{
+    Atlas,
+    AtlasDescriptor,
+    PackOptions,
+    SpriteImage
+};
+use crate::util::decode_texture_from_vfs;
+use crate::vfs::Vfs;
+
+/// Pack a set of named, decoded textures into a single atlas, returning the atlas texture and the
+/// table of where each sprite lies within it. Each texture must have a single layer of RGBA data;
+/// only its base level is used.
+pub fn pack_textures(
+    textures: Vec<(&str, TextureCreationData)>,
+    options: PackOptions
+) -> Result<(TextureCreationData, AtlasDescriptor), EngineError> {
+    let mut images = vec![];
+    for (name, texture) in textures.into_iter() {
+        let data = match (texture.format, texture.layer_data) {
+            (TexturePixelFormat::Rgba, Some(mut layers)) if layers.len() == 1 => layers.remove(0),
+            _ => return Err(EngineError::DecodeError(
+                format!("Sprite must have a single layer of RGBA data: {}", name)))
+        };
+        let base_level_size = (texture.width * texture.height * 4) as usize;
+        images.push(SpriteImage {
+            name: name.to_string(),
+            width: texture.width,
+            height: texture.height,
+            pixels: data[0..base_level_size].to_vec()
+        });
+    }
+    let atlas = atlas::pack(&images, options)
+        .map_err(|e| EngineError::DecodeError(format!("Error packing atlas: {:?}", e)))?;
+    Ok(into_texture(atlas))
+}
+
+/// Decode a set of image files from the VFS and pack them into a single atlas. Each sprite is
+/// named by its file name without the directory or extension, so "ui/button_ok.png" is found in
+/// the table as "button_ok".
+pub fn pack_textures_from_vfs(
+    vfs: &Vfs,
+    paths: Vec<&str>,
+    options: PackOptions
+) -> Result<(TextureCreationData, AtlasDescriptor), EngineError> {
+    let mut textures = vec![];
+    for path in paths.into_iter() {
+        let texture = decode_texture_from_vfs(vfs, path, ImageUsage::TextureSampleOnly)?;
+        textures.push((sprite_name(path), texture));
+    }
+    pack_textures(textures, options)
+}
+
+/// Read an atlas that was packed ahead of time, such as by a build script
+pub fn decode_atlas(atlas_file_bytes: &[u8]) -> Result<(TextureCreationData, AtlasDescriptor), EngineError> {
+    let atlas = Atlas::from_bytes(atlas_file_bytes)
+        .map_err(|e| EngineError::DecodeError(format!("Error reading atlas: {:?}", e)))?;
+    Ok(into_texture(atlas))
+}
+
+/// Read an atlas that was packed ahead of time from the VFS
+pub fn decode_atlas_from_vfs(vfs: &Vfs, path: &str) -> Result<(TextureCreationData, AtlasDescriptor), EngineError> {
+    decode_atlas(&vfs.read(path)?)
+}
+
+/// Make the set of floats for a UI rect showing the named sprite, in the form taken by
+/// map_ui_rects. The position is [left, top, right, bottom].
+pub fn sprite_ui_rect(descriptor: &AtlasDescriptor, name: &str, position: [f32; 4]) -> Result<[f32; 8], EngineError> {
+    let uv = descriptor.uv_rect(name)
+        .ok_or_else(|| EngineError::GeneralError(format!("No sprite in atlas: {}", name)))?;
+    Ok([position[0], position[1], position[2], position[3], uv[0], uv[1], uv[2], uv[3]])
+}
+
+fn sprite_name(path: &str) -> &str {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    match file_name.rfind('.') {
+        Some(0) | None => file_name,
+        Some(dot) => &file_name[0..dot]
+    }
+}
+
+fn into_texture(atlas: Atlas) -> (TextureCreationData, AtlasDescriptor) {
+    let texture = TextureCreationData {
+        layer_data: Some(vec![atlas.pixels]),
+        width: atlas.width,
+        height: atlas.height,
+        format: TexturePixelFormat::Rgba,
+        usage: ImageUsage::TextureSampleOnly,
+        mipmaps: MipmapMode::None,
+        sampler: SamplerConfig::default()
+    };
+    (texture, atlas.descriptor)
+}
+
+/// Test suite
+/// Check that packed sprites are found by name, both straight after packing and after a round trip
+/// through the packed atlas file format
+#[cfg(test)]
+mod test {
+    use crate::util::sprites::{
+        decode_atlas,
+        pack_textures,
+        sprite_name,
+        sprite_ui_rect
+    };
+    use atlas::{
+        Atlas,
+        PackOptions
+    };
+    use defs::render::{
+        TexturePixelFormat,
+        TextureCreationData,
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig
+    };
+
+    fn make_texture(width: u32, height: u32, colour: [u8; 4]) -> TextureCreationData {
+        TextureCreationData {
+            layer_data: Some(vec![colour.repeat((width * height) as usize)]),
+            width,
+            height,
+            format: TexturePixelFormat::Rgba,
+            usage: ImageUsage::TextureSampleOnly,
+            mipmaps: MipmapMode::None,
+            sampler: SamplerConfig::default()
+        }
+    }
+
+    #[test]
+    fn sprites_found_by_name() {
+        let options = PackOptions {
+            power_of_two: false,
+            ..PackOptions::default()
+        };
+        let (texture, descriptor) = pack_textures(vec![
+            ("wide", make_texture(8, 2, [255, 0, 0, 255])),
+            ("square", make_texture(4, 4, [0, 0, 255, 255]))
+        ], options).unwrap();
+
+        for (name, colour) in [("wide", [255, 0, 0, 255]), ("square", [0, 0, 255, 255])] {
+            let uv = descriptor.uv_rect(name).unwrap();
+            let rect = sprite_ui_rect(&descriptor, name, [-1.0, -0.5, 0.5, 1.0]).unwrap();
+            assert_eq!(rect, [-1.0, -0.5, 0.5, 1.0, uv[0], uv[1], uv[2], uv[3]]);
+
+            // Every pixel inside the sprite's rect holds its colour
+            let sprite = descriptor.sprite(name).unwrap();
+            let pixels = &texture.layer_data.as_ref().unwrap()[0];
+            for y in sprite.y..(sprite.y + sprite.height) {
+                for x in sprite.x..(sprite.x + sprite.width) {
+                    let at = ((y * texture.width + x) * 4) as usize;
+                    assert_eq!(pixels[at..(at + 4)], colour);
+                }
+            }
+        }
+        assert!(sprite_ui_rect(&descriptor, "missing", [0.0, 0.0, 1.0, 1.0]).is_err());
+
+        let atlas = Atlas {
+            width: texture.width,
+            height: texture.height,
+            pixels: texture.layer_data.unwrap().remove(0),
+            descriptor: descriptor.clone()
+        };
+        let (_, decoded_descriptor) = decode_atlas(&atlas.to_bytes()).unwrap();
+        assert_eq!(decoded_descriptor.uv_rect("square"), descriptor.uv_rect("square"));
+        assert!(decode_atlas(&[0u8; 8]).is_err());
+
+        assert!(pack_textures(vec![("bad", TextureCreationData {
+            format: TexturePixelFormat::Bc1,
+            ..make_texture(4, 4, [0, 0, 0, 0])
+        })], options).is_err());
+    }
+
+    #[test]
+    fn sprite_names_drop_directory_and_extension() {
+        assert_eq!(sprite_name("ui/button_ok.png"), "button_ok");
+        assert_eq!(sprite_name("ui\\icons\\close.tga"), "close");
+        assert_eq!(sprite_name("banner.v2.png"), "banner.v2");
+        assert_eq!(sprite_name("plain"), "plain");
+        assert_eq!(sprite_name("ui/.hidden"), ".hidden");
+    }
+}
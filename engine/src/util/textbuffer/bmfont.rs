@@ -0,0 +1,333 @@
+
+use defs::EngineError;
+
+/// Magic bytes at the start of a binary descriptor, followed by a version byte
+const BINARY_MAGIC: &[u8] = b"BMF";
+
+/// The only binary descriptor version supported
+const BINARY_VERSION: u8 = 3;
+
+/// Block types within a binary descriptor
+const BLOCK_COMMON: u8 = 2;
+const BLOCK_PAGES: u8 = 3;
+const BLOCK_CHARS: u8 = 4;
+const BLOCK_KERNING_PAIRS: u8 = 5;
+
+/// Sizes of each entry within the binary common, chars and kerning pairs blocks
+const COMMON_BLOCK_SIZE: usize = 15;
+const CHAR_ENTRY_SIZE: usize = 20;
+const KERNING_ENTRY_SIZE: usize = 10;
+
+/// CharDescription struct
+/// Properties of a single glyph, in pixels of its page texture
+pub struct CharDescription {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub advance_x: f32,
+    pub page: u32
+}
+
+/// FontDescription struct
+/// The contents of an AngelCode BMFont descriptor, read from any of its text, XML or binary
+/// variants. Page file names are indexed by page id.
+pub struct FontDescription {
+    pub line_height: f32,
+    pub base: f32,
+    pub scale_w: f32,
+    pub scale_h: f32,
+    pub pages: Vec<String>,
+    pub distance_range: Option<f32>,
+    pub multi_channel: bool,
+    pub chars: Vec<CharDescription>,
+    pub kernings: Vec<(u32, u32, f32)>
+}
+
+/// Record struct
+/// A single line of the text variant, or a single element of the XML variant, with its attributes
+struct Record {
+    tag: String,
+    attributes: Vec<(String, String)>
+}
+
+impl Record {
+
+    /// Get the value of an attribute
+    fn get(&self, key: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Get the value of an attribute as a number
+    fn number(&self, key: &str) -> Result<Option<f32>, EngineError> {
+        match self.get(key) {
+            Some(value) => value.parse::<f32>()
+                .map(Some)
+                .map_err(|_| EngineError::DecodeError(
+                    format!("Bad value in font descriptor: {} {}={}", self.tag, key, value))),
+            None => Ok(None)
+        }
+    }
+
+    /// Get the value of an attribute as a number, failing if it is absent
+    fn required_number(&self, key: &str) -> Result<f32, EngineError> {
+        self.number(key)?
+            .ok_or_else(|| EngineError::DecodeError(
+                format!("Missing value in font descriptor: {} {}", self.tag, key)))
+    }
+}
+
+/// Read a descriptor in any variant, detected from its first bytes
+pub fn parse(bytes: &[u8]) -> Result<FontDescription, EngineError> {
+    if bytes.starts_with(BINARY_MAGIC) {
+        return parse_binary(bytes);
+    }
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| EngineError::DecodeError("Font descriptor is not valid UTF-8".to_string()))?;
+    match text.trim_start_matches('\u{feff}').trim_start().starts_with('<') {
+        true => parse_xml(text),
+        false => parse_text(text)
+    }
+}
+
+/// Read the text variant, where each line is a tag followed by key=value pairs
+pub fn parse_text(text: &str) -> Result<FontDescription, EngineError> {
+    let records = text.lines()
+        .filter_map(|line| {
+            let line = line.trim_start_matches('\u{feff}').trim();
+            let tag_end = line.find(char::is_whitespace).unwrap_or(line.len());
+            match tag_end {
+                0 => None,
+                _ => Some(Record {
+                    tag: line[0..tag_end].to_string(),
+                    attributes: parse_attributes(&line[tag_end..])
+                })
+            }
+        })
+        .collect();
+    build_description(records)
+}
+
+/// Read the XML variant, where each element holds the same attributes as a line of the text
+/// variant. Nesting is ignored, as every element needed is identified by its name alone.
+pub fn parse_xml(text: &str) -> Result<FontDescription, EngineError> {
+    let mut records = vec![];
+    let mut remaining = text;
+    while let Some(start) = remaining.find('<') {
+        remaining = &remaining[(start + 1)..];
+        if let Some(comment) = remaining.strip_prefix("!--") {
+            remaining = match comment.find("-->") {
+                Some(end) => &comment[(end + 3)..],
+                None => ""
+            };
+            continue;
+        }
+        let end = remaining.find('>')
+            .ok_or_else(|| EngineError::DecodeError("Unterminated element in font descriptor".to_string()))?;
+        let element = &remaining[0..end];
+        remaining = &remaining[(end + 1)..];
+        if element.starts_with('?') || element.starts_with('!') || element.starts_with('/') {
+            continue;
+        }
+        let element = element.trim_end_matches('/');
+        let tag_end = element.find(char::is_whitespace).unwrap_or(element.len());
+        records.push(Record {
+            tag: element[0..tag_end].to_string(),
+            attributes: parse_attributes(&element[tag_end..])
+                .into_iter()
+                .map(|(k, v)| (k, unescape_xml(&v)))
+                .collect()
+        });
+    }
+    build_description(records)
+}
+
+/// Read the binary variant, version 3, which is a sequence of typed blocks
+pub fn parse_binary(bytes: &[u8]) -> Result<FontDescription, EngineError> {
+    if bytes.len() < 4 || !bytes.starts_with(BINARY_MAGIC) {
+        return Err(EngineError::DecodeError("Not a binary font descriptor".to_string()));
+    }
+    if bytes[3] != BINARY_VERSION {
+        return Err(EngineError::DecodeError(format!("Unsupported binary font descriptor version: {}", bytes[3])));
+    }
+
+    let mut common: Option<&[u8]> = None;
+    let mut pages = vec![];
+    let mut chars = vec![];
+    let mut kernings = vec![];
+    let mut offset = 4;
+    while offset < bytes.len() {
+        if offset + 5 > bytes.len() {
+            return Err(EngineError::DecodeError("Binary font descriptor block header is truncated".to_string()));
+        }
+        let block_type = bytes[offset];
+        let block_size = read_u32(bytes, offset + 1) as usize;
+        let block_start = offset + 5;
+        let block_end = block_start + block_size;
+        if block_end > bytes.len() {
+            return Err(EngineError::DecodeError("Binary font descriptor block is truncated".to_string()));
+        }
+        let block = &bytes[block_start..block_end];
+        match block_type {
+            BLOCK_COMMON if block.len() >= COMMON_BLOCK_SIZE => common = Some(block),
+            BLOCK_COMMON => return Err(EngineError::DecodeError("Binary font descriptor common block is too small".to_string())),
+            BLOCK_PAGES => {
+                pages = block.split(|b| *b == 0)
+                    .filter(|name| !name.is_empty())
+                    .map(|name| String::from_utf8_lossy(name).to_string())
+                    .collect();
+            },
+            BLOCK_CHARS => {
+                chars = block.chunks_exact(CHAR_ENTRY_SIZE)
+                    .map(|entry| CharDescription {
+                        id: read_u32(entry, 0),
+                        x: read_u16(entry, 4) as f32,
+                        y: read_u16(entry, 6) as f32,
+                        width: read_u16(entry, 8) as f32,
+                        height: read_u16(entry, 10) as f32,
+                        offset_x: read_u16(entry, 12) as i16 as f32,
+                        offset_y: read_u16(entry, 14) as i16 as f32,
+                        advance_x: read_u16(entry, 16) as i16 as f32,
+                        page: entry[18] as u32
+                    })
+                    .collect();
+            },
+            BLOCK_KERNING_PAIRS => {
+                kernings = block.chunks_exact(KERNING_ENTRY_SIZE)
+                    .map(|entry| (read_u32(entry, 0), read_u32(entry, 4), read_u16(entry, 8) as i16 as f32))
+                    .collect();
+            },
+            _ => ()
+        }
+        offset = block_end;
+    }
+
+    let common = common
+        .ok_or_else(|| EngineError::DecodeError("Binary font descriptor has no common block".to_string()))?;
+    Ok(FontDescription {
+        line_height: read_u16(common, 0) as f32,
+        base: read_u16(common, 2) as f32,
+        scale_w: read_u16(common, 4) as f32,
+        scale_h: read_u16(common, 6) as f32,
+        pages,
+        distance_range: None,
+        multi_channel: false,
+        chars,
+        kernings
+    })
+}
+
+/// Split the remainder of a line or element into key=value pairs, where values may be quoted
+fn parse_attributes(text: &str) -> Vec<(String, String)> {
+    let mut attributes = vec![];
+    let mut remaining = text.trim_start();
+    while let Some(sign_pos) = remaining.find('=') {
+        let key = remaining[0..sign_pos].trim().to_string();
+        let value_text = remaining[(sign_pos + 1)..].trim_start();
+        let (value, rest) = match value_text.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                let value_end = value_text[1..].find(quote).map(|i| i + 1).unwrap_or(value_text.len());
+                (&value_text[1..value_end], value_text.get((value_end + 1)..).unwrap_or(""))
+            },
+            _ => {
+                let value_end = value_text.find(char::is_whitespace).unwrap_or(value_text.len());
+                (&value_text[0..value_end], &value_text[value_end..])
+            }
+        };
+        attributes.push((key, value.to_string()));
+        remaining = rest.trim_start();
+    }
+    attributes
+}
+
+/// Replace the predefined XML entities in an attribute value
+fn unescape_xml(value: &str) -> String {
+    value.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Gather the records of the text or XML variants into a description
+fn build_description(records: Vec<Record>) -> Result<FontDescription, EngineError> {
+    let mut common: Option<(f32, f32, f32, f32)> = None;
+    let mut pages: Vec<String> = vec![];
+    let mut distance_range: Option<f32> = None;
+    let mut multi_channel = false;
+    let mut chars = vec![];
+    let mut kernings = vec![];
+    for record in records.iter() {
+        match record.tag.as_str() {
+            "common" => {
+                common = Some((
+                    record.required_number("lineHeight")?,
+                    record.required_number("base")?,
+                    record.required_number("scaleW")?,
+                    record.required_number("scaleH")?
+                ));
+            },
+            "page" => {
+                let id = record.required_number("id")? as usize;
+                if id >= pages.len() {
+                    pages.resize(id + 1, String::new());
+                }
+                pages[id] = record.get("file").unwrap_or("").to_string();
+            },
+            "distanceField" => {
+                multi_channel = matches!(record.get("fieldType"), Some("msdf") | Some("mtsdf"));
+                distance_range = record.number("distanceRange")?;
+            },
+            "char" => {
+                chars.push(CharDescription {
+                    id: record.required_number("id")? as u32,
+                    x: record.required_number("x")?,
+                    y: record.required_number("y")?,
+                    width: record.required_number("width")?,
+                    height: record.required_number("height")?,
+                    offset_x: record.number("xoffset")?.unwrap_or(0.0),
+                    offset_y: record.number("yoffset")?.unwrap_or(0.0),
+                    advance_x: record.required_number("xadvance")?,
+                    page: record.number("page")?.unwrap_or(0.0) as u32
+                });
+            },
+            "kerning" => {
+                kernings.push((
+                    record.required_number("first")? as u32,
+                    record.required_number("second")? as u32,
+                    record.required_number("amount")?
+                ));
+            },
+            _ => ()
+        }
+    }
+
+    let (line_height, base, scale_w, scale_h) = common
+        .ok_or_else(|| EngineError::DecodeError("Font descriptor has no common line".to_string()))?;
+    Ok(FontDescription {
+        line_height,
+        base,
+        scale_w,
+        scale_h,
+        pages,
+        distance_range,
+        multi_channel,
+        chars,
+        kernings
+    })
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&bytes[at..(at + 4)]);
+    u32::from_le_bytes(value)
+}
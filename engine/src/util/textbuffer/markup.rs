@@ -0,0 +1,299 @@
+
+use defs::EngineError;
+use model::types::StaticVertex;
+use crate::util::textbuffer::{
+    StyledChar,
+    TextAlignment,
+    TextGenerator,
+    WHITE_TINT,
+    layout_styled_text
+};
+
+/// Colours that may be used by name, either as a tag of their own or as the value of a colour tag
+const NAMED_COLOURS: [(&str, [f32; 3]); 12] = [
+    ("white", [1.0, 1.0, 1.0]),
+    ("black", [0.0, 0.0, 0.0]),
+    ("grey", [0.5, 0.5, 0.5]),
+    ("gray", [0.5, 0.5, 0.5]),
+    ("red", [1.0, 0.0, 0.0]),
+    ("green", [0.0, 1.0, 0.0]),
+    ("blue", [0.0, 0.0, 1.0]),
+    ("yellow", [1.0, 1.0, 0.0]),
+    ("orange", [1.0, 0.5, 0.0]),
+    ("cyan", [0.0, 1.0, 1.0]),
+    ("magenta", [1.0, 0.0, 1.0]),
+    ("purple", [0.5, 0.0, 0.5])
+];
+
+/// MarkupBatch struct
+/// Vertices for every character drawn with one page texture of one font, in the same format as
+/// those from TextGenerator::generate_vertex_buffer, each vertex holding the tint of its span
+pub struct MarkupBatch {
+    pub font: usize,
+    pub page: usize,
+    pub vertices: Vec<StaticVertex>
+}
+
+/// MarkupGenerator struct
+/// Lays out text containing markup tags that change the colour, size and font of spans of it.
+/// Tags are:
+///   <red>...</red>             Any of the named colours, such as white, grey, orange or cyan
+///   <colour=#ff8000>...</colour>  A colour as #rgb, #rrggbb or a name; "color" also works
+///   <scale=1.5>...</scale>     Size relative to the line height
+///   <font=bold>...</font>      One of the fonts added to the generator, by name
+///   </>                        Closes whichever tag was opened most recently
+/// Tags nest, and "<<" stands for a literal "<". Text starts in white, at a scale of 1.0, in the
+/// first font.
+pub struct MarkupGenerator {
+    fonts: Vec<(String, TextGenerator)>
+}
+
+/// SpanStyle struct
+/// The style in effect at some point in the markup
+#[derive(Copy, Clone)]
+struct SpanStyle {
+    font: usize,
+    scale: f32,
+    tint: [f32; 3]
+}
+
+impl MarkupGenerator {
+
+    /// Create a new instance with a single font, used for text outside of any font tag
+    pub fn new(name: &str, default_font: TextGenerator) -> MarkupGenerator {
+        MarkupGenerator {
+            fonts: vec![(name.to_string(), default_font)]
+        }
+    }
+
+    /// Add a font that can be selected by name, returning its index as used in MarkupBatch
+    pub fn add_font(&mut self, name: &str, font: TextGenerator) -> usize {
+        self.fonts.push((name.to_string(), font));
+        self.fonts.len() - 1
+    }
+
+    /// Get a font by the index used in MarkupBatch
+    pub fn font(&self, index: usize) -> Option<&TextGenerator> {
+        self.fonts.get(index).map(|(_, font)| font)
+    }
+
+    /// Generate vertices for marked-up text, laid out as described for
+    /// TextGenerator::generate_vertex_buffer. Characters on the same line share a baseline, and
+    /// each line is as tall as its tallest character. There is a batch for each font and page
+    /// texture that is used, to be drawn with that texture; with the paint colour left white,
+    /// each span is drawn in its own colour.
+    pub fn generate_vertex_buffers(
+        &self,
+        markup: &str,
+        left_top: (f32, f32),
+        box_size: (f32, f32),
+        max_line_height: f32,
+        horizontal_alignment: TextAlignment,
+        vertical_alignment: TextAlignment
+    ) -> Result<Vec<MarkupBatch>, EngineError> {
+        let paragraphs = self.parse(markup)?;
+        let fonts: Vec<&TextGenerator> = self.fonts.iter()
+            .map(|(_, font)| font)
+            .collect();
+        let quads = layout_styled_text(
+            &fonts, &paragraphs, left_top, box_size, max_line_height, horizontal_alignment, vertical_alignment);
+
+        let mut batches: Vec<MarkupBatch> = vec![];
+        for (font_index, font) in fonts.iter().enumerate() {
+            for page in 0..font.page_count() {
+                let vertices: Vec<StaticVertex> = quads.iter()
+                    .filter(|quad| quad.font == font_index && quad.page as usize == page)
+                    .flat_map(|quad| quad.vertices.iter().copied())
+                    .collect();
+                if !vertices.is_empty() {
+                    batches.push(MarkupBatch {
+                        font: font_index,
+                        page,
+                        vertices
+                    });
+                }
+            }
+        }
+        Ok(batches)
+    }
+
+    /// Break marked-up text into paragraphs of styled characters
+    fn parse(&self, markup: &str) -> Result<Vec<Vec<StyledChar>>, EngineError> {
+        let mut paragraphs: Vec<Vec<StyledChar>> = vec![vec![]];
+        let mut open_tags: Vec<(String, SpanStyle)> = vec![];
+        let mut style = SpanStyle {
+            font: 0,
+            scale: 1.0,
+            tint: WHITE_TINT
+        };
+        let mut remaining = markup;
+        while let Some(c) = remaining.chars().next() {
+            remaining = &remaining[c.len_utf8()..];
+            match c {
+                '<' if remaining.starts_with('<') => {
+                    remaining = &remaining[1..];
+                    push_char(&mut paragraphs, '<', &style);
+                },
+                '<' => {
+                    let end = remaining.find('>')
+                        .ok_or_else(|| EngineError::DecodeError(format!("Unterminated markup tag: <{}", remaining)))?;
+                    let tag = remaining[0..end].trim();
+                    remaining = &remaining[(end + 1)..];
+                    match tag.strip_prefix('/') {
+                        Some(closing) => {
+                            let closing = closing.trim();
+                            style = match open_tags.pop() {
+                                Some((name, outer_style)) if closing.is_empty() || closing == name => outer_style,
+                                Some((name, _)) => return Err(EngineError::DecodeError(
+                                    format!("Markup tag </{}> does not match <{}>", closing, name))),
+                                None => return Err(EngineError::DecodeError(
+                                    format!("Markup tag </{}> closes nothing", closing)))
+                            };
+                        },
+                        None => {
+                            let (name, new_style) = self.apply_tag(tag, &style)?;
+                            open_tags.push((name, style));
+                            style = new_style;
+                        }
+                    }
+                },
+                '\n' => paragraphs.push(vec![]),
+                '\r' => (),
+                _ => push_char(&mut paragraphs, c, &style)
+            }
+        }
+        Ok(paragraphs)
+    }
+
+    /// Get the name of an opening tag, by which it may be closed, and the style within it
+    fn apply_tag(&self, tag: &str, style: &SpanStyle) -> Result<(String, SpanStyle), EngineError> {
+        let (name, value) = match tag.find('=') {
+            Some(sign_pos) => (tag[0..sign_pos].trim(), Some(tag[(sign_pos + 1)..].trim().trim_matches('"'))),
+            None => (tag, None)
+        };
+        let mut new_style = *style;
+        match (name, value) {
+            ("colour", Some(value)) | ("color", Some(value)) => {
+                new_style.tint = parse_colour(value)
+                    .ok_or_else(|| EngineError::DecodeError(format!("Bad colour in markup: {}", value)))?;
+            },
+            ("scale", Some(value)) => {
+                new_style.scale = value.parse::<f32>().ok()
+                    .filter(|scale| *scale > 0.0)
+                    .ok_or_else(|| EngineError::DecodeError(format!("Bad scale in markup: {}", value)))?;
+            },
+            ("font", Some(value)) => {
+                new_style.font = self.fonts.iter()
+                    .position(|(font_name, _)| font_name == value)
+                    .ok_or_else(|| EngineError::DecodeError(format!("Unknown font in markup: {}", value)))?;
+            },
+            (name, None) => {
+                new_style.tint = named_colour(name)
+                    .ok_or_else(|| EngineError::DecodeError(format!("Unknown markup tag: <{}>", tag)))?;
+            },
+            _ => return Err(EngineError::DecodeError(format!("Unknown markup tag: <{}>", tag)))
+        }
+        Ok((name.to_string(), new_style))
+    }
+}
+
+fn push_char(paragraphs: &mut [Vec<StyledChar>], c: char, style: &SpanStyle) {
+    if let Some(paragraph) = paragraphs.last_mut() {
+        paragraph.push(StyledChar {
+            c,
+            font: style.font,
+            scale: style.scale,
+            tint: style.tint
+        });
+    }
+}
+
+fn named_colour(name: &str) -> Option<[f32; 3]> {
+    NAMED_COLOURS.iter()
+        .find(|(colour_name, _)| colour_name.eq_ignore_ascii_case(name))
+        .map(|(_, colour)| *colour)
+}
+
+/// Read a colour as #rgb, #rrggbb or one of the named colours
+fn parse_colour(value: &str) -> Option<[f32; 3]> {
+    let hex = match value.strip_prefix('#') {
+        Some(hex) => hex,
+        None => return named_colour(value)
+    };
+    let digits: Vec<u32> = hex.chars()
+        .map(|c| c.to_digit(16))
+        .collect::<Option<Vec<u32>>>()?;
+    let channels = match digits.len() {
+        3 => [digits[0] * 17, digits[1] * 17, digits[2] * 17],
+        6 => [digits[0] * 16 + digits[1], digits[2] * 16 + digits[3], digits[4] * 16 + digits[5]],
+        _ => return None
+    };
+    Some([channels[0] as f32 / 255.0, channels[1] as f32 / 255.0, channels[2] as f32 / 255.0])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::textbuffer::{
+        TextAlignment,
+        TextGenerator,
+        markup::MarkupGenerator,
+        test::TEST_FONT
+    };
+
+    fn generator() -> MarkupGenerator {
+        let mut generator = MarkupGenerator::new("regular", TextGenerator::from_resource(TEST_FONT));
+        generator.add_font("alt", TextGenerator::from_resource(&TEST_FONT.replace("scaleW=100", "scaleW=200")));
+        generator
+    }
+
+    /// Test suite
+    /// Check that colour tags tint the vertices of their span, and that tags nest
+    #[test]
+    fn colour_tags_tint_spans() {
+        let batches = generator().generate_vertex_buffers(
+            "a<red>a<colour=#0f8>a</colour>a</red>a", (0.0, 0.0), (4.0, 1.0), 1.0,
+            TextAlignment::Start, TextAlignment::Start).unwrap();
+        assert_eq!(batches.len(), 1);
+        let tints: Vec<(f32, f32, f32)> = batches[0].vertices.chunks(6)
+            .map(|quad| (quad[0].nx, quad[0].ny, quad[0].nz))
+            .collect();
+        assert_eq!(tints, vec![
+            (1.0, 1.0, 1.0), (1.0, 0.0, 0.0), (0.0, 1.0, 136.0 / 255.0), (1.0, 0.0, 0.0), (1.0, 1.0, 1.0)
+        ]);
+    }
+
+    /// Test suite
+    /// Check that scaled spans share the baseline of the rest of the line, which grows to fit
+    /// them, and that fonts are batched separately
+    #[test]
+    fn scale_and_font_tags() {
+        let batches = generator().generate_vertex_buffers(
+            "a<scale=2>a</scale>\n<font=alt>a</>", (0.0, 0.0), (4.0, 4.0), 1.0,
+            TextAlignment::Start, TextAlignment::Start).unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!((batches[0].font, batches[0].page, batches[0].vertices.len()), (0, 0, 12));
+        assert_eq!((batches[1].font, batches[1].page, batches[1].vertices.len()), (1, 0, 6));
+        let positions: Vec<(f32, f32)> = batches.iter()
+            .flat_map(|batch| batch.vertices.chunks(6).map(|quad| (quad[0].px, quad[0].py)))
+            .collect();
+        let expected = [(0.1, 1.0), (0.7, 0.4), (0.1, 2.2)];
+        for (a, e) in positions.iter().zip(expected.iter()) {
+            assert!((a.0 - e.0).abs() < 1e-5 && (a.1 - e.1).abs() < 1e-5, "{:?}", positions);
+        }
+        assert!((batches[1].vertices[0].tu - 0.05).abs() < 1e-5);
+    }
+
+    /// Test suite
+    /// Check that "<<" is a literal "<", and that bad markup is reported rather than drawn
+    #[test]
+    fn escapes_and_bad_markup() {
+        let generator = generator();
+        let batches = generator.generate_vertex_buffers(
+            "a<<b", (0.0, 0.0), (4.0, 1.0), 1.0, TextAlignment::Start, TextAlignment::Start).unwrap();
+        assert_eq!(batches[0].vertices.len(), 18);
+        for markup in ["<bold>a", "<red>a</blue>", "</red>", "<red", "<font=nope>a", "<scale=-1>a", "<colour=#12>a"].iter() {
+            assert!(generator.generate_vertex_buffers(
+                markup, (0.0, 0.0), (4.0, 1.0), 1.0, TextAlignment::Start, TextAlignment::Start).is_err(), "{}", markup);
+        }
+    }
+}
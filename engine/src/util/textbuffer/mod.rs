@@ -1,14 +1,23 @@
 
+mod bmfont;
+pub mod markup;
+
+use defs::EngineError;
 use model::types::StaticVertex;
 
-/// The number of glyphs stored in the texture file
-const FONT_TEXTURE_GLYPH_COUNT: i32 = 128;
+use std::collections::HashMap;
+
+/// Characters tried, in order, when the font has no glyph for a character: the Unicode
+/// replacement character, the missing glyph that Hiero exports as id 0, and the question mark
+const FALLBACK_CHARS: [u32; 3] = [0xfffd, 0, 0x3f];
 
-/// The size (width and height) of the texture file, in pixels
-const FONT_TEXTURE_SIZE: f32 = 512.0;
+/// Tint stored in the normal of each vertex of text drawn without markup, which leaves the
+/// shader's paint colour unchanged
+const WHITE_TINT: [f32; 3] = [1.0, 1.0, 1.0];
 
 /// Glyph struct
-/// Properties of a glyph, as exported into the description file exported from Hiero
+/// Properties of a glyph, as read from a BMFont description file such as those exported from
+/// Hiero or produced by the fontbake crate, and the page texture that holds it
 #[derive(Copy, Clone)]
 struct Glyph {
     texture_s: f32,
@@ -17,7 +26,8 @@ struct Glyph {
     offset_y: f32,
     width: f32,
     height: f32,
-    advance_x: f32
+    advance_x: f32,
+    page: u32
 }
 
 impl Glyph {
@@ -29,15 +39,17 @@ impl Glyph {
             texture_t: 0.0,
             offset_x: 0.0,
             offset_y: 0.0,
-            width: 1.0,
-            height: 1.0,
-            advance_x: 0.0
+            width: 0.0,
+            height: 0.0,
+            advance_x: 0.0,
+            page: 0
         }
     }
 }
 
 /// TextAlignment enum
 /// The alignment options that can be specified along either the vertical or horizontal axis
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum TextAlignment {
     Start,
     Centre,
@@ -45,147 +57,115 @@ pub enum TextAlignment {
 }
 
 /// TextGenerator struct
-/// The full set of glyphs, and other information, decoded from a description file exported from
-/// Hiero. Can produce vertex buffers for text rendering based on the glyph data that it holds.
+/// The full set of glyphs, and other information, decoded from a BMFont description file. Can
+/// produce vertex buffers for text rendering based on the glyph data that it holds. Glyphs are
+/// looked up by Unicode code point; characters the font does not have are drawn with a fallback
+/// glyph. Fonts may spread their glyphs over several page textures, all of the same size.
 pub struct TextGenerator {
     descent_to_baseline: f32,
     line_height: f32,
-    glyphs: Vec<Glyph>
+    texture_width: f32,
+    texture_height: f32,
+    distance_range: Option<f32>,
+    multi_channel: bool,
+    pages: Vec<String>,
+    glyphs: HashMap<u32, Glyph>,
+    kernings: HashMap<(u32, u32), f32>,
+    fallback_glyph: Glyph
 }
 
 impl TextGenerator {
 
-    /// Constant strings used internally when decoding the description file exported from Hiero
-    const KEY_LINE_INFO: &'static str = "info";
-    const KEY_LINE_COMMON: &'static str = "common";
-    const KEY_LINE_PAGE: &'static str = "page";
-    const KEY_LINE_CHARS: &'static str = "chars";
-    const KEY_LINE_CHAR: &'static str = "char";
-    const KEY_FIELD_LINE_HEIGHT: &'static str = "lineHeight";
-    const KEY_FIELD_LINE_BASE: &'static str = "base";
-    const KEY_FIELD_CHAR_COUNT: &'static str = "count";
-    const KEY_FIELD_ID: &'static str = "id";
-    const KEY_FIELD_X: &'static str = "x";
-    const KEY_FIELD_Y: &'static str = "y";
-    const KEY_FIELD_WIDTH: &'static str = "width";
-    const KEY_FIELD_HEIGHT: &'static str = "height";
-    const KEY_FIELD_OFFSET_X: &'static str = "xoffset";
-    const KEY_FIELD_OFFSET_Y: &'static str = "yoffset";
-    const KEY_FIELD_X_ADVANCE: &'static str = "xadvance";
-
-    /// Build a new instance, initialised fully from file data
+    /// Build a new instance from a BMFont descriptor in the text or XML variant, panicking if it
+    /// cannot be read
     pub fn from_resource(file_data: &str) -> TextGenerator {
-        let mut glyph_set = vec!();
-        glyph_set.resize(FONT_TEXTURE_GLYPH_COUNT as usize, Glyph::new());
-
-        let mut base: Option<i32> = None;
-        let mut line_height: Option<i32> = None;
-        let mut char_count: Option<i32> = None;
-
-        let mut id: Option<i32> = None;
-        let mut x: i32 = 0;
-        let mut y: i32 = 0;
-        let mut width: i32 = 0;
-        let mut height: i32 = 0;
-        let mut offset_x: i32 = 0;
-        let mut offset_y: i32 = 0;
-        let mut x_advance: i32 = 0;
-
-        // Iterate over all lines in file, check first word and handle from there
-        for line in file_data.lines() {
-
-            // Assemble glyph if just read from a line
-            if let Some(pending_id) = id {
-                if pending_id < FONT_TEXTURE_GLYPH_COUNT {
-                    glyph_set[pending_id as usize] = Glyph {
-                        texture_s: x as f32,
-                        texture_t: y as f32,
-                        width: width as f32,
-                        height: height as f32,
-                        offset_x: offset_x as f32,
-                        offset_y: offset_y as f32,
-                        advance_x: x_advance as f32
-                    };
-                }
-                id = None;
-            }
+        Self::from_bytes(file_data.as_bytes()).unwrap()
+    }
 
-            // Read next line
-            let mut word_iter = line.split_whitespace();
-            let line_key = word_iter.next().unwrap();
-            if line_key == Self::KEY_LINE_INFO {
-                // Nothing to get from info line
-            } else if line_key == Self::KEY_LINE_COMMON {
-                // Get line height and base integers from common line
-                while let Some(next_word) = word_iter.next() {
-                    let sign_pos = match next_word.find('=') {
-                        Some(pos) => pos,
-                        None => continue
-                    };
-                    let value = next_word[(sign_pos + 1)..].parse::<i32>().unwrap();
-                    if next_word.starts_with(Self::KEY_FIELD_LINE_HEIGHT) {
-                        line_height = Some(value);
-                    } else if next_word.starts_with(Self::KEY_FIELD_LINE_BASE) {
-                        base = Some(value);
-                    }
-                }
-            } else if line_key == Self::KEY_LINE_PAGE {
-                // Nothing to get from page line
-            } else if line_key == Self::KEY_LINE_CHARS {
-                // Get count integer from chars line
-                while let Some(next_word) = word_iter.next() {
-                    if next_word.starts_with(Self::KEY_FIELD_CHAR_COUNT) {
-                        let sign_pos = match next_word.find('=') {
-                            Some(pos) => pos,
-                            None => continue
-                        };
-                        char_count = Some(
-                            next_word[(sign_pos + 1)..].parse::<i32>().unwrap());
-                    }
-                }
-            } else if line_key == Self::KEY_LINE_CHAR {
-                // Get all fields for this glyph, then add to glyph set
-                while let Some(next_word) = word_iter.next() {
-                    let sign_pos = match next_word.find('=') {
-                        Some(pos) => pos,
-                        None => continue
-                    };
-                    let value = next_word[(sign_pos + 1)..].parse::<i32>().unwrap();
-                    if next_word.starts_with(Self::KEY_FIELD_ID) {
-                        id = Some(value);
-                    } else if next_word.starts_with(Self::KEY_FIELD_WIDTH) {
-                        width = value;
-                    } else if next_word.starts_with(Self::KEY_FIELD_HEIGHT) {
-                        height = value;
-                    } else if next_word.starts_with(Self::KEY_FIELD_OFFSET_X) {
-                        offset_x = value;
-                    } else if next_word.starts_with(Self::KEY_FIELD_OFFSET_Y) {
-                        offset_y = value;
-                    } else if next_word.starts_with(Self::KEY_FIELD_X_ADVANCE) {
-                        x_advance = value;
-                    } else if next_word.starts_with(Self::KEY_FIELD_X) {
-                        x = value;
-                    } else if next_word.starts_with(Self::KEY_FIELD_Y) {
-                        y = value;
-                    }
-                }
-            }
+    /// Build a new instance from a BMFont descriptor in the text, XML or binary variant
+    pub fn from_bytes(file_data: &[u8]) -> Result<TextGenerator, EngineError> {
+        let description = bmfont::parse(file_data)?;
+        let glyphs: HashMap<u32, Glyph> = description.chars.iter()
+            .map(|char| (char.id, Glyph {
+                texture_s: char.x,
+                texture_t: char.y,
+                offset_x: char.offset_x,
+                offset_y: char.offset_y,
+                width: char.width,
+                height: char.height,
+                advance_x: char.advance_x,
+                page: char.page
+            }))
+            .collect();
+        let kernings: HashMap<(u32, u32), f32> = description.kernings.iter()
+            .map(|(first, second, amount)| ((*first, *second), *amount))
+            .collect();
+        let fallback_glyph = FALLBACK_CHARS.iter()
+            .find_map(|id| glyphs.get(id).copied())
+            .unwrap_or_else(Glyph::new);
+        Ok(TextGenerator {
+            descent_to_baseline: description.base,
+            line_height: description.line_height,
+            texture_width: description.scale_w,
+            texture_height: description.scale_h,
+            distance_range: description.distance_range,
+            multi_channel: description.multi_channel,
+            pages: description.pages,
+            glyphs,
+            kernings,
+            fallback_glyph
+        })
+    }
+
+    /// File names of the page textures, indexed by page number
+    pub fn page_files(&self) -> &[String] {
+        &self.pages
+    }
+
+    /// The number of page textures, which is at least one
+    pub fn page_count(&self) -> usize {
+        self.pages.len().max(1)
+    }
+
+    /// The distance range of the glyph texture in texels, if it holds a distance field rather than
+    /// coverage, to be passed to the distance field text shader
+    pub fn distance_range(&self) -> Option<f32> {
+        self.distance_range
+    }
+
+    /// Whether the glyph texture holds a multi-channel distance field
+    pub fn is_multi_channel(&self) -> bool {
+        self.multi_channel
+    }
+
+    /// Get the glyph for a character, or the fallback glyph if the font does not have one
+    fn glyph(&self, c: char) -> Glyph {
+        match self.glyphs.get(&(c as u32)) {
+            Some(glyph) => *glyph,
+            None => self.fallback_glyph
         }
+    }
 
-        match (base, line_height, char_count) {
-            (Some(b), Some(l), Some(_)) => TextGenerator {
-                descent_to_baseline: b as f32,
-                line_height: l as f32,
-                glyphs: glyph_set
-            },
-            _ => panic!()
+    /// Get the adjustment to the advance between a pair of characters, in font pixels
+    fn kerning(&self, first: char, second: char) -> f32 {
+        match self.kernings.get(&(first as u32, second as u32)) {
+            Some(amount) => *amount,
+            None => 0.0
         }
     }
 
     /// Generate data for a vertex buffer. Vertices use the format specified by the
-    /// model::types::StaticVertex struct, and there are 6 vertices per character.
-    /// Text will be drawn from (left, top) in an area sized by (box_width, box_height).
-    /// TODO - This comment does not describe how text fitting is affected by all parameters
+    /// model::types::StaticVertex struct, and there are 6 vertices per character drawn. The
+    /// normal of each vertex holds an RGB tint that the text shaders apply to the paint colour,
+    /// which is white here; see the markup module for text with coloured spans.
+    /// Text will be drawn from (left, top) in an area sized by (box_width, box_height). Each line
+    /// is as high as the lesser of max_line_height and box_height, with glyphs scaled to suit.
+    /// Lines end at newline characters, which are not drawn, and are wrapped at the last space
+    /// before the text would exceed the box width, dropping that space, or within a word if the
+    /// word alone is too wide. Each line is aligned horizontally within the box, ignoring trailing
+    /// spaces, and the lines together are aligned vertically; text that is too tall for the box
+    /// overflows it, from the top, middle or bottom as aligned.
+    /// Characters from every page are included, so this suits fonts with a single page texture.
     pub fn generate_vertex_buffer(
         &self,
         for_text: &str,
@@ -195,120 +175,424 @@ impl TextGenerator {
         horizontal_alignment: TextAlignment,
         vertical_alignment: TextAlignment
     ) -> Vec<StaticVertex> {
+        self.layout_quads(for_text, left_top, box_size, max_line_height, horizontal_alignment, vertical_alignment)
+            .iter()
+            .flat_map(|(_, quad)| quad.iter().copied())
+            .collect()
+    }
 
-        let text_chars: Vec<char> = for_text.chars().collect();
-        let vertex_count = text_chars.len() * 6;
-        let mut vertices: Vec<StaticVertex> = vec![StaticVertex::default(); vertex_count];
-
-        let line_height_units = match box_size.1 < max_line_height {
-            true => box_size.1,
-            false => max_line_height
-        };
-        let units_per_font_pixel = line_height_units / self.line_height as f32;
-
-        // First pass determines how many lines need to be rendered, and how many
-        // characters will be on each of those lines
-        let mut characters_per_line: Vec<usize> = vec![];
-        let mut unit_width_of_line: Vec<f32> = vec![];
-        let mut units_across_this_line: f32 = 0.0;
-        let mut units_in_line_up_to_word_end: f32 = 0.0;
-        let mut current_word_begun_at: usize = 0;
-        let mut units_into_this_word: f32 = 0.0;
-        let mut chars_for_this_line = 0;
-        for (index, c) in for_text.char_indices() {
-            let glyph = self.glyphs[c as usize];
-            let advance = glyph.advance_x as f32 * units_per_font_pixel;
-            units_across_this_line += advance;
-            units_into_this_word += advance;
-            chars_for_this_line += 1;
-            if c == ' ' {
-                units_in_line_up_to_word_end = units_across_this_line - advance;
-                current_word_begun_at = index + 1;
-                units_into_this_word = 0.0;
-            } else if units_across_this_line > box_size.0 {
-                if index - current_word_begun_at + 1 == chars_for_this_line {
-                    characters_per_line.push(index - current_word_begun_at);
-                    current_word_begun_at = index;
-                    chars_for_this_line = 1;
-                    unit_width_of_line.push(units_across_this_line - advance);
-                    units_across_this_line = advance;
-                    units_into_this_word = advance;
-                } else {
-                    let characters_for_next_line = index + 1 - current_word_begun_at;
-                    characters_per_line.push(chars_for_this_line - characters_for_next_line);
-                    chars_for_this_line = characters_for_next_line;
-                    unit_width_of_line.push(units_in_line_up_to_word_end);
-                    units_across_this_line = units_into_this_word;
-                }
+    /// Generate data for one vertex buffer per page texture, laid out the same as by
+    /// generate_vertex_buffer, with each character in the buffer for the page holding its glyph.
+    /// Each buffer is to be drawn with its own page texture.
+    pub fn generate_page_vertex_buffers(
+        &self,
+        for_text: &str,
+        left_top: (f32, f32),
+        box_size: (f32, f32),
+        max_line_height: f32,
+        horizontal_alignment: TextAlignment,
+        vertical_alignment: TextAlignment
+    ) -> Vec<Vec<StaticVertex>> {
+        let mut buffers: Vec<Vec<StaticVertex>> = vec![vec![]; self.page_count()];
+        let quads = self.layout_quads(
+            for_text, left_top, box_size, max_line_height, horizontal_alignment, vertical_alignment);
+        for (page, quad) in quads.iter() {
+            if let Some(buffer) = buffers.get_mut(*page as usize) {
+                buffer.extend_from_slice(quad);
             }
         }
-        if chars_for_this_line > 0 {
-            characters_per_line.push(chars_for_this_line);
-            unit_width_of_line.push(units_across_this_line);
+        buffers
+    }
+
+    /// Lay out text as described for generate_vertex_buffer, giving the page and six vertices of
+    /// each character drawn
+    fn layout_quads(
+        &self,
+        for_text: &str,
+        left_top: (f32, f32),
+        box_size: (f32, f32),
+        max_line_height: f32,
+        horizontal_alignment: TextAlignment,
+        vertical_alignment: TextAlignment
+    ) -> Vec<(u32, [StaticVertex; 6])> {
+        let paragraphs: Vec<Vec<StyledChar>> = for_text.split('\n')
+            .map(|paragraph| paragraph.chars()
+                .filter(|c| *c != '\r')
+                .map(|c| StyledChar { c, font: 0, scale: 1.0, tint: WHITE_TINT })
+                .collect())
+            .collect();
+        layout_styled_text(
+            &[self], &paragraphs, left_top, box_size, max_line_height, horizontal_alignment, vertical_alignment)
+            .into_iter()
+            .map(|quad| (quad.page, quad.vertices))
+            .collect()
+    }
+}
+
+/// StyledChar struct
+/// A character to be laid out, with the index of the font to draw it in, its size relative to the
+/// font's line height, and the RGB tint stored in its vertices
+#[derive(Copy, Clone)]
+struct StyledChar {
+    c: char,
+    font: usize,
+    scale: f32,
+    tint: [f32; 3]
+}
+
+/// StyledQuad struct
+/// The six vertices of a character that has been laid out, and the font and page texture that
+/// its glyph is drawn from
+struct StyledQuad {
+    font: usize,
+    page: u32,
+    vertices: [StaticVertex; 6]
+}
+
+/// Scale from font pixels to layout units for a character
+fn units_per_font_pixel(fonts: &[&TextGenerator], styled: &StyledChar, line_height_units: f32) -> f32 {
+    line_height_units * styled.scale / fonts[styled.font].line_height
+}
+
+/// Adjustment to the advance between two adjacent characters, in layout units; kerning only
+/// applies between characters drawn in the same font at the same size
+fn kerning_units(fonts: &[&TextGenerator], first: &StyledChar, second: &StyledChar, line_height_units: f32) -> f32 {
+    match first.font == second.font && first.scale == second.scale {
+        true => fonts[second.font].kerning(first.c, second.c) *
+            units_per_font_pixel(fonts, second, line_height_units),
+        false => 0.0
+    }
+}
+
+/// Width of a line of characters, including kerning but not trailing spaces
+fn line_width_units(fonts: &[&TextGenerator], line: &[StyledChar], line_height_units: f32) -> f32 {
+    let end = line.iter().rposition(|s| s.c != ' ').map(|index| index + 1).unwrap_or(0);
+    let mut width = 0.0;
+    for (index, styled) in line[0..end].iter().enumerate() {
+        if index > 0 {
+            width += kerning_units(fonts, &line[index - 1], styled, line_height_units);
+        }
+        width += fonts[styled.font].glyph(styled.c).advance_x *
+            units_per_font_pixel(fonts, styled, line_height_units);
+    }
+    width
+}
+
+/// Height of a line, and the distance from its top to its baseline, which are the greatest of
+/// those of the characters on it; an empty line is one unscaled line high
+fn line_metrics(fonts: &[&TextGenerator], line: &[StyledChar], line_height_units: f32) -> (f32, f32) {
+    if line.is_empty() {
+        return (line_height_units, fonts[0].descent_to_baseline * line_height_units / fonts[0].line_height);
+    }
+    line.iter()
+        .map(|styled| {
+            let font = fonts[styled.font];
+            let scale = units_per_font_pixel(fonts, styled, line_height_units);
+            (font.line_height * scale, font.descent_to_baseline * scale)
+        })
+        .fold((0.0, 0.0), |(height, base), (h, b)| (height.max(h), base.max(b)))
+}
+
+/// Lay out paragraphs of styled characters, which may mix fonts and sizes, as described for
+/// TextGenerator::generate_vertex_buffer. Characters on the same line share a baseline, and each
+/// line is as tall as its tallest character.
+fn layout_styled_text(
+    fonts: &[&TextGenerator],
+    paragraphs: &[Vec<StyledChar>],
+    left_top: (f32, f32),
+    box_size: (f32, f32),
+    max_line_height: f32,
+    horizontal_alignment: TextAlignment,
+    vertical_alignment: TextAlignment
+) -> Vec<StyledQuad> {
+
+    let line_height_units = match box_size.1 < max_line_height {
+        true => box_size.1,
+        false => max_line_height
+    };
+
+    // First pass breaks the text into lines, wrapping any that would be too wide
+    let mut lines: Vec<Vec<StyledChar>> = vec![];
+    for paragraph in paragraphs.iter() {
+        let mut line: Vec<StyledChar> = vec![];
+        for styled in paragraph.iter() {
+            line.push(*styled);
+            if styled.c == ' ' || line.len() == 1 || line_width_units(fonts, &line, line_height_units) <= box_size.0 {
+                continue;
+            }
+            let wrap_at_space = line.iter()
+                .rposition(|s| s.c == ' ')
+                .filter(|space_index| line[0..*space_index].iter().any(|s| s.c != ' '));
+            let next_line = match wrap_at_space {
+                Some(space_index) => line.split_off(space_index + 1),
+                None => vec![line.pop().unwrap()]
+            };
+            while line.last().map(|s| s.c) == Some(' ') {
+                line.pop();
+            }
+            lines.push(line);
+            line = next_line;
         }
+        lines.push(line);
+    }
 
-        // Set side margin, horizontal margin depends on supplied alignment
-        let total_text_height_units = characters_per_line.len() as f32 * line_height_units;
-        let margin_y_units: f32 = match vertical_alignment {
+    // Set vertical margin, which depends on supplied alignment
+    let metrics: Vec<(f32, f32)> = lines.iter()
+        .map(|line| line_metrics(fonts, line, line_height_units))
+        .collect();
+    let total_text_height_units: f32 = metrics.iter().map(|(height, _)| height).sum();
+    let margin_y_units: f32 = match vertical_alignment {
+        TextAlignment::Start => 0.0,
+        TextAlignment::Centre => 0.5 * (box_size.1 - total_text_height_units),
+        TextAlignment::End => box_size.1 - total_text_height_units
+    };
+
+    // Place the characters, with horizontal margin set per line
+    let mut quads: Vec<StyledQuad> = vec![];
+    let mut line_top = left_top.1 + margin_y_units;
+    for (line, (line_height, line_base)) in lines.iter().zip(metrics.iter()) {
+        let line_width_units = line_width_units(fonts, line, line_height_units);
+        let margin_x_units: f32 = match horizontal_alignment {
             TextAlignment::Start => 0.0,
-            TextAlignment::Centre => 0.5 * (box_size.1 - total_text_height_units),
-            TextAlignment::End => box_size.1 - total_text_height_units
+            TextAlignment::End => box_size.0 - line_width_units,
+            TextAlignment::Centre => 0.5 * (box_size.0 - line_width_units)
         };
+        let pen_y = line_top + line_base;
+        let mut pen_x = left_top.0 + margin_x_units;
+        for (index, styled) in line.iter().enumerate() {
+            if index > 0 {
+                pen_x += kerning_units(fonts, &line[index - 1], styled, line_height_units);
+            }
+            let font = fonts[styled.font];
+            let glyph = font.glyph(styled.c);
+            let scale = units_per_font_pixel(fonts, styled, line_height_units);
 
-        // Start building the buffer
-        let mut chars_rendered = 0;
-        let mut pen_y =
-            left_top.1 + margin_y_units + self.descent_to_baseline as f32 * units_per_font_pixel;
-        let mut text_index: usize = 0;
-        for (index, chars_on_line) in characters_per_line.iter().enumerate() {
-            let line_width_units = unit_width_of_line[index];
-            let margin_x_units: f32 = match horizontal_alignment {
-                TextAlignment::Start => 0.0,
-                TextAlignment::End => box_size.0 - line_width_units,
-                TextAlignment::Centre => 0.5 * (box_size.0 - line_width_units)
-            };
-            let mut pen_x = left_top.0 + margin_x_units;
-            for _i in 0..(*chars_on_line as i32) {
-                let char = text_chars[text_index];
-                text_index += 1;
-                let glyph = self.glyphs[char as usize];
-
-                let x_min = pen_x + glyph.offset_x as f32 * units_per_font_pixel;
-                let x_max = x_min + glyph.width as f32 * units_per_font_pixel;
-                let y_min = pen_y -
-                    (self.descent_to_baseline - glyph.offset_y) as f32 * units_per_font_pixel;
-                let y_max = y_min + glyph.height as f32 * units_per_font_pixel;
-
-                let s_min = glyph.texture_s as f32 / FONT_TEXTURE_SIZE;
-                let s_max = s_min + glyph.width as f32 / FONT_TEXTURE_SIZE;
-                let t_min = glyph.texture_t as f32 / FONT_TEXTURE_SIZE;
-                let t_max = t_min + glyph.height as f32 / FONT_TEXTURE_SIZE;
-
-                let i = chars_rendered * 6;
-                vertices[i    ] = StaticVertex::from_components(
-                    (x_min, y_min, 0.0), (0.0, 0.0, -1.0), (s_min, t_min)
-                );
-                vertices[i + 1] = StaticVertex::from_components(
-                    (x_min, y_max, 0.0), (0.0, 0.0, -1.0), (s_min, t_max)
-                );
-                vertices[i + 2] = StaticVertex::from_components(
-                    (x_max, y_max, 0.0), (0.0, 0.0, -1.0), (s_max, t_max)
-                );
-                vertices[i + 3] = StaticVertex::from_components(
-                    (x_max, y_max, 0.0), (0.0, 0.0, -1.0), (s_max, t_max)
-                );
-                vertices[i + 4] = StaticVertex::from_components(
-                    (x_max, y_min, 0.0), (0.0, 0.0, -1.0), (s_max, t_min)
-                );
-                vertices[i + 5] = StaticVertex::from_components(
-                    (x_min, y_min, 0.0), (0.0, 0.0, -1.0), (s_min, t_min)
-                );
-
-                pen_x += glyph.advance_x * units_per_font_pixel;
-                chars_rendered += 1;
+            let x_min = pen_x + glyph.offset_x * scale;
+            let x_max = x_min + glyph.width * scale;
+            let y_min = pen_y - (font.descent_to_baseline - glyph.offset_y) * scale;
+            let y_max = y_min + glyph.height * scale;
+
+            let s_min = glyph.texture_s / font.texture_width;
+            let s_max = s_min + glyph.width / font.texture_width;
+            let t_min = glyph.texture_t / font.texture_height;
+            let t_max = t_min + glyph.height / font.texture_height;
+
+            let tint = (styled.tint[0], styled.tint[1], styled.tint[2]);
+            quads.push(StyledQuad {
+                font: styled.font,
+                page: glyph.page,
+                vertices: [
+                    StaticVertex::from_components((x_min, y_min, 0.0), tint, (s_min, t_min)),
+                    StaticVertex::from_components((x_min, y_max, 0.0), tint, (s_min, t_max)),
+                    StaticVertex::from_components((x_max, y_max, 0.0), tint, (s_max, t_max)),
+                    StaticVertex::from_components((x_max, y_max, 0.0), tint, (s_max, t_max)),
+                    StaticVertex::from_components((x_max, y_min, 0.0), tint, (s_max, t_min)),
+                    StaticVertex::from_components((x_min, y_min, 0.0), tint, (s_min, t_min))
+                ]
+            });
+
+            pen_x += glyph.advance_x * scale;
+        }
+        line_top += line_height;
+    }
+    quads
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::textbuffer::{
+        TextAlignment,
+        TextGenerator
+    };
+    use model::types::StaticVertex;
+
+    pub const TEST_FONT: &str = "info face=\"Test\" size=10 bold=0 italic=0
+common lineHeight=10 base=8 scaleW=100 scaleH=50 pages=1 packed=0
+page id=0 file=\"test.png\"
+chars count=4
+char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=5 page=0 chnl=15
+char id=97 x=10 y=0 width=4 height=6 xoffset=1 yoffset=2 xadvance=5 page=0 chnl=15
+char id=98 x=20 y=0 width=4 height=8 xoffset=1 yoffset=0 xadvance=5 page=0 chnl=15
+char id=63 x=30 y=10 width=4 height=8 xoffset=0 yoffset=0 xadvance=6 page=0 chnl=15
+kernings count=1
+kerning first=97 second=98 amount=-2";
+
+    /// Left and top position of each character drawn; with this font and a line height of 1.0,
+    /// 'a' is drawn 0.1 right of and 0.2 below its pen position, and advances by 0.5
+    fn char_positions(vertices: &[StaticVertex]) -> Vec<(f32, f32)> {
+        assert_eq!(vertices.len() % 6, 0);
+        vertices.chunks(6)
+            .map(|quad| (quad[0].px, quad[0].py))
+            .collect()
+    }
+
+    fn assert_positions(actual: Vec<(f32, f32)>, expected: &[(f32, f32)]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a.0 - e.0).abs() < 1e-5 && (a.1 - e.1).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    /// Test suite
+    /// Check that lines wrap at spaces, dropping the space, and within words that are too long
+    #[test]
+    fn text_wraps_at_spaces_then_within_words() {
+        let generator = TextGenerator::from_resource(TEST_FONT);
+        let vertices = generator.generate_vertex_buffer(
+            "aa aa aa", (0.0, 0.0), (1.6, 3.0), 1.0, TextAlignment::Start, TextAlignment::Start);
+        assert_positions(char_positions(&vertices), &[
+            (0.1, 0.2), (0.6, 0.2), (0.1, 1.2), (0.6, 1.2), (0.1, 2.2), (0.6, 2.2)
+        ]);
+
+        let vertices = generator.generate_vertex_buffer(
+            "aaaaa", (0.0, 0.0), (1.2, 3.0), 1.0, TextAlignment::Start, TextAlignment::Start);
+        assert_positions(char_positions(&vertices), &[
+            (0.1, 0.2), (0.6, 0.2), (0.1, 1.2), (0.6, 1.2), (0.1, 2.2)
+        ]);
+    }
+
+    /// Test suite
+    /// Check that lines are aligned within the box, ignoring trailing spaces, and that the line
+    /// height is limited by the box height
+    #[test]
+    fn text_aligns_within_box() {
+        let generator = TextGenerator::from_resource(TEST_FONT);
+        let vertices = generator.generate_vertex_buffer(
+            "a ", (0.0, 0.0), (2.0, 3.0), 1.0, TextAlignment::Centre, TextAlignment::Centre);
+        assert_positions(char_positions(&vertices), &[(0.85, 1.2), (1.25, 1.0)]);
+
+        let vertices = generator.generate_vertex_buffer(
+            "a", (1.0, 1.0), (2.0, 3.0), 1.0, TextAlignment::End, TextAlignment::End);
+        assert_positions(char_positions(&vertices), &[(2.6, 3.2)]);
+
+        let vertices = generator.generate_vertex_buffer(
+            "a", (0.0, 0.0), (2.0, 0.5), 1.0, TextAlignment::Start, TextAlignment::Start);
+        assert_positions(char_positions(&vertices), &[(0.05, 0.1)]);
+    }
+
+    /// Test suite
+    /// Check that kerning pairs adjust the advance, including when measuring lines for alignment
+    #[test]
+    fn kerning_is_applied() {
+        let generator = TextGenerator::from_resource(TEST_FONT);
+        let vertices = generator.generate_vertex_buffer(
+            "ab", (0.0, 0.0), (2.0, 1.0), 1.0, TextAlignment::Start, TextAlignment::Start);
+        assert_positions(char_positions(&vertices), &[(0.1, 0.2), (0.4, 0.0)]);
+
+        let vertices = generator.generate_vertex_buffer(
+            "ba", (0.0, 0.0), (2.0, 1.0), 1.0, TextAlignment::Start, TextAlignment::Start);
+        assert_positions(char_positions(&vertices), &[(0.1, 0.0), (0.6, 0.2)]);
+
+        let vertices = generator.generate_vertex_buffer(
+            "ab", (0.0, 0.0), (2.0, 1.0), 1.0, TextAlignment::End, TextAlignment::Start);
+        assert_positions(char_positions(&vertices), &[(1.3, 0.2), (1.6, 0.0)]);
+    }
+
+    /// Test suite
+    /// Check that newlines start new lines without being drawn, and that characters the font does
+    /// not have use the fallback glyph rather than panicking
+    #[test]
+    fn newlines_and_missing_glyphs() {
+        let generator = TextGenerator::from_resource(TEST_FONT);
+        let vertices = generator.generate_vertex_buffer(
+            "a\r\n\u{e9}\u{1f600}\n\na", (0.0, 0.0), (2.0, 4.0), 1.0, TextAlignment::Start, TextAlignment::Start);
+        assert_positions(char_positions(&vertices), &[(0.1, 0.2), (0.0, 1.0), (0.6, 1.0), (0.1, 3.2)]);
+        assert!((vertices[6].tu - 0.3).abs() < 1e-5 && (vertices[6].tv - 0.2).abs() < 1e-5);
+    }
+
+    /// Test suite
+    /// Check that the XML and binary descriptor variants give the same layout as the text variant
+    #[test]
+    fn xml_and_binary_descriptors_match_text() {
+        let xml_font = "<?xml version=\"1.0\"?>
+<font>
+  <info face=\"Test\" size=\"10\"/>
+  <common lineHeight=\"10\" base=\"8\" scaleW=\"100\" scaleH=\"50\" pages=\"1\" packed=\"0\"/>
+  <pages>
+    <page id=\"0\" file=\"test.png\"/>
+  </pages>
+  <!-- <char id=\"97\" x=\"0\"> is not read from comments -->
+  <chars count=\"4\">
+    <char id=\"32\" x=\"0\" y=\"0\" width=\"0\" height=\"0\" xoffset=\"0\" yoffset=\"0\" xadvance=\"5\" page=\"0\" chnl=\"15\"/>
+    <char id=\"97\" x=\"10\" y=\"0\" width=\"4\" height=\"6\" xoffset=\"1\" yoffset=\"2\" xadvance=\"5\" page=\"0\" chnl=\"15\"/>
+    <char id=\"98\" x=\"20\" y=\"0\" width=\"4\" height=\"8\" xoffset=\"1\" yoffset=\"0\" xadvance=\"5\" page=\"0\" chnl=\"15\"/>
+    <char id=\"63\" x=\"30\" y=\"10\" width=\"4\" height=\"8\" xoffset=\"0\" yoffset=\"0\" xadvance=\"6\" page=\"0\" chnl=\"15\"/>
+  </chars>
+  <kernings count=\"1\">
+    <kerning first=\"97\" second=\"98\" amount=\"-2\"/>
+  </kernings>
+</font>";
+
+        let mut binary_font = b"BMF\x03".to_vec();
+        let mut common = vec![];
+        for value in [10u16, 8, 100, 50, 1].iter() {
+            common.extend_from_slice(&value.to_le_bytes());
+        }
+        common.extend_from_slice(&[0, 0, 0, 0, 0]);
+        push_block(&mut binary_font, 2, &common);
+        push_block(&mut binary_font, 3, b"test.png\0");
+        let mut chars = vec![];
+        for (id, x, y, width, height, offset_x, offset_y, advance) in [
+            (32u32, 0u16, 0u16, 0u16, 0u16, 0i16, 0i16, 5i16),
+            (97, 10, 0, 4, 6, 1, 2, 5),
+            (98, 20, 0, 4, 8, 1, 0, 5),
+            (63, 30, 10, 4, 8, 0, 0, 6)
+        ].iter() {
+            chars.extend_from_slice(&id.to_le_bytes());
+            for value in [*x, *y, *width, *height].iter() {
+                chars.extend_from_slice(&value.to_le_bytes());
+            }
+            for value in [*offset_x, *offset_y, *advance].iter() {
+                chars.extend_from_slice(&value.to_le_bytes());
+            }
+            chars.extend_from_slice(&[0, 15]);
+        }
+        push_block(&mut binary_font, 4, &chars);
+        let mut kernings = vec![];
+        kernings.extend_from_slice(&97u32.to_le_bytes());
+        kernings.extend_from_slice(&98u32.to_le_bytes());
+        kernings.extend_from_slice(&(-2i16).to_le_bytes());
+        push_block(&mut binary_font, 5, &kernings);
+
+        let text = "ab a\n\u{e9}";
+        let expected = TextGenerator::from_resource(TEST_FONT).generate_vertex_buffer(
+            text, (0.0, 0.0), (2.0, 3.0), 1.0, TextAlignment::Centre, TextAlignment::Start);
+        for font in [xml_font.as_bytes(), binary_font.as_slice()].iter() {
+            let generator = TextGenerator::from_bytes(font).unwrap();
+            assert_eq!(generator.page_files(), &["test.png".to_string()]);
+            let vertices = generator.generate_vertex_buffer(
+                text, (0.0, 0.0), (2.0, 3.0), 1.0, TextAlignment::Centre, TextAlignment::Start);
+            assert_positions(char_positions(&vertices), &char_positions(&expected));
+            for (a, e) in vertices.iter().zip(expected.iter()) {
+                assert!((a.tu - e.tu).abs() < 1e-5 && (a.tv - e.tv).abs() < 1e-5);
             }
-            pen_y += line_height_units;
         }
-        vertices
+    }
+
+    /// Test suite
+    /// Check that characters of a multi-page font are split into a buffer per page
+    #[test]
+    fn multi_page_fonts_split_by_page() {
+        let font = TEST_FONT
+            .replace("pages=1", "pages=2")
+            .replace("file=\"test.png\"", "file=\"test 0.png\"\npage id=1 file=\"test 1.png\"")
+            .replace("xadvance=5 page=0 chnl=15\nchar id=63", "xadvance=5 page=1 chnl=15\nchar id=63");
+        let generator = TextGenerator::from_resource(&font);
+        assert_eq!(generator.page_count(), 2);
+        assert_eq!(generator.page_files(), &["test 0.png".to_string(), "test 1.png".to_string()]);
+
+        let buffers = generator.generate_page_vertex_buffers(
+            "aba", (0.0, 0.0), (2.0, 1.0), 1.0, TextAlignment::Start, TextAlignment::Start);
+        assert_eq!(buffers.len(), 2);
+        assert_positions(char_positions(&buffers[0]), &[(0.1, 0.2), (0.9, 0.2)]);
+        assert_positions(char_positions(&buffers[1]), &[(0.4, 0.0)]);
+        assert_eq!(generator.generate_vertex_buffer(
+            "aba", (0.0, 0.0), (2.0, 1.0), 1.0, TextAlignment::Start, TextAlignment::Start).len(), 18);
+    }
+
+    fn push_block(bytes: &mut Vec<u8>, block_type: u8, block: &[u8]) {
+        bytes.push(block_type);
+        bytes.extend_from_slice(&(block.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(block);
     }
 }
@@ -0,0 +1,223 @@
+
+use defs::EngineError;
+use archive::{
+    Archive,
+    normalise_path
+};
+
+use std::path::{Path, PathBuf};
+
+/// VfsSource trait
+/// Something that can be mounted into the virtual file system to supply files by path
+pub trait VfsSource {
+    fn contains(&self, path: &str) -> bool;
+    fn read(&self, path: &str) -> Result<Vec<u8>, EngineError>;
+}
+
+/// DirectorySource struct
+/// Supplies loose files from a directory on disk, with VFS paths resolved relative to its root
+pub struct DirectorySource {
+    root: PathBuf
+}
+
+impl DirectorySource {
+
+    pub fn new(root: &Path) -> DirectorySource {
+        DirectorySource {
+            root: root.to_path_buf()
+        }
+    }
+
+    /// Get the path on disk for a VFS path, refusing any path that climbs out of the root
+    fn resolve(&self, path: &str) -> Result<PathBuf, EngineError> {
+        let mut resolved = self.root.clone();
+        for part in normalise_path(path).split('/') {
+            if part == ".." {
+                return Err(EngineError::GeneralError(format!("VFS path leaves its mounted root: {}", path)));
+            }
+            resolved.push(part);
+        }
+        Ok(resolved)
+    }
+}
+
+impl VfsSource for DirectorySource {
+
+    fn contains(&self, path: &str) -> bool {
+        self.resolve(path).is_ok_and(|file_path| file_path.is_file())
+    }
+
+    fn read(&self, path: &str) -> Result<Vec<u8>, EngineError> {
+        let file_path = self.resolve(path)?;
+        std::fs::read(&file_path)
+            .map_err(|e| EngineError::GeneralError(format!("Error reading file: {:?} - {:?}", file_path, e)))
+    }
+}
+
+/// ArchiveSource struct
+/// Supplies files from a packed archive
+pub struct ArchiveSource {
+    archive: Archive
+}
+
+impl ArchiveSource {
+
+    pub fn new(archive: Archive) -> ArchiveSource {
+        ArchiveSource {
+            archive
+        }
+    }
+}
+
+impl VfsSource for ArchiveSource {
+
+    fn contains(&self, path: &str) -> bool {
+        self.archive.contains(path)
+    }
+
+    fn read(&self, path: &str) -> Result<Vec<u8>, EngineError> {
+        self.archive.read(path)
+            .map_err(|e| EngineError::GeneralError(format!("Error reading archive entry: {} - {:?}", path, e)))
+    }
+}
+
+/// Vfs struct
+/// Virtual file system that resolves asset paths against a stack of mounted sources. Sources
+/// mounted later take priority, so a loose directory mounted after an archive can override
+/// individual files within it.
+#[derive(Default)]
+pub struct Vfs {
+    sources: Vec<Box<dyn VfsSource>>
+}
+
+impl Vfs {
+
+    /// Create a new VFS with nothing mounted
+    pub fn new() -> Vfs {
+        Vfs {
+            sources: vec![]
+        }
+    }
+
+    /// Mount any source, giving it priority over those already mounted
+    pub fn mount(&mut self, source: Box<dyn VfsSource>) {
+        self.sources.push(source);
+    }
+
+    /// Mount a directory of loose files
+    pub fn mount_directory(&mut self, root: &Path) -> Result<(), EngineError> {
+        if !root.is_dir() {
+            return Err(EngineError::GeneralError(format!("Not a directory: {:?}", root)));
+        }
+        self.mount(Box::new(DirectorySource::new(root)));
+        Ok(())
+    }
+
+    /// Mount an archive file, reading entries from disk as they are requested
+    pub fn mount_archive_file(&mut self, file_path: &Path) -> Result<(), EngineError> {
+        let archive = Archive::open(file_path)
+            .map_err(|e| EngineError::GeneralError(format!("Error opening archive: {:?} - {:?}", file_path, e)))?;
+        self.mount(Box::new(ArchiveSource::new(archive)));
+        Ok(())
+    }
+
+    /// Mount an archive that is already held in memory
+    pub fn mount_archive_bytes(&mut self, bytes: Vec<u8>) -> Result<(), EngineError> {
+        let archive = Archive::from_bytes(bytes)
+            .map_err(|e| EngineError::GeneralError(format!("Error reading archive: {:?}", e)))?;
+        self.mount(Box::new(ArchiveSource::new(archive)));
+        Ok(())
+    }
+
+    /// Check whether any mounted source has a file at the path
+    pub fn exists(&self, path: &str) -> bool {
+        self.sources.iter().rev().any(|s| s.contains(path))
+    }
+
+    /// Read a file from the highest-priority source that has it
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, EngineError> {
+        match self.sources.iter().rev().find(|s| s.contains(path)) {
+            Some(source) => source.read(path),
+            None => Err(EngineError::GeneralError(format!("File not found in VFS: {}", path)))
+        }
+    }
+
+    /// Read a file from the VFS as UTF-8 text
+    pub fn read_to_string(&self, path: &str) -> Result<String, EngineError> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|_| EngineError::DecodeError(format!("File is not valid UTF-8: {}", path)))
+    }
+}
+
+/// Test suite
+/// Check that later mounts take priority, that loose files override an archive, and that paths
+/// cannot climb out of a mounted directory
+#[cfg(test)]
+mod test {
+    use crate::vfs::{
+        DirectorySource,
+        Vfs,
+        VfsSource
+    };
+    use archive::{
+        ArchiveWriter,
+        EntryOptions
+    };
+    use std::path::{Path, PathBuf};
+
+    /// Make an empty directory under the system temp directory for one test
+    fn make_temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("shining_vfs_{}_{}", name, std::process::id()));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(root: &Path, path: &str, contents: &str) {
+        let file_path = root.join(path);
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        std::fs::write(file_path, contents).unwrap();
+    }
+
+    #[test]
+    fn later_mounts_and_loose_files_take_priority() {
+        let mut writer = ArchiveWriter::new();
+        writer.add_entry("textures/grass.txt", b"archive grass", EntryOptions::default()).unwrap();
+        writer.add_entry("textures/rock.txt", b"archive rock", EntryOptions::default()).unwrap();
+        let first = make_temp_dir("priority_first");
+        write_file(&first, "textures/rock.txt", "first rock");
+        write_file(&first, "font/font.fnt", "first font");
+        let second = make_temp_dir("priority_second");
+        write_file(&second, "font/font.fnt", "second font");
+
+        let mut vfs = Vfs::new();
+        vfs.mount_archive_bytes(writer.to_bytes()).unwrap();
+        vfs.mount_directory(&first).unwrap();
+        vfs.mount_directory(&second).unwrap();
+        assert_eq!(vfs.read_to_string("textures/grass.txt").unwrap(), "archive grass");
+        assert_eq!(vfs.read_to_string("textures/rock.txt").unwrap(), "first rock");
+        assert_eq!(vfs.read_to_string("./font\\font.fnt").unwrap(), "second font");
+        assert!(!vfs.exists("textures/sand.txt"));
+        assert!(vfs.read("textures/sand.txt").is_err());
+
+        std::fs::remove_dir_all(&first).unwrap();
+        std::fs::remove_dir_all(&second).unwrap();
+    }
+
+    #[test]
+    fn paths_cannot_leave_mounted_root() {
+        let parent = make_temp_dir("escape");
+        write_file(&parent, "secret.txt", "secret");
+        write_file(&parent, "root/inside.txt", "inside");
+        let source = DirectorySource::new(&parent.join("root"));
+
+        assert_eq!(source.read("inside.txt").unwrap(), b"inside");
+        assert!(!source.contains("../secret.txt"));
+        assert!(source.read("../secret.txt").is_err());
+        assert!(source.read("root/../../secret.txt").is_err());
+
+        std::fs::remove_dir_all(&parent).unwrap();
+    }
+}
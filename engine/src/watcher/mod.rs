@@ -0,0 +1,141 @@
+
+use archive::normalise_path;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime
+};
+
+/// ResourceWatcher struct
+/// Polls directories on disk for files that have been added or modified since they were last
+/// seen. Changed files are reported with paths relative to the directory they were found in, so
+/// that they match the paths used to look them up through a VFS with those directories mounted.
+pub struct ResourceWatcher {
+    roots: Vec<PathBuf>,
+    poll_interval_millis: u64,
+    millis_since_poll: u64,
+    modified_times: HashMap<PathBuf, SystemTime>
+}
+
+impl ResourceWatcher {
+
+    /// Create a new instance with no directories watched, which will poll at the given interval
+    pub fn new(poll_interval_millis: u64) -> ResourceWatcher {
+        ResourceWatcher {
+            roots: vec![],
+            poll_interval_millis,
+            millis_since_poll: 0,
+            modified_times: HashMap::new()
+        }
+    }
+
+    /// Start watching a directory, and everything below it. Files already present are recorded
+    /// so that only subsequent changes are reported.
+    pub fn watch_directory(&mut self, root: &Path) {
+        let root = root.to_path_buf();
+        let mut current = HashMap::new();
+        scan_directory(&root, &mut current);
+        self.modified_times.extend(current);
+        self.roots.push(root);
+    }
+
+    /// Advance the poll timer, and if the poll interval has elapsed, scan all watched directories
+    /// and return the relative paths of any files that have changed
+    pub fn on_time_elapsed(&mut self, time_step_millis: u64) -> Vec<String> {
+        self.millis_since_poll += time_step_millis;
+        if self.millis_since_poll < self.poll_interval_millis {
+            return vec![];
+        }
+        self.millis_since_poll = 0;
+        self.poll()
+    }
+
+    /// Scan all watched directories now, returning the relative paths of any changed files
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut changed_paths = vec![];
+        for root in self.roots.iter() {
+            let mut current = HashMap::new();
+            scan_directory(root, &mut current);
+            for (file_path, modified_time) in current.into_iter() {
+                let changed = match self.modified_times.get(&file_path) {
+                    Some(previous_time) => *previous_time != modified_time,
+                    None => true
+                };
+                if !changed {
+                    continue;
+                }
+                if let Ok(relative_path) = file_path.strip_prefix(root) {
+                    if let Some(relative_path) = relative_path.to_str() {
+                        changed_paths.push(normalise_path(relative_path));
+                    }
+                }
+                self.modified_times.insert(file_path, modified_time);
+            }
+        }
+        changed_paths.sort();
+        changed_paths.dedup();
+        changed_paths
+    }
+}
+
+/// Recursively record the modification time of every file in a directory; anything that cannot
+/// be read is skipped, as it may be mid-way through being written
+fn scan_directory(dir: &Path, modified_times: &mut HashMap<PathBuf, SystemTime>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue
+        };
+        if metadata.is_dir() {
+            scan_directory(&path, modified_times);
+        } else if let Ok(modified_time) = metadata.modified() {
+            modified_times.insert(path, modified_time);
+        }
+    }
+}
+
+/// Test suite
+/// Check that new and modified files are each reported once, relative to their watched directory,
+/// and only once the poll interval has passed
+#[cfg(test)]
+mod test {
+    use crate::watcher::ResourceWatcher;
+    use std::{
+        fs::File,
+        time::{Duration, SystemTime}
+    };
+
+    #[test]
+    fn changed_files_reported_once_after_interval() {
+        let root = std::env::temp_dir().join(format!("shining_watcher_{}", std::process::id()));
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(root.join("textures")).unwrap();
+        std::fs::write(root.join("textures").join("grass.png"), b"grass").unwrap();
+        std::fs::write(root.join("unchanged.txt"), b"unchanged").unwrap();
+
+        let mut watcher = ResourceWatcher::new(100);
+        watcher.watch_directory(&root);
+        assert!(watcher.on_time_elapsed(100).is_empty());
+
+        // Set the modified time explicitly, as the file system may only store whole seconds
+        std::fs::write(root.join("textures").join("grass.png"), b"new grass").unwrap();
+        File::options().write(true).open(root.join("textures").join("grass.png")).unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        std::fs::create_dir_all(root.join("models")).unwrap();
+        std::fs::write(root.join("models").join("tree.mdl"), b"tree").unwrap();
+
+        assert!(watcher.on_time_elapsed(60).is_empty());
+        assert_eq!(watcher.on_time_elapsed(60), vec!["models/tree.mdl", "textures/grass.png"]);
+        assert!(watcher.on_time_elapsed(100).is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
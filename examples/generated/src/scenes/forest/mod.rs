@@ -32,7 +32,7 @@ impl SceneUpdates for ForestScene {
         let pv_matrix = p_matrix * v_matrix;
 
         self.ubo_compose_skybox.matrix = pv_matrix;
-        self.ubo_compose_terrain.matrix = pv_matrix;
+        self.update_node_matrices(pv_matrix);
         self.ubo_compose_text_overlay.camera_matrix = Matrix4::identity();
         self.ubo_compose_text_overlay.paint_color = Vector4 { x: 1.0, y: rand::random(), z: 0.0, w: 1.0 };
     }
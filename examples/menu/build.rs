@@ -0,0 +1,91 @@
+
+use atlas::{PackOptions, SpriteImage};
+use fontbake::{BakeOptions, bake_font_file};
+
+use std::{
+    env,
+    path::{Path, PathBuf}
+};
+
+/// Colours of the parts of the menus' UI skin
+const MENU_SKIN_COLOURS: [(&str, [u8; 4]); 10] = [
+    ("panel", [48, 24, 24, 208]),
+    ("button", [128, 40, 40, 255]),
+    ("button_hover", [168, 64, 56, 255]),
+    ("button_pressed", [88, 28, 28, 255]),
+    ("checkbox_off", [32, 16, 16, 255]),
+    ("checkbox_on", [232, 184, 72, 255]),
+    ("slider_track", [32, 16, 16, 255]),
+    ("slider_handle", [232, 184, 72, 255]),
+    ("text_field", [24, 12, 12, 255]),
+    ("focus", [255, 255, 255, 48])
+];
+
+/// Size in pixels of each sprite of the menus' UI skin
+const MENU_SKIN_SPRITE_SIZE: u32 = 8;
+
+/// Build script
+///
+/// Packs the menus' UI skin, along with banners cut out of the menu texture, into an atlas file,
+/// which the application includes and looks sprites up in by name. Also bakes the menu font from
+/// its TrueType file into the BMFont description and texture that the text generator reads.
+fn main() {
+    let resources_dir = {
+        let mut dir = std::env::current_dir().unwrap();
+        dir.pop();
+        dir.push("resources");
+        dir
+    };
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    pack_menu_atlas(&resources_dir.join("textures"), &out_dir);
+
+    // Written as DejaVuSans_64.fnt and DejaVuSans_64.png
+    let font_file = resources_dir.join("ttf").join("DejaVuSans.ttf");
+    bake_font_file(&font_file, &out_dir, "DejaVuSans", &[64], &BakeOptions::default()).unwrap();
+    println!("cargo:rerun-if-changed={}", font_file.to_str().unwrap());
+}
+
+fn pack_menu_atlas(textures_dir: &Path, out_dir: &Path) {
+    let menu_texture_file = textures_dir.join("menu_texture.png");
+    let menu_texture = image::open(&menu_texture_file).unwrap().to_rgba8();
+
+    // The banners are drawn from the top quarter of the menu texture, one of them upside down
+    let banner_height = menu_texture.height() / 4;
+    let banner = image::imageops::crop_imm(&menu_texture, 0, 0, menu_texture.width(), banner_height)
+        .to_image();
+    let flipped_banner = image::imageops::flip_vertical(&banner);
+    let mut sprites = vec![
+        SpriteImage {
+            name: String::from("menu_banner"),
+            width: banner.width(),
+            height: banner.height(),
+            pixels: banner.into_raw()
+        },
+        SpriteImage {
+            name: String::from("menu_banner_flipped"),
+            width: flipped_banner.width(),
+            height: flipped_banner.height(),
+            pixels: flipped_banner.into_raw()
+        }
+    ];
+
+    // Each part of the UI skin, named as the engine's UI looks them up, is a block of flat colour
+    for (name, colour) in MENU_SKIN_COLOURS.iter() {
+        sprites.push(SpriteImage {
+            name: name.to_string(),
+            width: MENU_SKIN_SPRITE_SIZE,
+            height: MENU_SKIN_SPRITE_SIZE,
+            pixels: colour.repeat((MENU_SKIN_SPRITE_SIZE * MENU_SKIN_SPRITE_SIZE) as usize)
+        });
+    }
+
+    let options = PackOptions {
+        power_of_two: false,
+        ..PackOptions::default()
+    };
+    atlas::pack(&sprites, options).unwrap()
+        .write_to_file(&out_dir.join("menu.atlas"))
+        .unwrap();
+    println!("cargo:rerun-if-changed={}", menu_texture_file.to_str().unwrap());
+}
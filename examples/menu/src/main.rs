@@ -1,4 +1,5 @@
 
+mod resources;
 mod start;
 mod submenu;
 
@@ -0,0 +1,54 @@
+
+use defs::render::{
+    ImageUsage,
+    TextureCreationData
+};
+use engine::{
+    ui::{
+        Ui,
+        UiStyle,
+        layout::UiRect,
+        skin::UiSkin
+    },
+    util::{
+        TextureCodec,
+        decode_texture,
+        sprites::decode_atlas,
+        textbuffer::TextGenerator
+    }
+};
+
+/// The menus' atlas, packed by the build script, holding the UI skin and banner sprites
+const MENU_ATLAS_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/menu.atlas"));
+
+/// The menu font, baked from its TrueType file by the build script
+pub const MENU_FONT_DESCRIPTION: &str = include_str!(concat!(env!("OUT_DIR"), "/DejaVuSans_64.fnt"));
+const MENU_FONT_TEXTURE_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/DejaVuSans_64.png"));
+
+/// The most vertices that each of the UI's dynamic VBOs can hold
+pub const UI_VERTEX_CAPACITY: usize = 6 * 1024;
+
+/// Create a UI covering the screen and skinned from the menus' atlas
+pub fn make_menu_ui() -> Ui {
+    let (_, descriptor) = decode_atlas(MENU_ATLAS_BYTES).unwrap();
+    Ui::new(
+        UiSkin::from_atlas(&descriptor).unwrap(),
+        TextGenerator::from_resource(MENU_FONT_DESCRIPTION),
+        UiStyle::default(),
+        UiRect::new(-1.0, -1.0, 1.0, 1.0))
+}
+
+/// Decode the texture of the menus' atlas, which UI rects are drawn with
+pub fn decode_menu_atlas_texture() -> TextureCreationData {
+    let (texture, _) = decode_atlas(MENU_ATLAS_BYTES).unwrap();
+    texture
+}
+
+/// Decode the texture of the menu font
+pub fn decode_menu_font_texture() -> TextureCreationData {
+    decode_texture(
+        MENU_FONT_TEXTURE_BYTES,
+        TextureCodec::Png,
+        ImageUsage::TextureSampleOnly)
+        .unwrap()
+}
@@ -11,22 +11,29 @@ use defs::{
         FramebufferTarget,
         Shader,
         VertexFormat,
-        VboCreationData,
         TextureCreationData,
-        ResourcePreloads,
-        ImageUsage
+        ResourcePreloads
     },
     ubo::*
 };
-use engine::util::{
-    TextureCodec,
-    decode_texture,
-    map_ui_rects,
-    textbuffer::{
-        TextGenerator,
-        TextAlignment
+use engine::{
+    control::null::NullControl,
+    ui::{
+        Ui,
+        UiOutput,
+        layout::{
+            Anchor,
+            StackDirection
+        }
     }
 };
+use model::types::StaticVertex;
+use crate::resources::{
+    UI_VERTEX_CAPACITY,
+    make_menu_ui,
+    decode_menu_atlas_texture,
+    decode_menu_font_texture
+};
 use cgmath::{
     Matrix4,
     Vector4,
@@ -34,37 +41,68 @@ use cgmath::{
 };
 use std::collections::HashMap;
 
-const MENU_TEXTURE_BYTES: &[u8] = include_bytes!("../../resources/textures/menu_texture.png");
-const MUSICA_FONT_BYTES: &[u8] = include_bytes!("../../resources/textures/Musica.png");
+const VBO_INDEX_UI_RECTS: usize = 0;
+const VBO_INDEX_UI_TEXT: usize = 1;
 
-const VBO_INDEX_BG: usize = 0;
-const VBO_INDEX_HUD: usize = 1;
-
-const TEXTURE_INDEX_BG: usize = 0;
+const TEXTURE_INDEX_UI_SKIN: usize = 0;
 const TEXTURE_INDEX_FONT: usize = 1;
 
 pub struct StartMenuScene {
-    text_generator: TextGenerator,
     camera_ubo: CameraUbo,
     text_paint_ubo: TextPaintUbo,
-    frame_counter: usize
+    ui: Ui,
+    ui_output: UiOutput,
+    ui_changed: bool
 }
 
 impl Default for StartMenuScene {
     fn default() -> StartMenuScene {
-        StartMenuScene {
-            text_generator: TextGenerator::from_resource(
-                include_str!("../../resources/font/Musica.fnt")
-            ),
+        let mut scene = StartMenuScene {
             camera_ubo: CameraUbo {
                 camera_matrix: Matrix4::identity()
             },
             text_paint_ubo: TextPaintUbo {
                 camera_matrix: Matrix4::identity(),
-                paint_color: Vector4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 }
+                paint_color: Vector4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 },
+                outline_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                shadow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                glow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                shadow_offset: [0.0, 0.0],
+                outline_width: 0.0,
+                glow_width: 0.0,
+                distance_range: 0.0,
+                multi_channel: 0.0,
+                unused: [0.0, 0.0]
+            },
+            ui: make_menu_ui(),
+            ui_output: UiOutput {
+                rect_vertices: vec![],
+                text_vertices: vec![]
             },
-            frame_counter: 0
+            ui_changed: false
+        };
+        scene.build_ui(&NullControl::default());
+        scene
+    }
+}
+
+impl StartMenuScene {
+
+    /// Show the start menu for this frame, returning whether the settings button was clicked
+    fn build_ui(&mut self, controller: &dyn Control) -> bool {
+        self.ui.begin_frame(controller);
+        self.ui.image("menu_banner", Anchor::Top, (2.0, 0.5), (0.0, 0.0)).unwrap();
+        self.ui.image("menu_banner_flipped", Anchor::Bottom, (2.0, 0.5), (0.0, 0.0)).unwrap();
+        self.ui.label("Ey, mate");
+        self.ui.begin_panel(Anchor::Centre, (0.6, 0.18), (0.0, 0.0), StackDirection::Vertical);
+        let settings_clicked = self.ui.button("settings", "Settings");
+        self.ui.end();
+        let output = self.ui.end_frame();
+        if output != self.ui_output {
+            self.ui_output = output;
+            self.ui_changed = true;
         }
+        settings_clicked
     }
 }
 
@@ -73,58 +111,18 @@ impl Scene for StartMenuScene {}
 impl SceneInfo for StartMenuScene {
 
     fn make_preloads(&self) -> ResourcePreloads {
-
-        let (menu_model_data, menu_vertex_count) = {
-            let float_data = map_ui_rects(vec![
-                [-1.0, -1.0, 1.0, -0.5, 0.0, 0.0, 1.0, 0.25],
-                [-1.0, 0.5, 1.0, 1.0, 0.0, 0.25, 1.0, 0.0]
-            ]);
-            let vertex_count = float_data.len();
-            (float_data, vertex_count)
-        };
-
-        let hud_data = self.text_generator.generate_vertex_buffer(
-            "Ey, mate",
-            (-1.0, -1.0),
-            (2.0, 1.0),
-            0.125,
-            TextAlignment::Start,
-            TextAlignment::Start);
-        let hud_data_vertex_count = hud_data.len();
-
-        let mut vbo_loads = HashMap::<usize, VboCreationData>::new();
-        vbo_loads.insert(VBO_INDEX_BG, VboCreationData {
-            vertex_format: VertexFormat::PositionNormalTexture,
-            vertex_data: menu_model_data,
-            vertex_count: menu_vertex_count,
-            draw_indexed: false,
-            index_data: None
-        });
-        vbo_loads.insert(VBO_INDEX_HUD, VboCreationData {
-            vertex_format: VertexFormat::PositionNormalTexture,
-            vertex_data: hud_data,
-            vertex_count: hud_data_vertex_count,
-            draw_indexed: false,
-            index_data: None
-        });
-
-        let menu_texture = decode_texture(
-            MENU_TEXTURE_BYTES,
-            TextureCodec::Png,
-            ImageUsage::TextureSampleOnly)
-            .unwrap();
-        let font_texture = decode_texture(
-            MUSICA_FONT_BYTES,
-            TextureCodec::Png,
-            ImageUsage::TextureSampleOnly)
-            .unwrap();
         let mut texture_loads = HashMap::<usize, TextureCreationData>::new();
-        texture_loads.insert(TEXTURE_INDEX_BG, menu_texture);
-        texture_loads.insert(TEXTURE_INDEX_FONT, font_texture);
+        texture_loads.insert(TEXTURE_INDEX_UI_SKIN, decode_menu_atlas_texture());
+        texture_loads.insert(TEXTURE_INDEX_FONT, decode_menu_font_texture());
 
         ResourcePreloads {
-            vbo_preloads: vbo_loads,
-            texture_preloads: texture_loads
+            vbo_preloads: HashMap::new(),
+            texture_preloads: texture_loads,
+            instance_preloads: HashMap::new(),
+            dynamic_vbo_preloads: self.ui_output.make_dynamic_vbo_preloads(
+                VBO_INDEX_UI_RECTS,
+                VBO_INDEX_UI_TEXT,
+                UI_VERTEX_CAPACITY)
         }
     }
 
@@ -136,21 +134,24 @@ impl SceneInfo for StartMenuScene {
                     steps: vec![
                         DrawingStep {
                             shader: Shader::PlainPnt,
-                            vbo_index: VBO_INDEX_BG,
+                            vbo_index: VBO_INDEX_UI_RECTS,
                             vbo_format: VertexFormat::PositionNormalTexture,
                             draw_indexed: false,
-                            texture_indices: vec![TEXTURE_INDEX_BG],
-                            depth_test: true
+                            texture_indices: vec![TEXTURE_INDEX_UI_SKIN],
+                            depth_test: true,
+                            instance_buffer_index: None
                         },
                         DrawingStep {
                             shader: Shader::Text,
-                            vbo_index: VBO_INDEX_HUD,
+                            vbo_index: VBO_INDEX_UI_TEXT,
                             vbo_format: VertexFormat::PositionNormalTexture,
                             draw_indexed: false,
                             texture_indices: vec![TEXTURE_INDEX_FONT],
-                            depth_test: true
+                            depth_test: true,
+                            instance_buffer_index: None
                         }
-                    ]
+                    ],
+                    reversed_depth: false
                 }
             ]
         }
@@ -180,19 +181,21 @@ impl SceneUpdates for StartMenuScene {
     fn on_time_elapsed(
         &mut self,
         _time_step_millis: u64,
-        _controller: &dyn Control
+        controller: &dyn Control
     ) -> Option<Box<dyn Scene>> {
-        self.frame_counter += 1;
-        if self.frame_counter == 60 {
-            Some(Box::new(crate::submenu::SubMenuScene::new()))
-        } else {
-            None
+        match self.build_ui(controller) {
+            true => Some(Box::new(crate::submenu::SubMenuScene::new())),
+            false => None
         }
     }
 
-    fn on_pre_render(&mut self) {
-        let red: f32 = 1.0;
-        self.text_paint_ubo.paint_color.x = red;
-        self.text_paint_ubo.paint_color.z = 1.0 - red;
+    fn on_pre_render(&mut self) {}
+
+    fn take_dynamic_vbo_updates(&mut self) -> Option<HashMap<usize, Vec<StaticVertex>>> {
+        if !self.ui_changed {
+            return None;
+        }
+        self.ui_changed = false;
+        Some(self.ui_output.dynamic_vbo_updates(VBO_INDEX_UI_RECTS, VBO_INDEX_UI_TEXT))
     }
 }
@@ -19,11 +19,26 @@ use defs::{
 };
 use engine::{
     camera::null::NullCamera,
+    control::null::NullControl,
+    ui::{
+        Ui,
+        UiOutput,
+        layout::{
+            Anchor,
+            StackDirection
+        }
+    },
     util::textbuffer::{
         TextGenerator,
         TextAlignment
     }
 };
+use model::types::StaticVertex;
+use crate::resources::{
+    MENU_FONT_DESCRIPTION,
+    UI_VERTEX_CAPACITY,
+    make_menu_ui
+};
 use cgmath::{
     Matrix4,
     Vector4,
@@ -31,36 +46,101 @@ use cgmath::{
 };
 use std::collections::HashMap;
 
-const VBO_INDEX_BG: usize = 0; // Re-used
 const VBO_INDEX_HUD_SUB: usize = 2;
+const VBO_INDEX_UI_RECTS: usize = 3;
+const VBO_INDEX_UI_TEXT: usize = 4;
 
-const TEXTURE_INDEX_BG: usize = 0; // Re-used
+const TEXTURE_INDEX_UI_SKIN: usize = 0; // Re-used
 const TEXTURE_INDEX_FONT: usize = 1; // Re-used
 
+/// The longest player name that may be typed, so that the UI's vertices stay within capacity
+const MAX_PLAYER_NAME_LENGTH: usize = 24;
+
 pub struct SubMenuScene {
     camera: NullCamera,
     text_generator: TextGenerator,
     camera_ubo: CameraUbo,
-    text_paint_ubo: TextPaintUbo
+    text_paint_ubo: TextPaintUbo,
+    ui_paint_ubo: TextPaintUbo,
+    ui: Ui,
+    ui_output: UiOutput,
+    ui_changed: bool,
+    sound_on: bool,
+    volume: f32,
+    player_name: String
 }
 
 impl Scene for SubMenuScene {}
 
 impl SubMenuScene {
     pub fn new() -> SubMenuScene {
-        SubMenuScene {
+        let mut scene = SubMenuScene {
             camera: NullCamera::default(),
-            text_generator: TextGenerator::from_resource(
-                include_str!("../../resources/font/Musica.fnt")
-            ),
+            text_generator: TextGenerator::from_resource(MENU_FONT_DESCRIPTION),
             camera_ubo: CameraUbo {
                 camera_matrix: Matrix4::identity()
             },
             text_paint_ubo: TextPaintUbo {
                 camera_matrix: Matrix4::identity(),
-                paint_color: Vector4 { x: 0.0, y: 1.0, z: 0.0, w: 1.0 }
-            }
+                paint_color: Vector4 { x: 0.0, y: 1.0, z: 0.0, w: 1.0 },
+                outline_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                shadow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                glow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                shadow_offset: [0.0, 0.0],
+                outline_width: 0.0,
+                glow_width: 0.0,
+                distance_range: 0.0,
+                multi_channel: 0.0,
+                unused: [0.0, 0.0]
+            },
+            ui_paint_ubo: TextPaintUbo {
+                camera_matrix: Matrix4::identity(),
+                paint_color: Vector4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 },
+                outline_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                shadow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                glow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                shadow_offset: [0.0, 0.0],
+                outline_width: 0.0,
+                glow_width: 0.0,
+                distance_range: 0.0,
+                multi_channel: 0.0,
+                unused: [0.0, 0.0]
+            },
+            ui: make_menu_ui(),
+            ui_output: UiOutput {
+                rect_vertices: vec![],
+                text_vertices: vec![]
+            },
+            ui_changed: false,
+            sound_on: true,
+            volume: 0.5,
+            player_name: String::from("Player")
+        };
+        scene.build_ui(&NullControl::default());
+        scene
+    }
+
+    /// Show the settings panel for this frame, returning whether the back button was clicked
+    fn build_ui(&mut self, controller: &dyn Control) -> bool {
+        self.ui.begin_frame(controller);
+        self.ui.image("menu_banner", Anchor::Top, (2.0, 0.5), (0.0, 0.0)).unwrap();
+        self.ui.begin_panel(Anchor::Bottom, (1.2, 0.74), (0.0, 0.1), StackDirection::Vertical);
+        self.ui.label("Settings");
+        self.ui.checkbox("sound", "Sound", &mut self.sound_on);
+        self.ui.slider("volume", &mut self.volume, 0.0, 1.0);
+        if self.ui.text_field("name", &mut self.player_name) {
+            self.player_name = self.player_name.chars().take(MAX_PLAYER_NAME_LENGTH).collect();
+        }
+        self.ui.begin_group(0.1, StackDirection::Horizontal);
+        let back_clicked = self.ui.button("back", "Back");
+        self.ui.end();
+        self.ui.end();
+        let output = self.ui.end_frame();
+        if output != self.ui_output {
+            self.ui_output = output;
+            self.ui_changed = true;
         }
+        back_clicked
     }
 }
 
@@ -87,7 +167,12 @@ impl SceneInfo for SubMenuScene {
 
         ResourcePreloads {
             vbo_preloads: vbo_loads,
-            texture_preloads: HashMap::new()
+            texture_preloads: HashMap::new(),
+            instance_preloads: HashMap::new(),
+            dynamic_vbo_preloads: self.ui_output.make_dynamic_vbo_preloads(
+                VBO_INDEX_UI_RECTS,
+                VBO_INDEX_UI_TEXT,
+                UI_VERTEX_CAPACITY)
         }
     }
 
@@ -100,11 +185,12 @@ impl SceneInfo for SubMenuScene {
                     steps: vec![
                         DrawingStep {
                             shader: Shader::PlainPnt,
-                            vbo_index: VBO_INDEX_BG,
+                            vbo_index: VBO_INDEX_UI_RECTS,
                             vbo_format: VertexFormat::PositionNormalTexture,
                             draw_indexed: false,
-                            texture_indices: vec![TEXTURE_INDEX_BG],
-                            depth_test: true
+                            texture_indices: vec![TEXTURE_INDEX_UI_SKIN],
+                            depth_test: true,
+                            instance_buffer_index: None
                         },
                         DrawingStep {
                             shader: Shader::Text,
@@ -112,9 +198,20 @@ impl SceneInfo for SubMenuScene {
                             vbo_format: VertexFormat::PositionNormalTexture,
                             draw_indexed: false,
                             texture_indices: vec![TEXTURE_INDEX_FONT],
-                            depth_test: true
+                            depth_test: true,
+                            instance_buffer_index: None
+                        },
+                        DrawingStep {
+                            shader: Shader::Text,
+                            vbo_index: VBO_INDEX_UI_TEXT,
+                            vbo_format: VertexFormat::PositionNormalTexture,
+                            draw_indexed: false,
+                            texture_indices: vec![TEXTURE_INDEX_FONT],
+                            depth_test: true,
+                            instance_buffer_index: None
                         }
-                    ]
+                    ],
+                    reversed_depth: false
                 }
             ]
         }
@@ -132,6 +229,9 @@ impl SceneInfo for SubMenuScene {
             (0, 1) => (
                 &self.text_paint_ubo as *const TextPaintUbo as *const u8,
                 std::mem::size_of::<TextPaintUbo>()),
+            (0, 2) => (
+                &self.ui_paint_ubo as *const TextPaintUbo as *const u8,
+                std::mem::size_of::<TextPaintUbo>()),
             _ => panic!("Cannot get UBO for SubMenuScene")
         }
     }
@@ -146,10 +246,21 @@ impl SceneUpdates for SubMenuScene {
     fn on_time_elapsed(
         &mut self,
         _time_step_millis: u64,
-        _controller: &dyn Control
+        controller: &dyn Control
     ) -> Option<Box<dyn Scene>> {
-        None
+        match self.build_ui(controller) {
+            true => Some(Box::new(crate::start::StartMenuScene::default())),
+            false => None
+        }
     }
 
     fn on_pre_render(&mut self) {}
+
+    fn take_dynamic_vbo_updates(&mut self) -> Option<HashMap<usize, Vec<StaticVertex>>> {
+        if !self.ui_changed {
+            return None;
+        }
+        self.ui_changed = false;
+        Some(self.ui_output.dynamic_vbo_updates(VBO_INDEX_UI_RECTS, VBO_INDEX_UI_TEXT))
+    }
 }
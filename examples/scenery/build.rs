@@ -1,4 +1,5 @@
 
+use archive::{ArchiveWriter, Compression, EntryOptions};
 use collada::COLLADA;
 use collada::config::Config;
 use model::factory::FromFile;
@@ -13,7 +14,8 @@ use std::{
 /// Build script
 ///
 /// Reads Collada files (*.dae) from the ./resources/models/ directory and writes the model data to
-/// a custom binary format.
+/// a custom binary format. The models, textures and fonts are then packed into a single archive
+/// that the application reads at runtime.
 fn main() {
     let collada_models_dir = {
         let mut dir = std::env::current_dir().unwrap();
@@ -32,6 +34,39 @@ fn main() {
     };
 
     convert_collada_files_in_directory(&collada_models_dir, &binary_models_dir);
+
+    let resources_dir = {
+        let mut dir = std::env::current_dir().unwrap();
+        dir.pop();
+        dir.push("resources");
+        dir
+    };
+    let archive_file = {
+        let mut file = PathBuf::from(env::var("OUT_DIR").unwrap());
+        file.push("assets.pak");
+        file
+    };
+    let mut writer = ArchiveWriter::new();
+    add_files_in_directory(&mut writer, &binary_models_dir, "models", Compression::Deflate);
+    add_files_in_directory(&mut writer, &resources_dir.join("textures"), "textures", Compression::None);
+    add_files_in_directory(&mut writer, &resources_dir.join("font"), "font", Compression::Deflate);
+    writer.write_to_file(&archive_file).unwrap();
+    println!("cargo:rerun-if-changed={}", resources_dir.to_str().unwrap());
+}
+
+fn add_files_in_directory(writer: &mut ArchiveWriter, dir: &Path, prefix: &str, compression: Compression) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if !path.is_file() {
+            continue;
+        }
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        let options = EntryOptions {
+            compression,
+            ..EntryOptions::default()
+        };
+        writer.add_file(&format!("{}/{}", prefix, file_name), &path, options).unwrap();
+    }
 }
 
 fn convert_collada_files_in_directory(collada_models_dir: &Path, binary_models_dir: &Path) {
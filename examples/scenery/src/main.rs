@@ -1,15 +1,23 @@
 
 mod scene;
 
-use scene::SceneryScene;
+use scene::{
+    SceneryScene,
+    RESOURCES_DIR,
+    BUILT_RESOURCES_DIR
+};
 
 use platform_windows::PlatformWindows;
 use renderer::vk_renderer::VkRenderer;
 use engine::Engine;
 use defs::render::FeatureDeclaration;
 
+use std::path::Path;
+
 const APP_TITLE: &str = "Scenery Example";
 
+const RESOURCE_POLL_INTERVAL_MILLIS: u64 = 500;
+
 fn main() {
 
     let mut platform = PlatformWindows::new_window(APP_TITLE)
@@ -18,9 +26,18 @@ fn main() {
             std::process::exit(1);
         });
 
-    let engine: Engine<VkRenderer> = Engine::new_uninitialised(
-        Box::from(SceneryScene::new()),
+    let scene = SceneryScene::new()
+        .unwrap_or_else(|e| {
+            println!("Error loading the scene: {:?}", e);
+            std::process::exit(1);
+        });
+    let mut engine: Engine<VkRenderer> = Engine::new_uninitialised(
+        Box::from(scene),
         vec![FeatureDeclaration::ClipPlanes]);
+    if cfg!(debug_assertions) {
+        engine.watch_resources(Path::new(RESOURCES_DIR), RESOURCE_POLL_INTERVAL_MILLIS);
+        engine.watch_resources(Path::new(BUILT_RESOURCES_DIR), RESOURCE_POLL_INTERVAL_MILLIS);
+    }
 
     platform.run(engine)
         .unwrap_or_else(|e| {
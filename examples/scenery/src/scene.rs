@@ -1,5 +1,6 @@
 
 use defs::{
+    EngineError,
     Camera,
     SceneInfo,
     SceneUpdates,
@@ -15,6 +16,8 @@ use defs::{
         FramebufferCreationData,
         TexturePixelFormat,
         ImageUsage,
+        MipmapMode,
+        SamplerConfig,
         DrawingDescription,
         DrawingPass,
         DrawingStep
@@ -23,32 +26,61 @@ use defs::{
 };
 use engine::{
     camera::player::PlayerCamera,
+    ecs::{
+        Schedule,
+        World,
+        render::{
+            Material,
+            Mesh
+        },
+        scene::SceneWorld
+    },
+    scene_graph::Transform,
     util::{
-        TextureCodec,
-        decode_texture,
-        decode_texture_array,
+        decode_texture_from_vfs,
+        decode_texture_array_from_vfs,
         make_skybox_vertices,
-        decode_model,
+        decode_model_from_vfs,
         textbuffer::{
             TextGenerator,
             TextAlignment
         }
-    }
+    },
+    vfs::Vfs
 };
 
 use cgmath::{Matrix4, Vector4, SquareMatrix};
-use std::collections::HashMap;
-
-const MENU_MODEL_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/models/SceneTerrain.mdl"));
-const RIVER_MODEL_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/models/River.mdl"));
-const TERRAIN_TEXTURE_BYTES: &[u8] = include_bytes!("../../resources/textures/simple_outdoor_texture.jpg");
-const MUSICA_FONT_BYTES: &[u8] = include_bytes!("../../resources/textures/Musica.png");
-const SKYBOX_LEFT_BYTES: &[u8] = include_bytes!("../../resources/textures/bluecloud_lf.jpg");
-const SKYBOX_RIGHT_BYTES: &[u8] = include_bytes!("../../resources/textures/bluecloud_rt.jpg");
-const SKYBOX_UP_BYTES: &[u8] = include_bytes!("../../resources/textures/bluecloud_up.jpg");
-const SKYBOX_DOWN_BYTES: &[u8] = include_bytes!("../../resources/textures/bluecloud_dn.jpg");
-const SKYBOX_FRONT_BYTES: &[u8] = include_bytes!("../../resources/textures/bluecloud_ft.jpg");
-const SKYBOX_BACK_BYTES: &[u8] = include_bytes!("../../resources/textures/bluecloud_bk.jpg");
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf}
+};
+
+/// File name of the asset archive, which the build script writes to its output directory
+const ASSET_ARCHIVE_NAME: &str = "assets.pak";
+
+/// Directory holding the resource source files, which are mounted over the archive and watched
+/// for changes in debug builds
+pub const RESOURCES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../resources");
+
+/// Directory that the build script writes converted models into, under models/. Models reload
+/// when these converted files change, which only happens when the build script runs again; the
+/// Collada sources under resources/models/ are not converted at runtime, so editing one needs a
+/// cargo build before the running scene picks it up.
+pub const BUILT_RESOURCES_DIR: &str = env!("OUT_DIR");
+
+const SCENE_MODEL_PATH: &str = "models/SceneTerrain.mdl";
+const RIVER_MODEL_PATH: &str = "models/River.mdl";
+const TERRAIN_TEXTURE_PATH: &str = "textures/simple_outdoor_texture.jpg";
+const FONT_TEXTURE_PATH: &str = "textures/Musica.png";
+const FONT_DESCRIPTION_PATH: &str = "font/Musica.fnt";
+const SKYBOX_TEXTURE_PATHS: [&str; 6] = [
+    "textures/bluecloud_lf.jpg",
+    "textures/bluecloud_rt.jpg",
+    "textures/bluecloud_dn.jpg",
+    "textures/bluecloud_up.jpg",
+    "textures/bluecloud_ft.jpg",
+    "textures/bluecloud_bk.jpg"
+];
 
 const VBO_INDEX_SCENE: usize = 0;
 const VBO_INDEX_RIVER: usize = 1;
@@ -65,29 +97,82 @@ const TEXTURE_INDEX_SKYBOX: usize = 6;
 
 const OFFSCREEN_RENDER_SIZE: u32 = 1024;
 
+/// Find the asset archive beside the executable or in the working directory, where a packaged
+/// build would keep it, or failing that in the build script's output directory
+fn find_asset_archive() -> Result<PathBuf, EngineError> {
+    let beside_executable = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(ASSET_ARCHIVE_NAME)));
+    beside_executable.into_iter()
+        .chain(std::iter::once(PathBuf::from(ASSET_ARCHIVE_NAME)))
+        .chain(std::iter::once(Path::new(env!("OUT_DIR")).join(ASSET_ARCHIVE_NAME)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| EngineError::GeneralError(format!("Could not find {} beside the executable, in the working directory or in the build output", ASSET_ARCHIVE_NAME)))
+}
+
 pub struct SceneryScene {
+    vfs: Vfs,
     camera: PlayerCamera,
     text_generator: TextGenerator,
     skybox_reflection_pass_ubo: MvpClippingUbo,
     terrain_reflection_pass_ubo: MvpClippingUbo,
     skybox_pass_ubo: MvpUbo,
-    terrain_pass_ubo: MvpUbo,
-    river_pass_ubo: MvpUbo,
-    text_paint_ubo: TextPaintUbo
+    text_paint_ubo: TextPaintUbo,
+    world: SceneWorld
+}
+
+/// Create the world holding the terrain and the river, which are drawn in the main pass
+fn make_world() -> World {
+    let mut world = World::new();
+    let terrain = world.spawn();
+    world.insert(terrain, Transform::default());
+    world.insert(terrain, Mesh {
+        vbo_index: VBO_INDEX_SCENE,
+        vbo_format: VertexFormat::PositionNormalTexture,
+        draw_indexed: false
+    });
+    world.insert(terrain, Material {
+        shader: Shader::PlainPnt,
+        texture_indices: vec![TEXTURE_INDEX_TERRAIN],
+        depth_test: true
+    });
+    let river = world.spawn();
+    world.insert(river, Transform::default());
+    world.insert(river, Mesh {
+        vbo_index: VBO_INDEX_RIVER,
+        vbo_format: VertexFormat::PositionNormalTexture,
+        draw_indexed: false
+    });
+
+    // TODO - One reflection texture per swapchain image
+    world.insert(river, Material {
+        shader: Shader::Water,
+        texture_indices: vec![TEXTURE_INDEX_REFLECTION_COLOR],
+        depth_test: true
+    });
+    world
 }
 
 impl SceneryScene {
-    pub fn new() -> SceneryScene {
-        SceneryScene {
+    /// Create the scene, mounting its resources; fails if the asset archive can't be found or the
+    /// font description in it doesn't parse
+    pub fn new() -> Result<SceneryScene, EngineError> {
+        let mut vfs = Vfs::new();
+        vfs.mount_archive_file(&find_asset_archive()?)?;
+        if cfg!(debug_assertions) {
+            vfs.mount_directory(Path::new(BUILT_RESOURCES_DIR))?;
+            vfs.mount_directory(Path::new(RESOURCES_DIR))?;
+        }
+        let text_generator = TextGenerator::from_bytes(&vfs.read(FONT_DESCRIPTION_PATH)?)?;
+        Ok(SceneryScene {
+            vfs,
             camera: PlayerCamera::new(
                 1.0,
                 10.0,
                 -3.0,
                 -15.0,
                 std::f32::consts::FRAC_PI_6 * 5.0),
-            text_generator: TextGenerator::from_resource(
-                include_str!("../../resources/font/Musica.fnt")
-            ),
+            text_generator,
             skybox_reflection_pass_ubo: MvpClippingUbo {
                 matrix: Matrix4::identity(),
                 y_bias: 0.0,
@@ -103,33 +188,42 @@ impl SceneryScene {
             skybox_pass_ubo: MvpUbo {
                 matrix: Matrix4::identity()
             },
-            terrain_pass_ubo: MvpUbo {
-                matrix: Matrix4::identity()
-            },
-            river_pass_ubo: MvpUbo {
-                matrix: Matrix4::identity()
-            },
             text_paint_ubo: TextPaintUbo {
                 camera_matrix: Matrix4::identity(),
-                paint_color: Vector4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 }
-            }
-        }
+                paint_color: Vector4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 },
+                outline_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                shadow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                glow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                shadow_offset: [0.0, 0.0],
+                outline_width: 0.0,
+                glow_width: 0.0,
+                distance_range: 0.0,
+                multi_channel: 0.0,
+                unused: [0.0, 0.0]
+            },
+            world: SceneWorld::new(make_world(), Schedule::new())
+        })
     }
-}
 
-impl Scene for SceneryScene {}
-
-impl SceneInfo for SceneryScene {
-
-    fn make_preloads(&self) -> ResourcePreloads {
+    /// Index in the main pass of the HUD step, which follows the world's steps
+    fn hud_step_index(&self) -> usize {
+        1 + self.world.step_count()
+    }
 
-        let (scene_model_data, scene_vertex_count) =
-            decode_model(MENU_MODEL_BYTES);
-        let (river_model_data, river_vertex_count) =
-            decode_model(RIVER_MODEL_BYTES);
-        let (cube_vertex_data, cube_vertex_count) =
-            make_skybox_vertices(20.0);
+    /// Load a model from the VFS into a new VBO description
+    fn load_model_vbo(&self, path: &str) -> Result<VboCreationData, EngineError> {
+        let (vertex_data, vertex_count) = decode_model_from_vfs(&self.vfs, path)?;
+        Ok(VboCreationData {
+            vertex_format: VertexFormat::PositionNormalTexture,
+            vertex_data,
+            vertex_count,
+            draw_indexed: false,
+            index_data: None
+        })
+    }
 
+    /// Generate the VBO description for the HUD text
+    fn make_hud_vbo(&self) -> VboCreationData {
         let hud_data = self.text_generator.generate_vertex_buffer(
             "Ey, mate",
             (-1.0, -1.0),
@@ -138,22 +232,48 @@ impl SceneInfo for SceneryScene {
             TextAlignment::Start,
             TextAlignment::Start);
         let hud_data_vertex_count = hud_data.len();
-
-        let mut vbo_loads = HashMap::<usize, VboCreationData>::new();
-        vbo_loads.insert(VBO_INDEX_SCENE, VboCreationData {
+        VboCreationData {
             vertex_format: VertexFormat::PositionNormalTexture,
-            vertex_data: scene_model_data,
-            vertex_count: scene_vertex_count,
-            draw_indexed: false,
-            index_data: None
-        });
-        vbo_loads.insert(VBO_INDEX_RIVER, VboCreationData {
-            vertex_format: VertexFormat::PositionNormalTexture,
-            vertex_data: river_model_data,
-            vertex_count: river_vertex_count,
+            vertex_data: hud_data,
+            vertex_count: hud_data_vertex_count,
             draw_indexed: false,
             index_data: None
-        });
+        }
+    }
+
+    /// Load the terrain texture from the VFS, with mipmaps and anisotropic filtering so that it
+    /// holds up at a distance
+    fn load_terrain_texture(&self) -> Result<TextureCreationData, EngineError> {
+        let mut texture = decode_texture_from_vfs(
+            &self.vfs,
+            TERRAIN_TEXTURE_PATH,
+            ImageUsage::TextureSampleOnly)?;
+        texture.mipmaps = MipmapMode::GenerateOnGpu;
+        texture.sampler.max_anisotropy = 8.0;
+        Ok(texture)
+    }
+
+    /// Load the six skybox faces from the VFS into a cube texture
+    fn load_skybox_texture(&self) -> Result<TextureCreationData, EngineError> {
+        decode_texture_array_from_vfs(
+            &self.vfs,
+            SKYBOX_TEXTURE_PATHS.to_vec(),
+            ImageUsage::Skybox)
+    }
+}
+
+impl Scene for SceneryScene {}
+
+impl SceneInfo for SceneryScene {
+
+    fn make_preloads(&self) -> ResourcePreloads {
+
+        let (cube_vertex_data, cube_vertex_count) =
+            make_skybox_vertices(20.0);
+
+        let mut vbo_loads = HashMap::<usize, VboCreationData>::new();
+        vbo_loads.insert(VBO_INDEX_SCENE, self.load_model_vbo(SCENE_MODEL_PATH).unwrap());
+        vbo_loads.insert(VBO_INDEX_RIVER, self.load_model_vbo(RIVER_MODEL_PATH).unwrap());
         vbo_loads.insert(VBO_INDEX_SKYBOX, VboCreationData {
             vertex_format: VertexFormat::PositionNormalTexture,
             vertex_data: cube_vertex_data,
@@ -161,36 +281,15 @@ impl SceneInfo for SceneryScene {
             draw_indexed: false,
             index_data: None
         });
-        vbo_loads.insert(VBO_INDEX_HUD, VboCreationData {
-            vertex_format: VertexFormat::PositionNormalTexture,
-            vertex_data: hud_data,
-            vertex_count: hud_data_vertex_count,
-            draw_indexed: false,
-            index_data: None
-        });
+        vbo_loads.insert(VBO_INDEX_HUD, self.make_hud_vbo());
 
-        let scene_texture = decode_texture(
-            TERRAIN_TEXTURE_BYTES,
-            TextureCodec::Jpeg,
-            ImageUsage::TextureSampleOnly)
-            .unwrap();
-        let font_texture = decode_texture(
-            MUSICA_FONT_BYTES,
-            TextureCodec::Png,
+        let scene_texture = self.load_terrain_texture().unwrap();
+        let font_texture = decode_texture_from_vfs(
+            &self.vfs,
+            FONT_TEXTURE_PATH,
             ImageUsage::TextureSampleOnly)
             .unwrap();
-        let skybox_texture = decode_texture_array(
-            vec![
-                SKYBOX_LEFT_BYTES,
-                SKYBOX_RIGHT_BYTES,
-                SKYBOX_DOWN_BYTES,
-                SKYBOX_UP_BYTES,
-                SKYBOX_FRONT_BYTES,
-                SKYBOX_BACK_BYTES
-            ],
-            TextureCodec::Jpeg,
-            ImageUsage::Skybox)
-            .unwrap();
+        let skybox_texture = self.load_skybox_texture().unwrap();
         let mut texture_loads = HashMap::<usize, TextureCreationData>::new();
         texture_loads.insert(TEXTURE_INDEX_TERRAIN, scene_texture);
         texture_loads.insert(TEXTURE_INDEX_FONT, font_texture);
@@ -200,37 +299,68 @@ impl SceneInfo for SceneryScene {
             width: OFFSCREEN_RENDER_SIZE,
             height: OFFSCREEN_RENDER_SIZE,
             format: TexturePixelFormat::Rgba,
-            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth
+            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth,
+            mipmaps: MipmapMode::None,
+            sampler: SamplerConfig::default()
         });
         texture_loads.insert(TEXTURE_INDEX_REFLECTION_DEPTH, TextureCreationData {
             layer_data: None,
             width: OFFSCREEN_RENDER_SIZE,
             height: OFFSCREEN_RENDER_SIZE,
             format: TexturePixelFormat::Unorm16,
-            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth
+            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth,
+            mipmaps: MipmapMode::None,
+            sampler: SamplerConfig::default()
         });
         texture_loads.insert(TEXTURE_INDEX_REFRACTION_COLOR, TextureCreationData {
             layer_data: None,
             width: OFFSCREEN_RENDER_SIZE,
             height: OFFSCREEN_RENDER_SIZE,
             format: TexturePixelFormat::Rgba,
-            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth
+            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth,
+            mipmaps: MipmapMode::None,
+            sampler: SamplerConfig::default()
         });
         texture_loads.insert(TEXTURE_INDEX_REFRACTION_DEPTH, TextureCreationData {
             layer_data: None,
             width: OFFSCREEN_RENDER_SIZE,
             height: OFFSCREEN_RENDER_SIZE,
             format: TexturePixelFormat::Unorm16,
-            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth
+            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth,
+            mipmaps: MipmapMode::None,
+            sampler: SamplerConfig::default()
         });
 
         ResourcePreloads {
             vbo_preloads: vbo_loads,
-            texture_preloads: texture_loads
+            texture_preloads: texture_loads,
+            instance_preloads: HashMap::new(),
+            dynamic_vbo_preloads: HashMap::new()
         }
     }
 
     fn make_description(&self) -> DrawingDescription {
+        let mut main_steps = vec![
+            DrawingStep {
+                shader: Shader::Cube,
+                vbo_index: VBO_INDEX_SKYBOX,
+                vbo_format: VertexFormat::PositionNormalTexture,
+                draw_indexed: false,
+                texture_indices: vec![TEXTURE_INDEX_SKYBOX],
+                depth_test: false,
+                instance_buffer_index: None
+            }
+        ];
+        main_steps.extend(self.world.make_steps());
+        main_steps.push(DrawingStep {
+            shader: Shader::Text,
+            vbo_index: VBO_INDEX_HUD,
+            vbo_format: VertexFormat::PositionNormalTexture,
+            draw_indexed: false,
+            texture_indices: vec![TEXTURE_INDEX_FONT],
+            depth_test: true,
+            instance_buffer_index: None
+        });
         DrawingDescription {
             passes: vec![
                 DrawingPass {
@@ -249,7 +379,8 @@ impl SceneInfo for SceneryScene {
                             vbo_format: VertexFormat::PositionNormalTexture,
                             draw_indexed: false,
                             texture_indices: vec![TEXTURE_INDEX_SKYBOX],
-                            depth_test: false
+                            depth_test: false,
+                            instance_buffer_index: None
                         },
                         DrawingStep {
                             shader: Shader::PlainPntClipped,
@@ -257,49 +388,16 @@ impl SceneInfo for SceneryScene {
                             vbo_format: VertexFormat::PositionNormalTexture,
                             draw_indexed: false,
                             texture_indices: vec![TEXTURE_INDEX_TERRAIN],
-                            depth_test: true
+                            depth_test: true,
+                            instance_buffer_index: None
                         }
-                    ]
+                    ],
+                    reversed_depth: false
                 },
                 DrawingPass {
                     target: FramebufferTarget::DefaultFramebuffer,
-                    steps: vec![
-                        DrawingStep {
-                            shader: Shader::Cube,
-                            vbo_index: VBO_INDEX_SKYBOX,
-                            vbo_format: VertexFormat::PositionNormalTexture,
-                            draw_indexed: false,
-                            texture_indices: vec![TEXTURE_INDEX_SKYBOX],
-                            depth_test: false
-                        },
-                        DrawingStep {
-                            shader: Shader::PlainPnt,
-                            vbo_index: VBO_INDEX_SCENE,
-                            vbo_format: VertexFormat::PositionNormalTexture,
-                            draw_indexed: false,
-                            texture_indices: vec![TEXTURE_INDEX_TERRAIN],
-                            depth_test: true
-                        },
-                        DrawingStep {
-                            shader: Shader::Water,
-                            vbo_index: VBO_INDEX_RIVER,
-                            vbo_format: VertexFormat::PositionNormalTexture,
-                            draw_indexed: false,
-
-                            // TODO - One of these per swapchain image
-                            texture_indices: vec![TEXTURE_INDEX_REFLECTION_COLOR],
-
-                            depth_test: true
-                        },
-                        DrawingStep {
-                            shader: Shader::Text,
-                            vbo_index: VBO_INDEX_HUD,
-                            vbo_format: VertexFormat::PositionNormalTexture,
-                            draw_indexed: false,
-                            texture_indices: vec![TEXTURE_INDEX_FONT],
-                            depth_test: true
-                        }
-                    ]
+                    steps: main_steps,
+                    reversed_depth: false
                 }
             ]
         }
@@ -320,18 +418,20 @@ impl SceneInfo for SceneryScene {
             (1, 0) => (
                 &self.skybox_pass_ubo as *const MvpUbo as *const u8,
                 std::mem::size_of::<MvpUbo>()),
-            (1, 1) => (
-                &self.terrain_pass_ubo as *const MvpUbo as *const u8,
-                std::mem::size_of::<MvpUbo>()),
-            (1, 2) => (
-                &self.river_pass_ubo as *const MvpUbo as *const u8,
-                std::mem::size_of::<MvpUbo>()),
-            (1, 3) => (
+            (1, i) if i == self.hud_step_index() => (
                 &self.text_paint_ubo as *const TextPaintUbo as *const u8,
                 std::mem::size_of::<TextPaintUbo>()),
+            (1, i) if i < self.hud_step_index() => self.world.get_ubo_data_ptr_and_size(i - 1),
             _ => panic!("Cannot get UBO for SceneryScene")
         }
     }
+
+    fn is_step_visible(&self, pass_index: usize, step_index: usize) -> bool {
+        match (pass_index, step_index) {
+            (1, i) if i > 0 && i < self.hud_step_index() => self.world.is_step_visible(i - 1),
+            _ => true
+        }
+    }
 }
 
 impl SceneUpdates for SceneryScene {
@@ -346,6 +446,8 @@ impl SceneUpdates for SceneryScene {
         controller: &dyn Control
     ) -> Option<Box<dyn Scene>> {
         self.camera.update(time_step_millis, controller);
+        let pv_matrix = self.camera.get_projection_matrix() * self.camera.get_view_matrix();
+        self.world.update(pv_matrix, time_step_millis, controller);
         None
     }
 
@@ -357,10 +459,7 @@ impl SceneUpdates for SceneryScene {
         let pv_matrix = p_matrix * v_matrix;
         let pv_inverted_matrix = p_matrix * v_inverted_matrix;
 
-        self.river_pass_ubo.matrix = pv_matrix;
-
         let red = 0.5 + 0.5 * pv_matrix.x.x;
-        self.terrain_pass_ubo.matrix = pv_matrix;
         self.terrain_reflection_pass_ubo.matrix = pv_inverted_matrix;
         self.text_paint_ubo.paint_color.x = red;
         self.text_paint_ubo.paint_color.z = 1.0 - red;
@@ -374,4 +473,48 @@ impl SceneUpdates for SceneryScene {
         self.skybox_pass_ubo.matrix = p_matrix * v_matrix;
         self.skybox_reflection_pass_ubo.matrix = p_matrix * v_inverted_matrix;
     }
+
+    fn on_resources_changed(&mut self, changed_paths: &[String]) -> Result<Option<ResourcePreloads>, EngineError> {
+        let mut replacements = ResourcePreloads {
+            vbo_preloads: HashMap::new(),
+            texture_preloads: HashMap::new(),
+            instance_preloads: HashMap::new(),
+            dynamic_vbo_preloads: HashMap::new()
+        };
+        let mut text_generator = None;
+        for path in changed_paths.iter() {
+            match path.as_str() {
+                SCENE_MODEL_PATH => {
+                    replacements.vbo_preloads.insert(VBO_INDEX_SCENE, self.load_model_vbo(path)?);
+                },
+                RIVER_MODEL_PATH => {
+                    replacements.vbo_preloads.insert(VBO_INDEX_RIVER, self.load_model_vbo(path)?);
+                },
+                TERRAIN_TEXTURE_PATH => {
+                    replacements.texture_preloads.insert(TEXTURE_INDEX_TERRAIN, self.load_terrain_texture()?);
+                },
+                FONT_TEXTURE_PATH => {
+                    let texture = decode_texture_from_vfs(&self.vfs, path, ImageUsage::TextureSampleOnly)?;
+                    replacements.texture_preloads.insert(TEXTURE_INDEX_FONT, texture);
+                },
+                FONT_DESCRIPTION_PATH => {
+                    text_generator = Some(TextGenerator::from_bytes(&self.vfs.read(path)?)?);
+                },
+                p if SKYBOX_TEXTURE_PATHS.contains(&p) => {
+                    replacements.texture_preloads.insert(TEXTURE_INDEX_SKYBOX, self.load_skybox_texture()?);
+                },
+                _ => {}
+            };
+        }
+
+        // Only switch to a changed font once every other file has decoded
+        if let Some(text_generator) = text_generator {
+            self.text_generator = text_generator;
+            replacements.vbo_preloads.insert(VBO_INDEX_HUD, self.make_hud_vbo());
+        }
+        if replacements.vbo_preloads.is_empty() && replacements.texture_preloads.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(replacements))
+    }
 }
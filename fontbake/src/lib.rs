@@ -0,0 +1,382 @@
+
+mod sdf;
+
+use ab_glyph::{
+    Font,
+    FontRef,
+    PxScale,
+    ScaleFont,
+    point
+};
+use atlas::{
+    PackOptions,
+    SpriteImage
+};
+
+use std::path::Path;
+
+/// BakeError enum
+/// Errors that can occur while baking a font
+#[derive(Debug)]
+pub enum BakeError {
+    IoError(String),
+    BadFont(String),
+    BadOptions(String),
+    PackError(String)
+}
+
+/// BakeOptions struct
+/// Options for baking a font. The size is the height in pixels from the highest ascent to the
+/// lowest descent of the font, and the character ranges are inclusive. Characters that the font
+/// does not have are skipped. If a distance field spread is given, glyphs are baked as a signed
+/// distance field reaching that many pixels either side of each edge, for use with the distance
+/// field text shader; the distance range to give that shader is then twice the spread.
+#[derive(Clone, Debug)]
+pub struct BakeOptions {
+    pub size: f32,
+    pub char_ranges: Vec<(char, char)>,
+    pub padding: u32,
+    pub max_texture_size: u32,
+    pub sdf_spread: Option<u32>
+}
+
+impl Default for BakeOptions {
+    fn default() -> Self {
+        BakeOptions {
+            size: 64.0,
+            char_ranges: vec![(' ', '~')],
+            padding: 2,
+            max_texture_size: 2048,
+            sdf_spread: None
+        }
+    }
+}
+
+/// BakedGlyph struct
+/// Placement and metrics of a single glyph, in pixels, using the same conventions as the BMFont
+/// format; offsets are from the pen position at the top of the line
+#[derive(Copy, Clone, Debug)]
+pub struct BakedGlyph {
+    pub id: u32,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub advance_x: i32
+}
+
+/// BakedFont struct
+/// A font rasterised at a single size; an RGBA texture holding every glyph with its coverage, or
+/// its distance field, in all four channels, as the text shaders sample the red channel, and the
+/// metrics needed to lay out text with it
+pub struct BakedFont {
+    pub size: f32,
+    pub distance_range: Option<u32>,
+    pub line_height: i32,
+    pub base: i32,
+    pub texture_width: u32,
+    pub texture_height: u32,
+    pub pixels: Vec<u8>,
+    pub glyphs: Vec<BakedGlyph>
+}
+
+impl BakedFont {
+
+    /// Produce a description file in the BMFont text format, as read by the engine's
+    /// TextGenerator, referring to the texture by the given file name
+    pub fn to_bmfont_text(&self, face_name: &str, texture_file_name: &str) -> String {
+        let mut text = format!(
+            "info face=\"{}\" size={} bold=0 italic=0 charset=\"\" unicode=1 stretchH=100 smooth=1 aa=1 padding=0,0,0,0 spacing=0,0\n",
+            face_name, self.size.round() as i32);
+        text.push_str(&format!(
+            "common lineHeight={} base={} scaleW={} scaleH={} pages=1 packed=0\n",
+            self.line_height, self.base, self.texture_width, self.texture_height));
+        text.push_str(&format!("page id=0 file=\"{}\"\n", texture_file_name));
+        if let Some(distance_range) = self.distance_range {
+            text.push_str(&format!("distanceField fieldType=sdf distanceRange={}\n", distance_range));
+        }
+        text.push_str(&format!("chars count={}\n", self.glyphs.len()));
+        for glyph in self.glyphs.iter() {
+            text.push_str(&format!(
+                "char id={} x={} y={} width={} height={} xoffset={} yoffset={} xadvance={} page=0 chnl=15\n",
+                glyph.id, glyph.x, glyph.y, glyph.width, glyph.height, glyph.offset_x, glyph.offset_y, glyph.advance_x));
+        }
+        text
+    }
+
+    /// Write the description file and texture into a directory, as <name>.fnt and <name>.png
+    pub fn write_files(&self, output_dir: &Path, name: &str) -> Result<(), BakeError> {
+        let texture_file_name = format!("{}.png", name);
+        let texture_path = output_dir.join(&texture_file_name);
+        image::save_buffer(&texture_path, &self.pixels, self.texture_width, self.texture_height, image::ColorType::Rgba8)
+            .map_err(|e| BakeError::IoError(format!("Error writing file: {:?} - {:?}", texture_path, e)))?;
+        let description_path = output_dir.join(format!("{}.fnt", name));
+        std::fs::write(&description_path, self.to_bmfont_text(name, &texture_file_name))
+            .map_err(|e| BakeError::IoError(format!("Error writing file: {:?} - {:?}", description_path, e)))
+    }
+}
+
+/// Rasterise the given characters of a TrueType or OpenType font into a glyph texture
+pub fn bake_font(font_file_bytes: &[u8], options: &BakeOptions) -> Result<BakedFont, BakeError> {
+    if options.size <= 0.0 {
+        return Err(BakeError::BadOptions(format!("Font size must be positive: {}", options.size)));
+    }
+    let font = FontRef::try_from_slice(font_file_bytes)
+        .map_err(|e| BakeError::BadFont(format!("{:?}", e)))?;
+    let scale = PxScale::from(options.size);
+    let scaled_font = font.as_scaled(scale);
+    let base = scaled_font.ascent().round() as i32;
+    let line_height = (scaled_font.ascent() - scaled_font.descent() + scaled_font.line_gap()).round() as i32;
+
+    // Rasterise every glyph that has an outline; glyphs without one, like the space, only have an
+    // advance and take up no room in the texture
+    let mut glyphs = vec![];
+    let mut images = vec![];
+    let mut image_glyph_indices = vec![];
+    for c in options.char_ranges.iter().flat_map(|(first, last)| *first..=*last) {
+        let glyph_id = font.glyph_id(c);
+        if glyph_id.0 == 0 {
+            continue;
+        }
+        let mut glyph = BakedGlyph {
+            id: c as u32,
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            offset_x: 0,
+            offset_y: 0,
+            advance_x: scaled_font.h_advance(glyph_id).round() as i32
+        };
+        if let Some(spread) = options.sdf_spread {
+            let bitmap = font.outline(glyph_id).and_then(|outline|
+                sdf::render_sdf(&outline, scaled_font.h_scale_factor(), scaled_font.v_scale_factor(), spread));
+            if let Some(bitmap) = bitmap {
+                glyph.width = bitmap.width;
+                glyph.height = bitmap.height;
+                glyph.offset_x = bitmap.left;
+                glyph.offset_y = base + bitmap.top;
+                image_glyph_indices.push(glyphs.len());
+                images.push(SpriteImage {
+                    name: glyph.id.to_string(),
+                    width: bitmap.width,
+                    height: bitmap.height,
+                    pixels: bitmap.pixels
+                });
+            }
+        } else if let Some(outlined) = font.outline_glyph(glyph_id.with_scale_and_position(scale, point(0.0, 0.0))) {
+            let bounds = outlined.px_bounds();
+            let width = bounds.width() as u32;
+            let height = bounds.height() as u32;
+            if width > 0 && height > 0 {
+                let mut pixels = vec![0u8; (width * height * 4) as usize];
+                outlined.draw(|x, y, coverage| {
+                    let index = ((y * width + x) * 4) as usize;
+                    let value = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    pixels[index..(index + 4)].copy_from_slice(&[value; 4]);
+                });
+                glyph.width = width;
+                glyph.height = height;
+                glyph.offset_x = bounds.min.x as i32;
+                glyph.offset_y = base + bounds.min.y as i32;
+                image_glyph_indices.push(glyphs.len());
+                images.push(SpriteImage {
+                    name: glyph.id.to_string(),
+                    width,
+                    height,
+                    pixels
+                });
+            }
+        }
+        glyphs.push(glyph);
+    }
+    if glyphs.is_empty() {
+        return Err(BakeError::BadOptions("The font has none of the requested characters".to_string()));
+    }
+    if images.is_empty() {
+        images.push(SpriteImage {
+            name: "blank".to_string(),
+            width: 1,
+            height: 1,
+            pixels: vec![0; 4]
+        });
+    }
+
+    let pack_options = PackOptions {
+        padding: options.padding,
+        bleed: 0,
+        max_size: options.max_texture_size,
+        power_of_two: true
+    };
+    let atlas = atlas::pack(&images, pack_options)
+        .map_err(|e| BakeError::PackError(format!("{:?}", e)))?;
+    for (image, glyph_index) in images.iter().zip(image_glyph_indices.iter()) {
+        if let Some(rect) = atlas.descriptor.sprite(&image.name) {
+            glyphs[*glyph_index].x = rect.x;
+            glyphs[*glyph_index].y = rect.y;
+        }
+    }
+
+    Ok(BakedFont {
+        size: options.size,
+        distance_range: options.sdf_spread.map(|spread| spread * 2),
+        line_height,
+        base,
+        texture_width: atlas.width,
+        texture_height: atlas.height,
+        pixels: atlas.pixels,
+        glyphs
+    })
+}
+
+/// Rasterise a font file at each of the given sizes, writing the results into a directory. Each
+/// size is written as <name>_<size>.fnt and <name>_<size>.png. Intended for use in build scripts.
+pub fn bake_font_file(
+    font_path: &Path,
+    output_dir: &Path,
+    name: &str,
+    sizes: &[u32],
+    options: &BakeOptions
+) -> Result<(), BakeError> {
+    let font_file_bytes = std::fs::read(font_path)
+        .map_err(|e| BakeError::IoError(format!("Error reading file: {:?} - {:?}", font_path, e)))?;
+    for size in sizes.iter() {
+        let size_options = BakeOptions {
+            size: *size as f32,
+            ..options.clone()
+        };
+        let baked = bake_font(&font_file_bytes, &size_options)?;
+        baked.write_files(output_dir, &format!("{}_{}", name, size))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        BakedFont,
+        BakedGlyph,
+        BakeOptions,
+        bake_font,
+        bake_font_file,
+        sdf::render_sdf
+    };
+    use ab_glyph::{
+        Outline,
+        OutlineCurve,
+        Rect,
+        point
+    };
+
+    /// Test suite
+    /// Check that the description file has the layout the engine's TextGenerator expects
+    #[test]
+    fn bmfont_text_layout() {
+        let baked = BakedFont {
+            size: 32.0,
+            distance_range: None,
+            line_height: 38,
+            base: 30,
+            texture_width: 128,
+            texture_height: 64,
+            pixels: vec![0; 128 * 64 * 4],
+            glyphs: vec![
+                BakedGlyph { id: 32, x: 0, y: 0, width: 0, height: 0, offset_x: 0, offset_y: 0, advance_x: 9 },
+                BakedGlyph { id: 65, x: 2, y: 2, width: 20, height: 22, offset_x: -1, offset_y: 8, advance_x: 19 }
+            ]
+        };
+        let text = baked.to_bmfont_text("Test", "test.png");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("info face=\"Test\" size=32 "));
+        assert_eq!(lines[1], "common lineHeight=38 base=30 scaleW=128 scaleH=64 pages=1 packed=0");
+        assert_eq!(lines[2], "page id=0 file=\"test.png\"");
+        assert_eq!(lines[3], "chars count=2");
+        assert_eq!(lines[5], "char id=65 x=2 y=2 width=20 height=22 xoffset=-1 yoffset=8 xadvance=19 page=0 chnl=15");
+    }
+
+    /// A real TrueType font, the same one the menu example bakes at build time
+    const FIXTURE_FONT_BYTES: &[u8] = include_bytes!("../../examples/resources/ttf/DejaVuSans.ttf");
+
+    /// Test suite
+    /// Check that baking a real font gives every printable ASCII glyph, with the space empty and
+    /// every other glyph holding some coverage within its own rect of the texture
+    #[test]
+    fn bake_fixture_font() {
+        let baked = bake_font(FIXTURE_FONT_BYTES, &BakeOptions { size: 32.0, ..BakeOptions::default() }).unwrap();
+        assert_eq!(baked.glyphs.len(), 95);
+        assert!(baked.line_height >= 32 && baked.base > 0 && baked.base < baked.line_height);
+        assert_eq!(baked.pixels.len(), (baked.texture_width * baked.texture_height * 4) as usize);
+
+        for glyph in baked.glyphs.iter() {
+            assert!(glyph.advance_x > 0);
+            assert!(glyph.x + glyph.width <= baked.texture_width && glyph.y + glyph.height <= baked.texture_height);
+            if glyph.id == ' ' as u32 {
+                assert_eq!((glyph.width, glyph.height), (0, 0));
+                continue;
+            }
+            let covered = (glyph.y..(glyph.y + glyph.height))
+                .flat_map(|y| (glyph.x..(glyph.x + glyph.width)).map(move |x| (x, y)))
+                .any(|(x, y)| baked.pixels[((y * baked.texture_width + x) * 4) as usize] > 128);
+            assert!(covered, "Glyph {} has no coverage", glyph.id);
+        }
+
+        // Capitals sit on the baseline, and descenders reach below it
+        let glyph = |c: char| *baked.glyphs.iter().find(|g| g.id == c as u32).unwrap();
+        let capital = glyph('H');
+        let descender = glyph('g');
+        assert!((capital.offset_y + capital.height as i32 - baked.base).abs() <= 1);
+        assert!(descender.offset_y + descender.height as i32 > baked.base + 2);
+    }
+
+    /// Test suite
+    /// Check that baking a font file writes a description and texture for each size
+    #[test]
+    fn bake_fixture_font_file() {
+        let dir = std::env::temp_dir().join(format!("shining_fontbake_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let font_path = dir.join("fixture.ttf");
+        std::fs::write(&font_path, FIXTURE_FONT_BYTES).unwrap();
+
+        bake_font_file(&font_path, &dir, "fixture", &[16, 24], &BakeOptions::default()).unwrap();
+        for size in [16, 24] {
+            let description = std::fs::read_to_string(dir.join(format!("fixture_{}.fnt", size))).unwrap();
+            assert!(description.starts_with(&format!("info face=\"fixture_{}\" size={} ", size, size)));
+            assert!(description.contains(&format!("page id=0 file=\"fixture_{}.png\"", size)));
+            let texture = image::open(dir.join(format!("fixture_{}.png", size))).unwrap().to_rgba8();
+            assert!(description.contains(&format!("scaleW={} scaleH={} ", texture.width(), texture.height())));
+        }
+        assert!(bake_font_file(&dir.join("missing.ttf"), &dir, "missing", &[16], &BakeOptions::default()).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test suite
+    /// Check that bad input is rejected
+    #[test]
+    fn bad_input_is_rejected() {
+        assert!(bake_font(&[0, 1, 2, 3], &BakeOptions::default()).is_err());
+        assert!(bake_font(&[], &BakeOptions { size: 0.0, ..BakeOptions::default() }).is_err());
+    }
+
+    /// Test suite
+    /// Check that a square outline gives a distance field that is half way on its edges, and
+    /// rises inside and falls outside by the expected amounts
+    #[test]
+    fn square_distance_field() {
+        let corners = [point(0.0, 0.0), point(0.0, 10.0), point(10.0, 10.0), point(10.0, 0.0)];
+        let outline = Outline {
+            bounds: Rect { min: point(0.0, 0.0), max: point(10.0, 10.0) },
+            curves: (0..4).map(|i| OutlineCurve::Line(corners[i], corners[(i + 1) % 4])).collect()
+        };
+        let bitmap = render_sdf(&outline, 1.0, 1.0, 4).unwrap();
+        assert_eq!((bitmap.left, bitmap.top, bitmap.width, bitmap.height), (-4, -14, 18, 18));
+        let value_at = |x: i32, y: i32| bitmap.pixels[(((y - bitmap.top) * bitmap.width as i32 + x - bitmap.left) * 4) as usize];
+        assert_eq!(value_at(5, -5), 255);
+        assert_eq!(value_at(-4, -5), 16);
+        assert!(value_at(0, -5) > 128 && value_at(0, -5) < 160);
+        assert!(value_at(-1, -5) < 128 && value_at(-1, -5) > 96);
+    }
+}
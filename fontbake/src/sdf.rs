@@ -0,0 +1,151 @@
+
+use ab_glyph::{
+    Outline,
+    OutlineCurve,
+    Point
+};
+
+/// The number of line segments that each curve in an outline is split into
+const SEGMENTS_PER_CURVE: u32 = 8;
+
+/// Segment struct
+/// A straight piece of a glyph outline, in pixels, with y increasing downwards
+#[derive(Copy, Clone)]
+struct Segment {
+    start: (f32, f32),
+    end: (f32, f32)
+}
+
+/// SdfBitmap struct
+/// A glyph rendered as a signed distance field, and where its top-left corner lies relative to the
+/// pen position on the baseline
+pub struct SdfBitmap {
+    pub left: i32,
+    pub top: i32,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+
+/// Render an outline as a signed distance field extending the given number of pixels either side
+/// of the edge. Distances are stored in all four channels, with 0.5 on the edge and larger values
+/// inside the glyph. Returns None if the outline has no area.
+pub fn render_sdf(outline: &Outline, h_scale: f32, v_scale: f32, spread: u32) -> Option<SdfBitmap> {
+    let segments = flatten_outline(outline, h_scale, v_scale);
+    if segments.is_empty() {
+        return None;
+    }
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for segment in segments.iter() {
+        for (x, y) in [segment.start, segment.end].iter() {
+            min_x = min_x.min(*x);
+            min_y = min_y.min(*y);
+            max_x = max_x.max(*x);
+            max_y = max_y.max(*y);
+        }
+    }
+    if max_x <= min_x || max_y <= min_y {
+        return None;
+    }
+
+    let left = min_x.floor() as i32 - spread as i32;
+    let top = min_y.floor() as i32 - spread as i32;
+    let width = (max_x.ceil() - min_x.floor()) as u32 + 2 * spread;
+    let height = (max_y.ceil() - min_y.floor()) as u32 + 2 * spread;
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for j in 0..height {
+        for i in 0..width {
+            let point = (left as f32 + i as f32 + 0.5, top as f32 + j as f32 + 0.5);
+            let distance = segments.iter()
+                .map(|segment| distance_to_segment(point, segment))
+                .fold(f32::MAX, f32::min);
+            let signed_distance = match winding_number(point, &segments) {
+                0 => -distance,
+                _ => distance
+            };
+            let value = (0.5 + signed_distance / (2.0 * spread.max(1) as f32)).clamp(0.0, 1.0);
+            let value = (value * 255.0).round() as u8;
+            pixels.extend_from_slice(&[value; 4]);
+        }
+    }
+
+    Some(SdfBitmap {
+        left,
+        top,
+        width,
+        height,
+        pixels
+    })
+}
+
+/// Convert an outline from font units into line segments in pixels
+fn flatten_outline(outline: &Outline, h_scale: f32, v_scale: f32) -> Vec<Segment> {
+    let to_pixels = |p: &Point| (p.x * h_scale, -p.y * v_scale);
+    let mut segments = vec![];
+    for curve in outline.curves.iter() {
+        match curve {
+            OutlineCurve::Line(p0, p1) => segments.push(Segment {
+                start: to_pixels(p0),
+                end: to_pixels(p1)
+            }),
+            OutlineCurve::Quad(p0, p1, p2) => {
+                let (a, b, c) = (to_pixels(p0), to_pixels(p1), to_pixels(p2));
+                push_curve_segments(&mut segments, |t| {
+                    let s = 1.0 - t;
+                    (
+                        s * s * a.0 + 2.0 * s * t * b.0 + t * t * c.0,
+                        s * s * a.1 + 2.0 * s * t * b.1 + t * t * c.1
+                    )
+                });
+            },
+            OutlineCurve::Cubic(p0, p1, p2, p3) => {
+                let (a, b, c, d) = (to_pixels(p0), to_pixels(p1), to_pixels(p2), to_pixels(p3));
+                push_curve_segments(&mut segments, |t| {
+                    let s = 1.0 - t;
+                    (
+                        s * s * s * a.0 + 3.0 * s * s * t * b.0 + 3.0 * s * t * t * c.0 + t * t * t * d.0,
+                        s * s * s * a.1 + 3.0 * s * s * t * b.1 + 3.0 * s * t * t * c.1 + t * t * t * d.1
+                    )
+                });
+            }
+        }
+    }
+    segments
+}
+
+fn push_curve_segments(segments: &mut Vec<Segment>, point_at: impl Fn(f32) -> (f32, f32)) {
+    let mut start = point_at(0.0);
+    for step in 1..=SEGMENTS_PER_CURVE {
+        let end = point_at(step as f32 / SEGMENTS_PER_CURVE as f32);
+        segments.push(Segment { start, end });
+        start = end;
+    }
+}
+
+fn distance_to_segment(point: (f32, f32), segment: &Segment) -> f32 {
+    let (dx, dy) = (segment.end.0 - segment.start.0, segment.end.1 - segment.start.1);
+    let (px, py) = (point.0 - segment.start.0, point.1 - segment.start.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = match length_squared > 0.0 {
+        true => ((px * dx + py * dy) / length_squared).clamp(0.0, 1.0),
+        false => 0.0
+    };
+    let (ex, ey) = (px - t * dx, py - t * dy);
+    (ex * ex + ey * ey).sqrt()
+}
+
+/// Count how many times the outline winds around a point; a point is inside the glyph if this is
+/// non-zero, which is the fill rule used by TrueType and OpenType fonts
+fn winding_number(point: (f32, f32), segments: &[Segment]) -> i32 {
+    let mut winding = 0;
+    for segment in segments.iter() {
+        let (a, b) = (segment.start, segment.end);
+        let side = (b.0 - a.0) * (point.1 - a.1) - (point.0 - a.0) * (b.1 - a.1);
+        if a.1 <= point.1 && b.1 > point.1 && side > 0.0 {
+            winding += 1;
+        } else if b.1 <= point.1 && a.1 > point.1 && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
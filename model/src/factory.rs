@@ -6,6 +6,7 @@ use std::{
 };
 
 const VERTEX_SIZE_BYTES: usize = 32;
+const BOUNDS_SIZE_BYTES: usize = 40;
 
 pub trait FromFile<E> where E : Sized {
 
@@ -26,11 +27,17 @@ impl FromFile<crate::types::StaticVertex> for crate::types::Model<crate::types::
         bytes: &[u8]
     ) -> Result<crate::types::Model<crate::types::StaticVertex>, String> {
 
-        // Read in vertex data
-        let name_length: usize = *(bytes as *const [u8] as *const u32) as usize;
-        let name = String::from_utf8_unchecked(bytes[4..(4 + name_length)].to_vec());
-        let vertex_count: u32 =
-            *(&bytes[(4 + name_length)..(8 + name_length)] as *const [u8] as *const u32);
+        // Read in vertex data, checking the lengths against the bytes given, as a file being
+        // written out may be read part of the way through
+        let name_length = read_u32(bytes, 0)? as usize;
+        let name_bytes = bytes.get(4..(4 + name_length))
+            .ok_or_else(|| String::from("Model file ends within its name"))?;
+        let name = String::from_utf8(name_bytes.to_vec())
+            .map_err(|e| format!("Model name is not valid UTF-8: {:?}", e))?;
+        let vertex_count = read_u32(bytes, 4 + name_length)?;
+        if bytes.len() < 8 + name_length + vertex_count as usize * VERTEX_SIZE_BYTES {
+            return Err(format!("Model file {} ends within its {} vertices", name, vertex_count));
+        }
         let mut vertices: Vec<crate::types::StaticVertex> =
             vec![crate::types::StaticVertex::default(); vertex_count as usize];
         let vertex_src_ptr =
@@ -40,10 +47,21 @@ impl FromFile<crate::types::StaticVertex> for crate::types::Model<crate::types::
             std::slice::from_raw_parts(vertex_src_ptr, vertex_count as usize);
         vertices.copy_from_slice(vertex_src_slice);
 
+        // Read in bounds, which follow the vertices; files written before bounds were stored end
+        // after the vertices, so compute them instead
+        let bounds_start = 8 + name_length + vertex_count as usize * VERTEX_SIZE_BYTES;
+        let bounds = match bytes.len() >= bounds_start + BOUNDS_SIZE_BYTES {
+            true => (bytes[bounds_start..(bounds_start + BOUNDS_SIZE_BYTES)].as_ptr()
+                as *const crate::types::Bounds).read_unaligned(),
+            false => crate::types::Bounds::from_positions(
+                vertices.iter().map(|v| [v.px, v.py, v.pz]))
+        };
+
         // Done
         Ok(crate::types::Model::<crate::types::StaticVertex> {
             name,
-            vertices
+            vertices,
+            bounds
         })
     }
 
@@ -62,8 +80,18 @@ impl FromFile<crate::types::StaticVertex> for crate::types::Model<crate::types::
                 &*(vertex as *const crate::types::StaticVertex as *const [u8; VERTEX_SIZE_BYTES])
             ).unwrap();
         }
+        file.write_all(
+            &*(&self.bounds as *const crate::types::Bounds as *const [u8; BOUNDS_SIZE_BYTES])
+        ).unwrap();
 
         // Done
         Ok(())
     }
 }
+
+/// Read a native-endian u32 at the given offset, if the bytes extend that far
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes.get(offset..(offset + 4))
+        .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| String::from("Model file ends within its header"))
+}
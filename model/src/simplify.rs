@@ -0,0 +1,469 @@
+
+use crate::types::{
+    Model,
+    StaticVertex
+};
+use std::{
+    cmp::Ordering,
+    collections::{
+        BinaryHeap,
+        HashMap
+    }
+};
+
+/// Weight of the planes added along open edges, so that the outlines of meshes with holes or
+/// borders are kept in place while their interiors are simplified
+const BOUNDARY_WEIGHT: f64 = 1000.0;
+
+/// Smallest cosine allowed between a triangle's normal before and after a collapse; anything
+/// less means the triangle would fold over
+const MIN_NORMAL_COSINE: f64 = 0.2;
+
+/// Quadric struct
+/// Symmetric 4x4 matrix, stored as its upper triangle, which sums the squared distances of a point
+/// to a set of planes
+#[derive(Copy, Clone, Default)]
+struct Quadric {
+    m: [f64; 10]
+}
+
+impl Quadric {
+
+    /// Create the quadric for a single plane ax + by + cz + d = 0 with a unit normal
+    fn from_plane(a: f64, b: f64, c: f64, d: f64, weight: f64) -> Quadric {
+        Quadric {
+            m: [
+                a * a, a * b, a * c, a * d,
+                b * b, b * c, b * d,
+                c * c, c * d,
+                d * d
+            ].map(|v| v * weight)
+        }
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (value, other_value) in self.m.iter_mut().zip(other.m.iter()) {
+            *value += other_value;
+        }
+    }
+
+    /// Get the sum of squared distances from a point to the planes in this quadric
+    fn error(&self, p: [f64; 3]) -> f64 {
+        let m = &self.m;
+        let [x, y, z] = p;
+        m[0] * x * x + 2.0 * m[1] * x * y + 2.0 * m[2] * x * z + 2.0 * m[3] * x +
+            m[4] * y * y + 2.0 * m[5] * y * z + 2.0 * m[6] * y +
+            m[7] * z * z + 2.0 * m[8] * z +
+            m[9]
+    }
+}
+
+/// Collapse struct
+/// Candidate for moving one vertex onto another, ordered so that the heap yields the cheapest first
+struct Collapse {
+    cost: f64,
+    from: usize,
+    to: usize,
+    from_version: u32,
+    to_version: u32
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Mesh struct
+/// Triangle list being simplified. Corners that share a position share a vertex, so that
+/// collapses join up across the mesh, while each corner keeps its own normal and texture
+/// coordinate. Vertices whose corners disagree on those lie on a seam, and are never moved.
+struct Mesh {
+    positions: Vec<[f64; 3]>,
+    seams: Vec<bool>,
+    quadrics: Vec<Quadric>,
+    versions: Vec<u32>,
+    vertex_triangles: Vec<Vec<usize>>,
+    triangles: Vec<[usize; 3]>,
+    corners: Vec<[StaticVertex; 3]>,
+    alive: Vec<bool>,
+    live_count: usize
+}
+
+impl Mesh {
+
+    /// Build from a non-indexed triangle list, merging corners at the same position
+    fn from_vertices(vertices: &[StaticVertex]) -> Mesh {
+        let mut lookup: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut positions = vec![];
+        let mut triangles = vec![];
+        let mut corners = vec![];
+        for chunk in vertices.chunks_exact(3) {
+            let mut triangle = [0; 3];
+            for (corner, vertex) in chunk.iter().enumerate() {
+                let key = [vertex.px.to_bits(), vertex.py.to_bits(), vertex.pz.to_bits()];
+                triangle[corner] = *lookup.entry(key).or_insert_with(|| {
+                    positions.push([vertex.px as f64, vertex.py as f64, vertex.pz as f64]);
+                    positions.len() - 1
+                });
+            }
+            triangles.push(triangle);
+            corners.push([chunk[0], chunk[1], chunk[2]]);
+        }
+
+        let mut vertex_triangles = vec![vec![]; positions.len()];
+        let mut first_corners: Vec<Option<StaticVertex>> = vec![None; positions.len()];
+        let mut seams = vec![false; positions.len()];
+        for (index, triangle) in triangles.iter().enumerate() {
+            for (vertex, corner) in triangle.iter().zip(corners[index].iter()) {
+                vertex_triangles[*vertex].push(index);
+                match first_corners[*vertex] {
+                    Some(first) => seams[*vertex] |= !same_attributes(&first, corner),
+                    None => first_corners[*vertex] = Some(*corner)
+                }
+            }
+        }
+        let live_count = triangles.len();
+        let mut mesh = Mesh {
+            quadrics: vec![Quadric::default(); positions.len()],
+            versions: vec![0; positions.len()],
+            positions,
+            seams,
+            vertex_triangles,
+            alive: vec![true; triangles.len()],
+            triangles,
+            corners,
+            live_count
+        };
+        mesh.accumulate_quadrics();
+        mesh
+    }
+
+    /// Sum each triangle's plane into the quadrics of its corners, and add planes along open edges
+    /// that are perpendicular to the triangle, to resist moving the edge
+    fn accumulate_quadrics(&mut self) {
+        let mut edge_uses: HashMap<(usize, usize), usize> = HashMap::new();
+        for triangle in self.triangles.iter() {
+            for corner in 0..3 {
+                let (a, b) = (triangle[corner], triangle[(corner + 1) % 3]);
+                *edge_uses.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        }
+        for index in 0..self.triangles.len() {
+            let triangle = self.triangles[index];
+            let normal = match self.triangle_normal(triangle) {
+                Some(normal) => normal,
+                None => continue
+            };
+            let p0 = self.positions[triangle[0]];
+            let d = -dot(normal, p0);
+            let quadric = Quadric::from_plane(normal[0], normal[1], normal[2], d, 1.0);
+            for vertex in triangle.iter() {
+                self.quadrics[*vertex].add(&quadric);
+            }
+            for corner in 0..3 {
+                let (a, b) = (triangle[corner], triangle[(corner + 1) % 3]);
+                if edge_uses[&(a.min(b), a.max(b))] != 1 {
+                    continue;
+                }
+                let edge = sub(self.positions[b], self.positions[a]);
+                if let Some(side) = normalise(cross(edge, normal)) {
+                    let d = -dot(side, self.positions[a]);
+                    let quadric = Quadric::from_plane(side[0], side[1], side[2], d, BOUNDARY_WEIGHT);
+                    self.quadrics[a].add(&quadric);
+                    self.quadrics[b].add(&quadric);
+                }
+            }
+        }
+    }
+
+    /// Get the unit normal of a triangle, if it has any area
+    fn triangle_normal(&self, triangle: [usize; 3]) -> Option<[f64; 3]> {
+        let [a, b, c] = triangle.map(|v| self.positions[v]);
+        normalise(cross(sub(b, a), sub(c, a)))
+    }
+
+    /// Make the candidate for moving one vertex onto another, costed by the error of the combined
+    /// quadric at the destination
+    fn make_collapse(&self, from: usize, to: usize) -> Collapse {
+        let mut quadric = self.quadrics[from];
+        quadric.add(&self.quadrics[to]);
+        Collapse {
+            cost: quadric.error(self.positions[to]),
+            from,
+            to,
+            from_version: self.versions[from],
+            to_version: self.versions[to]
+        }
+    }
+
+    /// Push both directions of every edge around a vertex onto the heap, except those moving a
+    /// vertex off a seam
+    fn push_collapses_around(&self, vertex: usize, heap: &mut BinaryHeap<Collapse>) {
+        for triangle in self.vertex_triangles[vertex].iter() {
+            for other in self.triangles[*triangle].iter() {
+                if *other == vertex {
+                    continue;
+                }
+                if !self.seams[vertex] {
+                    heap.push(self.make_collapse(vertex, *other));
+                }
+                if !self.seams[*other] {
+                    heap.push(self.make_collapse(*other, vertex));
+                }
+            }
+        }
+    }
+
+    /// Check that moving a vertex would not fold over any triangle that survives the move
+    fn collapse_keeps_orientation(&self, from: usize, to: usize) -> bool {
+        for index in self.vertex_triangles[from].iter() {
+            let triangle = self.triangles[*index];
+            if triangle.contains(&to) {
+                continue;
+            }
+            let before = match self.triangle_normal(triangle) {
+                Some(normal) => normal,
+                None => continue
+            };
+            let moved = triangle.map(|v| match v == from { true => to, false => v });
+            match self.triangle_normal(moved) {
+                Some(after) if dot(before, after) >= MIN_NORMAL_COSINE => {},
+                _ => return false
+            }
+        }
+        true
+    }
+
+    /// Check that the destination of a collapse has the same normal and texture coordinate in every
+    /// triangle sharing the edge, which fails where a seam through it ends at the vertex moved
+    fn collapse_keeps_attributes(&self, from: usize, to: usize) -> bool {
+        let mut shared_corners = self.vertex_triangles[from].iter()
+            .filter_map(|index| self.triangles[*index].iter()
+                .position(|vertex| *vertex == to)
+                .map(|corner| self.corners[*index][corner]));
+        match shared_corners.next() {
+            Some(first) => shared_corners.all(|corner| same_attributes(&first, &corner)),
+            None => true
+        }
+    }
+
+    /// Move one vertex onto another, removing the triangles that become degenerate. The moved
+    /// corners take the normal and texture coordinate of the destination from a triangle sharing
+    /// the edge between them, which is on the same side of any seam through the destination as
+    /// every triangle around the vertex being moved.
+    fn collapse(&mut self, from: usize, to: usize) {
+        let from_triangles = std::mem::take(&mut self.vertex_triangles[from]);
+        let destination = from_triangles.iter()
+            .find_map(|index| self.triangles[*index].iter()
+                .position(|vertex| *vertex == to)
+                .map(|corner| self.corners[*index][corner]));
+        for index in from_triangles.into_iter() {
+            if self.triangles[index].contains(&to) {
+                self.alive[index] = false;
+                self.live_count -= 1;
+                for vertex in self.triangles[index] {
+                    self.vertex_triangles[vertex].retain(|t| *t != index);
+                }
+                continue;
+            }
+            for (vertex, corner) in self.triangles[index].iter_mut().zip(self.corners[index].iter_mut()) {
+                if *vertex == from {
+                    *vertex = to;
+                    if let Some(destination) = destination {
+                        *corner = destination;
+                    }
+                }
+            }
+            self.vertex_triangles[to].push(index);
+        }
+        let from_quadric = self.quadrics[from];
+        self.quadrics[to].add(&from_quadric);
+        self.versions[from] += 1;
+        self.versions[to] += 1;
+    }
+
+    /// Write out the surviving triangles as a non-indexed triangle list
+    fn to_vertices(&self) -> Vec<StaticVertex> {
+        let mut vertices = Vec::with_capacity(self.live_count * 3);
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            if !self.alive[index] {
+                continue;
+            }
+            for (vertex, corner) in triangle.iter().zip(self.corners[index].iter()) {
+                let position = self.positions[*vertex];
+                vertices.push(StaticVertex {
+                    px: position[0] as f32,
+                    py: position[1] as f32,
+                    pz: position[2] as f32,
+                    ..*corner
+                });
+            }
+        }
+        vertices
+    }
+}
+
+/// Reduce a non-indexed triangle list to around the given number of triangles by quadric error
+/// edge collapse, moving each vertex onto a neighbour where doing so changes the surface least.
+/// Open edges and texture or normal seams are kept in place, and collapses that would fold
+/// triangles over are skipped, so the result may have more triangles than asked for.
+pub fn simplify_vertices(vertices: &[StaticVertex], target_triangle_count: usize) -> Vec<StaticVertex> {
+    let mut mesh = Mesh::from_vertices(vertices);
+    let mut heap = BinaryHeap::new();
+    for vertex in 0..mesh.positions.len() {
+        mesh.push_collapses_around(vertex, &mut heap);
+    }
+    while mesh.live_count > target_triangle_count {
+        let candidate = match heap.pop() {
+            Some(candidate) => candidate,
+            None => break
+        };
+        if candidate.from_version != mesh.versions[candidate.from] ||
+            candidate.to_version != mesh.versions[candidate.to] ||
+            mesh.vertex_triangles[candidate.from].is_empty() ||
+            !mesh.collapse_keeps_orientation(candidate.from, candidate.to) ||
+            !mesh.collapse_keeps_attributes(candidate.from, candidate.to) {
+            continue;
+        }
+        mesh.collapse(candidate.from, candidate.to);
+        mesh.push_collapses_around(candidate.to, &mut heap);
+    }
+    mesh.to_vertices()
+}
+
+/// Make lower levels of detail for a model, one per ratio of its triangle count, named after the
+/// model with suffixes _lod1, _lod2 and so on
+pub fn make_lod_chain(model: &Model<StaticVertex>, ratios: &[f32]) -> Vec<Model<StaticVertex>> {
+    let triangle_count = model.vertices.len() / 3;
+    ratios.iter()
+        .enumerate()
+        .map(|(index, ratio)| {
+            let target = (triangle_count as f32 * ratio.clamp(0.0, 1.0)).round() as usize;
+            Model::new_from_components(
+                format!("{}_lod{}", model.name, index + 1),
+                simplify_vertices(&model.vertices, target))
+        })
+        .collect()
+}
+
+/// Check whether two corners have the same normal and texture coordinate
+fn same_attributes(a: &StaticVertex, b: &StaticVertex) -> bool {
+    a.nx == b.nx && a.ny == b.ny && a.nz == b.nz && a.tu == b.tu && a.tv == b.tv
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn normalise(v: [f64; 3]) -> Option<[f64; 3]> {
+    let length = dot(v, v).sqrt();
+    match length > 1e-12 {
+        true => Some([v[0] / length, v[1] / length, v[2] / length]),
+        false => None
+    }
+}
+
+/// Test suite
+/// Check that simplification reduces flat areas without moving their outline or tearing their
+/// texture coordinates, and names LODs
+#[cfg(test)]
+mod test {
+    use crate::{
+        simplify::{
+            make_lod_chain,
+            simplify_vertices
+        },
+        types::{
+            Model,
+            StaticVertex
+        }
+    };
+
+    /// Make a flat grid of quads in the x-z plane, with the given number of quads along each side,
+    /// textured across the grid. Quads from the seam column onwards, if there is one, have their
+    /// texture coordinates shifted along u by 1.
+    fn make_grid(quads: usize, seam: Option<usize>) -> Vec<StaticVertex> {
+        let vertex = |x: usize, z: usize, shift: f32| StaticVertex::from_components(
+            (x as f32, 0.0, z as f32),
+            (0.0, -1.0, 0.0),
+            (x as f32 / quads as f32 + shift, z as f32 / quads as f32));
+        let mut vertices = vec![];
+        for z in 0..quads {
+            for x in 0..quads {
+                let shift = match seam {
+                    Some(seam) if x >= seam => 1.0,
+                    _ => 0.0
+                };
+                vertices.extend_from_slice(&[vertex(x, z, shift), vertex(x, z + 1, shift), vertex(x + 1, z + 1, shift)]);
+                vertices.extend_from_slice(&[vertex(x, z, shift), vertex(x + 1, z + 1, shift), vertex(x + 1, z, shift)]);
+            }
+        }
+        vertices
+    }
+
+    #[test]
+    fn flat_grid_simplifies_without_moving_its_outline() {
+        let model = Model::new_from_components(String::from("Field"), make_grid(8, None));
+        let lods = make_lod_chain(&model, &[0.5, 0.1]);
+        assert_eq!(lods[0].name, "Field_lod1");
+        assert_eq!(lods[1].name, "Field_lod2");
+        assert!(lods[0].vertices.len() / 3 <= 64);
+        assert!(lods[1].vertices.len() / 3 <= 13);
+
+        // Flat, so nothing leaves the plane, and the outline keeps the bounds
+        assert!(lods[1].vertices.iter().all(|v| v.py == 0.0));
+        assert_eq!(lods[1].bounds.min, [0.0, 0.0, 0.0]);
+        assert_eq!(lods[1].bounds.max, [8.0, 0.0, 8.0]);
+
+        // The remaining triangles still cover the whole area, all facing the same way as before
+        let areas: Vec<f32> = lods[1].vertices.chunks_exact(3)
+            .map(|t| 0.5 * ((t[2].px - t[0].px) * (t[1].pz - t[0].pz) - (t[1].px - t[0].px) * (t[2].pz - t[0].pz)))
+            .collect();
+        assert!(areas.iter().all(|area| *area > 0.0));
+        let area: f32 = areas.iter().sum();
+        assert!((area - 64.0).abs() < 0.001, "Area {} is not 64", area);
+    }
+
+    #[test]
+    fn texture_coordinates_stay_on_moved_vertices_and_seams() {
+        let simplified = simplify_vertices(&make_grid(8, Some(4)), 16);
+        assert!(simplified.len() / 3 < 128);
+
+        // Every corner keeps the texture coordinate of its position, on the side of the seam that
+        // its triangle is on, so no triangle stretches across to the other side
+        for triangle in simplified.chunks_exact(3) {
+            let shift = (triangle[0].tu - triangle[0].px / 8.0).round();
+            for corner in triangle.iter() {
+                assert!((corner.tu - (corner.px / 8.0 + shift)).abs() < 0.0001);
+                assert!((corner.tv - corner.pz / 8.0).abs() < 0.0001);
+            }
+            assert!(triangle.iter().all(|corner| match shift == 0.0 {
+                true => corner.px <= 4.0,
+                false => corner.px >= 4.0
+            }));
+        }
+    }
+}
@@ -1,18 +1,22 @@
 
 /// Model struct
-/// Represents a model with a name, along with a set of vertices of a generic sized type.
+/// Represents a model with a name, along with a set of vertices of a generic sized type and the
+/// bounding volumes that enclose them.
 pub struct Model<E> where E : Sized {
     pub name: String,
-    pub vertices: Vec<E>
+    pub vertices: Vec<E>,
+    pub bounds: Bounds
 }
 
-impl<E> Model<E> {
+impl<E: Positioned> Model<E> {
 
-    /// Construct a new instance from a set of vertices
+    /// Construct a new instance from a set of vertices, computing its bounds
     pub fn new_from_components(name: String, vertices: Vec<E>) -> Model<E> {
+        let bounds = Bounds::from_positions(vertices.iter().map(|v| v.position()));
         Model {
             name,
-            vertices
+            vertices,
+            bounds
         }
     }
 
@@ -24,10 +28,55 @@ impl<E> Model<E> {
                 all_vertices.push(vertex);
             }
         }
-        Model {
-            name: name.to_string(),
-            vertices: all_vertices
+        Model::new_from_components(name.to_string(), all_vertices)
+    }
+}
+
+/// Positioned trait
+/// Vertex types that have a position in model space, from which bounds can be computed
+pub trait Positioned {
+    fn position(&self) -> [f32; 3];
+}
+
+/// Bounds struct
+/// Volumes enclosing every vertex of a model, in model space: an axis-aligned box given by its
+/// minimum and maximum corners, and a sphere centred on the middle of that box. A model with no
+/// vertices has empty bounds at the origin.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Bounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    pub centre: [f32; 3],
+    pub radius: f32
+}
+
+impl Bounds {
+
+    /// Compute the bounds enclosing a set of positions
+    pub fn from_positions(positions: impl Iterator<Item = [f32; 3]> + Clone) -> Bounds {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        let mut any = false;
+        for position in positions.clone() {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(position[axis]);
+                max[axis] = max[axis].max(position[axis]);
+            }
+            any = true;
+        }
+        if !any {
+            return Bounds { min: [0.0; 3], max: [0.0; 3], centre: [0.0; 3], radius: 0.0 };
         }
+        let centre = [
+            0.5 * (min[0] + max[0]),
+            0.5 * (min[1] + max[1]),
+            0.5 * (min[2] + max[2])
+        ];
+        let radius_squared = positions
+            .map(|p| (p[0] - centre[0]).powi(2) + (p[1] - centre[1]).powi(2) + (p[2] - centre[2]).powi(2))
+            .fold(0.0, f32::max);
+        Bounds { min, max, centre, radius: radius_squared.sqrt() }
     }
 }
 
@@ -35,7 +84,7 @@ impl<E> Model<E> {
 /// Vertex definition for a three-dimensional vertex with a position, normal and two-
 /// dimensional texture coordinate
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct StaticVertex {
     pub px: f32,
     pub py: f32,
@@ -59,6 +108,14 @@ impl StaticVertex {
     }
 }
 
+impl Positioned for StaticVertex {
+
+    /// Get the position of this vertex
+    fn position(&self) -> [f32; 3] {
+        [self.px, self.py, self.pz]
+    }
+}
+
 impl Default for StaticVertex {
 
     /// Construct a new instance with position at the origin, texture coordinates at the origin,
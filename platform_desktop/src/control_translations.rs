@@ -1,11 +1,13 @@
 
 use defs::control::{
     KeyCode,
-    InputState
+    InputState,
+    MouseButton
 };
 use winit::event::{
     VirtualKeyCode,
-    ElementState
+    ElementState,
+    MouseButton as WinitMouseButton
 };
 
 /// Translate Winit key codes into the abstract codes from the defs crate
@@ -15,6 +17,14 @@ pub fn translate_code(winit_code: VirtualKeyCode) -> KeyCode {
         VirtualKeyCode::Right => KeyCode::Right,
         VirtualKeyCode::Up => KeyCode::Up,
         VirtualKeyCode::Down => KeyCode::Down,
+        VirtualKeyCode::Return => KeyCode::Enter,
+        VirtualKeyCode::Tab => KeyCode::Tab,
+        VirtualKeyCode::Back => KeyCode::Backspace,
+        VirtualKeyCode::Delete => KeyCode::Delete,
+        VirtualKeyCode::Home => KeyCode::Home,
+        VirtualKeyCode::End => KeyCode::End,
+        VirtualKeyCode::PageUp => KeyCode::PageUp,
+        VirtualKeyCode::PageDown => KeyCode::PageDown,
         _ => KeyCode::Unknown
     }
 }
@@ -26,3 +36,13 @@ pub fn translate_state(winit_state: ElementState) -> InputState {
         ElementState::Released => InputState::Released
     }
 }
+
+/// Translate Winit mouse buttons into the abstract buttons from the defs crate, if recognised
+pub fn translate_mouse_button(winit_button: WinitMouseButton) -> Option<MouseButton> {
+    match winit_button {
+        WinitMouseButton::Left => Some(MouseButton::Left),
+        WinitMouseButton::Right => Some(MouseButton::Right),
+        WinitMouseButton::Middle => Some(MouseButton::Middle),
+        WinitMouseButton::Other(_) => None
+    }
+}
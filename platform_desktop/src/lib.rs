@@ -97,6 +97,27 @@ impl PlatformWindows {
                                 _ => {}
                             }
                         },
+                        WindowEvent::CursorMoved { position, .. } => {
+                            let size = self.window.inner_size();
+                            engine.process_mouse_move(Some((
+                                position.x as f32 / size.width.max(1) as f32,
+                                position.y as f32 / size.height.max(1) as f32
+                            )));
+                        },
+                        WindowEvent::CursorLeft { .. } => {
+                            engine.process_mouse_move(None);
+                        },
+                        WindowEvent::MouseInput { state, button, .. } => {
+                            if let Some(button) = crate::control_translations::translate_mouse_button(button) {
+                                engine.process_mouse_button_event(
+                                    button,
+                                    crate::control_translations::translate_state(state)
+                                );
+                            }
+                        },
+                        WindowEvent::ReceivedCharacter(c) if !c.is_control() => {
+                            engine.process_character(c);
+                        },
                         WindowEvent::Resized(_) => {
                             engine.recreate_surface(&self.window).unwrap();
                         }
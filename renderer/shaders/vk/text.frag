@@ -3,6 +3,7 @@
 #extension GL_ARB_shading_language_420pack : enable
 
 layout (location = 0) in vec2 o_tex_coord;
+layout (location = 1) in vec3 o_tint;
 
 layout (set = 0, binding = 0) uniform UniformBufferObject {
     mat4 mvp_matrix;
@@ -14,5 +15,5 @@ layout (location = 0) out vec4 uFragColor;
 
 void main() {
     float sampleColor = texture(textureSampler, o_tex_coord).r;
-    uFragColor = vec4(ubo.paintColor.rgb, sampleColor * ubo.paintColor.a);
+    uFragColor = vec4(ubo.paintColor.rgb * o_tint, sampleColor * ubo.paintColor.a);
 }
\ No newline at end of file
@@ -12,8 +12,10 @@ layout (set = 0, binding = 0) uniform UniformBufferObject {
 } ubo;
 
 layout (location = 0) out vec2 o_tex_coord;
+layout (location = 1) out vec3 o_tint;
 
 void main() {
     o_tex_coord = tex_coord;
+    o_tint = normal;
     gl_Position = ubo.mvp_matrix * vec4(pos, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+#version 400
+#extension GL_ARB_separate_shader_objects : enable
+#extension GL_ARB_shading_language_420pack : enable
+
+layout (location = 0) in vec2 o_tex_coord;
+layout (location = 1) in vec3 o_tint;
+
+layout (set = 0, binding = 0) uniform UniformBufferObject {
+    mat4 mvp_matrix;
+    vec4 paintColor;
+    vec4 outlineColor;
+    vec4 shadowColor;
+    vec4 glowColor;
+    vec2 shadowOffset;
+    float outlineWidth;
+    float glowWidth;
+    float distanceRange;
+    float multiChannel;
+} ubo;
+layout (set = 0, binding = 1) uniform sampler2D textureSampler;
+
+layout (location = 0) out vec4 uFragColor;
+
+float median(float r, float g, float b) {
+    return max(min(r, g), min(max(r, g), b));
+}
+
+// Distance to the glyph edge in texels, positive inside
+float distanceAt(vec2 texCoord) {
+    vec4 texel = texture(textureSampler, texCoord);
+    float field = ubo.multiChannel > 0.5 ? median(texel.r, texel.g, texel.b) : texel.r;
+    return (field - 0.5) * ubo.distanceRange;
+}
+
+// Layer a premultiplied color over another
+vec4 over(vec4 top, vec4 bottom) {
+    return top + bottom * (1.0 - top.a);
+}
+
+void main() {
+    // Screen pixels per texel, so that edges stay one pixel wide at any scale
+    vec2 textureSizeTexels = vec2(textureSize(textureSampler, 0));
+    vec2 texelsPerPixel = fwidth(o_tex_coord) * textureSizeTexels;
+    float pixelsPerTexel = 1.0 / max(0.5 * (texelsPerPixel.x + texelsPerPixel.y), 0.0001);
+
+    float distance = distanceAt(o_tex_coord);
+    float fillCoverage = clamp(distance * pixelsPerTexel + 0.5, 0.0, 1.0);
+    float outlineCoverage = clamp((distance + ubo.outlineWidth) * pixelsPerTexel + 0.5, 0.0, 1.0);
+
+    vec4 color = vec4(0.0);
+    if (ubo.glowWidth > 0.0) {
+        float glow = clamp(1.0 + (distance + ubo.outlineWidth) / ubo.glowWidth, 0.0, 1.0);
+        color = vec4(ubo.glowColor.rgb, 1.0) * ubo.glowColor.a * glow * glow;
+    }
+    if (ubo.shadowColor.a > 0.0) {
+        float shadowDistance = distanceAt(o_tex_coord - ubo.shadowOffset / textureSizeTexels);
+        float shadow = clamp((shadowDistance + ubo.outlineWidth) * pixelsPerTexel + 0.5, 0.0, 1.0);
+        color = over(vec4(ubo.shadowColor.rgb, 1.0) * ubo.shadowColor.a * shadow, color);
+    }
+    color = over(vec4(ubo.outlineColor.rgb, 1.0) * ubo.outlineColor.a * outlineCoverage, color);
+    // Per-vertex tint colours the fill only, leaving effects around the glyph unchanged
+    color = over(vec4(ubo.paintColor.rgb * o_tint, 1.0) * ubo.paintColor.a * fillCoverage, color);
+
+    // The pipeline blends with straight alpha
+    uFragColor = color.a > 0.0 ? vec4(color.rgb / color.a, color.a) : vec4(0.0);
+}
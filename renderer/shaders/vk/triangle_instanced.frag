@@ -0,0 +1,14 @@
+#version 400
+#extension GL_ARB_separate_shader_objects : enable
+#extension GL_ARB_shading_language_420pack : enable
+
+layout (location = 0) in vec2 o_tex_coord;
+layout (location = 1) in vec4 o_tint;
+
+layout (set = 0, binding = 1) uniform sampler2D textureSampler;
+
+layout (location = 0) out vec4 uFragColor;
+
+void main() {
+    uFragColor = texture(textureSampler, o_tex_coord) * o_tint;
+}
\ No newline at end of file
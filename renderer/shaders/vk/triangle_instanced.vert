@@ -0,0 +1,22 @@
+#version 400
+#extension GL_ARB_separate_shader_objects : enable
+#extension GL_ARB_shading_language_420pack : enable
+
+layout (location = 0) in vec3 pos;
+layout (location = 1) in vec3 normal;
+layout (location = 2) in vec2 tex_coord;
+layout (location = 3) in mat4 instance_model_matrix;
+layout (location = 7) in vec4 instance_tint;
+
+layout (set = 0, binding = 0) uniform UniformBufferObject {
+    mat4 pv_matrix;
+} ubo;
+
+layout (location = 0) out vec2 o_tex_coord;
+layout (location = 1) out vec4 o_tint;
+
+void main() {
+    o_tex_coord = tex_coord;
+    o_tint = instance_tint;
+    gl_Position = ubo.pv_matrix * instance_model_matrix * vec4(pos, 1.0);
+}
\ No newline at end of file
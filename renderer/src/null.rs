@@ -8,9 +8,11 @@ use defs::{
         PresentResult,
         DrawingDescription,
         ResourcePreloads,
-        FeatureDeclaration
+        FeatureDeclaration,
+        InstanceData
     }
 };
+use model::types::StaticVertex;
 use raw_window_handle::HasRawWindowHandle;
 
 /// NullRenderer struct
@@ -52,6 +54,21 @@ impl RendererApi for NullRenderer {
         Ok(())
     }
 
+    /// No-op
+    fn replace_scene_resources(&mut self, _replacements: &ResourcePreloads, _description: &DrawingDescription) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    /// No-op
+    fn update_instance_data(&mut self, _instance_buffer_index: usize, _instances: &[InstanceData]) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    /// No-op
+    fn update_dynamic_vbo_data(&mut self, _vbo_index: usize, _vertices: &[StaticVertex]) -> Result<(), EngineError> {
+        Ok(())
+    }
+
     /// Return sensible aspect ratio number; static value
     fn get_aspect_ratio(&self) -> f32 {
         1.0
@@ -0,0 +1,510 @@
+
+use defs::{
+    EngineError,
+    render::TexturePixelFormat
+};
+
+/// Decompress block-compressed texture data into RGBA, for devices that cannot sample the
+/// compressed format directly. Each layer holds the given number of mip levels, packed one after
+/// another, largest first; the result has the same layout in RGBA.
+pub fn decompress_layers(
+    format: TexturePixelFormat,
+    width: u32,
+    height: u32,
+    mip_levels: u32,
+    layer_data: &[Vec<u8>]
+) -> Result<Vec<Vec<u8>>, EngineError> {
+    let block_size_bytes: usize = match format {
+        TexturePixelFormat::Bc1 | TexturePixelFormat::Bc4 => 8,
+        TexturePixelFormat::Bc2 | TexturePixelFormat::Bc3 | TexturePixelFormat::Bc5 |
+        TexturePixelFormat::Bc7 => 16,
+        _ => return Err(EngineError::RenderError(
+            format!("No CPU decompression available for format {:?}", format)))
+    };
+
+    let mut decompressed_layers = vec![];
+    for data in layer_data.iter() {
+        let mut decompressed = vec![];
+        let mut level_start = 0;
+        for level in 0..mip_levels {
+            let level_width = (width >> level).max(1) as usize;
+            let level_height = (height >> level).max(1) as usize;
+            let level_size = format.level_size_bytes(level_width as u32, level_height as u32);
+            if data.len() < level_start + level_size {
+                return Err(EngineError::RenderError(
+                    String::from("Compressed texture data is shorter than expected")));
+            }
+            let blocks_wide = level_width.div_ceil(4);
+            let mut pixels = vec![0u8; level_width * level_height * 4];
+            for (block_index, block) in data[level_start..(level_start + level_size)]
+                .chunks_exact(block_size_bytes)
+                .enumerate() {
+                let texels = decode_block(format, block);
+                let block_x = (block_index % blocks_wide) * 4;
+                let block_y = (block_index / blocks_wide) * 4;
+                for (texel_index, texel) in texels.iter().enumerate() {
+                    let x = block_x + texel_index % 4;
+                    let y = block_y + texel_index / 4;
+                    if x < level_width && y < level_height {
+                        let offset = (y * level_width + x) * 4;
+                        pixels[offset..(offset + 4)].copy_from_slice(texel);
+                    }
+                }
+            }
+            decompressed.extend_from_slice(&pixels);
+            level_start += level_size;
+        }
+        decompressed_layers.push(decompressed);
+    }
+    Ok(decompressed_layers)
+}
+
+/// Decode a single block into its 16 texels, in row-major order. Single- and dual-channel formats
+/// fill the remaining channels the same way the device would when sampling them.
+fn decode_block(format: TexturePixelFormat, block: &[u8]) -> [[u8; 4]; 16] {
+    let mut texels = [[0u8; 4]; 16];
+    match format {
+        TexturePixelFormat::Bc1 => {
+            texels = decode_color_block(&block[0..8], true);
+        },
+        TexturePixelFormat::Bc2 => {
+            texels = decode_color_block(&block[8..16], false);
+            for (i, texel) in texels.iter_mut().enumerate() {
+                let nibble = (block[i / 2] >> ((i % 2) * 4)) & 0x0f;
+                texel[3] = nibble * 17;
+            }
+        },
+        TexturePixelFormat::Bc3 => {
+            texels = decode_color_block(&block[8..16], false);
+            let alphas = decode_channel_block(&block[0..8]);
+            for (texel, alpha) in texels.iter_mut().zip(alphas.iter()) {
+                texel[3] = *alpha;
+            }
+        },
+        TexturePixelFormat::Bc4 => {
+            let reds = decode_channel_block(&block[0..8]);
+            for (texel, red) in texels.iter_mut().zip(reds.iter()) {
+                *texel = [*red, 0, 0, 255];
+            }
+        },
+        TexturePixelFormat::Bc5 => {
+            let reds = decode_channel_block(&block[0..8]);
+            let greens = decode_channel_block(&block[8..16]);
+            for (i, texel) in texels.iter_mut().enumerate() {
+                *texel = [reds[i], greens[i], 0, 255];
+            }
+        },
+        TexturePixelFormat::Bc7 => {
+            texels = decode_bc7_block(block);
+        },
+        _ => ()
+    };
+    texels
+}
+
+/// Decode the 8-byte colour part of a BC1, BC2 or BC3 block. Only BC1 may use the three-colour
+/// mode with transparent black, selected when the first endpoint is not greater than the second.
+fn decode_color_block(block: &[u8], allow_transparency: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let rgb0 = expand_565(c0);
+    let rgb1 = expand_565(c1);
+    let mix = |a: u8, b: u8, wa: u32, wb: u32| ((a as u32 * wa + b as u32 * wb) / (wa + wb)) as u8;
+
+    let mut palette = [[0u8; 4]; 4];
+    palette[0] = [rgb0[0], rgb0[1], rgb0[2], 255];
+    palette[1] = [rgb1[0], rgb1[1], rgb1[2], 255];
+    if c0 > c1 || !allow_transparency {
+        for channel in 0..3 {
+            palette[2][channel] = mix(rgb0[channel], rgb1[channel], 2, 1);
+            palette[3][channel] = mix(rgb0[channel], rgb1[channel], 1, 2);
+        }
+        palette[2][3] = 255;
+        palette[3][3] = 255;
+    } else {
+        for channel in 0..3 {
+            palette[2][channel] = mix(rgb0[channel], rgb1[channel], 1, 1);
+        }
+        palette[2][3] = 255;
+        palette[3] = [0, 0, 0, 0];
+    }
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    let mut texels = [[0u8; 4]; 16];
+    for (i, texel) in texels.iter_mut().enumerate() {
+        *texel = palette[((indices >> (i * 2)) & 0x03) as usize];
+    }
+    texels
+}
+
+/// Decode an 8-byte single-channel block, as used for BC3 alpha and for BC4 and BC5 channels
+fn decode_channel_block(block: &[u8]) -> [u8; 16] {
+    let a0 = block[0] as u32;
+    let a1 = block[1] as u32;
+    let mut palette = [0u8; 8];
+    palette[0] = a0 as u8;
+    palette[1] = a1 as u8;
+    if a0 > a1 {
+        for i in 1..7 {
+            palette[i + 1] = (((7 - i as u32) * a0 + i as u32 * a1) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = (((5 - i as u32) * a0 + i as u32 * a1) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+
+    let mut index_bits: u64 = 0;
+    for (i, byte) in block[2..8].iter().enumerate() {
+        index_bits |= (*byte as u64) << (i * 8);
+    }
+    let mut values = [0u8; 16];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = palette[((index_bits >> (i * 3)) & 0x07) as usize];
+    }
+    values
+}
+
+/// Expand a 5-6-5 packed colour to 8 bits per channel
+fn expand_565(color: u16) -> [u8; 3] {
+    let r = ((color >> 11) & 0x1f) as u32;
+    let g = ((color >> 5) & 0x3f) as u32;
+    let b = (color & 0x1f) as u32;
+    [
+        ((r * 255 + 15) / 31) as u8,
+        ((g * 255 + 31) / 63) as u8,
+        ((b * 255 + 15) / 31) as u8
+    ]
+}
+
+/// BC7 mode layout: the number of subsets, the bit counts of each field, whether each endpoint
+/// or each subset has a P-bit, and the bit counts of the primary and secondary texel indices
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_p_bits: bool,
+    shared_p_bits: bool,
+    index_bits: u32,
+    secondary_index_bits: u32
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode { subsets: 3, partition_bits: 4, rotation_bits: 0, index_selection_bits: 0, color_bits: 4, alpha_bits: 0, endpoint_p_bits: true, shared_p_bits: false, index_bits: 3, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 6, alpha_bits: 0, endpoint_p_bits: false, shared_p_bits: true, index_bits: 3, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 3, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 0, endpoint_p_bits: false, shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 0, endpoint_p_bits: true, shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 1, color_bits: 5, alpha_bits: 6, endpoint_p_bits: false, shared_p_bits: false, index_bits: 2, secondary_index_bits: 3 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 0, color_bits: 7, alpha_bits: 8, endpoint_p_bits: false, shared_p_bits: false, index_bits: 2, secondary_index_bits: 2 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 7, endpoint_p_bits: true, shared_p_bits: false, index_bits: 4, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 5, endpoint_p_bits: true, shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 }
+];
+
+/// Subset of each texel for the 64 two-subset partitions, two bits per texel, first texel lowest
+const BC7_PARTITIONS_2: [u32; 64] = [
+    0x50505050, 0x40404040, 0x54545454, 0x54505040, 0x50404000, 0x55545450, 0x55545040, 0x54504000,
+    0x50400000, 0x55555450, 0x55544000, 0x54400000, 0x55555440, 0x55550000, 0x55555500, 0x55000000,
+    0x55150100, 0x00004054, 0x15010000, 0x00405054, 0x00004050, 0x15050100, 0x05010000, 0x40505054,
+    0x00404050, 0x05010100, 0x14141414, 0x05141450, 0x01155440, 0x00555500, 0x15014054, 0x05414150,
+    0x44444444, 0x55005500, 0x11441144, 0x05055050, 0x05500550, 0x11114444, 0x41144114, 0x44111144,
+    0x15055054, 0x01055040, 0x05041050, 0x05455150, 0x14414114, 0x50050550, 0x41411414, 0x00141400,
+    0x00041504, 0x00105410, 0x10541000, 0x04150400, 0x50410514, 0x41051450, 0x05415014, 0x14054150,
+    0x41050514, 0x41505014, 0x40011554, 0x54150140, 0x50505500, 0x00555050, 0x15151010, 0x54540404
+];
+
+/// Subset of each texel for the 64 three-subset partitions, packed as for two subsets
+const BC7_PARTITIONS_3: [u32; 64] = [
+    0xaa685050, 0x6a5a5040, 0x5a5a4200, 0x5450a0a8, 0xa5a50000, 0xa0a05050, 0x5555a0a0, 0x5a5a5050,
+    0xaa550000, 0xaa555500, 0xaaaa5500, 0x90909090, 0x94949494, 0xa4a4a4a4, 0xa9a59450, 0x2a0a4250,
+    0xa5945040, 0x0a425054, 0xa5a5a500, 0x55a0a0a0, 0xa8a85454, 0x6a6a4040, 0xa4a45000, 0x1a1a0500,
+    0x0050a4a4, 0xaaa59090, 0x14696914, 0x69691400, 0xa08585a0, 0xaa821414, 0x50a4a450, 0x6a5a0200,
+    0xa9a58000, 0x5090a0a8, 0xa8a09050, 0x24242424, 0x00aa5500, 0x24924924, 0x24499224, 0x50a50a50,
+    0x500aa550, 0xaaaa4444, 0x66660000, 0xa5a0a5a0, 0x50a050a0, 0x69286928, 0x44aaaa44, 0x66666600,
+    0xaa444444, 0x54a854a8, 0x95809580, 0x96969600, 0xa85454a8, 0x80959580, 0xaa141414, 0x96960000,
+    0xaaaa1414, 0xa05050a0, 0xa0a5a5a0, 0x96000000, 0x40804080, 0xa9a8a9a8, 0xaaaaaa44, 0x2a4a5254
+];
+
+/// Anchor texel of the second subset of each two-subset partition
+const BC7_ANCHORS_2: [usize; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2,
+    15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6,
+    6, 2, 6, 8, 15, 15, 2, 2, 15, 15, 15, 15, 15, 2, 2, 15
+];
+
+/// Anchor texel of the second subset of each three-subset partition
+const BC7_ANCHORS_3_SECOND: [usize; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3,
+    3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5, 15, 15,
+    8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15,
+    3, 15, 5, 5, 5, 8, 5, 10, 5, 10, 8, 13, 15, 12, 3, 3
+];
+
+/// Anchor texel of the third subset of each three-subset partition
+const BC7_ANCHORS_3_THIRD: [usize; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8,
+    15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6, 10, 15, 15, 10, 8,
+    15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8,
+    15, 3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8
+];
+
+/// Reads fields from a BC7 block, lowest bit first
+struct BlockBitReader {
+    bits: u128,
+    position: u32
+}
+
+impl BlockBitReader {
+
+    /// Read the next field of the given number of bits, which may be zero
+    fn read(&mut self, count: u32) -> u32 {
+        let value = ((self.bits >> self.position) & ((1u128 << count) - 1)) as u32;
+        self.position += count;
+        value
+    }
+}
+
+/// Decode a 16-byte BC7 block. The mode is given by the lowest set bit of the first byte, and a
+/// block with no mode set decodes to transparent black, as the device would sample it.
+fn decode_bc7_block(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut texels = [[0u8; 4]; 16];
+    let mode_index = match (0..8).find(|bit| block[0] & (1 << bit) != 0) {
+        Some(bit) => bit,
+        None => return texels
+    };
+    let mode = &BC7_MODES[mode_index];
+    let mut reader = BlockBitReader {
+        bits: block[0..16].iter().rev().fold(0u128, |bits, byte| (bits << 8) | *byte as u128),
+        position: mode_index as u32 + 1
+    };
+    let partition = reader.read(mode.partition_bits) as usize;
+    let rotation = reader.read(mode.rotation_bits);
+    let index_selection = reader.read(mode.index_selection_bits);
+
+    // Endpoints are stored channel by channel, two per subset, then any P-bits follow
+    let endpoint_count = mode.subsets * 2;
+    let channel_bits = [mode.color_bits, mode.color_bits, mode.color_bits, mode.alpha_bits];
+    let mut endpoints = [[0u32; 4]; 6];
+    for (channel, bits) in channel_bits.iter().enumerate() {
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[channel] = reader.read(*bits);
+        }
+    }
+    let mut p_bits = [0u32; 6];
+    if mode.endpoint_p_bits {
+        for p_bit in p_bits.iter_mut().take(endpoint_count) {
+            *p_bit = reader.read(1);
+        }
+    }
+    if mode.shared_p_bits {
+        for subset_p_bits in p_bits.chunks_mut(2).take(mode.subsets) {
+            let p_bit = reader.read(1);
+            subset_p_bits.fill(p_bit);
+        }
+    }
+
+    // P-bits become the lowest bit of every channel that is stored, then each channel is widened
+    // to 8 bits by repeating its highest bits; modes without alpha are opaque
+    let has_p_bits = mode.endpoint_p_bits || mode.shared_p_bits;
+    for (endpoint, p_bit) in endpoints.iter_mut().zip(p_bits.iter()).take(endpoint_count) {
+        for (value, bits) in endpoint.iter_mut().zip(channel_bits.iter()) {
+            let (stored, stored_bits) = match (*bits, has_p_bits) {
+                (0, _) => (255, 8),
+                (_, true) => ((*value << 1) | p_bit, bits + 1),
+                (_, false) => (*value, *bits)
+            };
+            *value = (stored << (8 - stored_bits)) | (stored >> (2 * stored_bits - 8));
+        }
+    }
+
+    // Each subset's anchor texel has its index stored with the highest bit left out, as zero
+    let partition_map = match mode.subsets {
+        2 => BC7_PARTITIONS_2[partition],
+        3 => BC7_PARTITIONS_3[partition],
+        _ => 0
+    };
+    let is_anchor = |texel: usize| match mode.subsets {
+        2 => texel == 0 || texel == BC7_ANCHORS_2[partition],
+        3 => texel == 0 || texel == BC7_ANCHORS_3_SECOND[partition] || texel == BC7_ANCHORS_3_THIRD[partition],
+        _ => texel == 0
+    };
+    let mut indices = [0u32; 16];
+    for (texel, index) in indices.iter_mut().enumerate() {
+        *index = reader.read(mode.index_bits - u32::from(is_anchor(texel)));
+    }
+    let mut secondary_indices = [0u32; 16];
+    if mode.secondary_index_bits > 0 {
+        for (texel, index) in secondary_indices.iter_mut().enumerate() {
+            *index = reader.read(mode.secondary_index_bits - u32::from(texel == 0));
+        }
+    }
+
+    // Modes with two sets of indices use one for colour and the other for alpha, swapped if the
+    // index selection bit is set, then a rotation may swap alpha with one of the colour channels
+    for (texel_index, texel) in texels.iter_mut().enumerate() {
+        let subset = ((partition_map >> (texel_index * 2)) & 0x03) as usize;
+        let primary = (indices[texel_index], mode.index_bits);
+        let secondary = (secondary_indices[texel_index], mode.secondary_index_bits);
+        let (color_index, alpha_index) = match (mode.secondary_index_bits, index_selection) {
+            (0, _) => (primary, primary),
+            (_, 0) => (primary, secondary),
+            _ => (secondary, primary)
+        };
+        for (channel, value) in texel.iter_mut().enumerate() {
+            let (index, index_bits) = match channel {
+                3 => alpha_index,
+                _ => color_index
+            };
+            let weight = bc7_weight(index_bits, index);
+            let start = endpoints[subset * 2][channel];
+            let end = endpoints[subset * 2 + 1][channel];
+            *value = (((64 - weight) * start + weight * end + 32) >> 6) as u8;
+        }
+        match rotation {
+            1 => texel.swap(0, 3),
+            2 => texel.swap(1, 3),
+            3 => texel.swap(2, 3),
+            _ => ()
+        };
+    }
+    texels
+}
+
+/// Interpolation weight, out of 64, of the second endpoint for an index of the given bit count
+fn bc7_weight(index_bits: u32, index: u32) -> u32 {
+    let weights: &[u32] = match index_bits {
+        2 => &[0, 21, 43, 64],
+        3 => &[0, 9, 18, 27, 37, 46, 55, 64],
+        _ => &[0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64]
+    };
+    weights[index as usize]
+}
+
+/// Test suite
+/// Check each block format against hand-encoded blocks, and that partial blocks are cropped
+#[cfg(test)]
+mod test {
+    use crate::vk_renderer::bcn::{
+        decode_block,
+        decompress_layers
+    };
+    use defs::render::TexturePixelFormat;
+
+    /// Colour part with pure red and pure blue endpoints, and the first four texels using indices
+    /// 0 to 3 in turn; the first endpoint is given first if red_first
+    fn make_color_block(red_first: bool) -> [u8; 8] {
+        let (c0, c1) = match red_first {
+            true => (0xf800u16, 0x001fu16),
+            false => (0x001fu16, 0xf800u16)
+        };
+        let [c0_low, c0_high] = c0.to_le_bytes();
+        let [c1_low, c1_high] = c1.to_le_bytes();
+        [c0_low, c0_high, c1_low, c1_high, 0xe4, 0, 0, 0]
+    }
+
+    #[test]
+    fn color_blocks_decode() {
+        let bc1 = decode_block(TexturePixelFormat::Bc1, &make_color_block(true));
+        assert_eq!(&bc1[0..5], &[[255, 0, 0, 255], [0, 0, 255, 255], [170, 0, 85, 255], [85, 0, 170, 255], [255, 0, 0, 255]]);
+
+        // With the endpoints the other way round, BC1 has a midpoint and transparent black
+        let bc1_transparent = decode_block(TexturePixelFormat::Bc1, &make_color_block(false));
+        assert_eq!(&bc1_transparent[2..4], &[[127, 0, 127, 255], [0, 0, 0, 0]]);
+
+        // BC2 always has four opaque colours, with alpha from the explicit nibbles
+        let mut bc2_block = [0u8; 16];
+        bc2_block[0] = 0xf0;
+        bc2_block[1] = 0x07;
+        bc2_block[8..16].copy_from_slice(&make_color_block(false));
+        let bc2 = decode_block(TexturePixelFormat::Bc2, &bc2_block);
+        assert_eq!(&bc2[0..4], &[[0, 0, 255, 0], [255, 0, 0, 255], [85, 0, 170, 119], [170, 0, 85, 0]]);
+
+        // BC3 alpha with the first endpoint greater uses eight interpolated values
+        let mut bc3_block = [0u8; 16];
+        bc3_block[0..8].copy_from_slice(&[255, 0, 0x88, 0, 0, 0, 0, 0]);
+        bc3_block[8..16].copy_from_slice(&make_color_block(true));
+        let bc3 = decode_block(TexturePixelFormat::Bc3, &bc3_block);
+        assert_eq!(&bc3[0..3], &[[255, 0, 0, 255], [0, 0, 255, 0], [170, 0, 85, 218]]);
+    }
+
+    #[test]
+    fn channel_blocks_decode_and_partial_blocks_crop() {
+
+        // With the first endpoint not greater, there are six values plus 0 and 255
+        let red = [0, 255, 0xf2, 0x01, 0, 0, 0, 0];
+        let bc4 = decode_block(TexturePixelFormat::Bc4, &red);
+        assert_eq!(&bc4[0..4], &[[51, 0, 0, 255], [0, 0, 0, 255], [255, 0, 0, 255], [0, 0, 0, 255]]);
+
+        let mut bc5_block = [0u8; 16];
+        bc5_block[0..8].copy_from_slice(&red);
+        bc5_block[8..16].copy_from_slice(&[200, 100, 0, 0, 0, 0, 0, 0]);
+        let bc5 = decode_block(TexturePixelFormat::Bc5, &bc5_block);
+        assert_eq!(&bc5[0..3], &[[51, 200, 0, 255], [0, 200, 0, 255], [255, 200, 0, 255]]);
+
+        // A 2x2 level takes the top-left texels of its one block, then a 1x1 level follows
+        let mut layer = make_color_block(true).to_vec();
+        layer.extend_from_slice(&make_color_block(false));
+        let decompressed = decompress_layers(TexturePixelFormat::Bc1, 2, 2, 2, &[layer.clone()]).unwrap();
+        assert_eq!(decompressed[0], vec![
+            255, 0, 0, 255, 0, 0, 255, 255,
+            255, 0, 0, 255, 255, 0, 0, 255,
+            0, 0, 255, 255]);
+        assert!(decompress_layers(TexturePixelFormat::Bc1, 2, 2, 2, &[layer[0..12].to_vec()]).is_err());
+        assert!(decompress_layers(TexturePixelFormat::Rgba, 4, 4, 1, &[vec![0u8; 64]]).is_err());
+    }
+
+    /// Pack (value, bit count) fields into a block, lowest bit first, as BC7 stores them
+    fn pack_fields(fields: &[(u32, u32)]) -> [u8; 16] {
+        let mut bits = 0u128;
+        let mut position = 0;
+        for (value, count) in fields.iter() {
+            bits |= (*value as u128) << position;
+            position += count;
+        }
+        bits.to_le_bytes()
+    }
+
+    #[test]
+    fn bc7_blocks_decode() {
+
+        // Mode 6 has one subset with P-bits on each endpoint, and 4-bit indices; the first
+        // texel's index is one bit shorter
+        let mode_6 = pack_fields(&[
+            (1 << 6, 7),
+            (127, 7), (0, 7), (0, 7), (127, 7), (0, 7), (0, 7), (127, 7), (127, 7),
+            (1, 1), (1, 1),
+            (0, 3), (15, 4), (8, 4)]);
+        let texels = decode_block(TexturePixelFormat::Bc7, &mode_6);
+        assert_eq!(&texels[0..4], &[[255, 1, 1, 255], [1, 255, 1, 255], [120, 136, 1, 255], [255, 1, 1, 255]]);
+
+        // Mode 1 partition 13 puts the top two rows in the first subset and the bottom two in the
+        // second, each with a shared P-bit
+        let mode_1 = pack_fields(&[
+            (1 << 1, 2), (13, 6),
+            (63, 6), (63, 6), (0, 6), (0, 6),
+            (0, 6), (0, 6), (0, 6), (0, 6),
+            (0, 6), (0, 6), (63, 6), (63, 6),
+            (1, 1), (1, 1)]);
+        let texels = decode_block(TexturePixelFormat::Bc7, &mode_1);
+        assert_eq!(texels[7], [255, 2, 2, 255]);
+        assert_eq!(texels[8], [2, 2, 255, 255]);
+        assert_eq!(texels[15], [2, 2, 255, 255]);
+
+        // Mode 5 with rotation 1 swaps red and alpha after interpolating
+        let mode_5 = pack_fields(&[
+            (1 << 5, 6), (1, 2),
+            (127, 7), (127, 7), (0, 7), (0, 7), (0, 7), (0, 7),
+            (64, 8), (64, 8)]);
+        let texels = decode_block(TexturePixelFormat::Bc7, &mode_5);
+        assert_eq!(texels[0], [64, 0, 0, 255]);
+
+        // A block with no mode bit set is transparent black
+        let decompressed = decompress_layers(TexturePixelFormat::Bc7, 4, 4, 1, &[vec![0u8; 16]]).unwrap();
+        assert_eq!(decompressed[0], vec![0u8; 64]);
+    }
+}
@@ -3,13 +3,20 @@ use defs::{
     EngineError,
     render::{
         TexturePixelFormat,
-        ImageUsage
+        ImageUsage,
+        MipmapMode,
+        SamplerConfig,
+        SamplerFilter,
+        SamplerAddressMode
     }
 };
 use ash::{
     vk,
     Device,
-    version::DeviceV1_0
+    version::{
+        InstanceV1_0,
+        DeviceV1_0
+    }
 };
 
 /// ImageCreationParams struct
@@ -19,17 +26,20 @@ struct ImageCreationParams {
     usage: vk::ImageUsageFlags,
     aspect: vk::ImageAspectFlags,
     view_type: vk::ImageViewType,
-    layer_count: u32
+    layer_count: u32,
+    mip_levels: u32
 }
 
 /// ImageWrapper struct
 /// Wraps a Vulkan image, image view, the format used by the image, and the memory allocation
-/// backing the image
+/// backing the image. Also holds the settings for samplers that will read the image.
 pub struct ImageWrapper {
     allocation: vk_mem::Allocation,
     pub image: vk::Image,
     pub image_view: vk::ImageView,
-    pub format: vk::Format
+    pub format: vk::Format,
+    pub mip_levels: u32,
+    pub sampler_config: SamplerConfig
 }
 
 impl ImageWrapper {
@@ -40,7 +50,9 @@ impl ImageWrapper {
             allocation: vk_mem::Allocation::null(),
             image: vk::Image::null(),
             image_view: vk::ImageView::null(),
-            format: vk::Format::UNDEFINED
+            format: vk::Format::UNDEFINED,
+            mip_levels: 1,
+            sampler_config: SamplerConfig::default()
         }
     }
 
@@ -51,10 +63,54 @@ impl ImageWrapper {
         format: TexturePixelFormat,
         width: u32,
         height: u32,
-        init_layer_data: Option<&[Vec<u8>]>
+        init_layer_data: Option<&[Vec<u8>]>,
+        mipmaps: MipmapMode
     ) -> Result<ImageWrapper, EngineError> {
 
-        let creation_params = match (usage, format) {
+        // Block-compressed formats the device cannot sample are decompressed on the CPU instead
+        if let Some(compressed_format) = vk_compressed_format(format) {
+            if mipmaps == MipmapMode::GenerateOnGpu {
+                return Err(EngineError::RenderError(
+                    String::from("Mipmaps cannot be generated on the GPU for compressed textures")));
+            }
+            if !render_core.supports_sampled_format(compressed_format) {
+                let layer_data = init_layer_data.ok_or_else(|| EngineError::RenderError(
+                    String::from("Compressed textures must be initialised")))?;
+                let mip_levels = match mipmaps {
+                    MipmapMode::Provided(levels) => levels,
+                    _ => 1
+                };
+                let decompressed = crate::vk_renderer::bcn::decompress_layers(
+                    format, width, height, mip_levels, layer_data)?;
+                return Self::new(
+                    render_core,
+                    usage,
+                    TexturePixelFormat::Rgba,
+                    width,
+                    height,
+                    Some(&decompressed),
+                    mipmaps);
+            }
+        }
+
+        let full_mip_levels = 32 - width.max(height).max(1).leading_zeros();
+        let mip_levels = match mipmaps {
+            MipmapMode::None => 1,
+            MipmapMode::Provided(levels) => {
+                if levels == 0 || levels > full_mip_levels {
+                    return Err(EngineError::RenderError(
+                        format!("Invalid mip level count {} for {}x{} image", levels, width, height)));
+                }
+                levels
+            },
+            MipmapMode::GenerateOnGpu => full_mip_levels
+        };
+        if mip_levels > 1 && init_layer_data.is_none() {
+            return Err(EngineError::RenderError(
+                String::from("Mipmaps are only supported for initialised textures")));
+        }
+
+        let mut creation_params = match (usage, format) {
             // Typical depth buffer
             (ImageUsage::DepthBuffer, TexturePixelFormat::Unorm16) => {
                 if init_layer_data.is_some() {
@@ -66,22 +122,25 @@ impl ImageWrapper {
                     usage: vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
                     aspect: vk::ImageAspectFlags::DEPTH,
                     view_type: vk::ImageViewType::TYPE_2D,
-                    layer_count: 1
+                    layer_count: 1,
+                    mip_levels
                 }
             },
 
             // Typical off-screen-rendered color attachment
-            (ImageUsage::OffscreenRenderSampleColorWriteDepth, TexturePixelFormat::Rgba) => {
+            (ImageUsage::OffscreenRenderSampleColorWriteDepth, TexturePixelFormat::Rgba) |
+            (ImageUsage::OffscreenRenderSampleColorWriteDepth, TexturePixelFormat::RgbaF16) => {
                 if init_layer_data.is_some() {
                     return Err(EngineError::RenderError(
                         String::from("Initialising off-screen render image not allowed")));
                 }
                 ImageCreationParams {
-                    format: vk::Format::R8G8B8A8_UNORM,
+                    format: vk_color_format(format),
                     usage: vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::COLOR_ATTACHMENT,
                     aspect: vk::ImageAspectFlags::COLOR,
                     view_type: vk::ImageViewType::TYPE_2D,
-                    layer_count: 1
+                    layer_count: 1,
+                    mip_levels
                 }
             },
 
@@ -96,37 +155,62 @@ impl ImageWrapper {
                     usage: vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
                     aspect: vk::ImageAspectFlags::DEPTH,
                     view_type: vk::ImageViewType::TYPE_2D,
-                    layer_count: 1
+                    layer_count: 1,
+                    mip_levels
                 }
             },
 
             // Typical initialised texture
-            (ImageUsage::TextureSampleOnly, TexturePixelFormat::Rgba) => {
+            (ImageUsage::TextureSampleOnly, TexturePixelFormat::Rgba) |
+            (ImageUsage::TextureSampleOnly, TexturePixelFormat::RgbaF16) => {
                 if init_layer_data.is_none() {
                     return Err(EngineError::RenderError(
                         String::from("Not initialising sample-only texture not allowed")));
                 }
                 ImageCreationParams {
-                    format: vk::Format::R8G8B8A8_UNORM,
+                    format: vk_color_format(format),
                     usage: vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
                     aspect: vk::ImageAspectFlags::COLOR,
                     view_type: vk::ImageViewType::TYPE_2D,
-                    layer_count: 1
+                    layer_count: 1,
+                    mip_levels
                 }
             },
 
             // Typical sky box (cube map)
-            (ImageUsage::Skybox, TexturePixelFormat::Rgba) => {
+            (ImageUsage::Skybox, TexturePixelFormat::Rgba) |
+            (ImageUsage::Skybox, TexturePixelFormat::RgbaF16) => {
                 if init_layer_data.is_none() {
                     return Err(EngineError::RenderError(
                         String::from("Not initialising sample-only texture not allowed")));
                 }
                 ImageCreationParams {
-                    format: vk::Format::R8G8B8A8_UNORM,
+                    format: vk_color_format(format),
                     usage: vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
                     aspect: vk::ImageAspectFlags::COLOR,
                     view_type: vk::ImageViewType::CUBE,
-                    layer_count: 6
+                    layer_count: 6,
+                    mip_levels
+                }
+            },
+
+            // Block-compressed texture, sampled as a 2D image or a cube map
+            (ImageUsage::TextureSampleOnly, _) | (ImageUsage::Skybox, _) if format.is_block_compressed() => {
+                if init_layer_data.is_none() {
+                    return Err(EngineError::RenderError(
+                        String::from("Not initialising sample-only texture not allowed")));
+                }
+                let (view_type, layer_count) = match usage {
+                    ImageUsage::Skybox => (vk::ImageViewType::CUBE, 6),
+                    _ => (vk::ImageViewType::TYPE_2D, 1)
+                };
+                ImageCreationParams {
+                    format: vk_compressed_format(format).unwrap(),
+                    usage: vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
+                    aspect: vk::ImageAspectFlags::COLOR,
+                    view_type,
+                    layer_count,
+                    mip_levels
                 }
             },
 
@@ -137,6 +221,37 @@ impl ImageWrapper {
             }
         };
 
+        // Generating mipmaps on the GPU requires blitting from each level into the next, with
+        // linear filtering; where the device can't, RGBA mipmaps are generated on the CPU instead
+        let generate_on_gpu = mipmaps == MipmapMode::GenerateOnGpu && mip_levels > 1;
+        if generate_on_gpu {
+            let format_properties = render_core.instance.get_physical_device_format_properties(
+                render_core.physical_device_properties.physical_device,
+                creation_params.format);
+            let required_features = vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR |
+                vk::FormatFeatureFlags::BLIT_SRC |
+                vk::FormatFeatureFlags::BLIT_DST;
+            if !format_properties.optimal_tiling_features.contains(required_features) {
+                if format != TexturePixelFormat::Rgba {
+                    return Err(EngineError::RenderError(
+                        String::from("Device cannot generate mipmaps for this format, and they can only be generated on the CPU for RGBA textures")));
+                }
+                let mut layer_data = init_layer_data.unwrap_or(&[]).to_vec();
+                for layer in layer_data.iter_mut() {
+                    crate::vk_renderer::mipmaps::append_rgba_mip_chain(layer, width, height);
+                }
+                return Self::new(
+                    render_core,
+                    usage,
+                    format,
+                    width,
+                    height,
+                    Some(&layer_data),
+                    MipmapMode::Provided(mip_levels));
+            }
+            creation_params.usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+        }
+
         let (allocation, image, image_view) = Self::make_image_and_view(
             render_core,
             width,
@@ -146,20 +261,58 @@ impl ImageWrapper {
         if let Some(layer_data) = init_layer_data {
             Self::initialise_read_only_color_texture(
                 render_core,
-                width,
-                height,
+                format,
+                vk::Extent2D { width, height },
                 &image,
-                layer_data)?;
+                layer_data,
+                mip_levels,
+                generate_on_gpu)?;
         }
 
         Ok(ImageWrapper {
             allocation,
             image,
             image_view,
-            format: creation_params.format
+            format: creation_params.format,
+            mip_levels,
+            sampler_config: SamplerConfig::default()
         })
     }
 
+    /// Create a sampler for reading this image, according to its sampler config
+    pub fn create_sampler(
+        &self,
+        render_core: &crate::vk_renderer::render_core::RenderCore
+    ) -> Result<vk::Sampler, EngineError> {
+        let config = &self.sampler_config;
+        let anisotropy_supported =
+            render_core.physical_device_properties.required_features.sampler_anisotropy == vk::TRUE;
+        let max_anisotropy = config.max_anisotropy
+            .min(render_core.physical_device_properties.max_sampler_anisotropy);
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .min_filter(vk_filter(config.min_filter))
+            .mag_filter(vk_filter(config.mag_filter))
+            .mipmap_mode(match config.mipmap_filter {
+                SamplerFilter::Nearest => vk::SamplerMipmapMode::NEAREST,
+                SamplerFilter::Linear => vk::SamplerMipmapMode::LINEAR
+            })
+            .address_mode_u(vk_address_mode(config.address_mode_u))
+            .address_mode_v(vk_address_mode(config.address_mode_v))
+            .address_mode_w(vk_address_mode(config.address_mode_w))
+            .anisotropy_enable(anisotropy_supported && max_anisotropy > 1.0)
+            .max_anisotropy(max_anisotropy.max(1.0))
+            .mip_lod_bias(config.lod_bias)
+            .min_lod(0.0)
+            .max_lod(self.mip_levels as f32);
+        unsafe {
+            render_core.device
+                .create_sampler(&sampler_info, None)
+                .map_err(|e| {
+                    EngineError::RenderError(format!("Error creating sampler: {:?}", e))
+                })
+        }
+    }
+
     /// Create the image and image view
     unsafe fn make_image_and_view(
         render_core: &crate::vk_renderer::render_core::RenderCore,
@@ -181,7 +334,7 @@ impl ImageWrapper {
             .flags(flags)
             .format(creation_params.format)
             .extent(extent3d)
-            .mip_levels(1)
+            .mip_levels(creation_params.mip_levels)
             .array_layers(creation_params.layer_count)
             .samples(vk::SampleCountFlags::TYPE_1)
             .tiling(vk::ImageTiling::OPTIMAL)
@@ -202,7 +355,7 @@ impl ImageWrapper {
         let subresource_range = vk::ImageSubresourceRange::builder()
             .aspect_mask(creation_params.aspect)
             .base_mip_level(0)
-            .level_count(1)
+            .level_count(creation_params.mip_levels)
             .base_array_layer(0)
             .layer_count(creation_params.layer_count);
         let image_view_create_info = vk::ImageViewCreateInfo::builder()
@@ -234,23 +387,33 @@ impl ImageWrapper {
 
     /// Initialise the image's memory with texture data; uses a staging buffer to allocate device-
     /// local memory and transitions the image into the optimal layout for reading in samplers in
-    /// shaders
+    /// shaders. Each layer's data holds either every mip level, packed largest first, or only the
+    /// base level if the rest are to be generated on the GPU. GPU generation needs blits, so it
+    /// runs on the graphics queue rather than the transfer queue.
     unsafe fn initialise_read_only_color_texture(
         render_core: &crate::vk_renderer::render_core::RenderCore,
-        width: u32,
-        height: u32,
+        format: TexturePixelFormat,
+        extent: vk::Extent2D,
         image: &vk::Image,
-        layer_data: &[Vec<u8>]) -> Result<(), EngineError> {
+        layer_data: &[Vec<u8>],
+        mip_levels: u32,
+        generate_on_gpu: bool) -> Result<(), EngineError> {
 
         if layer_data.is_empty() {
             panic!("Passed empty layer data as ImageWrapper init data")
         }
         let layer_count = layer_data.len();
         let layer_size_bytes = layer_data[0].len();
+        let provided_levels = if generate_on_gpu { 1 } else { mip_levels };
 
         // Staging buffer
-        let expected_data_size: usize = layer_count * 4 * width as usize * height as usize;
-        if expected_data_size != layer_count * layer_size_bytes {
+        let level_extents: Vec<(u32, u32)> = (0..provided_levels)
+            .map(|level| ((extent.width >> level).max(1), (extent.height >> level).max(1)))
+            .collect();
+        let expected_layer_size: usize = level_extents.iter()
+            .map(|(w, h)| format.level_size_bytes(*w, *h))
+            .sum();
+        if layer_data.iter().any(|data| data.len() != expected_layer_size) {
             panic!("Image data does not match expected size");
         }
         let mut staging_buffer = crate::vk_renderer::buffers::BufferWrapper::new(
@@ -267,9 +430,14 @@ impl ImageWrapper {
         }
 
         // Allocate a single-use command buffer and begin recording
-        // Using the transfer queue for this - note that it doesn't support all access or pipeline stage flags
+        // Using the transfer queue for this where possible - note that it doesn't support all
+        // access or pipeline stage flags
+        let (command_pool, queue) = match generate_on_gpu {
+            true => (render_core.graphics_command_buffer_pool, render_core.graphics_queue),
+            false => (render_core.transfer_command_buffer_pool, render_core.transfer_queue)
+        };
         let command_buffer_alloc_info = vk::CommandBufferAllocateInfo::builder()
-            .command_pool(render_core.transfer_command_buffer_pool)
+            .command_pool(command_pool)
             .command_buffer_count(1);
         let copy_command_buffer = render_core.device
             .allocate_command_buffers(&command_buffer_alloc_info)
@@ -295,7 +463,7 @@ impl ImageWrapper {
             .subresource_range(vk::ImageSubresourceRange {
                 aspect_mask: vk::ImageAspectFlags::COLOR,
                 base_mip_level: 0,
-                level_count: 1,
+                level_count: mip_levels,
                 base_array_layer: 0,
                 layer_count: layer_count as u32
             })
@@ -310,55 +478,72 @@ impl ImageWrapper {
             &[barrier]
         );
 
-        // Copy command
-        let image_subresource = vk::ImageSubresourceLayers {
-            aspect_mask: vk::ImageAspectFlags::COLOR,
-            mip_level: 0,
-            base_array_layer: 0,
-            layer_count: layer_count as u32
-        };
-        let region = vk::BufferImageCopy {
-            buffer_offset: 0,
-            buffer_row_length: 0,
-            buffer_image_height: 0,
-            image_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
-            image_extent: vk::Extent3D { width, height, depth: 1 },
-            image_subresource
-        };
+        // Copy commands, one per level per layer
+        let mut regions = vec![];
+        for layer_no in 0..layer_count {
+            let mut level_offset = 0;
+            for (level, (level_width, level_height)) in level_extents.iter().enumerate() {
+                regions.push(vk::BufferImageCopy {
+                    buffer_offset: (layer_no * layer_size_bytes + level_offset) as u64,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+                    image_extent: vk::Extent3D { width: *level_width, height: *level_height, depth: 1 },
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        mip_level: level as u32,
+                        base_array_layer: layer_no as u32,
+                        layer_count: 1
+                    }
+                });
+                level_offset += format.level_size_bytes(*level_width, *level_height);
+            }
+        }
         render_core.device.cmd_copy_buffer_to_image(
             copy_command_buffer,
             staging_buffer.buffer,
             *image,
             vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-            &[region]
+            regions.as_slice()
         );
 
-        // Final memory dependency
-        let barrier = vk::ImageMemoryBarrier::builder()
-            .image(*image)
-            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
-            .dst_access_mask(vk::AccessFlags::MEMORY_READ)
-            .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
-            .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
-            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-            .subresource_range(vk::ImageSubresourceRange {
-                aspect_mask: vk::ImageAspectFlags::COLOR,
-                base_mip_level: 0,
-                level_count: 1,
-                base_array_layer: 0,
-                layer_count: layer_count as u32
-            })
-            .build();
-        render_core.device.cmd_pipeline_barrier(
-            copy_command_buffer,
-            vk::PipelineStageFlags::TRANSFER,
-            vk::PipelineStageFlags::BOTTOM_OF_PIPE,
-            vk::DependencyFlags::empty(),
-            &[],
-            &[],
-            &[barrier]
-        );
+        // Final memory dependency, after generating the remaining levels if needed
+        if generate_on_gpu {
+            Self::record_mipmap_generation(
+                render_core,
+                copy_command_buffer,
+                image,
+                extent.width,
+                extent.height,
+                layer_count as u32,
+                mip_levels);
+        } else {
+            let barrier = vk::ImageMemoryBarrier::builder()
+                .image(*image)
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::MEMORY_READ)
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .subresource_range(vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: 0,
+                    level_count: mip_levels,
+                    base_array_layer: 0,
+                    layer_count: layer_count as u32
+                })
+                .build();
+            render_core.device.cmd_pipeline_barrier(
+                copy_command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[barrier]
+            );
+        }
 
         // Finish recording commands, create a fence, run the command, wait for fence, clean up
         render_core.device.end_command_buffer(copy_command_buffer)
@@ -376,7 +561,7 @@ impl ImageWrapper {
                 EngineError::RenderError(format!("Error creating fence: {:?}", e))
             })?;
         render_core.device
-            .queue_submit(render_core.transfer_queue, &submit_infos, fence)
+            .queue_submit(queue, &submit_infos, fence)
             .map_err(|e| {
                 EngineError::RenderError(format!("Error submitting to queue: {:?}", e))
             })?;
@@ -390,9 +575,166 @@ impl ImageWrapper {
         staging_buffer.destroy(render_core.get_mem_allocator())?;
         render_core.device
             .free_command_buffers(
-            render_core.transfer_command_buffer_pool,
+            command_pool,
             &[copy_command_buffer]);
 
         Ok(())
     }
+
+    /// Record commands to fill mip levels 1 and up by repeatedly blitting from the level above,
+    /// leaving every level in the shader read-only layout. Expects all levels to be in the
+    /// transfer destination layout, with level 0 already written.
+    unsafe fn record_mipmap_generation(
+        render_core: &crate::vk_renderer::render_core::RenderCore,
+        command_buffer: vk::CommandBuffer,
+        image: &vk::Image,
+        width: u32,
+        height: u32,
+        layer_count: u32,
+        mip_levels: u32
+    ) {
+        let level_barrier = |level: u32, old_layout, new_layout, src_access, dst_access| {
+            vk::ImageMemoryBarrier::builder()
+                .image(*image)
+                .src_access_mask(src_access)
+                .dst_access_mask(dst_access)
+                .old_layout(old_layout)
+                .new_layout(new_layout)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .subresource_range(vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: level,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count
+                })
+                .build()
+        };
+
+        for level in 1..mip_levels {
+            let src_width = (width >> (level - 1)).max(1) as i32;
+            let src_height = (height >> (level - 1)).max(1) as i32;
+            let dst_width = (width >> level).max(1) as i32;
+            let dst_height = (height >> level).max(1) as i32;
+
+            // Previous level becomes the blit source
+            render_core.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[level_barrier(
+                    level - 1,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    vk::AccessFlags::TRANSFER_WRITE,
+                    vk::AccessFlags::TRANSFER_READ)]
+            );
+
+            let blit = vk::ImageBlit {
+                src_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: level - 1,
+                    base_array_layer: 0,
+                    layer_count
+                },
+                src_offsets: [
+                    vk::Offset3D { x: 0, y: 0, z: 0 },
+                    vk::Offset3D { x: src_width, y: src_height, z: 1 }
+                ],
+                dst_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: level,
+                    base_array_layer: 0,
+                    layer_count
+                },
+                dst_offsets: [
+                    vk::Offset3D { x: 0, y: 0, z: 0 },
+                    vk::Offset3D { x: dst_width, y: dst_height, z: 1 }
+                ]
+            };
+            render_core.device.cmd_blit_image(
+                command_buffer,
+                *image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                *image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[blit],
+                vk::Filter::LINEAR
+            );
+
+            // Previous level is finished with
+            render_core.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[level_barrier(
+                    level - 1,
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    vk::AccessFlags::TRANSFER_READ,
+                    vk::AccessFlags::SHADER_READ)]
+            );
+        }
+
+        // Last level was only ever written to
+        render_core.device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[level_barrier(
+                mip_levels - 1,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                vk::AccessFlags::TRANSFER_WRITE,
+                vk::AccessFlags::SHADER_READ)]
+        );
+    }
+}
+
+/// Map the engine's filter setting to the Vulkan equivalent
+fn vk_filter(filter: SamplerFilter) -> vk::Filter {
+    match filter {
+        SamplerFilter::Nearest => vk::Filter::NEAREST,
+        SamplerFilter::Linear => vk::Filter::LINEAR
+    }
+}
+
+/// Map the engine's address mode setting to the Vulkan equivalent
+fn vk_address_mode(mode: SamplerAddressMode) -> vk::SamplerAddressMode {
+    match mode {
+        SamplerAddressMode::Repeat => vk::SamplerAddressMode::REPEAT,
+        SamplerAddressMode::MirroredRepeat => vk::SamplerAddressMode::MIRRORED_REPEAT,
+        SamplerAddressMode::ClampToEdge => vk::SamplerAddressMode::CLAMP_TO_EDGE
+    }
+}
+
+/// Vulkan format matching an uncompressed colour texture format
+fn vk_color_format(format: TexturePixelFormat) -> vk::Format {
+    match format {
+        TexturePixelFormat::RgbaF16 => vk::Format::R16G16B16A16_SFLOAT,
+        _ => vk::Format::R8G8B8A8_UNORM
+    }
+}
+
+/// Vulkan format matching a block-compressed texture format, or None for uncompressed formats
+fn vk_compressed_format(format: TexturePixelFormat) -> Option<vk::Format> {
+    match format {
+        TexturePixelFormat::Bc1 => Some(vk::Format::BC1_RGBA_UNORM_BLOCK),
+        TexturePixelFormat::Bc2 => Some(vk::Format::BC2_UNORM_BLOCK),
+        TexturePixelFormat::Bc3 => Some(vk::Format::BC3_UNORM_BLOCK),
+        TexturePixelFormat::Bc4 => Some(vk::Format::BC4_UNORM_BLOCK),
+        TexturePixelFormat::Bc5 => Some(vk::Format::BC5_UNORM_BLOCK),
+        TexturePixelFormat::Bc7 => Some(vk::Format::BC7_UNORM_BLOCK),
+        _ => None
+    }
 }
@@ -0,0 +1,35 @@
+/// Append a full mip chain, down to 1x1, to a single level of 8-bit RGBA data, using a box filter.
+/// Where a side has odd length, its last texel is shared out with the texels before it, so that
+/// every texel of one level counts towards the next. Returns the number of levels now held.
+pub fn append_rgba_mip_chain(layer: &mut Vec<u8>, width: u32, height: u32) -> u32 {
+    let level_count = 32 - width.max(height).max(1).leading_zeros();
+    let mut level_start = 0;
+    let mut width = width as usize;
+    let mut height = height as usize;
+    for _ in 1..level_count {
+        let next_width = (width / 2).max(1);
+        let next_height = (height / 2).max(1);
+        let mut next_level = Vec::with_capacity(next_width * next_height * 4);
+        for y in 0..next_height {
+            for x in 0..next_width {
+                let sample_xs = (x * width / next_width)..((x + 1) * width / next_width);
+                let sample_ys = (y * height / next_height)..((y + 1) * height / next_height);
+                let sample_count = (sample_xs.len() * sample_ys.len()) as u32;
+                for channel in 0..4 {
+                    let mut sum: u32 = 0;
+                    for sample_y in sample_ys.clone() {
+                        for sample_x in sample_xs.clone() {
+                            sum += layer[level_start + (sample_y * width + sample_x) * 4 + channel] as u32;
+                        }
+                    }
+                    next_level.push(((sum + sample_count / 2) / sample_count) as u8);
+                }
+            }
+        }
+        level_start += width * height * 4;
+        width = next_width;
+        height = next_height;
+        layer.extend_from_slice(&next_level);
+    }
+    level_count
+}
@@ -1,6 +1,8 @@
 
 pub mod buffers;
 pub mod images;
+pub mod mipmaps;
+mod bcn;
 mod render_core;
 mod per_image_resources;
 
@@ -18,9 +20,11 @@ use defs::{
         PresentResult,
         ResourcePreloads,
         DrawingDescription,
-        FeatureDeclaration
+        FeatureDeclaration,
+        InstanceData
     }
 };
+use model::types::StaticVertex;
 
 use ash::Entry;
 use raw_window_handle::HasRawWindowHandle;
@@ -109,6 +113,49 @@ impl RendererApi for VkRenderer {
             self.render_core.load_new_resources(resource_preloads).unwrap();
         }
 
+        self.rebuild_per_image_resources(description)
+    }
+
+    fn replace_scene_resources(&mut self, replacements: &ResourcePreloads, description: &DrawingDescription) -> Result<(), EngineError> {
+        self.render_core.wait_until_idle().unwrap();
+
+        // Swap out the image and buffer resources
+        unsafe {
+            self.render_core.replace_resources(replacements)?;
+        }
+
+        // Descriptor sets and command buffers refer to the old resources, so rebuild them
+        self.rebuild_per_image_resources(description)
+    }
+
+    fn update_instance_data(&mut self, instance_buffer_index: usize, instances: &[InstanceData]) -> Result<(), EngineError> {
+
+        // Each swapchain image's pipelines copy from here before their next frame is drawn
+        self.render_core.set_instance_data(instance_buffer_index, instances)
+    }
+
+    fn update_dynamic_vbo_data(&mut self, vbo_index: usize, vertices: &[StaticVertex]) -> Result<(), EngineError> {
+
+        // As with instances, each swapchain image's pipelines copy from here before their next
+        // frame is drawn, so nothing in use by the device is touched
+        self.render_core.set_dynamic_vbo_data(vbo_index, vertices)
+    }
+
+    fn get_aspect_ratio(&self) -> f32 {
+        if let Ok(extent) = self.render_core.get_extent() {
+            extent.width as f32 / extent.height as f32
+        } else {
+            1.0
+        }
+    }
+}
+
+impl VkRenderer {
+
+    /// Destroy and recreate all per-swapchain-image resources, with new command buffers recorded
+    /// for the given description. The device should be idle before calling this.
+    fn rebuild_per_image_resources(&mut self, description: &DrawingDescription) -> Result<(), EngineError> {
+
         // Destroy existing pipelines and render passes
         for resources in self.per_image_resources.iter_mut() {
             resources.destroy_resources(&self.render_core);
@@ -131,14 +178,6 @@ impl RendererApi for VkRenderer {
         }
         Ok(())
     }
-
-    fn get_aspect_ratio(&self) -> f32 {
-        if let Ok(extent) = self.render_core.get_extent() {
-            extent.width as f32 / extent.height as f32
-        } else {
-            1.0
-        }
-    }
 }
 
 impl Drop for VkRenderer {
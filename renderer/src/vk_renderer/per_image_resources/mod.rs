@@ -92,8 +92,8 @@ impl PerImageResources {
         }
     }
 
-    /// Perform pre-rendering operations, namely updating the uniform buffer for each pass within
-    /// this full render description
+    /// Perform pre-rendering operations, namely updating the uniform buffers and draw commands for
+    /// each pass within this full render description
     pub unsafe fn on_pre_render(
         &mut self,
         render_core: &mut crate::vk_renderer::render_core::RenderCore,
@@ -102,6 +102,8 @@ impl PerImageResources {
         for (pass_index, resources) in self.resources.iter_mut().enumerate() {
             resources.renderpass_pipeline_set
                 .update_uniform_buffer(render_core, scene_info, pass_index).unwrap();
+            resources.renderpass_pipeline_set
+                .update_draw_commands(render_core, scene_info, pass_index).unwrap();
         }
     }
 
@@ -11,7 +11,8 @@ use ash::{
 /// PerPassResources struct
 pub struct PerPassResources {
     pub renderpass: crate::vk_renderer::per_image_resources::renderpass::RenderpassWrapper,
-    pub renderpass_pipeline_set: crate::vk_renderer::per_image_resources::pipeline_set::PipelineSet
+    pub renderpass_pipeline_set: crate::vk_renderer::per_image_resources::pipeline_set::PipelineSet,
+    reversed_depth: bool
 }
 
 impl PerPassResources {
@@ -34,7 +35,8 @@ impl PerPassResources {
                 pass)?;
         Ok(PerPassResources {
             renderpass,
-            renderpass_pipeline_set
+            renderpass_pipeline_set,
+            reversed_depth: pass.reversed_depth
         })
     }
 
@@ -53,7 +55,10 @@ impl PerPassResources {
             },
             vk::ClearValue {
                 depth_stencil: vk::ClearDepthStencilValue {
-                    depth: 1.0,
+                    depth: match self.reversed_depth {
+                        true => 0.0,
+                        false => 1.0
+                    },
                     stencil: 0
                 }
             }
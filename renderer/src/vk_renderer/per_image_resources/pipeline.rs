@@ -3,10 +3,12 @@ use defs::{
     EngineError,
     render::{
         DrawingStep,
+        InstanceData,
         Shader,
         VertexFormat
     }
 };
+use model::types::StaticVertex;
 use ash::{
     vk,
     version::DeviceV1_0
@@ -21,6 +23,11 @@ pub struct PipelineWrapper {
     fragment_shader_module: vk::ShaderModule,
     vertex_buffer: vk::Buffer,
     vertex_count: usize,
+    dynamic_vbo_index: Option<usize>,
+    dynamic_vertex_buffer: crate::vk_renderer::buffers::BufferWrapper,
+    instance_buffer_index: Option<usize>,
+    instance_buffer: crate::vk_renderer::buffers::BufferWrapper,
+    indirect_buffer: crate::vk_renderer::buffers::BufferWrapper,
     uniform_buffer: crate::vk_renderer::buffers::BufferWrapper,
     texture_image_views: Vec<vk::ImageView>,
     samplers: Vec<vk::Sampler>,
@@ -40,6 +47,11 @@ impl PipelineWrapper {
             fragment_shader_module: vk::ShaderModule::null(),
             vertex_buffer: vk::Buffer::null(),
             vertex_count: 0,
+            dynamic_vbo_index: None,
+            dynamic_vertex_buffer: crate::vk_renderer::buffers::BufferWrapper::empty(),
+            instance_buffer_index: None,
+            instance_buffer: crate::vk_renderer::buffers::BufferWrapper::empty(),
+            indirect_buffer: crate::vk_renderer::buffers::BufferWrapper::empty(),
             uniform_buffer: crate::vk_renderer::buffers::BufferWrapper::empty(),
             texture_image_views: vec![],
             samplers: vec![],
@@ -58,6 +70,13 @@ impl PipelineWrapper {
             render_core.device.destroy_pipeline(self.pipeline, None);
             render_core.device.destroy_pipeline_layout(self.pipeline_layout, None);
             self.uniform_buffer.destroy(allocator).unwrap();
+            self.indirect_buffer.destroy(allocator).unwrap();
+            if self.instance_buffer_index.is_some() {
+                self.instance_buffer.destroy(allocator).unwrap();
+            }
+            if self.dynamic_vbo_index.is_some() {
+                self.dynamic_vertex_buffer.destroy(allocator).unwrap();
+            }
             render_core.device.destroy_descriptor_pool(self.descriptor_pool, None);
             render_core.device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
             for sampler in self.samplers.iter() {
@@ -74,9 +93,12 @@ impl PipelineWrapper {
         render_core: &crate::vk_renderer::render_core::RenderCore,
         renderpass_wrapper: &crate::vk_renderer::per_image_resources::renderpass::RenderpassWrapper,
         description: &DrawingStep,
+        reversed_depth: bool,
         render_extent: vk::Extent2D
     ) -> Result<(), EngineError> {
 
+        check_instancing(description)?;
+
         // Make shader modules
         let vertex_shader_create_info = vk::ShaderModuleCreateInfo::builder()
             .code(match description.shader {
@@ -86,12 +108,16 @@ impl PipelineWrapper {
                     vk_shader_macros::include_glsl!("shaders/vk/triangle_clipped.vert"),
                 Shader::Text =>
                     vk_shader_macros::include_glsl!("shaders/vk/text.vert"),
+                Shader::TextSdf =>
+                    vk_shader_macros::include_glsl!("shaders/vk/text.vert"),
                 Shader::Cube =>
                     vk_shader_macros::include_glsl!("shaders/vk/cube.vert"),
                 Shader::CubeClipped =>
                     vk_shader_macros::include_glsl!("shaders/vk/cube_clipped.vert"),
                 Shader::Water =>
                     vk_shader_macros::include_glsl!("shaders/vk/water.vert"),
+                Shader::PlainPntInstanced =>
+                    vk_shader_macros::include_glsl!("shaders/vk/triangle_instanced.vert"),
             });
         let vertex_shader_module = render_core.device
             .create_shader_module(&vertex_shader_create_info, None)
@@ -104,12 +130,16 @@ impl PipelineWrapper {
                     vk_shader_macros::include_glsl!("shaders/vk/triangle.frag"),
                 Shader::Text =>
                     vk_shader_macros::include_glsl!("shaders/vk/text.frag"),
+                Shader::TextSdf =>
+                    vk_shader_macros::include_glsl!("shaders/vk/text_sdf.frag"),
                 Shader::Cube =>
                     vk_shader_macros::include_glsl!("shaders/vk/cube.frag"),
                 Shader::CubeClipped =>
                     vk_shader_macros::include_glsl!("shaders/vk/cube.frag"),
                 Shader::Water =>
                     vk_shader_macros::include_glsl!("shaders/vk/water.frag"),
+                Shader::PlainPntInstanced =>
+                    vk_shader_macros::include_glsl!("shaders/vk/triangle_instanced.frag"),
             });
         let fragment_shader_module = render_core.device
             .create_shader_module(&fragment_shader_create_info, None)
@@ -126,15 +156,30 @@ impl PipelineWrapper {
         let shader_stages =
             vec![vertex_shader_stage.build(), fragment_shader_stage.build()];
 
-        // Vertex buffer
-        let (vbo_vertex_count, vbo_handle) =
-            render_core.query_vbo(description.vbo_index)?;
+        // Vertex buffer; a dynamic VBO gets a buffer of its own, sized for the most vertices it
+        // may hold, with its vertex count set by the indirect draw command
         let vertex_size_bytes: usize = match description.vbo_format {
             VertexFormat::PositionNormalTexture => 32
         };
+        let (dynamic_vbo_index, dynamic_vertex_buffer) = match render_core.query_dynamic_vbo_data(description.vbo_index) {
+            Some((capacity, _)) => {
+                let buffer = crate::vk_renderer::buffers::BufferWrapper::new(
+                    render_core.get_mem_allocator(),
+                    capacity.max(1) * vertex_size_bytes,
+                    vk::BufferUsageFlags::VERTEX_BUFFER,
+                    vk_mem::MemoryUsage::CpuToGpu)?;
+                (Some(description.vbo_index), buffer)
+            },
+            None => (None, crate::vk_renderer::buffers::BufferWrapper::empty())
+        };
+        let (vbo_vertex_count, vbo_handle) = match dynamic_vbo_index {
+            Some(_) => (0, dynamic_vertex_buffer.buffer()),
+            None => render_core.query_vbo(description.vbo_index)?
+        };
 
-        // Vertex input configuration
-        let vertex_attrib_descriptions = [
+        // Vertex input configuration; instance data follows the vertex attributes, with the model
+        // matrix taking one location per column
+        let mut vertex_attrib_descriptions = vec![
             vk::VertexInputAttributeDescription {
                 binding: 0,
                 location: 0,
@@ -154,13 +199,34 @@ impl PipelineWrapper {
                 format: vk::Format::R32G32_SFLOAT
             }
         ];
-        let vertex_binding_descriptions = [
+        let mut vertex_binding_descriptions = vec![
             vk::VertexInputBindingDescription {
                 binding: 0,
                 stride: vertex_size_bytes as u32,
                 input_rate: vk::VertexInputRate::VERTEX
             }
         ];
+        if description.instance_buffer_index.is_some() {
+            for column in 0..4 {
+                vertex_attrib_descriptions.push(vk::VertexInputAttributeDescription {
+                    binding: 1,
+                    location: 3 + column,
+                    offset: 16 * column,
+                    format: vk::Format::R32G32B32A32_SFLOAT
+                });
+            }
+            vertex_attrib_descriptions.push(vk::VertexInputAttributeDescription {
+                binding: 1,
+                location: 7,
+                offset: 64,
+                format: vk::Format::R32G32B32A32_SFLOAT
+            });
+            vertex_binding_descriptions.push(vk::VertexInputBindingDescription {
+                binding: 1,
+                stride: std::mem::size_of::<InstanceData>() as u32,
+                input_rate: vk::VertexInputRate::INSTANCE
+            });
+        }
         let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::builder()
             .vertex_attribute_descriptions(&vertex_attrib_descriptions)
             .vertex_binding_descriptions(&vertex_binding_descriptions);
@@ -171,10 +237,12 @@ impl PipelineWrapper {
         let ubo_size_bytes: usize = match description.shader {
             Shader::PlainPnt => 4 * 16,
             Shader::PlainPntClipped => 4 * 16 + 4 * 4,
-            Shader::Text => 4 * 20,
+            Shader::Text => 4 * 40,
+            Shader::TextSdf => 4 * 40,
             Shader::Cube => 4 * 16,
             Shader::CubeClipped => 4 * 16 + 4 * 4,
             Shader::Water => 4 * 16,
+            Shader::PlainPntInstanced => 4 * 16,
         };
         let uniform_buffer = {
             let uniform_buffer_data: Vec<f32> = vec![0.0; ubo_size_bytes];
@@ -192,6 +260,27 @@ impl PipelineWrapper {
             buffer
         };
 
+        // Instance buffer, sized for the most instances it may hold
+        let instance_buffer = match description.instance_buffer_index {
+            Some(index) => {
+                let (capacity, _) = render_core.query_instance_data(index)?;
+                crate::vk_renderer::buffers::BufferWrapper::new(
+                    render_core.get_mem_allocator(),
+                    capacity.max(1) * std::mem::size_of::<InstanceData>(),
+                    vk::BufferUsageFlags::VERTEX_BUFFER,
+                    vk_mem::MemoryUsage::CpuToGpu)?
+            },
+            None => crate::vk_renderer::buffers::BufferWrapper::empty()
+        };
+
+        // Indirect draw command, saying how many instances to draw this frame; none if the step
+        // is not visible
+        let indirect_buffer = crate::vk_renderer::buffers::BufferWrapper::new(
+            render_core.get_mem_allocator(),
+            std::mem::size_of::<vk::DrawIndirectCommand>(),
+            vk::BufferUsageFlags::INDIRECT_BUFFER,
+            vk_mem::MemoryUsage::CpuToGpu)?;
+
         // Texture image
         let mut texture_image_views: Vec<vk::ImageView> = description.texture_indices.iter()
             .map(|texture_index|
@@ -199,15 +288,11 @@ impl PipelineWrapper {
             )
             .collect();
 
-        // Samplers
-        let sampler_info = vk::SamplerCreateInfo::builder()
-            .min_filter(vk::Filter::LINEAR)
-            .mag_filter(vk::Filter::LINEAR);
-        let mut samplers: Vec<vk::Sampler> = texture_image_views.iter()
-            .map(|_|
-                render_core.device
-                    .create_sampler(&sampler_info, None)
-                    .map_err(|e| format!("Error creating sampler: {:?}", e))
+        // Samplers, configured per texture
+        let mut samplers: Vec<vk::Sampler> = description.texture_indices.iter()
+            .map(|texture_index|
+                render_core.query_texture(*texture_index).unwrap()
+                    .create_sampler(render_core)
                     .unwrap()
             )
             .collect();
@@ -217,9 +302,11 @@ impl PipelineWrapper {
             Shader::PlainPnt => vk::ShaderStageFlags::VERTEX,
             Shader::PlainPntClipped => vk::ShaderStageFlags::VERTEX,
             Shader::Text => vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+            Shader::TextSdf => vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
             Shader::Cube => vk::ShaderStageFlags::VERTEX,
             Shader::CubeClipped => vk::ShaderStageFlags::VERTEX,
             Shader::Water => vk::ShaderStageFlags::VERTEX,
+            Shader::PlainPntInstanced => vk::ShaderStageFlags::VERTEX,
         };
         let descriptor_set_layout_binding_infos: Vec<vk::DescriptorSetLayoutBinding> = {
             let mut bindings = vec![vk::DescriptorSetLayoutBinding::builder()
@@ -336,7 +423,10 @@ impl PipelineWrapper {
         let depth_stencil_info = vk::PipelineDepthStencilStateCreateInfo::builder()
             .depth_test_enable(true)
             .depth_write_enable(true)
-            .depth_compare_op(vk::CompareOp::LESS_OR_EQUAL);
+            .depth_compare_op(match reversed_depth {
+                true => vk::CompareOp::GREATER_OR_EQUAL,
+                false => vk::CompareOp::LESS_OR_EQUAL
+            });
         let colour_blend_attachments = [
             vk::PipelineColorBlendAttachmentState::builder()
                 .blend_enable(true)
@@ -384,6 +474,11 @@ impl PipelineWrapper {
         self.fragment_shader_module = fragment_shader_module;
         self.vertex_buffer = vbo_handle;
         self.vertex_count = vbo_vertex_count;
+        self.dynamic_vbo_index = dynamic_vbo_index;
+        self.dynamic_vertex_buffer = dynamic_vertex_buffer;
+        self.instance_buffer_index = description.instance_buffer_index;
+        self.instance_buffer = instance_buffer;
+        self.indirect_buffer = indirect_buffer;
         self.uniform_buffer = uniform_buffer;
         self.texture_image_views.clear();
         self.texture_image_views.append(&mut texture_image_views);
@@ -394,6 +489,7 @@ impl PipelineWrapper {
         self.descriptor_set = descriptor_set;
         self.pipeline_layout = pipeline_layout;
         self.pipeline = graphics_pipeline[0];
+        self.update_draw_command(render_core, true)?;
 
         Ok(())
     }
@@ -409,11 +505,6 @@ impl PipelineWrapper {
             command_buffer,
             vk::PipelineBindPoint::GRAPHICS,
             self.pipeline);
-        render_core.device.cmd_bind_vertex_buffers(
-            command_buffer,
-            0,
-            &[self.vertex_buffer],
-            &[0]);
         render_core.device.cmd_bind_descriptor_sets(
             command_buffer,
             vk::PipelineBindPoint::GRAPHICS,
@@ -421,12 +512,79 @@ impl PipelineWrapper {
             0,
             &[self.descriptor_set],
             &[]);
-        render_core.device.cmd_draw(
+        match self.instance_buffer_index {
+            Some(_) => render_core.device.cmd_bind_vertex_buffers(
+                command_buffer,
+                0,
+                &[self.vertex_buffer, self.instance_buffer.buffer()],
+                &[0, 0]),
+            None => render_core.device.cmd_bind_vertex_buffers(
+                command_buffer,
+                0,
+                &[self.vertex_buffer],
+                &[0])
+        }
+        render_core.device.cmd_draw_indirect(
             command_buffer,
-            self.vertex_count as u32,
+            self.indirect_buffer.buffer(),
+            0,
             1,
+            std::mem::size_of::<vk::DrawIndirectCommand>() as u32);
+    }
+
+    /// Set the instance count of the indirect draw command for this step: none if it is not
+    /// visible, otherwise one, or for instanced steps however many instances are current, which
+    /// are copied into the instance buffer. Steps drawing a dynamic VBO likewise copy its current
+    /// vertices into their own vertex buffer and draw that many. The recorded commands are
+    /// unchanged, so what is drawn can vary between frames.
+    pub unsafe fn update_draw_command(
+        &mut self,
+        render_core: &crate::vk_renderer::render_core::RenderCore,
+        visible: bool
+    ) -> Result<(), EngineError> {
+        let allocator = render_core.get_mem_allocator();
+        let instance_count = match (visible, self.instance_buffer_index) {
+            (false, _) => 0,
+            (true, None) => 1,
+            (true, Some(index)) => {
+                let (_, instances) = render_core.query_instance_data(index)?;
+                if !instances.is_empty() {
+                    self.instance_buffer.update::<InstanceData>(
+                        allocator,
+                        0,
+                        instances.as_ptr(),
+                        instances.len())?;
+                }
+                instances.len()
+            }
+        };
+        let vertex_count = match self.dynamic_vbo_index {
+            Some(index) => {
+                let (_, vertices) = render_core.query_dynamic_vbo_data(index)
+                    .ok_or_else(|| EngineError::RenderError(
+                        String::from("Queried dynamic VBO that is not loaded")))?;
+                if !vertices.is_empty() {
+                    self.dynamic_vertex_buffer.update::<StaticVertex>(
+                        allocator,
+                        0,
+                        vertices.as_ptr(),
+                        vertices.len())?;
+                }
+                vertices.len()
+            },
+            None => self.vertex_count
+        };
+        let draw_command = vk::DrawIndirectCommand {
+            vertex_count: vertex_count as u32,
+            instance_count: instance_count as u32,
+            first_vertex: 0,
+            first_instance: 0
+        };
+        self.indirect_buffer.update::<vk::DrawIndirectCommand>(
+            allocator,
             0,
-            0);
+            &draw_command,
+            1)
     }
 
     /// Update the uniform buffer for this step from the supplied pointer and data size
@@ -443,3 +601,46 @@ impl PipelineWrapper {
             size_bytes)
     }
 }
+
+/// Check that a step has an instance buffer if and only if its shader is instanced, as instanced
+/// shaders read attributes that only an instance buffer provides
+fn check_instancing(description: &DrawingStep) -> Result<(), EngineError> {
+    match description.shader.is_instanced() == description.instance_buffer_index.is_some() {
+        true => Ok(()),
+        false => Err(EngineError::RenderError(format!(
+            "Shader {:?} cannot be drawn with instance buffer {:?}",
+            description.shader, description.instance_buffer_index)))
+    }
+}
+
+/// Test suite
+/// Check that steps are only accepted when their shader and instance buffer agree
+#[cfg(test)]
+mod test {
+    use crate::vk_renderer::per_image_resources::pipeline::check_instancing;
+    use defs::render::{
+        DrawingStep,
+        Shader,
+        VertexFormat
+    };
+
+    fn make_step(shader: Shader, instance_buffer_index: Option<usize>) -> DrawingStep {
+        DrawingStep {
+            shader,
+            vbo_index: 0,
+            vbo_format: VertexFormat::PositionNormalTexture,
+            draw_indexed: false,
+            texture_indices: vec![0],
+            depth_test: true,
+            instance_buffer_index
+        }
+    }
+
+    #[test]
+    fn instanced_shaders_need_instance_buffers() {
+        assert!(check_instancing(&make_step(Shader::PlainPntInstanced, Some(0))).is_ok());
+        assert!(check_instancing(&make_step(Shader::PlainPnt, None)).is_ok());
+        assert!(check_instancing(&make_step(Shader::PlainPntInstanced, None)).is_err());
+        assert!(check_instancing(&make_step(Shader::PlainPnt, Some(0))).is_err());
+    }
+}
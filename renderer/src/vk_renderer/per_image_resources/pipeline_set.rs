@@ -59,6 +59,7 @@ impl PipelineSet {
                 render_core,
                 renderpass_wrapper,
                 &description.steps[i],
+                description.reversed_depth,
                 render_extent)?;
         }
         Ok(())
@@ -97,4 +98,19 @@ impl PipelineSet {
         }
         Ok(())
     }
+
+    /// Update the indirect draw command for each pipeline within this set, skipping steps that
+    /// the scene reports are not visible this frame
+    pub unsafe fn update_draw_commands(
+        &mut self,
+        render_core: &crate::vk_renderer::render_core::RenderCore,
+        scene_info: &dyn Scene,
+        pass_index: usize
+    ) -> Result<(), EngineError> {
+        for (step_index, pipeline) in self.pipelines.iter_mut().enumerate() {
+            let visible = scene_info.is_step_visible(pass_index, step_index);
+            pipeline.update_draw_command(render_core, visible)?;
+        }
+        Ok(())
+    }
 }
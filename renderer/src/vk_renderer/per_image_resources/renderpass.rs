@@ -191,6 +191,7 @@ impl RenderpassWrapper {
             .image_view;
         let color_format = match config.color_format {
             TexturePixelFormat::Rgba => vk::Format::R8G8B8A8_UNORM,
+            TexturePixelFormat::RgbaF16 => vk::Format::R16G16B16A16_SFLOAT,
             _ => return Err(EngineError::RenderError(
                 format!("Cannot set color attachment tp {:?}", config.color_format)))
         };
@@ -23,7 +23,8 @@ pub struct PhysicalDeviceProperties {
     pub physical_device: vk::PhysicalDevice,
     pub graphics_queue_family_index: u32,
     pub transfer_queue_family_index: u32,
-    pub required_features: vk::PhysicalDeviceFeatures
+    pub required_features: vk::PhysicalDeviceFeatures,
+    pub max_sampler_anisotropy: f32
 }
 
 /// All device-related initialisation - chooses a physical device, creates the logical device, and
@@ -151,11 +152,13 @@ unsafe fn select_physical_device(
             }
         }
         if graphics_index != unset_value && transfer_index != unset_value {
+            let device_properties = instance.get_physical_device_properties(*physical_device);
             return Ok(PhysicalDeviceProperties {
                 physical_device: *physical_device,
                 graphics_queue_family_index: graphics_index,
                 transfer_queue_family_index: transfer_index,
-                required_features: features_to_enable
+                required_features: features_to_enable,
+                max_sampler_anisotropy: device_properties.limits.max_sampler_anisotropy
             });
         }
     }
@@ -166,6 +169,8 @@ unsafe fn select_physical_device(
 
 /// Return set of features to enable during device creation, knowing that all of those features
 /// are supported by the physical device. If they are not all supported, this returns None.
+/// Optional features that the engine makes use of when available, such as anisotropic filtering
+/// and BCn texture compression, are enabled whenever they are supported.
 fn make_feature_set_to_enable(
     features: &[FeatureDeclaration],
     supported_features: &vk::PhysicalDeviceFeatures
@@ -182,5 +187,33 @@ fn make_feature_set_to_enable(
             }
         }
     }
+    if supported_features.sampler_anisotropy == vk::TRUE {
+        features_to_enable.sampler_anisotropy = vk::TRUE;
+    }
+    if supported_features.texture_compression_bc == vk::TRUE {
+        features_to_enable.texture_compression_bc = vk::TRUE;
+    }
     Some(features_to_enable)
 }
+
+/// Check whether images of the given format, with optimal tiling, can be sampled in shaders and
+/// filled by transfer operations on the physical device
+pub fn supports_sampled_format(
+    instance: &Instance,
+    physical_device_properties: &PhysicalDeviceProperties,
+    format: vk::Format
+) -> bool {
+    if is_bc_format(format) && physical_device_properties.required_features.texture_compression_bc != vk::TRUE {
+        return false;
+    }
+    let format_properties = unsafe {
+        instance.get_physical_device_format_properties(physical_device_properties.physical_device, format)
+    };
+    format_properties.optimal_tiling_features.contains(
+        vk::FormatFeatureFlags::SAMPLED_IMAGE | vk::FormatFeatureFlags::TRANSFER_DST)
+}
+
+fn is_bc_format(format: vk::Format) -> bool {
+    format.as_raw() >= vk::Format::BC1_RGB_UNORM_BLOCK.as_raw() &&
+        format.as_raw() <= vk::Format::BC7_SRGB_BLOCK.as_raw()
+}
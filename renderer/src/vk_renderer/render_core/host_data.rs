@@ -0,0 +1,173 @@
+
+use defs::EngineError;
+use std::collections::HashMap;
+
+/// HostData struct
+/// Items held on the host for buffers whose contents change between frames, such as instance
+/// buffers and dynamic VBOs, keyed by buffer index. Each buffer has a fixed capacity that its
+/// items may not exceed, and pipelines copy the current items into their own device buffers before
+/// each frame they draw. The names describe the buffers and their items in error messages.
+pub struct HostData<T> {
+    buffer_name: &'static str,
+    item_name: &'static str,
+    buffers: HashMap<usize, (usize, Vec<T>)>
+}
+
+impl<T: Copy> HostData<T> {
+
+    /// Create an empty set of buffers, described by the given names
+    pub fn new(buffer_name: &'static str, item_name: &'static str) -> HostData<T> {
+        HostData {
+            buffer_name,
+            item_name,
+            buffers: HashMap::new()
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.buffers.contains_key(&index)
+    }
+
+    /// Add a buffer of the given capacity holding the given items, unless one is already loaded at
+    /// that index; nothing is added if the items exceed the capacity
+    pub fn load(&mut self, index: usize, capacity: usize, items: &[T]) -> Result<(), EngineError> {
+        if self.contains(index) {
+            return Ok(());
+        }
+        self.buffers.insert(index, (capacity, vec![]));
+        if let Err(e) = self.set(index, items) {
+            self.buffers.remove(&index);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Replace the items held for a loaded buffer, leaving them unchanged if the new items exceed
+    /// its capacity
+    pub fn set(&mut self, index: usize, items: &[T]) -> Result<(), EngineError> {
+        match self.buffers.get_mut(&index) {
+            Some((capacity, data)) => {
+                if items.len() > *capacity {
+                    return Err(EngineError::RenderError(format!(
+                        "{} {} exceed the capacity of {} for {} {}",
+                        items.len(), self.item_name, capacity, self.buffer_name, index)));
+                }
+                data.clear();
+                data.extend_from_slice(items);
+                Ok(())
+            },
+            None => Err(EngineError::RenderError(
+                format!("Updated {} that is not loaded", self.buffer_name)))
+        }
+    }
+
+    /// Query the capacity and current items of a loaded buffer
+    pub fn query(&self, index: usize) -> Option<(usize, &[T])> {
+        self.buffers.get(&index)
+            .map(|(capacity, data)| (*capacity, data.as_slice()))
+    }
+
+    /// Remove the buffers at the given indices, returning those that were loaded so that they can
+    /// be put back if replacing them fails
+    pub fn detach<'a>(&mut self, indices: impl Iterator<Item = &'a usize>) -> Vec<(usize, (usize, Vec<T>))> {
+        indices
+            .filter_map(|index| self.buffers.remove(index).map(|buffer| (*index, buffer)))
+            .collect()
+    }
+
+    /// Remove the buffers at the given indices, and put back those that were detached from them
+    pub fn restore<'a>(
+        &mut self,
+        indices: impl Iterator<Item = &'a usize>,
+        detached: Vec<(usize, (usize, Vec<T>))>
+    ) {
+        for index in indices {
+            self.buffers.remove(index);
+        }
+        self.buffers.extend(detached);
+    }
+}
+
+/// Test suite
+/// Check that capacities are enforced without losing the current items, and that detached buffers
+/// are either replaced or put back as they were
+#[cfg(test)]
+mod test {
+    use crate::vk_renderer::render_core::host_data::HostData;
+    use defs::{
+        EngineError,
+        render::InstanceData
+    };
+    use cgmath::{
+        Matrix4,
+        Vector3,
+        Vector4
+    };
+
+    /// Make instances spaced out along x
+    fn make_instances(count: usize) -> Vec<InstanceData> {
+        (0..count)
+            .map(|i| InstanceData {
+                model_matrix: Matrix4::from_translation(Vector3::new(i as f32, 0.0, 0.0)),
+                tint: Vector4::new(1.0, 1.0, 1.0, 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn items_beyond_capacity_are_rejected() {
+        let mut data = HostData::<InstanceData>::new("instance buffer", "instances");
+        data.load(2, 3, &make_instances(2)).unwrap();
+        assert_eq!(data.query(2).unwrap().0, 3);
+        data.set(2, &make_instances(3)).unwrap();
+        assert_eq!(data.query(2).unwrap().1.len(), 3);
+
+        match data.set(2, &make_instances(4)) {
+            Err(EngineError::RenderError(message)) =>
+                assert_eq!(message, "4 instances exceed the capacity of 3 for instance buffer 2"),
+            _ => panic!("Expected instances beyond capacity to be rejected")
+        }
+        assert_eq!(data.query(2).unwrap().1, make_instances(3).as_slice());
+
+        // Loading too many from the start leaves nothing loaded, and unloaded buffers can't be set
+        assert!(data.load(5, 1, &make_instances(2)).is_err());
+        assert!(!data.contains(5));
+        assert!(data.set(5, &make_instances(1)).is_err());
+        assert!(data.query(5).is_none());
+    }
+
+    #[test]
+    fn loading_over_an_existing_buffer_keeps_it() {
+        let mut data = HostData::<InstanceData>::new("instance buffer", "instances");
+        data.load(0, 4, &make_instances(1)).unwrap();
+        data.load(0, 8, &make_instances(2)).unwrap();
+        let (capacity, instances) = data.query(0).unwrap();
+        assert_eq!(capacity, 4);
+        assert_eq!(instances.len(), 1);
+    }
+
+    #[test]
+    fn replacing_drops_old_items_unless_put_back() {
+        let mut data = HostData::<InstanceData>::new("instance buffer", "instances");
+        data.load(0, 4, &make_instances(4)).unwrap();
+        data.load(1, 4, &make_instances(1)).unwrap();
+
+        // A successful replacement leaves only the new items
+        let replaced = [0usize];
+        let detached = data.detach(replaced.iter());
+        data.load(0, 2, &make_instances(2)).unwrap();
+        drop(detached);
+        let (capacity, instances) = data.query(0).unwrap();
+        assert_eq!(capacity, 2);
+        assert_eq!(instances, make_instances(2).as_slice());
+
+        // A failed replacement puts the old items back, including for indices that did load
+        let replaced = [0usize, 1usize];
+        let detached = data.detach(replaced.iter());
+        data.load(0, 8, &make_instances(8)).unwrap();
+        assert!(data.load(1, 1, &make_instances(2)).is_err());
+        data.restore(replaced.iter(), detached);
+        assert_eq!(data.query(0).unwrap(), (2, make_instances(2).as_slice()));
+        assert_eq!(data.query(1).unwrap(), (4, make_instances(1).as_slice()));
+    }
+}
@@ -1,6 +1,7 @@
 
 mod instance;
 mod debug;
+mod host_data;
 mod device;
 mod swapchain;
 
@@ -11,8 +12,10 @@ use defs::{
         VertexFormat,
         ImageUsage,
         TexturePixelFormat,
+        MipmapMode,
         FeatureDeclaration,
-        ResourcePreloads
+        ResourcePreloads,
+        InstanceData
     }
 };
 use model::types::StaticVertex;
@@ -66,7 +69,9 @@ pub struct RenderCore {
     pub image_views: Vec<vk::ImageView>,
     depth_image: Option<crate::vk_renderer::images::ImageWrapper>,
     vbo_objects: HashMap<usize, (usize, crate::vk_renderer::buffers::BufferWrapper)>,
-    texture_objects: HashMap<usize, crate::vk_renderer::images::ImageWrapper>
+    texture_objects: HashMap<usize, crate::vk_renderer::images::ImageWrapper>,
+    instance_data: host_data::HostData<InstanceData>,
+    dynamic_vbo_data: host_data::HostData<StaticVertex>
 }
 
 impl Drop for RenderCore {
@@ -191,7 +196,9 @@ impl RenderCore {
                 image_views: vec![],
                 depth_image: None,
                 vbo_objects: HashMap::new(),
-                texture_objects: HashMap::new()
+                texture_objects: HashMap::new(),
+                instance_data: host_data::HostData::new("instance buffer", "instances"),
+                dynamic_vbo_data: host_data::HostData::new("dynamic VBO", "vertices")
             }
         )
     }
@@ -204,7 +211,7 @@ impl RenderCore {
 
         // VBOs
         for (vbo_index, creation_data) in resource_preloads.vbo_preloads.iter() {
-            if self.vbo_objects.contains_key(vbo_index) {
+            if self.vbo_objects.contains_key(vbo_index) || self.dynamic_vbo_data.contains(*vbo_index) {
                 continue;
             }
             let vertex_size_bytes: usize = match creation_data.vertex_format {
@@ -231,14 +238,15 @@ impl RenderCore {
             if self.texture_objects.contains_key(texture_index) {
                 continue;
             }
-            let texture = match creation_data.layer_data.as_ref() {
+            let mut texture = match creation_data.layer_data.as_ref() {
                 Some(data) => crate::vk_renderer::images::ImageWrapper::new(
                     self,
                     creation_data.usage,
                     creation_data.format,
                     creation_data.width,
                     creation_data.height,
-                    Some(data.as_slice()))?,
+                    Some(data.as_slice()),
+                    creation_data.mipmaps)?,
                 // TODO - One per swapchain image
                 None => crate::vk_renderer::images::ImageWrapper::new(
                     self,
@@ -246,12 +254,71 @@ impl RenderCore {
                     creation_data.format,
                     creation_data.width,
                     creation_data.height,
-                    None
+                    None,
+                    creation_data.mipmaps
                 )?
             };
+            texture.sampler_config = creation_data.sampler;
             self.texture_objects.insert(*texture_index, texture);
         }
 
+        // Instance data, copied into each pipeline's own buffers before every frame it draws
+        for (instance_buffer_index, creation_data) in resource_preloads.instance_preloads.iter() {
+            self.instance_data.load(*instance_buffer_index, creation_data.capacity, &creation_data.instances)?;
+        }
+
+        // Dynamic VBO vertices, which like instances are copied into each pipeline's own buffers
+        for (vbo_index, creation_data) in resource_preloads.dynamic_vbo_preloads.iter() {
+            if self.vbo_objects.contains_key(vbo_index) {
+                continue;
+            }
+            self.dynamic_vbo_data.load(*vbo_index, creation_data.capacity, &creation_data.vertex_data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace resources that are already loaded, destroying the old ones; indices that are not
+    /// yet loaded are simply loaded. If any replacement fails to load, those that did load are
+    /// destroyed and the old resources are put back, so that everything is as it was before. The
+    /// resources must not be in use by the device.
+    pub unsafe fn replace_resources(
+        &mut self,
+        replacements: &ResourcePreloads
+    ) -> Result<(), EngineError> {
+        let old_vbos: Vec<_> = replacements.vbo_preloads.keys()
+            .filter_map(|index| self.vbo_objects.remove(index).map(|vbo| (*index, vbo)))
+            .collect();
+        let old_textures: Vec<_> = replacements.texture_preloads.keys()
+            .filter_map(|index| self.texture_objects.remove(index).map(|texture| (*index, texture)))
+            .collect();
+        let old_instance_data = self.instance_data.detach(replacements.instance_preloads.keys());
+        let old_dynamic_vbo_data = self.dynamic_vbo_data.detach(replacements.dynamic_vbo_preloads.keys());
+
+        if let Err(e) = self.load_new_resources(replacements) {
+            for vbo_index in replacements.vbo_preloads.keys() {
+                if let Some((_, buffer)) = self.vbo_objects.remove(vbo_index) {
+                    buffer.destroy(&self.mem_allocator)?;
+                }
+            }
+            for texture_index in replacements.texture_preloads.keys() {
+                if let Some(image) = self.texture_objects.remove(texture_index) {
+                    image.destroy(&self.device, &self.mem_allocator)?;
+                }
+            }
+            self.instance_data.restore(replacements.instance_preloads.keys(), old_instance_data);
+            self.dynamic_vbo_data.restore(replacements.dynamic_vbo_preloads.keys(), old_dynamic_vbo_data);
+            self.vbo_objects.extend(old_vbos);
+            self.texture_objects.extend(old_textures);
+            return Err(e);
+        }
+
+        for (_, (_, buffer)) in old_vbos.into_iter() {
+            buffer.destroy(&self.mem_allocator)?;
+        }
+        for (_, image) in old_textures.into_iter() {
+            image.destroy(&self.device, &self.mem_allocator)?;
+        }
         Ok(())
     }
 
@@ -286,6 +353,37 @@ impl RenderCore {
         }
     }
 
+    /// Replace the instances held for a preloaded instance buffer
+    pub fn set_instance_data(
+        &mut self,
+        index: usize,
+        instances: &[InstanceData]
+    ) -> Result<(), EngineError> {
+        self.instance_data.set(index, instances)
+    }
+
+    /// Query the capacity and current instances of a preloaded instance buffer
+    pub fn query_instance_data(&self, index: usize) -> Result<(usize, &[InstanceData]), EngineError> {
+        self.instance_data.query(index)
+            .ok_or_else(|| EngineError::RenderError(
+                String::from("Queried instance buffer that is not loaded")))
+    }
+
+    /// Replace the vertices held for a preloaded dynamic VBO
+    pub fn set_dynamic_vbo_data(
+        &mut self,
+        index: usize,
+        vertices: &[StaticVertex]
+    ) -> Result<(), EngineError> {
+        self.dynamic_vbo_data.set(index, vertices)
+    }
+
+    /// Query the capacity and current vertices of a preloaded dynamic VBO; None if the VBO at
+    /// that index is not dynamic
+    pub fn query_dynamic_vbo_data(&self, index: usize) -> Option<(usize, &[StaticVertex])> {
+        self.dynamic_vbo_data.query(index)
+    }
+
     /// Create a new surface for the given window owner
     unsafe fn make_new_surface(
         entry: &Entry,
@@ -355,7 +453,8 @@ impl RenderCore {
             TexturePixelFormat::Unorm16,
             extent.width as u32,
             extent.height as u32,
-            None)?;
+            None,
+            MipmapMode::None)?;
         self.depth_image = Some(depth_image);
 
         // Synchronisation objects
@@ -474,6 +573,14 @@ impl RenderCore {
         &self.mem_allocator
     }
 
+    /// Check whether the device can sample textures of the given format
+    pub fn supports_sampled_format(&self, format: vk::Format) -> bool {
+        crate::vk_renderer::render_core::device::supports_sampled_format(
+            &self.instance,
+            &self.physical_device_properties,
+            format)
+    }
+
     // Increment current image number to focus on the next image in the chain, to wait for its
     // synchronisation objects and so on.
     //
@@ -1,6 +1,7 @@
 
 pub mod validator;
 pub mod types;
+pub mod strings;
 
 use crate::GeneratorError;
 use types::*;
@@ -34,6 +35,7 @@ pub fn parse_directory(project_dir: &PathBuf, spec_dir_name: &'static str) -> Re
             platform: AppPlatform::windows,
             graphics: AppGraphicsApi::vulkan,
             start_scene_id: "unset".to_string(),
+            string_tables: None,
             shared_resources: Resources::default()
         },
         scenes: vec![]
@@ -101,7 +103,7 @@ mod test {
     use crate::deserialiser::parse_app_file;
     use crate::deserialiser::parse_scene_file;
     use crate::deserialiser::types::*;
-    use crate::deserialiser::types::app::{App, AppFeature, AppGraphicsApi, AppPlatform};
+    use crate::deserialiser::types::app::{App, AppFeature, AppGraphicsApi, AppPlatform, StringTable};
     use crate::deserialiser::types::scene::*;
     use crate::GeneratorError;
 
@@ -178,17 +180,29 @@ mod test {
             platform: AppPlatform::windows,
             graphics: AppGraphicsApi::vulkan,
             start_scene_id: "scene".to_string(),
+            string_tables: Some(vec![
+                StringTable {
+                    language: "en".to_string(),
+                    file: "en.strings".to_string()
+                },
+                StringTable {
+                    language: "fr".to_string(),
+                    file: "fr.strings".to_string()
+                }
+            ]),
             shared_resources: Resources {
                 models: vec![
                     Model {
                         id: "skybox".to_string(),
                         file: None,
-                        generator: Some(ModelGenerator::skybox)
+                        generator: Some(ModelGenerator::skybox),
+                        text_key: None
                     },
                     Model {
                         id: "hud".to_string(),
                         file: None,
-                        generator: Some(ModelGenerator::text)
+                        generator: Some(ModelGenerator::text),
+                        text_key: Some("hud.title".to_string())
                     }
                 ],
                 textures: vec![
@@ -196,13 +210,19 @@ mod test {
                         id: "musica".to_string(),
                         format: TextureFormat::rgba8,
                         file: Some("Musica.png".to_string()),
-                        kind: None
+                        kind: None,
+                        layout: None,
+                        mipmaps: None,
+                        sampler: None
                     },
                     Texture {
                         id: "skybox".to_string(),
                         format: TextureFormat::rgb8,
                         file: Some("bluecloud.jpg".to_string()),
-                        kind: Some(TextureKind::cubemap)
+                        kind: Some(TextureKind::cubemap),
+                        layout: Some(CubemapLayout::separate_faces),
+                        mipmaps: None,
+                        sampler: None
                     }
                 ],
                 fonts: vec![
@@ -221,17 +241,60 @@ mod test {
         let expected_scene1 = Scene {
             id: "scene".to_string(),
             camera: Camera::player,
+            flight_path: None,
+            camera_settings: Some(CameraSettings {
+                position: Some([0.0, -2.0, -20.0]),
+                rotation_degrees: Some(90.0),
+                field_of_view_degrees: Some(60.0),
+                near_plane: Some(0.1),
+                far_plane: Some(500.0),
+                reversed_z: Some(true),
+                movement: Some(MovementSettings {
+                    deadzone: None,
+                    max_speed: Some(20.0),
+                    max_reverse_speed: None,
+                    acceleration: Some(12.0),
+                    deceleration: None,
+                    max_angular_speed: None,
+                    angular_acceleration: None,
+                    angular_deceleration: None
+                }),
+                terrain: Some(TerrainSettings {
+                    model_id: "terrain".to_string(),
+                    eye_height: Some(1.5),
+                    smoothing: None,
+                    collision_radius: None
+                })
+            }),
+            nodes: Some(vec![
+                SceneNode {
+                    id: "water".to_string(),
+                    parent: None,
+                    translation: Some([0.0, 0.5, 0.0]),
+                    rotation_degrees: None,
+                    scale: None
+                },
+                SceneNode {
+                    id: "ripples".to_string(),
+                    parent: Some("water".to_string()),
+                    translation: None,
+                    rotation_degrees: Some([0.0, 45.0, 0.0]),
+                    scale: Some([2.0, 1.0, 2.0])
+                }
+            ]),
             resources: Resources {
                 models: vec![
                     Model {
                         id: "terrain".to_string(),
                         file: Some("SceneTerrain.mdl".to_string()),
-                        generator: None
+                        generator: None,
+                        text_key: None
                     },
                     Model {
                         id: "river".to_string(),
                         file: Some("River.mdl".to_string()),
-                        generator: None
+                        generator: None,
+                        text_key: None
                     }
                 ],
                 textures: vec![
@@ -239,19 +302,33 @@ mod test {
                         id: "outdoors".to_string(),
                         format: TextureFormat::rgba8,
                         file: Some("simple_outdoor_texture.jpg".to_string()),
-                        kind: None
+                        kind: None,
+                        layout: None,
+                        mipmaps: Some(TextureMipmaps::gpu),
+                        sampler: Some(TextureSampler {
+                            wrap_u: Some(SamplerWrap::mirrored_repeat),
+                            anisotropy: Some(8.0),
+                            lod_bias: Some(-0.5),
+                            ..TextureSampler::default()
+                        })
                     },
                     Texture {
                         id: "reflection_colour".to_string(),
                         format: TextureFormat::rgb8,
                         file: None,
-                        kind: Some(TextureKind::uninitialised)
+                        kind: Some(TextureKind::uninitialised),
+                        layout: None,
+                        mipmaps: None,
+                        sampler: None
                     },
                     Texture {
                         id: "reflection_depth".to_string(),
                         format: TextureFormat::d16,
                         file: None,
-                        kind: Some(TextureKind::uninitialised)
+                        kind: Some(TextureKind::uninitialised),
+                        layout: None,
+                        mipmaps: None,
+                        sampler: None
                     }
                 ],
                 fonts: vec![]
@@ -269,13 +346,19 @@ mod test {
                             name: "skybox".to_string(),
                             render: RenderFunction::reflection_pre_render,
                             model_id: "skybox".to_string(),
-                            texture_ids: vec!["skybox".to_string()]
+                            texture_ids: vec!["skybox".to_string()],
+                            node: None,
+                            instances: None,
+                            lods: None
                         },
                         Step {
                             name: "terrain".to_string(),
                             render: RenderFunction::reflection_pre_render,
                             model_id: "terrain".to_string(),
-                            texture_ids: vec!["outdoors".to_string()]
+                            texture_ids: vec!["outdoors".to_string()],
+                            node: None,
+                            instances: None,
+                            lods: None
                         }
                     ]
                 },
@@ -288,19 +371,28 @@ mod test {
                             name: "skybox".to_string(),
                             render: RenderFunction::basic_textured,
                             model_id: "skybox".to_string(),
-                            texture_ids: vec!["skybox".to_string()]
+                            texture_ids: vec!["skybox".to_string()],
+                            node: None,
+                            instances: None,
+                            lods: None
                         },
                         Step {
                             name: "river".to_string(),
                             render: RenderFunction::basic_textured,
                             model_id: "river".to_string(),
-                            texture_ids: vec!["reflection".to_string()]
+                            texture_ids: vec!["reflection".to_string()],
+                            node: Some("water".to_string()),
+                            instances: None,
+                            lods: None
                         },
                         Step {
                             name: "terrain".to_string(),
                             render: RenderFunction::basic_textured,
                             model_id: "terrain".to_string(),
-                            texture_ids: vec!["outdoors".to_string()]
+                            texture_ids: vec!["outdoors".to_string()],
+                            node: None,
+                            instances: None,
+                            lods: None
                         }
                     ]
                 },
@@ -313,7 +405,10 @@ mod test {
                             name: "text_overlay".to_string(),
                             render: RenderFunction::text_paint,
                             model_id: "hud".to_string(),
-                            texture_ids: vec!["musica".to_string()]
+                            texture_ids: vec!["musica".to_string()],
+                            node: None,
+                            instances: None,
+                            lods: None
                         }
                     ]
                 }
@@ -326,6 +421,17 @@ mod test {
         let expected_scene2 = Scene {
             id: "cutscene".to_string(),
             camera: Camera::flight_path,
+            flight_path: Some(FlightPath {
+                spline: Some(SplineKind::catmull_rom),
+                points: Some(vec![[0.0, -2.0, -10.0], [10.0, -4.0, 0.0], [0.0, -2.0, 10.0]]),
+                file: None,
+                look_at: Some(vec![[0.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 0.0]]),
+                duration: 12.0,
+                easing: Some(FlightPathEasing::ease_in_out),
+                looping: Some(false)
+            }),
+            camera_settings: None,
+            nodes: None,
             resources: Resources {
                 models: vec![],
                 textures: vec![],
@@ -341,7 +447,10 @@ mod test {
                             name: "box".to_string(),
                             render: RenderFunction::basic_textured,
                             model_id: "skybox".to_string(),
-                            texture_ids: vec!["skybox".to_string()]
+                            texture_ids: vec!["skybox".to_string()],
+                            node: None,
+                            instances: None,
+                            lods: None
                         }
                     ]
                 }
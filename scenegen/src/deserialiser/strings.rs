@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::deserialiser::types::app::App;
+use crate::GeneratorError;
+
+/// StringTableContents struct
+/// The strings of one language, read from a file of `key = value` lines as loaded by the
+/// engine's localisation module. Plural forms keep their suffix, as in `key[one]`.
+pub struct StringTableContents {
+    pub language: String,
+    pub entries: HashMap<String, String>
+}
+
+impl StringTableContents {
+
+    /// Check whether the table has a string, in any of its plural forms
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key) || self.entries.keys().any(|k| base_key(k) == key)
+    }
+
+    /// Get the text of a string, using its other or one form if it has plural forms
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.entries.get(key)
+            .or_else(|| self.entries.get(&format!("{}[other]", key)))
+            .or_else(|| self.entries.get(&format!("{}[one]", key)))
+            .map(|value| value.as_str())
+    }
+}
+
+/// Read every string table listed by the app, from the strings subdirectory of the resources
+/// directory, in the order they are listed
+pub fn load_string_tables(
+    project_dir: &PathBuf,
+    resources_dir_name: &'static str,
+    app: &App
+) -> Result<Vec<StringTableContents>, GeneratorError> {
+    let mut tables = vec![];
+    for table in app.string_tables.iter().flatten() {
+        let mut path = PathBuf::from(project_dir);
+        path.push(resources_dir_name);
+        path.push("strings");
+        path.push(&table.file);
+        let text = std::fs::read_to_string(&path)
+            .map_err(|_| GeneratorError::OpenError(path.clone()))?;
+        tables.push(StringTableContents {
+            language: table.language.clone(),
+            entries: parse_string_table(&table.language, &text)?
+        });
+    }
+    Ok(tables)
+}
+
+fn parse_string_table(language: &str, text: &str) -> Result<HashMap<String, String>, GeneratorError> {
+    let mut entries = HashMap::new();
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let sign_pos = line.find('=')
+            .ok_or_else(|| GeneratorError::InvalidSpec(
+                format!("Expected key = value in {} strings, line {}", language, line_index + 1)))?;
+        let value = line[(sign_pos + 1)..].trim()
+            .replace("\\n", "\n")
+            .replace("\\\\", "\\");
+        entries.insert(line[0..sign_pos].trim().to_string(), value);
+    }
+    Ok(entries)
+}
+
+fn base_key(key: &str) -> &str {
+    match (key.find('['), key.ends_with(']')) {
+        (Some(open), true) => key[0..open].trim_end(),
+        _ => key
+    }
+}
@@ -10,9 +10,19 @@ pub struct App {
     pub platform: AppPlatform,
     pub graphics: AppGraphicsApi,
     pub start_scene_id: String,
+    pub string_tables: Option<Vec<StringTable>>,
     pub shared_resources: Resources
 }
 
+/// StringTable struct
+/// Names a file of localised strings for one language, found in the strings subdirectory of the
+/// resources directory. The first table listed is the default language.
+#[derive(Debug, Deserialize)]
+pub struct StringTable {
+    pub language: String,
+    pub file: String
+}
+
 #[derive(Debug, Deserialize)]
 pub enum AppFeature {
     clip_planes
@@ -19,7 +19,8 @@ pub struct Resources {
 pub struct Model {
     pub id: String,
     pub file: Option<String>,
-    pub generator: Option<ModelGenerator>
+    pub generator: Option<ModelGenerator>,
+    pub text_key: Option<String>
 }
 
 #[derive(Debug, Deserialize)]
@@ -35,7 +36,10 @@ pub struct Texture {
     pub id: String,
     pub format: TextureFormat,
     pub file: Option<String>,
-    pub kind: Option<TextureKind>
+    pub kind: Option<TextureKind>,
+    pub layout: Option<CubemapLayout>,
+    pub mipmaps: Option<TextureMipmaps>,
+    pub sampler: Option<TextureSampler>
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -43,6 +47,7 @@ pub enum TextureFormat {
     r8,
     rgb8,
     rgba8,
+    rgba16f,
     d16
 }
 
@@ -52,6 +57,50 @@ pub enum TextureKind {
     uninitialised
 }
 
+/// CubemapLayout enum
+/// How the faces of a cube map texture are arranged in its source file or files
+#[derive(Debug, Deserialize, PartialEq)]
+pub enum CubemapLayout {
+    separate_faces,
+    equirectangular,
+    horizontal_cross,
+    vertical_cross
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub enum TextureMipmaps {
+    none,
+    cpu,
+    gpu
+}
+
+/// TextureSampler struct
+/// Overrides for how a texture is sampled; anything not given uses the engine's default
+#[derive(Debug, Deserialize, Default)]
+pub struct TextureSampler {
+    pub min_filter: Option<SamplerFilter>,
+    pub mag_filter: Option<SamplerFilter>,
+    pub mipmap_filter: Option<SamplerFilter>,
+    pub wrap_u: Option<SamplerWrap>,
+    pub wrap_v: Option<SamplerWrap>,
+    pub wrap_w: Option<SamplerWrap>,
+    pub anisotropy: Option<f32>,
+    pub lod_bias: Option<f32>
+}
+
+#[derive(Debug, Deserialize)]
+pub enum SamplerFilter {
+    nearest,
+    linear
+}
+
+#[derive(Debug, Deserialize)]
+pub enum SamplerWrap {
+    repeat,
+    mirrored_repeat,
+    clamp_to_edge
+}
+
 /// Font struct
 /// Defines a font, including its glyph definition file and a reference to the texture which holds
 /// its pixel data
@@ -9,6 +9,9 @@ use crate::deserialiser::types::Resources;
 pub struct Scene {
     pub id: String,
     pub camera: Camera,
+    pub flight_path: Option<FlightPath>,
+    pub camera_settings: Option<CameraSettings>,
+    pub nodes: Option<Vec<SceneNode>>,
     pub resources: Resources,
     pub passes: Vec<Pass>
 }
@@ -17,9 +20,97 @@ pub struct Scene {
 pub enum Camera {
     player,
     flight_path,
+    orbit,
+    free_fly,
+    orthographic,
     null
 }
 
+/// FlightPath struct
+/// Defines the path followed by a flight_path camera, either as points given here or as a curve
+/// exported from Blender as OBJ into the paths subdirectory of the resources directory. Look-at
+/// targets are given one per point passed through - every point of a Catmull-Rom spline, or the
+/// anchors of a Bezier spline.
+#[derive(Debug, Deserialize)]
+pub struct FlightPath {
+    pub spline: Option<SplineKind>,
+    pub points: Option<Vec<[f32; 3]>>,
+    pub file: Option<String>,
+    pub look_at: Option<Vec<[f32; 3]>>,
+    pub duration: f32,
+    pub easing: Option<FlightPathEasing>,
+    pub looping: Option<bool>
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub enum SplineKind {
+    catmull_rom,
+    bezier
+}
+
+#[derive(Debug, Deserialize)]
+pub enum FlightPathEasing {
+    linear,
+    ease_in,
+    ease_out,
+    ease_in_out
+}
+
+/// CameraSettings struct
+/// Overrides for the camera's projection, and for the player camera's starting placement and
+/// movement, where anything not given keeps the engine's default for the camera. Angles are in
+/// degrees, and reversed_z also reverses the depth test of every pass in the scene. Terrain makes
+/// the player camera ride over one of the scene's models and collide with it.
+#[derive(Debug, Deserialize)]
+pub struct CameraSettings {
+    pub position: Option<[f32; 3]>,
+    pub rotation_degrees: Option<f32>,
+    pub field_of_view_degrees: Option<f32>,
+    pub near_plane: Option<f32>,
+    pub far_plane: Option<f32>,
+    pub reversed_z: Option<bool>,
+    pub movement: Option<MovementSettings>,
+    pub terrain: Option<TerrainSettings>
+}
+
+/// MovementSettings struct
+/// Overrides for the momentum of the player camera, in units or radians per second
+#[derive(Debug, Deserialize)]
+pub struct MovementSettings {
+    pub deadzone: Option<f32>,
+    pub max_speed: Option<f32>,
+    pub max_reverse_speed: Option<f32>,
+    pub acceleration: Option<f32>,
+    pub deceleration: Option<f32>,
+    pub max_angular_speed: Option<f32>,
+    pub angular_acceleration: Option<f32>,
+    pub angular_deceleration: Option<f32>
+}
+
+/// TerrainSettings struct
+/// The model, loaded from a file in the scene's own resources, that the player camera follows the
+/// ground of and collides with, along with overrides for how it does so
+#[derive(Debug, Deserialize)]
+pub struct TerrainSettings {
+    pub model_id: String,
+    pub eye_height: Option<f32>,
+    pub smoothing: Option<f32>,
+    pub collision_radius: Option<f32>
+}
+
+/// SceneNode struct
+/// An object in the scene's transform hierarchy, placed relative to its parent node, or to the
+/// world if it has none. Parents are listed before their children, rotations are in degrees about
+/// the x, y and z axes, and steps that name a node draw their model with its transform.
+#[derive(Debug, Deserialize)]
+pub struct SceneNode {
+    pub id: String,
+    pub parent: Option<String>,
+    pub translation: Option<[f32; 3]>,
+    pub rotation_degrees: Option<[f32; 3]>,
+    pub scale: Option<[f32; 3]>
+}
+
 /// Pass struct
 /// Defines one of the one-or-more rendering passes required to draw this scene, including a shader
 /// and render target (offscreen buffer or the default framebuffer), plus the various steps to draw
@@ -49,9 +140,11 @@ pub enum RenderFunction {
     basic_textured,
     basic_textured_clipped_y,
     text_paint,
+    text_paint_sdf,
     cube,
     cube_clipped_y,
-    reflection_pre_render
+    reflection_pre_render,
+    basic_textured_instanced
 }
 
 /// Step struct
@@ -62,5 +155,39 @@ pub struct Step {
     pub name: String,
     pub render: RenderFunction,
     pub model_id: String,
-    pub texture_ids: Vec<String>
+    pub texture_ids: Vec<String>,
+    pub node: Option<String>,
+    pub instances: Option<StepInstances>,
+    pub lods: Option<StepLods>
+}
+
+/// StepLods struct
+/// Coarser levels of detail for the model of a full-detail step, each drawn by another step in
+/// the same pass, given from the finest down. Each level, starting from the full-detail one, is
+/// used while the model covers at least its minimum fraction of the screen's height, so there is
+/// one minimum for each level but the last, and they should decrease.
+#[derive(Debug, Deserialize)]
+pub struct StepLods {
+    pub steps: Vec<String>,
+    pub min_screen_sizes: Vec<f32>
+}
+
+/// StepInstances struct
+/// The instance buffer drawn by an instanced step. The capacity is the most instances the buffer
+/// can ever hold, defaulting to the number of placements, which are the instances drawn initially.
+#[derive(Debug, Deserialize)]
+pub struct StepInstances {
+    pub capacity: Option<usize>,
+    pub placements: Vec<InstancePlacement>
+}
+
+/// InstancePlacement struct
+/// One instance of an instanced step's model, placed in the world with rotations in degrees about
+/// the x, y and z axes, and tinted by multiplying its texture colour
+#[derive(Debug, Deserialize)]
+pub struct InstancePlacement {
+    pub translation: Option<[f32; 3]>,
+    pub rotation_degrees: Option<[f32; 3]>,
+    pub scale: Option<[f32; 3]>,
+    pub tint: Option<[f32; 4]>
 }
@@ -1,7 +1,9 @@
 
 use jsonschema::JSONSchema;
 use std::path::PathBuf;
-use crate::deserialiser::types::{Resources, TextureKind, TextureFormat};
+use crate::deserialiser::types::{CubemapLayout, Resources, TextureKind, TextureFormat, TextureMipmaps};
+use crate::deserialiser::strings::StringTableContents;
+use crate::deserialiser::types::scene::{Camera, RenderFunction, Scene, SplineKind};
 use crate::generator::CompleteSpec;
 use crate::GeneratorError;
 
@@ -53,6 +55,11 @@ pub fn validate_complete_spec(spec: &CompleteSpec) -> Result<(), String> {
     for scene in spec.scenes.iter() {
 
         validate_resources_object(&scene.id, &scene.resources, Some(&spec.app.shared_resources))?;
+        validate_flight_path(scene)?;
+        validate_camera_settings(scene)?;
+        validate_nodes(scene)?;
+        validate_instances(scene)?;
+        validate_lods(scene)?;
 
         for pass in scene.passes.iter() {
             if let Some(target_texture_ids) = &pass.target_texture_ids {
@@ -74,6 +81,12 @@ pub fn validate_complete_spec(spec: &CompleteSpec) -> Result<(), String> {
                     return Err(format!("(Scene {}) Model doesn't exist: {}", scene.id, model_id));
                 }
 
+                if let Some(node_id) = &step.node {
+                    if !scene.nodes.iter().flatten().any(|node| &node.id == node_id) {
+                        return Err(format!("(Scene {}) Step node doesn't exist: {}", scene.id, node_id));
+                    }
+                }
+
                 for texture_id in step.texture_ids.iter() {
                     if !texture_exists_in(&scene.resources, texture_id) && !texture_exists_in(&spec.app.shared_resources, texture_id) {
                         return Err(format!("(Scene {}) Step texture doesn't exist: {}", scene.id, texture_id));
@@ -86,17 +99,234 @@ pub fn validate_complete_spec(spec: &CompleteSpec) -> Result<(), String> {
     Ok(())
 }
 
+/// Verify that the string key of every model exists in every language's string table
+pub fn validate_string_keys(spec: &CompleteSpec, string_tables: &[StringTableContents]) -> Result<(), String> {
+    let scene_resources = spec.scenes.iter()
+        .map(|scene| (scene.id.as_str(), &scene.resources));
+    for (scene_id, resources) in std::iter::once(("shared", &spec.app.shared_resources)).chain(scene_resources) {
+        for model in resources.models.iter() {
+            if let Some(key) = &model.text_key {
+                if string_tables.is_empty() {
+                    return Err(format!("(Scene {}) Text key used without any string tables: {}", scene_id, key));
+                }
+                for table in string_tables.iter() {
+                    if !table.contains(key) {
+                        return Err(format!("(Scene {}) String key {} missing for language {}", scene_id, key, table.language));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verify that a flight path is given if, and only if, the scene's camera follows one, and that
+/// its points suit the kind of spline
+fn validate_flight_path(scene: &Scene) -> Result<(), String> {
+    let path = match (&scene.camera, &scene.flight_path) {
+        (Camera::flight_path, Some(path)) => path,
+        (Camera::flight_path, None) => return Err(format!("(Scene {}) Flight path camera has no flight_path", scene.id)),
+        (_, Some(_)) => return Err(format!("(Scene {}) Flight path given for a camera that doesn't use it", scene.id)),
+        (_, None) => return Ok(())
+    };
+    let spline = path.spline.as_ref().unwrap_or(&SplineKind::catmull_rom);
+    let looping = path.looping.unwrap_or(false);
+    let point_count = match (&path.points, &path.file) {
+        (Some(points), None) => points.len(),
+        (None, Some(_)) if *spline == SplineKind::catmull_rom => return Ok(()),
+        (None, Some(_)) => return Err(format!("(Scene {}) Flight paths from files must use catmull_rom splines", scene.id)),
+        _ => return Err(format!("(Scene {}) Flight path needs exactly one of points or file", scene.id))
+    };
+    let (valid_count, key_count) = match (spline, looping) {
+        (SplineKind::catmull_rom, _) => (point_count >= 2, point_count),
+        (SplineKind::bezier, false) => (point_count >= 4 && point_count % 3 == 1, point_count.div_ceil(3)),
+        (SplineKind::bezier, true) => (point_count >= 3 && point_count.is_multiple_of(3), point_count / 3)
+    };
+    if !valid_count {
+        return Err(format!("(Scene {}) Wrong number of flight path points for {:?} spline: {}", scene.id, spline, point_count));
+    }
+    match &path.look_at {
+        Some(look_at) if look_at.len() != key_count => Err(format!(
+            "(Scene {}) Flight path needs {} look_at targets: {} given", scene.id, key_count, look_at.len())),
+        _ => Ok(())
+    }
+}
+
+/// Verify that camera settings are only given for perspective cameras, that only the player camera
+/// is given placement, movement or terrain, and that the clip planes are in order once the
+/// camera's defaults are filled in
+fn validate_camera_settings(scene: &Scene) -> Result<(), String> {
+    let (settings, default_near_plane) = match (&scene.camera, &scene.camera_settings) {
+        (_, None) => return Ok(()),
+        (Camera::player, Some(settings)) => (settings, 1.0),
+        (Camera::orbit | Camera::free_fly | Camera::flight_path, Some(settings)) => {
+            let player_only = settings.position.is_some() ||
+                settings.rotation_degrees.is_some() ||
+                settings.movement.is_some() ||
+                settings.terrain.is_some();
+            if player_only {
+                return Err(format!("(Scene {}) Camera settings other than the projection are only supported for the player camera", scene.id));
+            }
+            (settings, 0.1)
+        },
+        (_, Some(_)) => return Err(format!("(Scene {}) Camera settings are only supported for perspective cameras", scene.id))
+    };
+    let near_plane = settings.near_plane.unwrap_or(default_near_plane);
+    let far_plane = settings.far_plane.unwrap_or(100.0);
+    if far_plane <= near_plane {
+        return Err(format!("(Scene {}) Camera far plane {} is not beyond near plane {}", scene.id, far_plane, near_plane));
+    }
+
+    // The terrain is decoded from the same model bytes as its vertex buffer
+    match &settings.terrain {
+        Some(terrain) => match scene.resources.models.iter().find(|m| m.id == terrain.model_id) {
+            Some(model) if model.file.is_some() => Ok(()),
+            Some(_) => Err(format!("(Scene {}) Camera terrain must be a model loaded from a file: {}", scene.id, terrain.model_id)),
+            None => Err(format!("(Scene {}) Camera terrain model is not in the scene's resources: {}", scene.id, terrain.model_id))
+        },
+        None => Ok(())
+    }
+}
+
+/// Verify that scene node ids are unique, and that each parent is listed before its children,
+/// which also rules out cycles
+fn validate_nodes(scene: &Scene) -> Result<(), String> {
+    let mut seen_ids: Vec<&str> = vec![];
+    for node in scene.nodes.iter().flatten() {
+        if seen_ids.contains(&node.id.as_str()) {
+            return Err(format!("(Scene {}) Duplicate node id: {}", scene.id, node.id));
+        }
+        if let Some(parent) = &node.parent {
+            if !seen_ids.contains(&parent.as_str()) {
+                return Err(format!("(Scene {}) Node {} has a parent that isn't listed before it: {}", scene.id, node.id, parent));
+            }
+        }
+        seen_ids.push(&node.id);
+    }
+    Ok(())
+}
+
+/// Verify that instances are given if, and only if, a step is drawn instanced, that they fit in
+/// their buffer, and that instanced steps, being placed by their instances, don't name a node
+fn validate_instances(scene: &Scene) -> Result<(), String> {
+    for pass in scene.passes.iter() {
+        for step in pass.steps.iter() {
+            let instanced = step.render == RenderFunction::basic_textured_instanced;
+            let instances = match (instanced, &step.instances) {
+                (true, Some(instances)) => instances,
+                (false, None) => continue,
+                (true, None) => return Err(format!(
+                    "(Scene {}) Instanced step has no instances: {}", scene.id, step.name)),
+                (false, Some(_)) => return Err(format!(
+                    "(Scene {}) Step with instances isn't drawn instanced: {}", scene.id, step.name))
+            };
+            if let Some(capacity) = instances.capacity {
+                if capacity < instances.placements.len() {
+                    return Err(format!(
+                        "(Scene {}) Step {} has more instances than its capacity of {}", scene.id, step.name, capacity));
+                }
+            }
+            if step.node.is_some() {
+                return Err(format!("(Scene {}) Instanced step can't be drawn with a node: {}", scene.id, step.name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verify that each step drawing a level of detail exists in the same pass as the full-detail
+/// step, belongs to just one chain, and draws a model of its own without instances, that the
+/// full-detail model has bounds from a file, and that there is a decreasing minimum screen size
+/// for each level but the last
+fn validate_lods(scene: &Scene) -> Result<(), String> {
+    for pass in scene.passes.iter() {
+        let mut levels: Vec<&str> = vec![];
+        for step in pass.steps.iter() {
+            let lods = match &step.lods {
+                Some(lods) => lods,
+                None => continue
+            };
+            if lods.min_screen_sizes.len() != lods.steps.len() {
+                return Err(format!(
+                    "(Scene {}) Step {} needs a minimum screen size for each level of detail but the last", scene.id, step.name));
+            }
+            if lods.min_screen_sizes.windows(2).any(|sizes| sizes[1] >= sizes[0]) {
+                return Err(format!("(Scene {}) Step {} has level of detail screen sizes that don't decrease", scene.id, step.name));
+            }
+            if !scene.resources.models.iter().any(|model| model.id == step.model_id && model.generator.is_none() && model.file.is_some()) {
+                return Err(format!("(Scene {}) Step {} has levels of detail but no model file to bound them by", scene.id, step.name));
+            }
+            levels.push(&step.name);
+            for level_name in lods.steps.iter() {
+                let level = match pass.steps.iter().find(|s| &s.name == level_name) {
+                    Some(level) => level,
+                    None => return Err(format!("(Scene {}) Level of detail step doesn't exist: {}", scene.id, level_name))
+                };
+                if levels.contains(&level_name.as_str()) {
+                    return Err(format!("(Scene {}) Step is in more than one chain of levels of detail: {}", scene.id, level_name));
+                }
+                levels.push(level_name);
+                if level.lods.is_some() {
+                    return Err(format!("(Scene {}) Level of detail step has levels of its own: {}", scene.id, level_name));
+                }
+            }
+            if step.instances.is_some() || lods.steps.iter().any(|level_name| pass.steps.iter().any(|s| &s.name == level_name && s.instances.is_some())) {
+                return Err(format!("(Scene {}) Instanced steps can't have levels of detail: {}", scene.id, step.name));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn validate_resources_object(scene_id: &str, resources: &Resources, parent_resources: Option<&Resources>) -> Result<(), String> {
 
-    // Verify cubemap textures use RGBA8
+    // Verify cubemap textures use RGBA8 or RGBA16F
     for texture in resources.textures.iter() {
         if matches!(&texture.kind, Some(TextureKind::cubemap)) {
-            if texture.format != TextureFormat::rgba8 {
-                return Err(format!("(Scene {}) Non-RGBA8 cubemaps are not supported: {}", scene_id, texture.id));
+            if texture.format != TextureFormat::rgba8 && texture.format != TextureFormat::rgba16f {
+                return Err(format!("(Scene {}) Non-RGBA cubemaps are not supported: {}", scene_id, texture.id));
+            }
+        }
+    }
+
+    // Verify cube map layouts are only given for cube maps, and only for plain images
+    for texture in resources.textures.iter() {
+        let single_image_layout = matches!(&texture.layout,
+            Some(CubemapLayout::equirectangular) | Some(CubemapLayout::horizontal_cross) | Some(CubemapLayout::vertical_cross));
+        if texture.layout.is_some() && !matches!(&texture.kind, Some(TextureKind::cubemap)) {
+            return Err(format!("(Scene {}) Layout is only supported for cubemaps: {}", scene_id, texture.id));
+        }
+        if let Some(file) = &texture.file {
+            if single_image_layout && !is_plain_image_file(file) && !is_float_image_file(file) {
+                return Err(format!("(Scene {}) Cubemap layouts need a single image file: {}", scene_id, texture.id));
+            }
+        }
+    }
+
+    // Verify HDR and OpenEXR image files are used with a float format, and other image files are not
+    for texture in resources.textures.iter() {
+        if let Some(file) = &texture.file {
+            let is_float_file = is_float_image_file(file);
+            if is_float_file && texture.format != TextureFormat::rgba16f {
+                return Err(format!("(Scene {}) HDR and OpenEXR image files must use format rgba16f: {}", scene_id, texture.id));
+            }
+            if !is_float_file && texture.format == TextureFormat::rgba16f && is_plain_image_file(file) {
+                return Err(format!("(Scene {}) Only HDR and OpenEXR image files can use format rgba16f: {}", scene_id, texture.id));
             }
         }
     }
 
+    // Verify mipmaps are only requested for textures with initial data
+    for texture in resources.textures.iter() {
+        let wants_mipmaps = matches!(&texture.mipmaps, Some(TextureMipmaps::cpu) | Some(TextureMipmaps::gpu));
+        if wants_mipmaps && matches!(&texture.kind, Some(TextureKind::uninitialised)) {
+            return Err(format!("(Scene {}) Mipmaps are not supported for uninitialised textures: {}", scene_id, texture.id));
+        }
+        if matches!(&texture.mipmaps, Some(TextureMipmaps::cpu)) && texture.format != TextureFormat::rgba8 {
+            return Err(format!("(Scene {}) CPU mipmaps are only supported for rgba8 textures: {}", scene_id, texture.id));
+        }
+    }
+
     // Verify fonts reference known textures
     for font in resources.fonts.iter() {
         if !texture_exists_in(&resources, &font.texture_id) {
@@ -114,6 +344,18 @@ fn validate_resources_object(scene_id: &str, resources: &Resources, parent_resou
     Ok(())
 }
 
+/// Check whether a file is an image that is always decoded to 8-bit RGBA, as opposed to an HDR
+/// image or a container that keeps its own format
+fn is_plain_image_file(file: &str) -> bool {
+    let file_as_lower = file.to_lowercase();
+    [".jpg", ".jpeg", ".png", ".tga", ".bmp", ".webp"].iter().any(|e| file_as_lower.ends_with(e))
+}
+
+fn is_float_image_file(file: &str) -> bool {
+    let file_as_lower = file.to_lowercase();
+    [".hdr", ".exr"].iter().any(|e| file_as_lower.ends_with(e))
+}
+
 fn texture_exists_in(resources: &Resources, id: &str) -> bool {
     match resources.textures.iter().find(|texture| &texture.id == id) {
         Some(_) => true,
@@ -6,6 +6,8 @@ use std::path::PathBuf;
 use crate::deserialiser::types::app::App;
 use crate::deserialiser::types::scene::Scene;
 use crate::deserialiser::parse_directory;
+use crate::deserialiser::strings::load_string_tables;
+use crate::deserialiser::validator::validate_string_keys;
 use crate::generator::writer::write_app_files;
 use crate::GeneratorError;
 
@@ -27,6 +29,9 @@ pub struct CompleteSpec {
 ///   - textures
 ///     - some_image.jpg
 ///     - another_image.png
+///   - strings
+///     - en.strings
+///     - fr.strings
 /// - src
 ///   - app.rs*
 ///   - scenes
@@ -42,7 +47,10 @@ pub fn process_spec_path(
     resources_dir_name: &'static str
 ) -> Result<(), GeneratorError> {
     let complete_spec = parse_directory(project_dir, spec_dir_name)?;
-    write_app_files(project_dir, &complete_spec, resources_dir_name)?;
+    let string_tables = load_string_tables(project_dir, resources_dir_name, &complete_spec.app)?;
+    validate_string_keys(&complete_spec, &string_tables)
+        .map_err(GeneratorError::InvalidSpec)?;
+    write_app_files(project_dir, &complete_spec, &string_tables, resources_dir_name)?;
     Ok(())
 }
 
@@ -96,6 +104,41 @@ mod test {
         assert!(matches!(process_result, Err(GeneratorError::InvalidSpec(_))));
     }
 
+    #[test]
+    fn missing_string_key_fails_validation() {
+        let test_dir = get_test_dir("missing_string_key");
+        let process_result = process_spec_path(&test_dir, "spec", "resources");
+        assert!(matches!(process_result, Err(GeneratorError::InvalidSpec(_))));
+    }
+
+    #[test]
+    fn flight_path_camera_without_path_fails_validation() {
+        let test_dir = get_test_dir("missing_flight_path");
+        let process_result = process_spec_path(&test_dir, "spec", "resources");
+        assert!(matches!(process_result, Err(GeneratorError::InvalidSpec(_))));
+    }
+
+    #[test]
+    fn spectator_camera_with_placement_fails_validation() {
+        let test_dir = get_test_dir("spectator_camera_placement");
+        let process_result = process_spec_path(&test_dir, "spec", "resources");
+        assert!(matches!(process_result, Err(GeneratorError::InvalidSpec(_))));
+    }
+
+    #[test]
+    fn instances_beyond_capacity_fail_validation() {
+        let test_dir = get_test_dir("instances_over_capacity");
+        let process_result = process_spec_path(&test_dir, "spec", "resources");
+        assert!(matches!(process_result, Err(GeneratorError::InvalidSpec(_))));
+    }
+
+    #[test]
+    fn missing_level_of_detail_step_fails_validation() {
+        let test_dir = get_test_dir("lod_step_missing");
+        let process_result = process_spec_path(&test_dir, "spec", "resources");
+        assert!(matches!(process_result, Err(GeneratorError::InvalidSpec(_))));
+    }
+
     #[test]
     fn valid_files_in_directory_processed() {
         let test_dir = get_test_dir("full_featured_app");
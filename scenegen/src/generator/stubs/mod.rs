@@ -16,8 +16,9 @@ use scenes_list_module::generate_scenes_list_root_content;
 use scenes_list_regenerated::generate_scenes_list_regenerated_content;
 use scene_regenerated::generate_regenerated_scene_contents;
 use scene_starter::generate_starter_scene_contents;
-use crate::deserialiser::types::Resources;
+use crate::deserialiser::types::{CubemapLayout, Resources, Texture};
 use crate::deserialiser::types::app::App;
+use crate::deserialiser::strings::StringTableContents;
 use crate::deserialiser::types::scene::Scene;
 use crate::GeneratorError;
 
@@ -44,10 +45,38 @@ pub fn generate_scene_listing_stubs(configs: &Vec<Scene>) -> Result<(String, Str
 pub fn generate_scene_stubs(
     scene_number_one_based: usize,
     shared_resources: &Resources,
+    default_strings: Option<&StringTableContents>,
     config: &Scene,
     resources_dir_name: &'static str
 ) -> Result<(String, String), GeneratorError> {
     let scene_module_contents = generate_starter_scene_contents(config)?;
-    let scene_generated_contents = generate_regenerated_scene_contents(scene_number_one_based, shared_resources, config, resources_dir_name)?;
+    let scene_generated_contents = generate_regenerated_scene_contents(
+        scene_number_one_based, shared_resources, default_strings, config, resources_dir_name)?;
     Ok((scene_module_contents, scene_generated_contents))
 }
+
+/// Check whether a texture file is a container format, which holds every face of a cube map in
+/// the one file rather than needing a separate file for each face
+fn is_texture_container_file(file_name: &str) -> bool {
+    let file_as_lower = file_name.to_lowercase();
+    file_as_lower.ends_with(".ktx2") || file_as_lower.ends_with(".dds")
+}
+
+/// Get the engine's name for a cube map layout that builds all six faces from a single image, or
+/// None if the faces are in separate files or in a container
+fn single_image_cubemap_layout(layout: &Option<CubemapLayout>) -> Option<&'static str> {
+    match layout {
+        Some(CubemapLayout::equirectangular) => Some("Equirectangular"),
+        Some(CubemapLayout::horizontal_cross) => Some("HorizontalCross"),
+        Some(CubemapLayout::vertical_cross) => Some("VerticalCross"),
+        _ => None
+    }
+}
+
+/// Check whether a cube map texture is loaded from a single file rather than one file per face
+fn is_single_file_cubemap(texture: &Texture) -> bool {
+    match &texture.file {
+        Some(file) => is_texture_container_file(file) || single_image_cubemap_layout(&texture.layout).is_some(),
+        None => false
+    }
+}
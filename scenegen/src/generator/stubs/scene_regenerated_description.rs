@@ -9,6 +9,7 @@ fn translate_texture_format(format: &TextureFormat) -> Result<String, GeneratorE
         TextureFormat::rgb8 => Err(GeneratorError::InvalidSpec(
             format!("Texture format rgb8 not implemented"))),
         TextureFormat::rgba8 => Ok("TexturePixelFormat::Rgba".to_string()),
+        TextureFormat::rgba16f => Ok("TexturePixelFormat::RgbaF16".to_string()),
         TextureFormat::d16 => Ok("TexturePixelFormat::Unorm16".to_string())
     }
 }
@@ -18,14 +19,19 @@ fn translate_shader(shader: &RenderFunction) -> String {
         RenderFunction::basic_textured => "Shader::PlainPnt",
         RenderFunction::basic_textured_clipped_y => "Shader::PlainPntClipped",
         RenderFunction::text_paint => "Shader::Text",
+        RenderFunction::text_paint_sdf => "Shader::TextSdf",
         RenderFunction::cube => "Shader::Cube",
         RenderFunction::cube_clipped_y => "Shader::CubeClipped",
-        RenderFunction::reflection_pre_render => "Shader::Water"
+        RenderFunction::reflection_pre_render => "Shader::Water",
+        RenderFunction::basic_textured_instanced => "Shader::PlainPntInstanced"
     };
     String::from(function)
 }
 
 pub fn generate_description(shared_resources: &Resources, config: &Scene) -> Result<String, GeneratorError> {
+    let reversed_depth = config.camera_settings.as_ref()
+        .and_then(|settings| settings.reversed_z)
+        .unwrap_or(false);
     let mut passes = String::new();
     for pass in config.passes.iter() {
 
@@ -103,6 +109,10 @@ pub fn generate_description(shared_resources: &Resources, config: &Scene) -> Res
                         format!("(Scene {}) Model doesn't exist: {}", config.id, step.model_id)))
                 }
             };
+            let instance_buffer_index = match &step.instances {
+                Some(_) => format!("Some(INSTANCE_BUFFER_INDEX_{}_{})", pass.name.to_uppercase(), step.name.to_uppercase()),
+                None => String::from("None")
+            };
             steps = format!("{}
                         DrawingStep {{
                             shader: {},
@@ -110,19 +120,22 @@ pub fn generate_description(shared_resources: &Resources, config: &Scene) -> Res
                             vbo_format: VertexFormat::PositionNormalTexture,
                             draw_indexed: false,
                             texture_indices: vec![{}],
-                            depth_test: true
-                        }},", steps, shader, vbo_index_name, texture_indices);
+                            depth_test: true,
+                            instance_buffer_index: {}
+                        }},", steps, shader, vbo_index_name, texture_indices, instance_buffer_index);
         }
 
         passes = format!("{}
                 DrawingPass {{
                     {},
                     steps: vec![{}
-                    ]
+                    ],
+                    reversed_depth: {}
                 }},",
             passes,
             target,
-            steps
+            steps,
+            reversed_depth
         );
     }
     let content = format!("
@@ -1,6 +1,7 @@
 
 use crate::GeneratorError;
 use crate::deserialiser::types::{*, scene::*};
+use crate::deserialiser::strings::StringTableContents;
 
 fn get_codec_from_file_name(config: &Scene, texture: &Texture) -> Result<String, GeneratorError> {
     let file_as_lower = match &texture.file {
@@ -16,31 +17,134 @@ fn get_codec_from_file_name(config: &Scene, texture: &Texture) -> Result<String,
     let codec = match extension_begin {
         ".jpeg" | ".jpg" => String::from("TextureCodec::Jpeg"),
         ".png" => String::from("TextureCodec::Png"),
+        ".tga" => String::from("TextureCodec::Tga"),
+        ".bmp" => String::from("TextureCodec::Bmp"),
+        ".webp" => String::from("TextureCodec::WebP"),
+        ".hdr" => String::from("TextureCodec::Hdr"),
+        ".exr" => String::from("TextureCodec::Exr"),
+        ".ktx2" => String::from("TextureCodec::Ktx2"),
+        ".dds" => String::from("TextureCodec::Dds"),
         _ => return Err(GeneratorError::InvalidSpec(
             format!("Scene {} has file texture {} with an unknown file extension", config.id, texture.id)))
     };
     Ok(codec)
 }
 
-pub fn generate_preloads(config: &Scene) -> Result<String, GeneratorError> {
+/// Generate statements that apply a texture's mipmap and sampler settings to a mutable binding of
+/// its TextureCreationData; empty if the texture uses the defaults
+fn generate_texture_settings(texture: &Texture) -> String {
+    let mut settings = String::new();
+    match texture.mipmaps {
+        Some(TextureMipmaps::cpu) => settings = format!(
+            "{}\n        engine::util::generate_mipmaps(&mut {}_texture).unwrap();", settings, texture.id),
+        Some(TextureMipmaps::gpu) => settings = format!(
+            "{}\n        {}_texture.mipmaps = defs::render::MipmapMode::GenerateOnGpu;", settings, texture.id),
+        _ => ()
+    };
+    if let Some(sampler) = &texture.sampler {
+        let filter = |filter: &SamplerFilter| match filter {
+            SamplerFilter::nearest => "defs::render::SamplerFilter::Nearest",
+            SamplerFilter::linear => "defs::render::SamplerFilter::Linear"
+        };
+        let wrap = |wrap: &SamplerWrap| match wrap {
+            SamplerWrap::repeat => "defs::render::SamplerAddressMode::Repeat",
+            SamplerWrap::mirrored_repeat => "defs::render::SamplerAddressMode::MirroredRepeat",
+            SamplerWrap::clamp_to_edge => "defs::render::SamplerAddressMode::ClampToEdge"
+        };
+        let mut assignments: Vec<(&str, String)> = vec![];
+        if let Some(f) = &sampler.min_filter {
+            assignments.push(("min_filter", filter(f).to_string()));
+        }
+        if let Some(f) = &sampler.mag_filter {
+            assignments.push(("mag_filter", filter(f).to_string()));
+        }
+        if let Some(f) = &sampler.mipmap_filter {
+            assignments.push(("mipmap_filter", filter(f).to_string()));
+        }
+        if let Some(w) = &sampler.wrap_u {
+            assignments.push(("address_mode_u", wrap(w).to_string()));
+        }
+        if let Some(w) = &sampler.wrap_v {
+            assignments.push(("address_mode_v", wrap(w).to_string()));
+        }
+        if let Some(w) = &sampler.wrap_w {
+            assignments.push(("address_mode_w", wrap(w).to_string()));
+        }
+        if let Some(a) = sampler.anisotropy {
+            assignments.push(("max_anisotropy", format!("{:?}", a)));
+        }
+        if let Some(b) = sampler.lod_bias {
+            assignments.push(("lod_bias", format!("{:?}", b)));
+        }
+        for (field, value) in assignments.iter() {
+            settings = format!("{}\n        {}_texture.sampler.{} = {};", settings, texture.id, field, value);
+        }
+    }
+    settings
+}
+
+/// Generate the binding keyword for a texture, which needs to be mutable if settings are applied
+fn texture_binding(settings: &str) -> &'static str {
+    match settings.is_empty() {
+        true => "let",
+        false => "let mut"
+    }
+}
+
+/// Generate the map of instance buffers to preload, one per instanced step, initially holding all
+/// of each step's instances
+fn generate_instance_preloads(config: &Scene) -> String {
+    let mut insert_ops = String::new();
+    for pass in config.passes.iter() {
+        for step in pass.steps.iter() {
+            let instances = match &step.instances {
+                Some(instances) => instances,
+                None => continue
+            };
+            insert_ops = format!("{}
+        instance_loads.insert(INSTANCE_BUFFER_INDEX_{}_{}, InstanceBufferCreationData {{
+            capacity: {},
+            instances: self.instances_{}_{}.clone()
+        }});",
+                insert_ops, pass.name.to_uppercase(), step.name.to_uppercase(),
+                instances.capacity.unwrap_or(instances.placements.len()), pass.name, step.name);
+        }
+    }
+    match insert_ops.is_empty() {
+        true => String::from("HashMap::new()"),
+        false => format!("{{
+        let mut instance_loads = HashMap::<usize, InstanceBufferCreationData>::new();{}
+        instance_loads
+    }}", insert_ops)
+    }
+}
+
+pub fn generate_preloads(
+    config: &Scene,
+    default_strings: Option<&StringTableContents>
+) -> Result<String, GeneratorError> {
 
     let mut model_load_operations = String::new();
     for model in config.resources.models.iter() {
+        let text = match &model.text_key {
+            Some(key) => default_strings.and_then(|table| table.text(key)).unwrap_or(key),
+            None => "Hello!"
+        };
         let load_op = match model.generator {
             None => format!(
-                "        let ({}_vertex_data, {}_vertex_count) = engine::util::decode_model({}_MODEL_BYTES);",
+                "        let ({}_vertex_data, {}_vertex_count) = engine::util::decode_model({}_MODEL_BYTES).unwrap();",
                 model.id, model.id, model.id.to_uppercase()
             ),
             Some(ModelGenerator::text) => format!(
         "        let {}_vertex_data = self.text_generator.generate_vertex_buffer(
-            \"Hello!\",
+            {:?},
             (-1.0, -1.0),
             (2.0, 1.0),
             0.125,
             TextAlignment::Start,
             TextAlignment::Start);
         let {}_vertex_count = {}_vertex_data.len();",
-                model.id, model.id, model.id
+                model.id, text, model.id, model.id
             ),
             Some(ModelGenerator::skybox) => format!(
                 "        let ({}_vertex_data, {}_vertex_count) = engine::util::make_skybox_vertices(20.0);",
@@ -60,15 +164,16 @@ pub fn generate_preloads(config: &Scene) -> Result<String, GeneratorError> {
 
     let mut texture_load_operations = String::new();
     for texture in config.resources.textures.iter() {
+        let settings = generate_texture_settings(texture);
         match texture.kind {
             None => {
                 let codec = get_codec_from_file_name(config, texture)?;
                 let load_op = format!("
-        let {}_texture = engine::util::decode_texture(
+        {} {}_texture = engine::util::decode_texture(
             {}_TEXTURE_BYTES,
             {},
             ImageUsage::TextureSampleOnly)
-            .unwrap();", texture.id, texture.id.to_uppercase(), codec);
+            .unwrap();{}", texture_binding(&settings), texture.id, texture.id.to_uppercase(), codec, settings);
                 let insert_op = format!(
                     "        texture_loads.insert(TEXTURE_INDEX_{}, {}_texture);",
                     texture.id.to_uppercase(), texture.id
@@ -78,19 +183,46 @@ pub fn generate_preloads(config: &Scene) -> Result<String, GeneratorError> {
             Some(TextureKind::uninitialised) => {
                 // TODO - Enforce or infer specific usage of texture at this point
                 let content = format!("
-        texture_loads.insert(TEXTURE_INDEX_{}, TextureCreationData {{
+        {} {}_texture = TextureCreationData {{
             layer_data: None,
             width: OFFSCREEN_RENDER_SIZE,
             height: OFFSCREEN_RENDER_SIZE,
             format: TexturePixelFormat::Rgba,
-            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth
-        }});", texture.id.to_uppercase());
+            usage: ImageUsage::OffscreenRenderSampleColorWriteDepth,
+            mipmaps: defs::render::MipmapMode::None,
+            sampler: defs::render::SamplerConfig::default()
+        }};{}
+        texture_loads.insert(TEXTURE_INDEX_{}, {}_texture);",
+                    texture_binding(&settings), texture.id, settings, texture.id.to_uppercase(), texture.id);
                 texture_load_operations = format!("{}{}", texture_load_operations, content);
             },
+            Some(TextureKind::cubemap) if super::is_single_file_cubemap(texture) => {
+                let codec = get_codec_from_file_name(config, texture)?;
+                let load_op = match super::single_image_cubemap_layout(&texture.layout) {
+                    Some(layout) => format!("
+        {} {}_texture = engine::util::cubemap::decode_cubemap(
+            {}_TEXTURE_BYTES,
+            {},
+            engine::util::cubemap::CubemapLayout::{},
+            None)
+            .unwrap();{}", texture_binding(&settings), texture.id, texture.id.to_uppercase(), codec, layout, settings),
+                    None => format!("
+        {} {}_texture = engine::util::decode_texture(
+            {}_TEXTURE_BYTES,
+            {},
+            ImageUsage::Skybox)
+            .unwrap();{}", texture_binding(&settings), texture.id, texture.id.to_uppercase(), codec, settings)
+                };
+                let insert_op = format!(
+                    "        texture_loads.insert(TEXTURE_INDEX_{}, {}_texture);",
+                    texture.id.to_uppercase(), texture.id
+                );
+                texture_load_operations = format!("{}{}\n{}", texture_load_operations, load_op, insert_op);
+            },
             Some(TextureKind::cubemap) => {
                 let codec = get_codec_from_file_name(config, texture)?;
                 let load_op = format!("
-        let {}_texture = engine::util::decode_texture_array(
+        {} {}_texture = engine::util::decode_texture_array(
             vec![
                 {}_TEXTURE_LF_BYTES,
                 {}_TEXTURE_RT_BYTES,
@@ -101,7 +233,7 @@ pub fn generate_preloads(config: &Scene) -> Result<String, GeneratorError> {
             ],
             {},
             ImageUsage::Skybox)
-            .unwrap();", texture.id, texture.id.to_uppercase(), texture.id.to_uppercase(), texture.id.to_uppercase(), texture.id.to_uppercase(), texture.id.to_uppercase(), texture.id.to_uppercase(), codec);
+            .unwrap();{}", texture_binding(&settings), texture.id, texture.id.to_uppercase(), texture.id.to_uppercase(), texture.id.to_uppercase(), texture.id.to_uppercase(), texture.id.to_uppercase(), texture.id.to_uppercase(), codec, settings);
                 let insert_op = format!(
                     "        texture_loads.insert(TEXTURE_INDEX_{}, {}_texture);",
                     texture.id.to_uppercase(), texture.id
@@ -120,9 +252,11 @@ pub fn generate_preloads(config: &Scene) -> Result<String, GeneratorError> {
 
         ResourcePreloads {{
             vbo_preloads: vbo_loads,
-            texture_preloads: texture_loads
+            texture_preloads: texture_loads,
+            instance_preloads: {},
+            dynamic_vbo_preloads: HashMap::new()
         }}
     }}\
-    ", model_load_operations, texture_load_operations);
+    ", model_load_operations, texture_load_operations, generate_instance_preloads(config));
     Ok(content)
 }
@@ -1,9 +1,10 @@
 
 use crate::GeneratorError;
-use crate::deserialiser::types::scene::*;
+use crate::deserialiser::types::{ModelGenerator, Resources, scene::*};
 use heck::CamelCase;
 
 pub fn generate_struct_definition(
+    shared_resources: &Resources,
     config: &Scene,
     resources_dir_name: &'static str
 ) -> Result<String, GeneratorError> {
@@ -12,16 +13,40 @@ pub fn generate_struct_definition(
     let (camera_type, camera_constructor) = match config.camera {
         Camera::null => (
             "NullCamera",
-            "NullCamera::default()"
+            "NullCamera::default()".to_string()
         ),
         Camera::player => (
             "PlayerCamera",
-            "PlayerCamera::new(1.0, 10.0, -3.0, -15.0, std::f32::consts::FRAC_PI_6 * 5.0)"
+            generate_player_camera_constructor(config)
         ),
-        Camera::flight_path => unimplemented!()
+        Camera::orbit => (
+            "engine::camera::orbit::OrbitCamera",
+            format!(
+                "engine::camera::orbit::OrbitCamera::with_projection(\n                1.0,\n                cgmath::Vector3::new(0.0, 0.0, 0.0),\n                10.0,\n                2.0,\n                50.0,\n                {})",
+                generate_projection_config(config, "spectator"))
+        ),
+        Camera::free_fly => (
+            "engine::camera::free_fly::FreeFlyCamera",
+            format!(
+                "engine::camera::free_fly::FreeFlyCamera::with_projection(\n                1.0,\n                cgmath::Vector3::new(0.0, -3.0, -15.0),\n                0.0,\n                0.0,\n                {})",
+                generate_projection_config(config, "spectator"))
+        ),
+        Camera::orthographic => (
+            "engine::camera::orthographic::OrthographicCamera",
+            "engine::camera::orthographic::OrthographicCamera::new(1.0, 1.0)".to_string()
+        ),
+        Camera::flight_path => (
+            "engine::camera::flight_path::FlightPathCamera",
+            generate_flight_path_camera_constructor(config, resources_dir_name)?
+        )
     };
 
-    let (text_gen_decls, text_gen_constructors) = match config.resources.fonts.len() {
+    // Text models are laid out with the scene's fonts, or the shared fonts if it has none
+    let fonts = match config.resources.fonts.is_empty() && has_text_model(config) {
+        true => &shared_resources.fonts,
+        false => &config.resources.fonts
+    };
+    let (text_gen_decls, text_gen_constructors) = match fonts.len() {
         0 => (
             String::new(),
             String::new()
@@ -30,19 +55,19 @@ pub fn generate_struct_definition(
             String::from("\n    text_generator: TextGenerator,"),
             format!(
                 "\n            text_generator: TextGenerator::from_resource(include_str!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/{}/fonts/{}\"))),",
-                resources_dir_name, config.resources.fonts[0].file
+                resources_dir_name, fonts[0].file
             )
         ),
         _ => {
             let mut decls = String::new();
             let mut constructors = String::new();
-            for (i, _) in config.resources.fonts.iter().enumerate() {
+            for (i, _) in fonts.iter().enumerate() {
                 decls = format!("{}\n    text_generator_{}: TextGenerator,", decls, i);
                 constructors = format!(
                     "{}\n            text_generator_{}: TextGenerator::from_resource(include_str!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/{}/fonts/{}\"))),",
                     constructors,
                     i,
-                    resources_dir_name, config.resources.fonts[i].file
+                    resources_dir_name, fonts[i].file
                 );
             }
             (decls, constructors)
@@ -59,19 +84,23 @@ pub fn generate_struct_definition(
                     RenderFunction::basic_textured => "MvpUbo",
                     RenderFunction::basic_textured_clipped_y => "MvpClippingUbo",
                     RenderFunction::text_paint => "TextPaintUbo",
+                    RenderFunction::text_paint_sdf => "TextPaintUbo",
                     RenderFunction::cube => "MvpUbo",
                     RenderFunction::cube_clipped_y => "MvpClippingUbo",
-                    RenderFunction::reflection_pre_render => "MvpClippingUbo"
+                    RenderFunction::reflection_pre_render => "MvpClippingUbo",
+                    RenderFunction::basic_textured_instanced => "MvpUbo"
                 };
                 decls = format!("{}\n    ubo_{}_{}: {},", decls, pass.name, step.name, ubo_type);
 
                 let ubo_constructor = match step.render {
                     RenderFunction::basic_textured => "MvpUbo { matrix: Matrix4::identity() }",
                     RenderFunction::basic_textured_clipped_y => "MvpClippingUbo {\n                matrix: Matrix4::identity(),\n                y_bias: 0.0,\n                y_plane_normal: -1.0,\n                unused: [0.0, 0.0]\n            }",
-                    RenderFunction::text_paint => "TextPaintUbo {\n                camera_matrix: Matrix4::identity(),\n                paint_color: Vector4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 }\n            }",
+                    RenderFunction::text_paint => "TextPaintUbo {\n                camera_matrix: Matrix4::identity(),\n                paint_color: Vector4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 },\n                outline_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },\n                shadow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },\n                glow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },\n                shadow_offset: [0.0, 0.0],\n                outline_width: 0.0,\n                glow_width: 0.0,\n                distance_range: 0.0,\n                multi_channel: 0.0,\n                unused: [0.0, 0.0]\n            }",
+                    RenderFunction::text_paint_sdf => "TextPaintUbo {\n                camera_matrix: Matrix4::identity(),\n                paint_color: Vector4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 },\n                outline_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },\n                shadow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },\n                glow_color: Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },\n                shadow_offset: [0.0, 0.0],\n                outline_width: 0.0,\n                glow_width: 0.0,\n                distance_range: 8.0,\n                multi_channel: 0.0,\n                unused: [0.0, 0.0]\n            }",
                     RenderFunction::cube => "MvpUbo { matrix: Matrix4::identity() }",
                     RenderFunction::cube_clipped_y => "MvpClippingUbo {\n                matrix: Matrix4::identity(),\n                y_bias: 0.0,\n                y_plane_normal: -1.0,\n                unused: [0.0, 0.0]\n            }",
-                    RenderFunction::reflection_pre_render => "MvpClippingUbo {\n                matrix: Matrix4::identity(),\n                y_bias: 0.0,\n                y_plane_normal: -1.0,\n                unused: [0.0, 0.0]\n            }"
+                    RenderFunction::reflection_pre_render => "MvpClippingUbo {\n                matrix: Matrix4::identity(),\n                y_bias: 0.0,\n                y_plane_normal: -1.0,\n                unused: [0.0, 0.0]\n            }",
+                    RenderFunction::basic_textured_instanced => "MvpUbo { matrix: Matrix4::identity() }"
                 };
                 constructors = format!("{}\n            ubo_{}_{}: {},", constructors, pass.name, step.name, ubo_constructor);
             }
@@ -79,32 +108,288 @@ pub fn generate_struct_definition(
         (decls, constructors)
     };
 
+    let (node_decls, node_setup, node_constructors, node_matrix_fn) = generate_scene_graph(config);
+    let (bounds_decls, bounds_constructors) = generate_model_bounds(config);
+    let (instance_decls, instance_constructors, culling_fn) = generate_instances(config);
+    let (lod_decls, lod_constructors, lod_update_fn) = generate_lod_chains(config);
+
     let content = format!("
 pub struct {} {{
-    camera: {},{}{}
+    camera: {},{}{}{}{}{}{}
 }}
 
 impl {} {{
-    pub fn new() -> {} {{
+    pub fn new() -> {} {{{}
         {} {{
-            camera: {},{}{}
+            camera: {},{}{}{}{}{}{}
         }}
-    }}
+    }}{}{}{}
 }}
 ",
                       struct_name,
                       camera_type,
                       text_gen_decls,
+                      node_decls,
+                      bounds_decls,
+                      instance_decls,
+                      lod_decls,
                       ubo_decls,
                       struct_name,
                       struct_name,
+                      node_setup,
                       struct_name,
                       camera_constructor,
                       text_gen_constructors,
-                      ubo_constructors);
+                      node_constructors,
+                      bounds_constructors,
+                      instance_constructors,
+                      lod_constructors,
+                      ubo_constructors,
+                      node_matrix_fn,
+                      culling_fn,
+                      lod_update_fn);
     Ok(content)
 }
 
+/// Generate the scene graph holding the scene's nodes, if it has any: the struct fields, the
+/// statements building the graph, the fields' initialisers, and a method that copies each node's
+/// matrix into the uniform buffers of the steps drawn with it
+fn generate_scene_graph(config: &Scene) -> (String, String, String, String) {
+    let nodes = match &config.nodes {
+        Some(nodes) if !nodes.is_empty() => nodes,
+        _ => return (String::new(), String::new(), String::new(), String::new())
+    };
+    let mut decls = String::from("\n    scene_graph: engine::scene_graph::SceneGraph,");
+    let mut setup = String::from("\n        let mut scene_graph = engine::scene_graph::SceneGraph::new();");
+    let mut constructors = String::from("\n            scene_graph,");
+    for node in nodes.iter() {
+        let parent = match &node.parent {
+            Some(parent) => format!("Some(node_{})", parent),
+            None => String::from("None")
+        };
+        let translation = node.translation.unwrap_or([0.0, 0.0, 0.0]);
+        let rotation = node.rotation_degrees.unwrap_or([0.0, 0.0, 0.0]);
+        let scale = node.scale.unwrap_or([1.0, 1.0, 1.0]);
+        decls = format!("{}\n    node_{}: engine::scene_graph::NodeId,", decls, node.id);
+        setup = format!(
+            "{}\n        let node_{} = scene_graph.add_node({}, engine::scene_graph::Transform::from_components(\n            cgmath::Vector3::new({:?}, {:?}, {:?}),\n            cgmath::Vector3::new({:?}, {:?}, {:?}),\n            cgmath::Vector3::new({:?}, {:?}, {:?})));",
+            setup, node.id, parent,
+            translation[0], translation[1], translation[2],
+            rotation[0].to_radians(), rotation[1].to_radians(), rotation[2].to_radians(),
+            scale[0], scale[1], scale[2]);
+        constructors = format!("{}\n            node_{},", constructors, node.id);
+    }
+
+    let mut assignments = String::new();
+    for pass in config.passes.iter() {
+        for step in pass.steps.iter() {
+            if let Some(node_id) = &step.node {
+                assignments = format!(
+                    "{}\n        self.ubo_{}_{}.{} = self.scene_graph.get_mvp_matrix(self.node_{}, pv_matrix);",
+                    assignments, pass.name, step.name, ubo_matrix_field(&step.render), node_id);
+            }
+        }
+    }
+    let matrix_fn = format!("
+
+    /// Recalculate the world transforms of changed nodes, and set the uniform buffer matrix of
+    /// each step drawn with a node to its model matrix combined with the supplied camera matrix
+    fn update_node_matrices(&mut self, pv_matrix: Matrix4<f32>) {{
+        self.scene_graph.update_world_transforms();{}
+    }}", assignments);
+    (decls, setup, constructors, matrix_fn)
+}
+
+/// Get the field of a step's uniform buffer holding the matrix its vertices are transformed by
+fn ubo_matrix_field(render: &RenderFunction) -> &'static str {
+    match render {
+        RenderFunction::text_paint | RenderFunction::text_paint_sdf => "camera_matrix",
+        _ => "matrix"
+    }
+}
+
+/// Check whether a model is loaded from a file in the scene's own resources, and so has bounds
+/// that the scene can read when it is created
+fn has_bounds(config: &Scene, model_id: &str) -> bool {
+    config.resources.models.iter()
+        .any(|model| model.id == model_id && model.generator.is_none() && model.file.is_some())
+}
+
+/// Generate the fields holding the bounds of each model drawn by a step that has them, and their
+/// initialisers
+fn generate_model_bounds(config: &Scene) -> (String, String) {
+    let mut model_ids: Vec<&str> = vec![];
+    for pass in config.passes.iter() {
+        for step in pass.steps.iter() {
+            if has_bounds(config, &step.model_id) && !model_ids.contains(&step.model_id.as_str()) {
+                model_ids.push(&step.model_id);
+            }
+        }
+    }
+    let mut decls = String::new();
+    let mut constructors = String::new();
+    for model_id in model_ids.iter() {
+        decls = format!("{}\n    bounds_{}: engine::culling::Bounds,", decls, model_id);
+        constructors = format!(
+            "{}\n            bounds_{}: engine::util::decode_model_with_bounds({}_MODEL_BYTES).unwrap().2,",
+            constructors, model_id, model_id.to_uppercase());
+    }
+    (decls, constructors)
+}
+
+/// Generate the fields holding every instance of each instanced step, their initialisers, and a
+/// method that culls them against the camera for uploading; instances of models without bounds
+/// are all kept
+fn generate_instances(config: &Scene) -> (String, String, String) {
+    let mut decls = String::new();
+    let mut constructors = String::new();
+    let mut culled = String::new();
+    for pass in config.passes.iter() {
+        for step in pass.steps.iter() {
+            let instances = match &step.instances {
+                Some(instances) => instances,
+                None => continue
+            };
+            let mut placements = String::new();
+            for placement in instances.placements.iter() {
+                let translation = placement.translation.unwrap_or([0.0, 0.0, 0.0]);
+                let rotation = placement.rotation_degrees.unwrap_or([0.0, 0.0, 0.0]);
+                let scale = placement.scale.unwrap_or([1.0, 1.0, 1.0]);
+                let tint = placement.tint.unwrap_or([1.0, 1.0, 1.0, 1.0]);
+                placements = format!("{}
+                InstanceData {{
+                    model_matrix: engine::scene_graph::Transform::from_components(
+                        cgmath::Vector3::new({:?}, {:?}, {:?}),
+                        cgmath::Vector3::new({:?}, {:?}, {:?}),
+                        cgmath::Vector3::new({:?}, {:?}, {:?}))
+                        .to_matrix(),
+                    tint: cgmath::Vector4::new({:?}, {:?}, {:?}, {:?})
+                }},",
+                    placements,
+                    translation[0], translation[1], translation[2],
+                    rotation[0].to_radians(), rotation[1].to_radians(), rotation[2].to_radians(),
+                    scale[0], scale[1], scale[2],
+                    tint[0], tint[1], tint[2], tint[3]);
+            }
+            decls = format!("{}\n    instances_{}_{}: Vec<InstanceData>,", decls, pass.name, step.name);
+            constructors = format!("{}\n            instances_{}_{}: vec![{}\n            ],", constructors, pass.name, step.name, placements);
+
+            let kept = match has_bounds(config, &step.model_id) {
+                true => format!(
+                    "engine::culling::cull_instances(\n                &engine::culling::Frustum::from_matrix(self.ubo_{}_{}.matrix),\n                &self.bounds_{},\n                &self.instances_{}_{})",
+                    pass.name, step.name, step.model_id, pass.name, step.name),
+                false => format!("self.instances_{}_{}.clone()", pass.name, step.name)
+            };
+            culled = format!(
+                "{}\n        culled.insert(\n            INSTANCE_BUFFER_INDEX_{}_{},\n            {});",
+                culled, pass.name.to_uppercase(), step.name.to_uppercase(), kept);
+        }
+    }
+    if culled.is_empty() {
+        return (decls, constructors, String::new());
+    }
+    let culling_fn = format!("
+
+    /// Get the instances of each instanced step that may be seen by the camera its uniform buffer
+    /// was last set for, keyed by instance buffer, ready to return from take_instance_updates
+    fn make_culled_instances(&self) -> HashMap<usize, Vec<InstanceData>> {{
+        let mut culled = HashMap::<usize, Vec<InstanceData>>::new();{}
+        culled
+    }}", culled);
+    (decls, constructors, culling_fn)
+}
+
+/// Find the chain of levels of detail that a step draws one level of, if any, as the full-detail
+/// step declaring the chain and the step's level in it
+fn find_lod_level<'a>(pass: &'a Pass, step: &Step) -> Option<(&'a Step, usize)> {
+    pass.steps.iter().find_map(|full_detail| {
+        let lods = full_detail.lods.as_ref()?;
+        match full_detail.name == step.name {
+            true => Some((full_detail, 0)),
+            false => lods.steps.iter()
+                .position(|level| level == &step.name)
+                .map(|level| (full_detail, level + 1))
+        }
+    })
+}
+
+/// Generate the fields holding the chain of levels of detail for each step that declares one,
+/// their initialisers, and a method that picks each chain's level from how large the full-detail
+/// model's bounds appear on screen, placed by the step's node if it has one
+fn generate_lod_chains(config: &Scene) -> (String, String, String) {
+    let mut decls = String::new();
+    let mut constructors = String::new();
+    let mut updates = String::new();
+    for pass in config.passes.iter() {
+        for step in pass.steps.iter() {
+            let lods = match &step.lods {
+                Some(lods) => lods,
+                None => continue
+            };
+            let sizes: Vec<String> = lods.min_screen_sizes.iter().map(|size| format!("{:?}", size)).collect();
+            decls = format!("{}\n    lod_{}_{}: engine::lod::LodChain,", decls, pass.name, step.name);
+            constructors = format!(
+                "{}\n            lod_{}_{}: engine::lod::LodChain::new(vec![{}]),",
+                constructors, pass.name, step.name, sizes.join(", "));
+            let model_matrix = match &step.node {
+                Some(node_id) => format!("self.scene_graph.get_world_matrix(self.node_{})", node_id),
+                None => String::from("Matrix4::identity()")
+            };
+            updates = format!(
+                "{}\n        let (centre, radius) = engine::culling::transform_sphere(&self.bounds_{}, {});\n        self.lod_{}_{}.update(engine::lod::projected_sphere_size(view_matrix, projection_matrix, centre, radius));",
+                updates, step.model_id, model_matrix, pass.name, step.name);
+        }
+    }
+    if updates.is_empty() {
+        return (decls, constructors, String::new());
+    }
+    let update_fn = format!("
+
+    /// Pick the level of detail to draw for each chain, as seen by a camera with the given view
+    /// and projection matrices
+    fn update_lods(&mut self, view_matrix: Matrix4<f32>, projection_matrix: Matrix4<f32>) {{{}
+    }}", updates);
+    (decls, constructors, update_fn)
+}
+
+/// Generate the check for whether each step might be seen this frame. Steps drawing a model with
+/// bounds are tested against the frustum of the matrix last set in their uniform buffer, which,
+/// being the model's matrix combined with the camera's, keeps the bounds in model space. Steps
+/// drawing a level of detail are only drawn while their chain picks that level. Any other step,
+/// and instanced steps whose instances are culled separately, are always drawn.
+pub fn generate_is_step_visible_fn(config: &Scene) -> String {
+    let mut visibility_mappings = String::new();
+    for (pass_index, pass) in config.passes.iter().enumerate() {
+        for (step_index, step) in pass.steps.iter().enumerate() {
+            let mut conditions: Vec<String> = vec![];
+            if let Some((full_detail, level)) = find_lod_level(pass, step) {
+                conditions.push(format!("self.lod_{}_{}.is_level_visible({})", pass.name, full_detail.name, level));
+            }
+            if step.instances.is_none() && has_bounds(config, &step.model_id) {
+                conditions.push(format!(
+                    "engine::culling::Frustum::from_matrix(self.ubo_{}_{}.{})\n                .intersects_bounds(&self.bounds_{}, Matrix4::identity())",
+                    pass.name, step.name, ubo_matrix_field(&step.render), step.model_id));
+            }
+            if conditions.is_empty() {
+                continue;
+            }
+            visibility_mappings = format!(
+                "{}\n            ({}, {}) => {},",
+                visibility_mappings, pass_index, step_index, conditions.join(" &&\n                "));
+        }
+    }
+    if visibility_mappings.is_empty() {
+        return String::new();
+    }
+    format!("
+
+    fn is_step_visible(&self, pass_index: usize, step_index: usize) -> bool {{
+        match (pass_index, step_index) {{{}
+            _ => true
+        }}
+    }}", visibility_mappings)
+}
+
 pub fn generate_get_ubo_fn(config: &Scene) -> Result<String, GeneratorError> {
     let struct_name = format!("{}Scene", config.id.to_camel_case());
 
@@ -117,9 +402,11 @@ pub fn generate_get_ubo_fn(config: &Scene) -> Result<String, GeneratorError> {
                     RenderFunction::basic_textured => "MvpUbo",
                     RenderFunction::basic_textured_clipped_y => "MvpClippingUbo",
                     RenderFunction::text_paint => "TextPaintUbo",
+                    RenderFunction::text_paint_sdf => "TextPaintUbo",
                     RenderFunction::cube => "MvpUbo",
                     RenderFunction::cube_clipped_y => "MvpClippingUbo",
-                    RenderFunction::reflection_pre_render => "MvpClippingUbo"
+                    RenderFunction::reflection_pre_render => "MvpClippingUbo",
+                    RenderFunction::basic_textured_instanced => "MvpUbo"
                 };
 
                 ptr_mappings = format!("{}\n            ({}, {}) => (\n                &self.ubo_{}_{} as *const {} as *const u8,\n                std::mem::size_of::<{}>()),", ptr_mappings, pass_index, step_index, pass.name, step.name, ubo_type, ubo_type);
@@ -140,3 +427,148 @@ pub fn generate_get_ubo_fn(config: &Scene) -> Result<String, GeneratorError> {
     }}", ubo_ptr_mappings, struct_name);
     Ok(content)
 }
+
+/// Check whether the scene has a model generated from text, which needs a text generator
+fn has_text_model(config: &Scene) -> bool {
+    config.resources.models.iter().any(|model| matches!(model.generator, Some(ModelGenerator::text)))
+}
+
+/// Generate the construction of a flight path camera, from the points given in the spec or from a
+/// curve file in the resources
+fn generate_flight_path_camera_constructor(
+    config: &Scene,
+    resources_dir_name: &'static str
+) -> Result<String, GeneratorError> {
+    let path = config.flight_path.as_ref()
+        .ok_or_else(|| GeneratorError::InvalidSpec(format!("(Scene {}) Flight path camera has no flight_path", config.id)))?;
+    let looping = path.looping.unwrap_or(false);
+    let path_constructor = match (&path.points, &path.file) {
+        (Some(points), _) => {
+            let spline = match path.spline {
+                Some(SplineKind::bezier) => "Bezier",
+                _ => "CatmullRom"
+            };
+            format!(
+                "engine::camera::flight_path::FlightPath::new(\n                    engine::camera::flight_path::SplineKind::{},\n                    {},\n                    {:?},\n                    {})\n                    .unwrap()",
+                spline, generate_vector_list(points), path.duration, looping)
+        },
+        (None, Some(file)) => format!(
+            "engine::camera::flight_path::FlightPath::from_obj_curve(\n                    include_str!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/{}/paths/{}\")),\n                    {:?},\n                    {})\n                    .unwrap()",
+            resources_dir_name, file, path.duration, looping),
+        (None, None) => return Err(GeneratorError::InvalidSpec(
+            format!("(Scene {}) Flight path needs exactly one of points or file", config.id)))
+    };
+    let easing = match path.easing {
+        Some(FlightPathEasing::linear) | None => String::new(),
+        Some(FlightPathEasing::ease_in) => String::from("\n                    .with_easing(engine::camera::flight_path::Easing::EaseIn)"),
+        Some(FlightPathEasing::ease_out) => String::from("\n                    .with_easing(engine::camera::flight_path::Easing::EaseOut)"),
+        Some(FlightPathEasing::ease_in_out) => String::from("\n                    .with_easing(engine::camera::flight_path::Easing::EaseInOut)")
+    };
+    let look_at = match &path.look_at {
+        Some(targets) => format!(
+            "\n                    .with_look_at_targets({})\n                    .unwrap()",
+            generate_vector_list(targets)),
+        None => String::new()
+    };
+    Ok(format!(
+        "engine::camera::flight_path::FlightPathCamera::with_projection(\n                1.0,\n                {}{}{},\n                {})",
+        path_constructor, easing, look_at, generate_projection_config(config, "spectator")))
+}
+
+/// Generate the projection config for a camera, overriding the named base config with any
+/// projection given in the scene's camera settings
+fn generate_projection_config(config: &Scene, base: &str) -> String {
+    let mut fields = String::new();
+    if let Some(settings) = &config.camera_settings {
+        if let Some(degrees) = settings.field_of_view_degrees {
+            fields.push_str(&format!("\n                    field_of_view: cgmath::Rad({:?}),", degrees.to_radians()));
+        }
+        for (name, value) in [("near_plane", settings.near_plane), ("far_plane", settings.far_plane)] {
+            if let Some(value) = value {
+                fields.push_str(&format!("\n                    {}: {:?},", name, value));
+            }
+        }
+        if let Some(reversed_z) = settings.reversed_z {
+            fields.push_str(&format!("\n                    reversed_z: {},", reversed_z));
+        }
+    }
+    match fields.is_empty() {
+        true => format!("engine::camera::config::ProjectionConfig::{}()", base),
+        false => format!(
+            "engine::camera::config::ProjectionConfig {{{}\n                    ..engine::camera::config::ProjectionConfig::{}()\n                }}",
+            fields, base)
+    }
+}
+
+/// Generate the construction of a player camera, configured by the scene's camera settings if it
+/// has any, and riding over the terrain model if it has one
+fn generate_player_camera_constructor(config: &Scene) -> String {
+    let settings = match &config.camera_settings {
+        Some(settings) => settings,
+        None => return String::from("PlayerCamera::new(1.0, 10.0, -3.0, -15.0, std::f32::consts::FRAC_PI_6 * 5.0)")
+    };
+    let position = settings.position.unwrap_or([10.0, -3.0, -15.0]);
+    let rotation = match settings.rotation_degrees {
+        Some(degrees) => format!("{:?}", degrees.to_radians()),
+        None => String::from("std::f32::consts::FRAC_PI_6 * 5.0")
+    };
+
+    let mut config_fields = format!(
+        "\n                    projection: {},",
+        generate_projection_config(config, "default").replace('\n', "\n    "));
+    if let Some(movement) = &settings.movement {
+        let tuning = [
+            ("deadzone", movement.deadzone),
+            ("max_speed", movement.max_speed),
+            ("max_reverse_speed", movement.max_reverse_speed),
+            ("acceleration", movement.acceleration),
+            ("deceleration", movement.deceleration),
+            ("max_angular_speed", movement.max_angular_speed),
+            ("angular_acceleration", movement.angular_acceleration),
+            ("angular_deceleration", movement.angular_deceleration)
+        ];
+        let mut tuning_fields = String::new();
+        for (name, value) in tuning.iter() {
+            if let Some(value) = value {
+                tuning_fields.push_str(&format!("\n                        {}: {:?},", name, value));
+            }
+        }
+        config_fields.push_str(&format!(
+            "\n                    movement: engine::camera::config::MovementTuning {{{}\n                        ..engine::camera::config::MovementTuning::default()\n                    }},",
+            tuning_fields));
+    }
+    if let Some(terrain) = &settings.terrain {
+        let following = [
+            ("eye_height", terrain.eye_height),
+            ("smoothing", terrain.smoothing),
+            ("collision_radius", terrain.collision_radius)
+        ];
+        let mut following_fields = String::new();
+        for (name, value) in following.iter() {
+            if let Some(value) = value {
+                following_fields.push_str(&format!("\n                        {}: {:?},", name, value));
+            }
+        }
+        config_fields.push_str(&format!(
+            "\n                    terrain: engine::camera::config::TerrainFollowing {{{}\n                        ..engine::camera::config::TerrainFollowing::default()\n                    }},",
+            following_fields));
+    }
+
+    let constructor = format!(
+        "PlayerCamera::with_config(\n                1.0,\n                cgmath::Vector3::new({:?}, {:?}, {:?}),\n                {},\n                engine::camera::config::CameraConfig {{{}\n                    ..engine::camera::config::CameraConfig::default()\n                }})",
+        position[0], position[1], position[2], rotation, config_fields);
+    match &settings.terrain {
+        Some(terrain) => format!(
+            "{{\n                let mut camera = {};\n                camera.set_collision_world(Box::new(engine::collision::StaticGeometry::from_vertices(\n                    &engine::util::decode_model({}_MODEL_BYTES).unwrap().0)));\n                camera\n            }}",
+            constructor.replace('\n', "\n    "), terrain.model_id.to_uppercase()),
+        None => constructor
+    }
+}
+
+/// Generate a vec! of cgmath vectors
+fn generate_vector_list(points: &[[f32; 3]]) -> String {
+    let vectors: Vec<String> = points.iter()
+        .map(|p| format!("cgmath::Vector3::new({:?}, {:?}, {:?})", p[0], p[1], p[2]))
+        .collect();
+    format!("vec![{}]", vectors.join(", "))
+}
@@ -21,7 +21,7 @@ pub fn generate_top_level(
     let mut will_paint_text = false;
     for pass in config.passes.iter() {
         for step in pass.steps.iter() {
-            if step.render == RenderFunction::text_paint {
+            if step.render == RenderFunction::text_paint || step.render == RenderFunction::text_paint_sdf {
                 will_paint_text = true;
             }
         }
@@ -30,6 +30,16 @@ pub fn generate_top_level(
         additional_cgmath_imports = format!("{}, Vector4", additional_cgmath_imports);
     }
 
+    let mut additional_render_imports = String::new();
+    let has_instances = config.passes.iter()
+        .flat_map(|pass| pass.steps.iter())
+        .any(|step| step.instances.is_some());
+    if has_instances {
+        additional_render_imports = format!("{},
+        InstanceBufferCreationData,
+        InstanceData", additional_render_imports);
+    }
+
     let mut byte_decls = String::new();
     for model in config.resources.models.iter() {
         if let Some(src_file) = &model.file {
@@ -44,6 +54,10 @@ pub fn generate_top_level(
                     let decl = format!("const {}_TEXTURE_BYTES: &[u8] = include_bytes!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/{}/textures/{}\"));", texture.id.to_uppercase(), resources_dir_name, src_file);
                     byte_decls = format!("{}\n{}", byte_decls, decl);
                 },
+                Some(TextureKind::cubemap) if super::is_single_file_cubemap(texture) => {
+                    let decl = format!("const {}_TEXTURE_BYTES: &[u8] = include_bytes!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/{}/textures/{}\"));", texture.id.to_uppercase(), resources_dir_name, src_file);
+                    byte_decls = format!("{}\n{}", byte_decls, decl);
+                },
                 Some(TextureKind::cubemap) => {
                     let (name_part, extension) = match src_file.rfind(".") {
                         Some(index) => (&src_file[0..index], &src_file[index..]),
@@ -76,6 +90,14 @@ pub fn generate_top_level(
         texture_index_decls = format!("{}\nconst TEXTURE_INDEX_{}: usize = 0x{:08}{:08};", texture_index_decls, texture.id.to_uppercase(), scene_number_one_based, i);
     }
 
+    let mut instance_index_decls = String::new();
+    let instanced_steps = config.passes.iter()
+        .flat_map(|pass| pass.steps.iter().map(move |step| (pass, step)))
+        .filter(|(_, step)| step.instances.is_some());
+    for (i, (pass, step)) in instanced_steps.enumerate() {
+        instance_index_decls = format!("{}\nconst INSTANCE_BUFFER_INDEX_{}_{}: usize = 0x{:08}{:08};", instance_index_decls, pass.name.to_uppercase(), step.name.to_uppercase(), scene_number_one_based, i);
+    }
+
     let gen_content = format!("\
 use defs::{{
     SceneInfo,
@@ -89,7 +111,7 @@ use defs::{{
         ImageUsage,
         DrawingDescription,
         DrawingPass,
-        DrawingStep
+        DrawingStep{}
     }},
     ubo::*
 }};
@@ -104,8 +126,8 @@ use cgmath::{{Matrix4, SquareMatrix{}}};
 use std::collections::HashMap;
 {}
 {}
-{}
+{}{}
 
-const OFFSCREEN_RENDER_SIZE: u32 = 1024;", additional_util_imports, additional_cgmath_imports, byte_decls, vbo_index_decls, texture_index_decls);
+const OFFSCREEN_RENDER_SIZE: u32 = 1024;", additional_render_imports, additional_util_imports, additional_cgmath_imports, byte_decls, vbo_index_decls, texture_index_decls, instance_index_decls);
     Ok(gen_content)
 }
@@ -5,6 +5,29 @@ use heck::CamelCase;
 
 pub fn generate_starter_scene_contents(config: &Scene) -> Result<String, GeneratorError> {
     let struct_name = format!("{}Scene", config.id.to_camel_case());
+    let node_matrices_hint = match &config.nodes {
+        Some(nodes) if !nodes.is_empty() =>
+            "\n        // self.update_node_matrices(self.camera.get_projection_matrix() * self.camera.get_view_matrix());",
+        _ => ""
+    };
+    let has_lods = config.passes.iter()
+        .flat_map(|pass| pass.steps.iter())
+        .any(|step| step.lods.is_some());
+    let lods_hint = match has_lods {
+        true => "\n        // self.update_lods(self.camera.get_view_matrix(), self.camera.get_projection_matrix());",
+        false => ""
+    };
+    let has_instances = config.passes.iter()
+        .flat_map(|pass| pass.steps.iter())
+        .any(|step| step.instances.is_some());
+    let instance_updates_fn = match has_instances {
+        true => "
+
+    fn take_instance_updates(&mut self) -> Option<HashMap<usize, Vec<InstanceData>>> {
+        Some(self.make_culled_instances())
+    }",
+        false => ""
+    };
     let content = format!("
 use defs::{{
     Camera,
@@ -33,9 +56,9 @@ impl SceneUpdates for {} {{
     }}
 
     fn on_pre_render(&mut self) {{
-        // self.some_ubo.matrix = self.camera.get_projection_matrix();
-    }}
+        // self.some_ubo.matrix = self.camera.get_projection_matrix();{}{}
+    }}{}
 }}
-", struct_name, struct_name);
+", struct_name, struct_name, node_matrices_hint, lods_hint, instance_updates_fn);
     Ok(content)
 }
@@ -1,11 +1,13 @@
 use std::path::PathBuf;
 
 use crate::generator::{CompleteSpec, stubs};
+use crate::deserialiser::strings::StringTableContents;
 use crate::GeneratorError;
 
 pub fn write_app_files(
     project_dir: &PathBuf,
     complete_spec: &CompleteSpec,
+    string_tables: &[StringTableContents],
     resources_dir_name: &'static str
 ) -> Result<(), GeneratorError> {
 
@@ -47,7 +49,12 @@ pub fn write_app_files(
 
     for (scene_index, scene) in complete_spec.scenes.iter().enumerate() {
         let (scene_module_file_contents, scene_core_file_contents) =
-            stubs::generate_scene_stubs(scene_index + 1, &complete_spec.app.shared_resources, &scene, resources_dir_name)?;
+            stubs::generate_scene_stubs(
+                scene_index + 1,
+                &complete_spec.app.shared_resources,
+                string_tables.first(),
+                &scene,
+                resources_dir_name)?;
 
         let scene_module_file = make_project_file(
             project_dir,